@@ -0,0 +1,52 @@
+// -*- mode: rust; -*-
+//
+// This file is part of x25519-dalek.
+// Copyright (c) 2017-2019 isis agora lovecruft
+// Copyright (c) 2019 DebugSteven
+// See LICENSE for licensing information.
+//
+// Authors:
+// - isis agora lovecruft <isis@patternsinthevoid.net>
+// - DebugSteven <debugsteven@gmail.com>
+
+//! Benchmark batched Diffie-Hellman against a loop of single operations.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+
+use rand_core::OsRng;
+
+use x25519_dalek::{PublicKey, StaticSecret};
+
+fn bench_diffie_hellman_batch(c: &mut Criterion) {
+    let secret = StaticSecret::random_from_rng(OsRng);
+    let mut group = c.benchmark_group("diffie_hellman_batch");
+
+    for n in [16, 256] {
+        let publics: Vec<PublicKey> = (0..n)
+            .map(|_| PublicKey::from(&StaticSecret::random_from_rng(OsRng)))
+            .collect();
+
+        group.bench_with_input(BenchmarkId::new("loop", n), &publics, |b, publics| {
+            b.iter(|| {
+                publics
+                    .iter()
+                    .map(|public| secret.diffie_hellman(public))
+                    .collect::<Vec<_>>()
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("batch", n), &publics, |b, publics| {
+            b.iter(|| secret.diffie_hellman_batch(publics))
+        });
+    }
+    group.finish();
+}
+
+criterion_group! {
+    name = batch_benches;
+    config = Criterion::default();
+    targets =
+        bench_diffie_hellman_batch,
+}
+criterion_main! {
+    batch_benches,
+}
@@ -0,0 +1,176 @@
+// -*- mode: rust; -*-
+//
+// This file is part of x25519-dalek.
+// Copyright (c) 2017-2019 isis agora lovecruft
+// Copyright (c) 2019 DebugSteven
+// See LICENSE for licensing information.
+//
+// Authors:
+// - isis agora lovecruft <isis@patternsinthevoid.net>
+// - DebugSteven <debugsteven@gmail.com>
+
+//! Look for timing leaks with the methodology of dudect: time an operation
+//! on inputs from two classes, in random order, and compare the two
+//! distributions of times with Welch's t-test.
+//!
+//! Run with `cargo bench --features static_secrets --bench dudect`, on an
+//! otherwise idle machine.  A |t| above 4.5 in any test suggests that the
+//! time taken depends on the class of the input, and the process exits with
+//! an error.  The number of measurements per test can be set with
+//! `DUDECT_MEASUREMENTS`.
+
+use std::time::Instant;
+
+use criterion::black_box;
+use rand_core::{OsRng, RngCore};
+use subtle::ConstantTimeEq;
+
+use x25519_dalek::{PublicKey, StaticSecret};
+
+/// The |t| above which the classes are considered distinguishable.
+const THRESHOLD: f64 = 4.5;
+
+/// The number of comparisons timed together, as one comparison is too fast
+/// to time on its own.
+const COMPARISONS: usize = 64;
+
+/// Welch's t-test, with means and variances updated online.
+#[derive(Default)]
+struct Welch {
+    n: [f64; 2],
+    mean: [f64; 2],
+    m2: [f64; 2],
+}
+
+impl Welch {
+    fn push(&mut self, class: usize, x: f64) {
+        self.n[class] += 1.0;
+        let delta = x - self.mean[class];
+        self.mean[class] += delta / self.n[class];
+        self.m2[class] += delta * (x - self.mean[class]);
+    }
+
+    fn t(&self) -> f64 {
+        let var = |class: usize| self.m2[class] / (self.n[class] - 1.0);
+        (self.mean[0] - self.mean[1]) / (var(0) / self.n[0] + var(1) / self.n[1]).sqrt()
+    }
+}
+
+fn random_bytes() -> [u8; 32] {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    bytes
+}
+
+/// Time `op` on each input, and return the largest |t| over all the
+/// measurements and over those below several percentiles, as dudect does to
+/// discount the long tail of interrupted measurements.
+fn measure<T>(name: &str, inputs: &[(usize, T)], mut op: impl FnMut(&T)) -> f64 {
+    let times: Vec<(usize, f64)> = inputs
+        .iter()
+        .map(|(class, input)| {
+            let start = Instant::now();
+            op(input);
+            (*class, start.elapsed().as_nanos() as f64)
+        })
+        .collect();
+
+    let mut sorted: Vec<f64> = times.iter().map(|(_, time)| *time).collect();
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+    let mut max = 0f64;
+    for percentile in [1.0, 0.99, 0.9, 0.5] {
+        let cutoff = sorted[((sorted.len() - 1) as f64 * percentile) as usize];
+        let mut welch = Welch::default();
+        for (class, time) in times.iter().filter(|(_, time)| *time <= cutoff) {
+            welch.push(*class, *time);
+        }
+        max = max.max(welch.t().abs());
+    }
+
+    let verdict = if max > THRESHOLD {
+        "possible leak"
+    } else {
+        "ok"
+    };
+    println!(
+        "{}: max |t| = {:.2} over {} measurements, {}",
+        name,
+        max,
+        inputs.len(),
+        verdict
+    );
+    max
+}
+
+/// Assign each of `n` measurements to class 0 or 1 at random.
+fn classes(n: usize) -> impl Iterator<Item = usize> {
+    (0..n).map(|_| (OsRng.next_u32() & 1) as usize)
+}
+
+/// Diffie-Hellman with a fixed secret, of all zero bytes, against random
+/// secrets.
+fn diffie_hellman(n: usize) -> f64 {
+    let public = PublicKey::from(&StaticSecret::random_from_rng(OsRng));
+    let inputs: Vec<(usize, StaticSecret)> = classes(n)
+        .map(|class| match class {
+            0 => (class, StaticSecret::from([0u8; 32])),
+            _ => (class, StaticSecret::from(random_bytes())),
+        })
+        .collect();
+    measure("diffie_hellman", &inputs, |secret| {
+        black_box(secret.diffie_hellman(&public));
+    })
+}
+
+/// `PublicKey::ct_eq` on equal keys against unequal ones.
+fn public_key_ct_eq(n: usize) -> f64 {
+    let key = PublicKey::from(random_bytes());
+    let inputs: Vec<(usize, PublicKey)> = classes(n)
+        .map(|class| match class {
+            0 => (class, key),
+            _ => (class, PublicKey::from(random_bytes())),
+        })
+        .collect();
+    measure("PublicKey::ct_eq", &inputs, |other| {
+        for _ in 0..COMPARISONS {
+            black_box(black_box(&key).ct_eq(black_box(other)));
+        }
+    })
+}
+
+/// `SharedSecret::ct_eq` on equal secrets against unequal ones.
+fn shared_secret_ct_eq(n: usize) -> f64 {
+    let secret = StaticSecret::random_from_rng(OsRng);
+    let public = PublicKey::from(&StaticSecret::random_from_rng(OsRng));
+    let shared = secret.diffie_hellman(&public);
+    let inputs: Vec<(usize, _)> = classes(n)
+        .map(|class| match class {
+            0 => (class, secret.diffie_hellman(&public)),
+            _ => (
+                class,
+                secret.diffie_hellman(&PublicKey::from(random_bytes())),
+            ),
+        })
+        .collect();
+    measure("SharedSecret::ct_eq", &inputs, |other| {
+        for _ in 0..COMPARISONS {
+            black_box(black_box(&shared).ct_eq(black_box(other)));
+        }
+    })
+}
+
+fn main() {
+    let n = std::env::var("DUDECT_MEASUREMENTS")
+        .map(|n| n.parse().expect("DUDECT_MEASUREMENTS is not a number"))
+        .unwrap_or(100_000);
+
+    let t = [
+        diffie_hellman(n),
+        public_key_ct_eq(n),
+        shared_secret_ct_eq(n),
+    ];
+    if t.iter().any(|t| *t > THRESHOLD) {
+        std::process::exit(1);
+    }
+}
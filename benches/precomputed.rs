@@ -0,0 +1,44 @@
+// -*- mode: rust; -*-
+//
+// This file is part of x25519-dalek.
+// Copyright (c) 2017-2019 isis agora lovecruft
+// Copyright (c) 2019 DebugSteven
+// See LICENSE for licensing information.
+//
+// Authors:
+// - isis agora lovecruft <isis@patternsinthevoid.net>
+// - DebugSteven <debugsteven@gmail.com>
+
+//! Benchmark Diffie-Hellman against a precomputed public key.
+
+use criterion::{criterion_group, criterion_main, Criterion};
+
+use rand_core::OsRng;
+
+use x25519_dalek::{PrecomputedPublicKey, PublicKey, StaticSecret};
+
+fn bench_diffie_hellman_precomputed(c: &mut Criterion) {
+    let secret = StaticSecret::random_from_rng(OsRng);
+    let their_public = PublicKey::from(&StaticSecret::random_from_rng(OsRng));
+    let precomputed = PrecomputedPublicKey::from(&their_public);
+
+    c.bench_function("diffie_hellman", |b| {
+        b.iter(|| secret.diffie_hellman(&their_public))
+    });
+    c.bench_function("diffie_hellman_precomputed", |b| {
+        b.iter(|| secret.diffie_hellman_precomputed(&precomputed))
+    });
+    c.bench_function("precomputed_public_key", |b| {
+        b.iter(|| PrecomputedPublicKey::from(&their_public))
+    });
+}
+
+criterion_group! {
+    name = precomputed_benches;
+    config = Criterion::default();
+    targets =
+        bench_diffie_hellman_precomputed,
+}
+criterion_main! {
+    precomputed_benches,
+}
@@ -0,0 +1,146 @@
+// -*- mode: rust; -*-
+//
+// This file is part of x25519-dalek.
+// Copyright (c) 2017-2021 isis lovecruft
+// Copyright (c) 2019-2021 DebugSteven
+// See LICENSE for licensing information.
+//
+// Authors:
+// - isis agora lovecruft <isis@patternsinthevoid.net>
+// - DebugSteven <debugsteven@gmail.com>
+
+//! Answer an ACVP test vector set for X25519 shared secret computation.
+//!
+//! ```text
+//! cargo run --example acvp -- request.json > response.json
+//! ```
+//!
+//! ACVP has no X25519 mode, so the request takes the form of a
+//! `KAS-ECC-SSC` vector set with the curve's u-coordinates in the fields of
+//! x-coordinates, and no y-coordinates.  Keys, u-coordinates and shared
+//! secrets are the little-endian encodings of RFC7748, in hex.  The IUT (this
+//! implementation) uses an ephemeral key, and the shared secret `z` is not
+//! hashed:
+//!
+//! * In an `AFT` group, each test gives `ephemeralPublicServerX`, and the
+//!   response gives a fresh `ephemeralPublicIutX` and the resulting `z`.
+//! * In a `VAL` group, each test also gives `ephemeralPrivateIut`,
+//!   `ephemeralPublicIutX` and `z`, and the response gives `testPassed`,
+//!   which is false if the public key or shared secret differ, or if the
+//!   shared secret is all zeros.
+//!
+//! The request may be the bare vector set or, as served by ACVP, an array
+//! of the version object and the vector set, and the response takes the
+//! same form.
+
+use std::process::exit;
+
+use rand_core::{OsRng, RngCore};
+use serde_json::{json, Value};
+
+use x25519_dalek::{x25519, X25519_BASEPOINT_BYTES};
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02X}", byte)).collect()
+}
+
+/// Decode a hex field of 32 bytes from a test.
+fn field(test: &Value, name: &str) -> Result<[u8; 32], String> {
+    let s = test[name]
+        .as_str()
+        .ok_or_else(|| format!("missing field {}", name))?;
+    if s.len() != 64 || !s.is_ascii() {
+        return Err(format!("{} is not 32 bytes of hex", name));
+    }
+    let mut bytes = [0u8; 32];
+    for (i, byte) in bytes.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&s[2 * i..2 * i + 2], 16)
+            .map_err(|_| format!("{} is not 32 bytes of hex", name))?;
+    }
+    Ok(bytes)
+}
+
+fn answer_test(test_type: &str, test: &Value) -> Result<Value, String> {
+    let server_public = field(test, "ephemeralPublicServerX")?;
+    match test_type {
+        "AFT" => {
+            let mut secret = [0u8; 32];
+            OsRng.fill_bytes(&mut secret);
+            Ok(json!({
+                "tcId": test["tcId"],
+                "ephemeralPublicIutX": hex(&x25519(secret, X25519_BASEPOINT_BYTES)),
+                "z": hex(&x25519(secret, server_public)),
+            }))
+        }
+        "VAL" => {
+            let secret = field(test, "ephemeralPrivateIut")?;
+            let z = x25519(secret, server_public);
+            let passed = x25519(secret, X25519_BASEPOINT_BYTES)
+                == field(test, "ephemeralPublicIutX")?
+                && z == field(test, "z")?
+                && z != [0u8; 32];
+            Ok(json!({ "tcId": test["tcId"], "testPassed": passed }))
+        }
+        _ => Err(format!("unsupported test type {}", test_type)),
+    }
+}
+
+fn answer_vector_set(request: &Value) -> Result<Value, String> {
+    let mut groups = Vec::new();
+    for group in request["testGroups"]
+        .as_array()
+        .ok_or("missing testGroups")?
+    {
+        let test_type = group["testType"].as_str().ok_or("missing testType")?;
+        let tests = group["tests"]
+            .as_array()
+            .ok_or("missing tests")?
+            .iter()
+            .map(|test| answer_test(test_type, test))
+            .collect::<Result<Vec<_>, _>>()?;
+        groups.push(json!({ "tgId": group["tgId"], "tests": tests }));
+    }
+    let mut response = serde_json::Map::new();
+    for name in ["vsId", "algorithm", "mode", "revision", "isSample"] {
+        if let Some(value) = request.get(name) {
+            response.insert(name.into(), value.clone());
+        }
+    }
+    response.insert("testGroups".into(), groups.into());
+    Ok(response.into())
+}
+
+fn answer(request: &Value) -> Result<Value, String> {
+    match request.as_array() {
+        Some(parts) => parts
+            .iter()
+            .map(|part| match part.get("testGroups") {
+                Some(_) => answer_vector_set(part),
+                None => Ok(part.clone()),
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map(Value::Array),
+        None => answer_vector_set(request),
+    }
+}
+
+fn main() {
+    let path = match std::env::args().nth(1) {
+        Some(path) => path,
+        None => {
+            eprintln!("usage: acvp REQUEST.json");
+            exit(2);
+        }
+    };
+    let request = std::fs::read_to_string(&path)
+        .map_err(|e| e.to_string())
+        .and_then(|file| serde_json::from_str(&file).map_err(|e| e.to_string()))
+        .and_then(|request| answer(&request));
+    match request {
+        Ok(response) => println!("{}", serde_json::to_string_pretty(&response).unwrap()),
+        Err(e) => {
+            eprintln!("{}: {}", path, e);
+            exit(1);
+        }
+    }
+}
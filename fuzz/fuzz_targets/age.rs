@@ -0,0 +1,9 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use x25519_dalek::{PublicKey, StaticSecret};
+
+fuzz_target!(|data: &str| {
+    let _ = PublicKey::from_age_recipient(data);
+    let _ = StaticSecret::from_age_identity(data);
+});
@@ -0,0 +1,19 @@
+#![no_main]
+
+use core::str::FromStr;
+
+use libfuzzer_sys::fuzz_target;
+use x25519_dalek::{PublicKey, StaticSecret};
+
+fuzz_target!(|data: &[u8]| {
+    let _ = PublicKey::try_from(data);
+    let _ = StaticSecret::try_from(data);
+    if let Ok(bytes) = <[u8; 32]>::try_from(data) {
+        if let Ok(public) = PublicKey::from_canonical_bytes(bytes) {
+            assert_eq!(public.to_bytes(), bytes);
+        }
+    }
+    if let Ok(s) = core::str::from_utf8(data) {
+        let _ = PublicKey::from_str(s);
+    }
+});
@@ -0,0 +1,9 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use x25519_dalek::{PublicKey, StaticSecret};
+
+fuzz_target!(|data: &[u8]| {
+    let _ = PublicKey::from_cose_key(data);
+    let _ = StaticSecret::from_cose_key(data);
+});
@@ -0,0 +1,8 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use x25519_dalek::PublicKey;
+
+fuzz_target!(|data: &str| {
+    let _ = PublicKey::from_did_key(data);
+});
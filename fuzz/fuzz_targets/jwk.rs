@@ -0,0 +1,11 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use x25519_dalek::{Jwk, PublicKey, StaticSecret};
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(jwk) = serde_json::from_slice::<Jwk>(data) {
+        let _ = PublicKey::from_jwk(&jwk);
+        let _ = StaticSecret::from_jwk(&jwk);
+    }
+});
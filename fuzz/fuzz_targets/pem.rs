@@ -0,0 +1,11 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use x25519_dalek::pkcs8::spki::DecodePublicKey;
+use x25519_dalek::pkcs8::DecodePrivateKey;
+use x25519_dalek::{PublicKey, StaticSecret};
+
+fuzz_target!(|data: &str| {
+    let _ = StaticSecret::from_pkcs8_pem(data);
+    let _ = PublicKey::from_public_key_pem(data);
+});
@@ -0,0 +1,9 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use x25519_dalek::{PublicKey, StaticSecret};
+
+fuzz_target!(|data: &str| {
+    let _ = PublicKey::from_tor_auth_key(data);
+    let _ = StaticSecret::from_tor_auth_private(data);
+});
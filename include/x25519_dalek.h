@@ -0,0 +1,59 @@
+/*
+ * This file is part of x25519-dalek.
+ * Copyright (c) 2017-2021 isis lovecruft
+ * Copyright (c) 2019-2021 DebugSteven
+ * See LICENSE for licensing information.
+ *
+ * The C interface of x25519-dalek, built with:
+ *
+ *     cargo rustc --release --features ffi --crate-type cdylib --crate-type staticlib
+ *
+ * Every function takes pointers to 32-byte buffers and returns 0 on success
+ * or -1 on failure, in which case every output buffer which is not null is
+ * zeroed.  A null pointer is a failure.
+ */
+
+#ifndef X25519_DALEK_H
+#define X25519_DALEK_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* The length of secret keys, public keys and shared secrets. */
+#define X25519_KEY_LEN 32
+
+/*
+ * Generate a secret key with the operating system's RNG, and compute its
+ * public key.  Fails if the RNG fails.
+ */
+int x25519_keygen(uint8_t secret_out[X25519_KEY_LEN],
+                  uint8_t public_out[X25519_KEY_LEN]);
+
+/* Compute the public key of a secret key. */
+int x25519_public_from_secret(uint8_t public_out[X25519_KEY_LEN],
+                              const uint8_t secret[X25519_KEY_LEN]);
+
+/*
+ * Compute the shared secret of a secret key and a peer's public key.  As
+ * with libsodium's crypto_scalarmult, fails if the shared secret is all
+ * zeros, from a public key of small order.
+ */
+int x25519_dh(uint8_t shared_out[X25519_KEY_LEN],
+              const uint8_t secret[X25519_KEY_LEN],
+              const uint8_t their_public[X25519_KEY_LEN]);
+
+/*
+ * Zero len bytes at buf, in a way which the compiler will not remove.  Does
+ * nothing if buf is null.
+ */
+void x25519_zeroize(void *buf, size_t len);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* X25519_DALEK_H */
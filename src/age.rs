@@ -0,0 +1,115 @@
+// -*- mode: rust; -*-
+//
+// This file is part of x25519-dalek.
+// Copyright (c) 2017-2021 isis lovecruft
+// Copyright (c) 2019-2021 DebugSteven
+// See LICENSE for licensing information.
+//
+// Authors:
+// - isis agora lovecruft <isis@patternsinthevoid.net>
+// - DebugSteven <debugsteven@gmail.com>
+
+//! Bech32 encoding of X25519 keys as [age](https://age-encryption.org/v1)
+//! recipients (`age1...`) and identities (`AGE-SECRET-KEY-1...`).
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+
+use bech32::{FromBase32, ToBase32, Variant};
+use zeroize::Zeroizing;
+
+use crate::error::Error;
+use crate::x25519::PublicKey;
+
+#[cfg(feature = "static_secrets")]
+use crate::x25519::StaticSecret;
+
+/// The human-readable part of an age recipient.
+const RECIPIENT_HRP: &str = "age";
+
+/// The human-readable part of an age identity.
+#[cfg(feature = "static_secrets")]
+const IDENTITY_HRP: &str = "AGE-SECRET-KEY-";
+
+/// Errors which may occur when parsing an age recipient or identity.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum AgeKeyError {
+    /// The string is not valid Bech32, or has an invalid checksum.
+    InvalidEncoding,
+    /// The human-readable part is not `age` for a recipient or
+    /// `AGE-SECRET-KEY-` for an identity, or has the wrong case.
+    InvalidPrefix,
+    /// The encoded key is not 32 bytes.
+    InvalidLength,
+}
+
+impl fmt::Display for AgeKeyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AgeKeyError::InvalidEncoding => f.write_str("key is not valid Bech32"),
+            AgeKeyError::InvalidPrefix => f.write_str("key does not have the age prefix"),
+            AgeKeyError::InvalidLength => f.write_str("key is not 32 bytes"),
+        }
+    }
+}
+
+/// Encode a key with the given human-readable part.
+fn encode(hrp: &str, bytes: &[u8; 32]) -> String {
+    // The only possible error is an invalid human-readable part.
+    bech32::encode(hrp, bytes.to_base32(), Variant::Bech32).expect("valid age HRP")
+}
+
+/// Decode a key, checking that its human-readable part matches `hrp`
+/// exactly, including its case.
+fn decode(hrp: &str, encoded: &str) -> Result<Zeroizing<[u8; 32]>, AgeKeyError> {
+    let (_, data, variant) = bech32::decode(encoded).map_err(|_| AgeKeyError::InvalidEncoding)?;
+    if variant != Variant::Bech32 {
+        return Err(AgeKeyError::InvalidEncoding);
+    }
+    // `bech32::decode` lowercases the human-readable part, and rejects
+    // mixed-case strings, so compare against the original.
+    if !encoded.starts_with(hrp) || encoded.as_bytes().get(hrp.len()) != Some(&b'1') {
+        return Err(AgeKeyError::InvalidPrefix);
+    }
+
+    let decoded =
+        Zeroizing::new(Vec::<u8>::from_base32(&data).map_err(|_| AgeKeyError::InvalidEncoding)?);
+    let mut bytes = Zeroizing::new([0u8; 32]);
+    if decoded.len() != bytes.len() {
+        return Err(AgeKeyError::InvalidLength);
+    }
+    bytes.copy_from_slice(&decoded);
+    Ok(bytes)
+}
+
+impl PublicKey {
+    /// Encode this public key as an age recipient (`age1...`).
+    pub fn to_age_recipient(&self) -> String {
+        encode(RECIPIENT_HRP, self.as_bytes())
+    }
+
+    /// Parse a public key from an age recipient.
+    ///
+    /// As in age itself, the recipient must be lowercase.
+    pub fn from_age_recipient(recipient: &str) -> Result<PublicKey, Error> {
+        Ok(PublicKey::from(*decode(RECIPIENT_HRP, recipient)?))
+    }
+}
+
+#[cfg(feature = "static_secrets")]
+impl StaticSecret {
+    /// Encode this secret key as an age identity (`AGE-SECRET-KEY-1...`).
+    pub fn to_age_identity(&self) -> Zeroizing<String> {
+        let mut identity = Zeroizing::new(encode(IDENTITY_HRP, self.as_bytes()));
+        identity.make_ascii_uppercase();
+        identity
+    }
+
+    /// Parse a secret key from an age identity.
+    ///
+    /// As in age itself, the identity must be uppercase.
+    pub fn from_age_identity(identity: &str) -> Result<StaticSecret, Error> {
+        Ok(StaticSecret::from(*decode(IDENTITY_HRP, identity)?))
+    }
+}
@@ -0,0 +1,199 @@
+// -*- mode: rust; -*-
+//
+// This file is part of x25519-dalek.
+// Copyright (c) 2017-2021 isis lovecruft
+// Copyright (c) 2019-2021 DebugSteven
+// See LICENSE for licensing information.
+//
+// Authors:
+// - isis agora lovecruft <isis@patternsinthevoid.net>
+// - DebugSteven <debugsteven@gmail.com>
+
+//! Key agreement in the shape of `ring::agreement`, for migrating code
+//! written against ring.
+//!
+//! The types and functions mirror ring 0.16's, with X25519 as the only
+//! algorithm, so that migrating is mostly a matter of replacing
+//! `ring::agreement` with `x25519_dalek::agreement`.  Keys are generated
+//! from a `rand_core` RNG instead of a `ring::rand::SecureRandom`.
+//!
+//! ```
+//! use rand_core::OsRng;
+//! use x25519_dalek::agreement::{self, EphemeralPrivateKey, UnparsedPublicKey, X25519};
+//!
+//! fn kdf(shared: &[u8]) -> Result<[u8; 32], agreement::Unspecified> {
+//!     shared.try_into().map_err(|_| agreement::Unspecified)
+//! }
+//!
+//! let alice = EphemeralPrivateKey::generate(&X25519, OsRng)?;
+//! let bob = EphemeralPrivateKey::generate(&X25519, OsRng)?;
+//! let alice_public = UnparsedPublicKey::new(&X25519, alice.compute_public_key()?);
+//! let bob_public = UnparsedPublicKey::new(&X25519, bob.compute_public_key()?);
+//!
+//! let alice_key = agreement::agree_ephemeral(alice, &bob_public, agreement::Unspecified, kdf)?;
+//! let bob_key = agreement::agree_ephemeral(bob, &alice_public, agreement::Unspecified, kdf)?;
+//! assert_eq!(alice_key, bob_key);
+//! # Ok::<(), agreement::Unspecified>(())
+//! ```
+
+use core::fmt;
+
+use rand_core::{CryptoRng, RngCore};
+
+use crate::x25519::{self, EphemeralSecret};
+
+/// A key agreement algorithm.
+#[derive(PartialEq, Eq)]
+pub struct Algorithm {
+    name: &'static str,
+}
+
+impl fmt::Debug for Algorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name)
+    }
+}
+
+/// X25519, as specified in RFC7748.
+pub static X25519: Algorithm = Algorithm { name: "X25519" };
+
+/// An error with no details, as `ring::error::Unspecified`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Unspecified;
+
+impl fmt::Display for Unspecified {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Unspecified")
+    }
+}
+
+#[cfg(feature = "core_error")]
+impl core::error::Error for Unspecified {}
+
+/// An ephemeral private key, which can be used for one key agreement.
+pub struct EphemeralPrivateKey {
+    secret: EphemeralSecret,
+    algorithm: &'static Algorithm,
+}
+
+impl EphemeralPrivateKey {
+    /// Generate a new ephemeral private key for `alg` with `csprng`.
+    pub fn generate<T: RngCore + CryptoRng>(
+        alg: &'static Algorithm,
+        csprng: T,
+    ) -> Result<Self, Unspecified> {
+        let secret = EphemeralSecret::try_random_from_rng(csprng).map_err(|_| Unspecified)?;
+        Ok(EphemeralPrivateKey {
+            secret,
+            algorithm: alg,
+        })
+    }
+
+    /// Compute the public key of this private key.
+    pub fn compute_public_key(&self) -> Result<PublicKey, Unspecified> {
+        Ok(PublicKey {
+            bytes: x25519::PublicKey::from(&self.secret).to_bytes(),
+            algorithm: self.algorithm,
+        })
+    }
+
+    /// The algorithm of this private key.
+    #[inline]
+    pub fn algorithm(&self) -> &'static Algorithm {
+        self.algorithm
+    }
+}
+
+impl fmt::Debug for EphemeralPrivateKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EphemeralPrivateKey")
+            .field("algorithm", self.algorithm)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A public key, as computed from an [`EphemeralPrivateKey`].
+#[derive(Clone, Copy)]
+pub struct PublicKey {
+    bytes: [u8; 32],
+    algorithm: &'static Algorithm,
+}
+
+impl PublicKey {
+    /// The algorithm of this public key.
+    #[inline]
+    pub fn algorithm(&self) -> &'static Algorithm {
+        self.algorithm
+    }
+}
+
+impl AsRef<[u8]> for PublicKey {
+    fn as_ref(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+impl fmt::Debug for PublicKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PublicKey")
+            .field("algorithm", self.algorithm)
+            .field("bytes", &self.bytes)
+            .finish()
+    }
+}
+
+/// A peer's public key, whose encoding is only checked by
+/// [`agree_ephemeral`].
+#[derive(Clone, Copy, Debug)]
+pub struct UnparsedPublicKey<B: AsRef<[u8]>> {
+    algorithm: &'static Algorithm,
+    bytes: B,
+}
+
+impl<B: AsRef<[u8]>> UnparsedPublicKey<B> {
+    /// Wrap the encoding `bytes` of a public key for `algorithm`.
+    pub fn new(algorithm: &'static Algorithm, bytes: B) -> Self {
+        UnparsedPublicKey { algorithm, bytes }
+    }
+
+    /// The algorithm of this public key.
+    #[inline]
+    pub fn algorithm(&self) -> &'static Algorithm {
+        self.algorithm
+    }
+
+    /// The encoding of this public key.
+    #[inline]
+    pub fn bytes(&self) -> &B {
+        &self.bytes
+    }
+}
+
+/// Perform a key agreement between `my_private_key` and `peer_public_key`,
+/// and pass the shared secret to `kdf`, returning its result.
+///
+/// As with ring, returns `error_value` without calling `kdf` if the
+/// algorithms of the keys differ, if the peer's public key is not 32 bytes,
+/// or if the shared secret is all zeros, from a public key of small order.
+pub fn agree_ephemeral<B: AsRef<[u8]>, F, R, E>(
+    my_private_key: EphemeralPrivateKey,
+    peer_public_key: &UnparsedPublicKey<B>,
+    error_value: E,
+    kdf: F,
+) -> Result<R, E>
+where
+    F: FnOnce(&[u8]) -> Result<R, E>,
+{
+    if peer_public_key.algorithm != my_private_key.algorithm {
+        return Err(error_value);
+    }
+    let peer = match x25519::PublicKey::try_from(peer_public_key.bytes.as_ref()) {
+        Ok(peer) => peer,
+        Err(_) => return Err(error_value),
+    };
+    let shared = my_private_key.secret.diffie_hellman(&peer);
+    if !shared.was_contributory() {
+        return Err(error_value);
+    }
+    kdf(shared.as_bytes())
+}
@@ -0,0 +1,255 @@
+// -*- mode: rust; -*-
+//
+// This file is part of x25519-dalek.
+// Copyright (c) 2017-2021 isis lovecruft
+// Copyright (c) 2019-2021 DebugSteven
+// See LICENSE for licensing information.
+//
+// Authors:
+// - isis agora lovecruft <isis@patternsinthevoid.net>
+// - DebugSteven <debugsteven@gmail.com>
+
+//! ASN.1 DER encodings of X25519 keys, as specified in
+//! [RFC8410](https://tools.ietf.org/html/rfc8410).
+
+use pkcs8::der::asn1::OctetStringRef;
+use pkcs8::der::AnyRef;
+use pkcs8::der::Decode;
+use pkcs8::spki::{AlgorithmIdentifierRef, AssociatedAlgorithmIdentifier, SubjectPublicKeyInfoRef};
+use pkcs8::{DecodePrivateKey, ObjectIdentifier, PrivateKeyInfo};
+
+#[cfg(feature = "alloc")]
+use pkcs8::{
+    der::asn1::BitStringRef, der::zeroize::Zeroize, der::Encode, spki::EncodePublicKey, Document,
+    EncodePrivateKey, SecretDocument,
+};
+
+#[cfg(feature = "pkcs8_encryption")]
+use pkcs8::{
+    pkcs5::{pbes2::Kdf, EncryptionScheme},
+    EncryptedPrivateKeyInfo,
+};
+
+use crate::x25519::{PublicKey, StaticSecret};
+
+/// The `id-X25519` object identifier, `1.3.101.110`.
+pub(crate) const ALGORITHM_OID: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.3.101.110");
+
+/// The `AlgorithmIdentifier` for X25519 keys.  RFC8410 requires the
+/// parameters to be absent.
+pub(crate) const ALGORITHM_ID: AlgorithmIdentifierRef<'static> = AlgorithmIdentifierRef {
+    oid: ALGORITHM_OID,
+    parameters: None,
+};
+
+/// Length of the DER encoding of a `CurvePrivateKey`: an `OCTET STRING`
+/// header followed by the 32 bytes of the secret key.
+#[cfg(feature = "alloc")]
+const CURVE_PRIVATE_KEY_LEN: usize = 34;
+
+/// The largest PBKDF2 iteration count accepted when decrypting, a thousand
+/// times OpenSSL's default.
+#[cfg(feature = "pkcs8_encryption")]
+const MAX_PBKDF2_ITERATIONS: u32 = 2_048_000;
+
+/// The largest scrypt memory size accepted when decrypting, `128 * r * N`
+/// bytes: 1 GiB.
+#[cfg(feature = "pkcs8_encryption")]
+const MAX_SCRYPT_MEMORY: u128 = 1 << 30;
+
+/// The largest scrypt parallelism accepted when decrypting.
+#[cfg(feature = "pkcs8_encryption")]
+const MAX_SCRYPT_PARALLELISM: u16 = 16;
+
+/// Check that `algorithm` identifies X25519 with absent parameters.
+fn check_algorithm(algorithm: &AlgorithmIdentifierRef<'_>) -> pkcs8::Result<()> {
+    if algorithm.oid != ALGORITHM_OID {
+        return Err(pkcs8::Error::KeyMalformed);
+    }
+    if algorithm.parameters.is_some() {
+        return Err(pkcs8::Error::ParametersMalformed);
+    }
+    Ok(())
+}
+
+/// Decode the `CurvePrivateKey` wrapped inside a PKCS#8 `privateKey` field.
+fn decode_curve_private_key(private_key: &[u8]) -> pkcs8::Result<[u8; 32]> {
+    let octets = OctetStringRef::from_der(private_key)?;
+    octets
+        .as_bytes()
+        .try_into()
+        .map_err(|_| pkcs8::Error::KeyMalformed)
+}
+
+/// Encode `secret` as a `CurvePrivateKey` into `out`.
+#[cfg(feature = "alloc")]
+fn encode_curve_private_key(
+    secret: &[u8; 32],
+    out: &mut [u8; CURVE_PRIVATE_KEY_LEN],
+) -> pkcs8::Result<()> {
+    OctetStringRef::new(secret)?.encode_to_slice(out)?;
+    Ok(())
+}
+
+/// Encode `secret` as a PKCS#8 document.  If `public` is given, a v2
+/// (`OneAsymmetricKey`) document carrying the public key is produced.
+#[cfg(feature = "alloc")]
+fn encode_private_key_info(
+    secret: &StaticSecret,
+    public: Option<&PublicKey>,
+) -> pkcs8::Result<SecretDocument> {
+    // Encode the `CurvePrivateKey` on the stack so that we can wipe it
+    // once it has been copied into the (self-zeroizing) document.
+    let mut curve_private_key = [0u8; CURVE_PRIVATE_KEY_LEN];
+    let result =
+        encode_curve_private_key(secret.as_bytes(), &mut curve_private_key).and_then(|_| {
+            let mut info = PrivateKeyInfo::new(ALGORITHM_ID, &curve_private_key);
+            info.public_key = public.map(PublicKey::as_bytes).map(|bytes| &bytes[..]);
+            Ok(SecretDocument::encode_msg(&info)?)
+        });
+    curve_private_key.zeroize();
+    result
+}
+
+/// Decode a PKCS#8 v1 or v2 private key.  See
+/// [`StaticSecret::from_pkcs8_der`].
+fn decode_private_key_info(private_key: PrivateKeyInfo<'_>) -> pkcs8::Result<StaticSecret> {
+    check_algorithm(&private_key.algorithm)?;
+    let secret = decode_curve_private_key(private_key.private_key).map(StaticSecret::from)?;
+
+    if let Some(public_key) = private_key.public_key {
+        if public_key != PublicKey::from(&secret).as_bytes() {
+            return Err(pkcs8::Error::KeyMalformed);
+        }
+    }
+
+    Ok(secret)
+}
+
+// Implemented directly, rather than through `TryFrom<PrivateKeyInfo>` and
+// the blanket implementation, so that decrypting cannot bypass the key
+// derivation limits of `from_pkcs8_encrypted_der`.
+impl DecodePrivateKey for StaticSecret {
+    /// Decode a PKCS#8 v1 or v2 private key.
+    ///
+    /// If the document carries a public key (v2), it must match the public
+    /// key derived from the secret key, otherwise an error is returned.
+    fn from_pkcs8_der(bytes: &[u8]) -> pkcs8::Result<Self> {
+        decode_private_key_info(PrivateKeyInfo::try_from(bytes)?)
+    }
+
+    /// Decrypt a secret key from an encrypted PKCS#8 document with
+    /// `password`.
+    ///
+    /// The key derivation parameters are read from `bytes`, which PBES2 does
+    /// not authenticate, so they are limited to about two million PBKDF2
+    /// iterations, a thousand times OpenSSL's default, and to 1 GiB of
+    /// memory and a parallelism of 16 for scrypt, which bound the memory and
+    /// time a document from an untrusted source can make this function use.
+    /// Documents beyond these limits are rejected with
+    /// `pkcs5::Error::AlgorithmParametersInvalid`.  `from_pkcs8_encrypted_pem`
+    /// decrypts through this method, and so has the same limits.
+    #[cfg(feature = "pkcs8_encryption")]
+    fn from_pkcs8_encrypted_der(bytes: &[u8], password: impl AsRef<[u8]>) -> pkcs8::Result<Self> {
+        let info = EncryptedPrivateKeyInfo::try_from(bytes)?;
+        check_kdf_costs(&info)?;
+        let doc = info.decrypt(password)?;
+        StaticSecret::from_pkcs8_der(doc.as_bytes())
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl EncodePrivateKey for StaticSecret {
+    /// Encode this key as a PKCS#8 v1 document, which does not include the
+    /// public key.  See [`StaticSecret::to_pkcs8_v2_der`] for the v2 format.
+    fn to_pkcs8_der(&self) -> pkcs8::Result<SecretDocument> {
+        encode_private_key_info(self, None)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl StaticSecret {
+    /// Encode this key as a PKCS#8 v2 (`OneAsymmetricKey`, RFC5958) document,
+    /// which also carries the corresponding public key.
+    pub fn to_pkcs8_v2_der(&self) -> pkcs8::Result<SecretDocument> {
+        encode_private_key_info(self, Some(&PublicKey::from(self)))
+    }
+
+    /// Encode this key as a PEM-armored PKCS#8 v2 document.  See
+    /// [`StaticSecret::to_pkcs8_v2_der`].
+    #[cfg(feature = "pem")]
+    pub fn to_pkcs8_v2_pem(
+        &self,
+        line_ending: pkcs8::LineEnding,
+    ) -> pkcs8::Result<pkcs8::der::zeroize::Zeroizing<alloc::string::String>> {
+        use pkcs8::der::pem::PemLabel;
+
+        let doc = self.to_pkcs8_v2_der()?;
+        Ok(doc.to_pem(PrivateKeyInfo::PEM_LABEL, line_ending)?)
+    }
+}
+
+/// Check the key derivation costs of an encrypted PKCS#8 document against
+/// [`MAX_PBKDF2_ITERATIONS`], [`MAX_SCRYPT_MEMORY`] and
+/// [`MAX_SCRYPT_PARALLELISM`], before any key is derived.
+#[cfg(feature = "pkcs8_encryption")]
+fn check_kdf_costs(info: &EncryptedPrivateKeyInfo<'_>) -> pkcs8::Result<()> {
+    let kdf = match &info.encryption_algorithm {
+        EncryptionScheme::Pbes2(params) => &params.kdf,
+        // Only PBES2 can be decrypted, and anything else fails at no cost.
+        _ => return Ok(()),
+    };
+    let within_limits = match kdf {
+        Kdf::Pbkdf2(params) => params.iteration_count <= MAX_PBKDF2_ITERATIONS,
+        Kdf::Scrypt(params) => {
+            128 * u128::from(params.block_size) * u128::from(params.cost_parameter)
+                <= MAX_SCRYPT_MEMORY
+                && params.parallelization <= MAX_SCRYPT_PARALLELISM
+        }
+        _ => false,
+    };
+    if !within_limits {
+        return Err(kdf.to_alg_params_invalid().into());
+    }
+    Ok(())
+}
+
+impl AssociatedAlgorithmIdentifier for PublicKey {
+    type Params = AnyRef<'static>;
+
+    const ALGORITHM_IDENTIFIER: AlgorithmIdentifierRef<'static> = ALGORITHM_ID;
+}
+
+impl AssociatedAlgorithmIdentifier for StaticSecret {
+    type Params = AnyRef<'static>;
+
+    const ALGORITHM_IDENTIFIER: AlgorithmIdentifierRef<'static> = ALGORITHM_ID;
+}
+
+impl TryFrom<SubjectPublicKeyInfoRef<'_>> for PublicKey {
+    type Error = pkcs8::spki::Error;
+
+    fn try_from(public_key: SubjectPublicKeyInfoRef<'_>) -> pkcs8::spki::Result<Self> {
+        public_key.algorithm.assert_algorithm_oid(ALGORITHM_OID)?;
+        if public_key.algorithm.parameters.is_some() {
+            return Err(pkcs8::spki::Error::KeyMalformed);
+        }
+        let bytes: [u8; 32] = public_key
+            .subject_public_key
+            .as_bytes()
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or(pkcs8::spki::Error::KeyMalformed)?;
+        Ok(PublicKey::from(bytes))
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl EncodePublicKey for PublicKey {
+    fn to_public_key_der(&self) -> pkcs8::spki::Result<Document> {
+        let public_key = SubjectPublicKeyInfoRef {
+            algorithm: ALGORITHM_ID,
+            subject_public_key: BitStringRef::from_bytes(self.as_bytes())?,
+        };
+        Ok(Document::encode_msg(&public_key)?)
+    }
+}
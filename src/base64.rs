@@ -0,0 +1,81 @@
+// -*- mode: rust; -*-
+//
+// This file is part of x25519-dalek.
+// Copyright (c) 2017-2021 isis lovecruft
+// Copyright (c) 2019-2021 DebugSteven
+// See LICENSE for licensing information.
+//
+// Authors:
+// - isis agora lovecruft <isis@patternsinthevoid.net>
+// - DebugSteven <debugsteven@gmail.com>
+
+//! Standard, padded base64 encoding of X25519 keys, as specified in
+//! [RFC4648](https://tools.ietf.org/html/rfc4648#section-4).
+//!
+//! Decoding is constant-time with respect to the key material.
+
+use alloc::string::String;
+use core::fmt;
+
+use base64ct::{Base64, Encoding};
+use zeroize::Zeroizing;
+
+use crate::error::Error;
+use crate::x25519::PublicKey;
+
+#[cfg(feature = "static_secrets")]
+use crate::x25519::StaticSecret;
+
+/// Errors which may occur when decoding a base64 key.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Base64Error {
+    /// The input is not the padded base64 encoding of 32 bytes.
+    InvalidLength,
+    /// The input contains characters outside of the standard base64
+    /// alphabet, incorrect padding, or non-zero trailing bits.
+    InvalidEncoding,
+}
+
+impl fmt::Display for Base64Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Base64Error::InvalidLength => f.write_str("key is not 44 base64 characters"),
+            Base64Error::InvalidEncoding => f.write_str("key is not valid base64"),
+        }
+    }
+}
+
+/// Decode the padded standard base64 encoding of exactly 32 bytes.
+fn decode(encoded: &str) -> Result<Zeroizing<[u8; 32]>, Base64Error> {
+    if encoded.len() != 44 {
+        return Err(Base64Error::InvalidLength);
+    }
+    let mut bytes = Zeroizing::new([0u8; 32]);
+    Base64::decode(encoded, &mut bytes[..]).map_err(|_| Base64Error::InvalidEncoding)?;
+    Ok(bytes)
+}
+
+impl PublicKey {
+    /// Encode this public key as standard, padded base64.
+    pub fn to_base64(&self) -> String {
+        Base64::encode_string(self.as_bytes())
+    }
+
+    /// Decode a public key from standard, padded base64.
+    pub fn from_base64(encoded: &str) -> Result<PublicKey, Error> {
+        Ok(PublicKey::from(*decode(encoded)?))
+    }
+}
+
+#[cfg(feature = "static_secrets")]
+impl StaticSecret {
+    /// Encode this secret key as standard, padded base64.
+    pub fn to_base64(&self) -> Zeroizing<String> {
+        Zeroizing::new(Base64::encode_string(self.as_bytes()))
+    }
+
+    /// Decode a secret key from standard, padded base64, in constant time.
+    pub fn from_base64(encoded: &str) -> Result<StaticSecret, Error> {
+        Ok(StaticSecret::from(*decode(encoded)?))
+    }
+}
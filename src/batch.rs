@@ -0,0 +1,91 @@
+// -*- mode: rust; -*-
+//
+// This file is part of x25519-dalek.
+// Copyright (c) 2017-2021 isis lovecruft
+// Copyright (c) 2019-2021 DebugSteven
+// See LICENSE for licensing information.
+//
+// Authors:
+// - isis agora lovecruft <isis@patternsinthevoid.net>
+// - DebugSteven <debugsteven@gmail.com>
+
+//! Diffie-Hellman between one secret key and many public keys, with a
+//! single field inversion.
+//!
+//! `MontgomeryPoint::mul_clamped` converts each result out of projective
+//! coordinates with an inversion of its own.  Here the ladders of
+//! [`crate::ladder`] keep their results projective, and these are inverted
+//! together with Montgomery's trick, at the cost of one inversion and three
+//! multiplications per public key.
+
+use alloc::vec::Vec;
+
+use curve25519_dalek::montgomery::MontgomeryPoint;
+use curve25519_dalek::scalar::clamp_integer;
+use subtle::{Choice, ConditionallySelectable, ConstantTimeEq};
+use zeroize::Zeroizing;
+
+use crate::field::FieldElement;
+use crate::ladder::Ladder;
+use crate::x25519::{PublicKey, SharedSecret, StaticSecret};
+
+impl StaticSecret {
+    /// Perform a Diffie-Hellman key agreement between `self` and each of
+    /// `their_publics`, producing the same shared secrets as
+    /// [`StaticSecret::diffie_hellman`] in the same order, with one field
+    /// inversion for the whole batch rather than one per key agreement.
+    pub fn diffie_hellman_batch(&self, their_publics: &[PublicKey]) -> Vec<SharedSecret> {
+        let scalar = Zeroizing::new(clamp_integer(self.0));
+
+        let mut xs = Zeroizing::new(Vec::with_capacity(their_publics.len()));
+        let mut zs = Zeroizing::new(Vec::with_capacity(their_publics.len()));
+        for their_public in their_publics {
+            let (x, z) = Ladder::new(their_public.as_bytes()).mul_clamped(&scalar);
+            xs.push(x);
+            zs.push(z);
+        }
+
+        // The point at infinity has Z = 0, which would zero every inverse,
+        // so it is inverted as 1 and its result then replaced with zero, the
+        // result of `MontgomeryPoint::mul_clamped`.
+        let zero = FieldElement::from_u32(0);
+        let one = FieldElement::from_u32(1);
+        let at_infinity: Vec<Choice> = zs
+            .iter_mut()
+            .map(|z| {
+                let is_zero = z.ct_eq(&zero);
+                z.conditional_assign(&one, is_zero);
+                is_zero
+            })
+            .collect();
+        batch_invert(&mut zs);
+
+        xs.iter()
+            .zip(zs.iter())
+            .zip(at_infinity)
+            .map(|((x, z_inv), at_infinity)| {
+                let u = FieldElement::conditional_select(&(x * z_inv), &zero, at_infinity);
+                SharedSecret(MontgomeryPoint(u.to_bytes()))
+            })
+            .collect()
+    }
+}
+
+/// Replace each of the nonzero `elements` with its inverse, using a single
+/// field inversion.
+fn batch_invert(elements: &mut [FieldElement]) {
+    // prefixes[i] is the product of the elements before the i-th.
+    let mut prefixes = Zeroizing::new(Vec::with_capacity(elements.len()));
+    let mut acc = FieldElement::from_u32(1);
+    for element in elements.iter() {
+        prefixes.push(acc);
+        acc = &acc * element;
+    }
+
+    let mut inv = acc.invert();
+    for (element, prefix) in elements.iter_mut().zip(prefixes.iter()).rev() {
+        let element_inv = &inv * prefix;
+        inv = &inv * element;
+        *element = element_inv;
+    }
+}
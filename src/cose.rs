@@ -0,0 +1,318 @@
+// -*- mode: rust; -*-
+//
+// This file is part of x25519-dalek.
+// Copyright (c) 2017-2021 isis lovecruft
+// Copyright (c) 2019-2021 DebugSteven
+// See LICENSE for licensing information.
+//
+// Authors:
+// - isis agora lovecruft <isis@patternsinthevoid.net>
+// - DebugSteven <debugsteven@gmail.com>
+
+//! COSE_Key encoding of X25519 keys, as specified in
+//! [RFC9052](https://tools.ietf.org/html/rfc9052) and
+//! [RFC9053](https://tools.ietf.org/html/rfc9053).
+//!
+//! Keys are encoded as deterministically-encoded CBOR maps, and only the
+//! small subset of CBOR needed to read such maps is implemented here.
+
+use core::fmt;
+
+use crate::error::Error;
+use crate::x25519::PublicKey;
+
+#[cfg(feature = "static_secrets")]
+use crate::x25519::StaticSecret;
+
+#[cfg(feature = "static_secrets")]
+use zeroize::Zeroizing;
+
+/// The `kty` label.
+const LABEL_KTY: i64 = 1;
+/// The `crv` label for OKP keys.
+const LABEL_CRV: i64 = -1;
+/// The `x` (public key) label for OKP keys.
+const LABEL_X: i64 = -2;
+/// The `d` (private key) label for OKP keys.
+const LABEL_D: i64 = -4;
+
+/// The `kty` value for octet key pairs.
+const KTY_OKP: u64 = 1;
+/// The `crv` value for X25519.
+const CRV_X25519: u64 = 4;
+
+/// The CBOR major types used by COSE_Key maps.
+const MAJOR_UNSIGNED: u8 = 0;
+const MAJOR_NEGATIVE: u8 = 1;
+const MAJOR_BYTES: u8 = 2;
+const MAJOR_TEXT: u8 = 3;
+const MAJOR_ARRAY: u8 = 4;
+const MAJOR_MAP: u8 = 5;
+const MAJOR_TAG: u8 = 6;
+
+/// How deeply nested the values of unknown parameters may be.
+const MAX_DEPTH: usize = 8;
+
+/// The length of a public COSE_Key, as produced by [`PublicKey::to_cose_key`].
+const PUBLIC_COSE_KEY_LEN: usize = 40;
+
+/// The length of a private COSE_Key, as produced by `StaticSecret::to_cose_key`.
+#[cfg(feature = "static_secrets")]
+const PRIVATE_COSE_KEY_LEN: usize = 75;
+
+/// Errors which may occur when decoding a COSE_Key into a key.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CoseError {
+    /// The input is not a well-formed COSE_Key map, or a required parameter
+    /// is missing, duplicated or has the wrong length.
+    Malformed,
+    /// The `kty` parameter is not `OKP` (1).
+    UnsupportedKeyType,
+    /// The `crv` parameter is not `X25519` (4).
+    UnsupportedCurve,
+    /// The `d` parameter is required but missing.
+    MissingPrivateKey,
+    /// The `x` parameter does not match the public key derived from `d`.
+    PublicKeyMismatch,
+}
+
+impl fmt::Display for CoseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CoseError::Malformed => f.write_str("malformed COSE_Key"),
+            CoseError::UnsupportedKeyType => f.write_str("COSE_Key type is not OKP"),
+            CoseError::UnsupportedCurve => f.write_str("COSE_Key curve is not X25519"),
+            CoseError::MissingPrivateKey => f.write_str("COSE_Key is missing the private key"),
+            CoseError::PublicKeyMismatch => {
+                f.write_str("COSE_Key public key does not match the private key")
+            }
+        }
+    }
+}
+
+/// The parameters of an X25519 COSE_Key.
+struct CoseKey<'a> {
+    x: Option<&'a [u8; 32]>,
+    #[cfg_attr(not(feature = "static_secrets"), allow(dead_code))]
+    d: Option<&'a [u8; 32]>,
+}
+
+impl<'a> CoseKey<'a> {
+    /// Parse a COSE_Key map, checking its `kty` and `crv` parameters.
+    ///
+    /// Parameters other than `kty`, `crv`, `x` and `d` are skipped.
+    fn decode(bytes: &'a [u8]) -> Result<CoseKey<'a>, CoseError> {
+        let mut reader = Reader { bytes };
+        let entries = reader.expect(MAJOR_MAP)?;
+
+        let mut kty = None;
+        let mut crv = None;
+        let mut x = None;
+        let mut d = None;
+
+        for _ in 0..entries {
+            let slot = match reader.label()? {
+                Some(LABEL_KTY) => &mut kty,
+                Some(LABEL_CRV) => &mut crv,
+                Some(LABEL_X) => &mut x,
+                Some(LABEL_D) => &mut d,
+                _ => {
+                    reader.skip(MAX_DEPTH)?;
+                    continue;
+                }
+            };
+            if slot.is_some() {
+                return Err(CoseError::Malformed);
+            }
+            *slot = Some(reader.item()?);
+        }
+
+        if !reader.bytes.is_empty() {
+            return Err(CoseError::Malformed);
+        }
+
+        match kty.ok_or(CoseError::Malformed)? {
+            (MAJOR_UNSIGNED, KTY_OKP, _) => {}
+            _ => return Err(CoseError::UnsupportedKeyType),
+        }
+        match crv.ok_or(CoseError::Malformed)? {
+            (MAJOR_UNSIGNED, CRV_X25519, _) => {}
+            _ => return Err(CoseError::UnsupportedCurve),
+        }
+
+        Ok(CoseKey {
+            x: x.map(key_bytes).transpose()?,
+            d: d.map(key_bytes).transpose()?,
+        })
+    }
+}
+
+/// Extract the 32 bytes of an `x` or `d` parameter.
+fn key_bytes(item: (u8, u64, &[u8])) -> Result<&[u8; 32], CoseError> {
+    match item {
+        (MAJOR_BYTES, _, bytes) => bytes.try_into().map_err(|_| CoseError::Malformed),
+        _ => Err(CoseError::Malformed),
+    }
+}
+
+/// A reader over definite-length CBOR data items.
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], CoseError> {
+        if len > self.bytes.len() {
+            return Err(CoseError::Malformed);
+        }
+        let (head, tail) = self.bytes.split_at(len);
+        self.bytes = tail;
+        Ok(head)
+    }
+
+    /// Read the initial byte and argument of a data item, returning its major
+    /// type and argument.  Indefinite-length items are rejected.
+    fn head(&mut self) -> Result<(u8, u64), CoseError> {
+        let initial = self.take(1)?[0];
+        let len = match initial & 0x1f {
+            info @ 0..=23 => return Ok((initial >> 5, info as u64)),
+            24 => 1,
+            25 => 2,
+            26 => 4,
+            27 => 8,
+            _ => return Err(CoseError::Malformed),
+        };
+        let argument = self
+            .take(len)?
+            .iter()
+            .fold(0u64, |acc, &byte| (acc << 8) | byte as u64);
+        Ok((initial >> 5, argument))
+    }
+
+    /// Read the head of a data item of the given major type.
+    fn expect(&mut self, major: u8) -> Result<u64, CoseError> {
+        match self.head()? {
+            (m, argument) if m == major => Ok(argument),
+            _ => Err(CoseError::Malformed),
+        }
+    }
+
+    /// Read a map label, returning `None` for text string labels.
+    fn label(&mut self) -> Result<Option<i64>, CoseError> {
+        match self.head()? {
+            (MAJOR_UNSIGNED, n) => i64::try_from(n).map(Some).map_err(|_| CoseError::Malformed),
+            (MAJOR_NEGATIVE, n) => i64::try_from(n)
+                .map(|n| Some(-1 - n))
+                .map_err(|_| CoseError::Malformed),
+            (MAJOR_TEXT, len) => {
+                self.take_argument(len)?;
+                Ok(None)
+            }
+            _ => Err(CoseError::Malformed),
+        }
+    }
+
+    /// Read a parameter value, returning its major type, argument and, for
+    /// byte and text strings, its contents.
+    fn item(&mut self) -> Result<(u8, u64, &'a [u8]), CoseError> {
+        let (major, argument) = self.head()?;
+        match major {
+            MAJOR_UNSIGNED | MAJOR_NEGATIVE => Ok((major, argument, &[])),
+            MAJOR_BYTES | MAJOR_TEXT => Ok((major, argument, self.take_argument(argument)?)),
+            _ => Err(CoseError::Malformed),
+        }
+    }
+
+    /// Skip over a single data item of any type.
+    fn skip(&mut self, depth: usize) -> Result<(), CoseError> {
+        let depth = depth.checked_sub(1).ok_or(CoseError::Malformed)?;
+        let (major, argument) = self.head()?;
+        let items = match major {
+            MAJOR_BYTES | MAJOR_TEXT => {
+                self.take_argument(argument)?;
+                0
+            }
+            MAJOR_ARRAY => argument,
+            MAJOR_MAP => argument.checked_mul(2).ok_or(CoseError::Malformed)?,
+            MAJOR_TAG => 1,
+            // Integers and simple values are fully described by their head.
+            _ => 0,
+        };
+        for _ in 0..items {
+            self.skip(depth)?;
+        }
+        Ok(())
+    }
+
+    fn take_argument(&mut self, len: u64) -> Result<&'a [u8], CoseError> {
+        self.take(usize::try_from(len).map_err(|_| CoseError::Malformed)?)
+    }
+}
+
+/// Write the `kty`, `crv` and `x` parameters of a COSE_Key map with the given
+/// number of entries.
+fn encode_public(entries: u8, public: &PublicKey, out: &mut [u8]) {
+    out[..8].copy_from_slice(&[
+        (MAJOR_MAP << 5) | entries,
+        LABEL_KTY as u8,
+        KTY_OKP as u8,
+        (MAJOR_NEGATIVE << 5) | (-1 - LABEL_CRV) as u8,
+        CRV_X25519 as u8,
+        (MAJOR_NEGATIVE << 5) | (-1 - LABEL_X) as u8,
+        (MAJOR_BYTES << 5) | 24,
+        32,
+    ]);
+    out[8..PUBLIC_COSE_KEY_LEN].copy_from_slice(public.as_bytes());
+}
+
+impl PublicKey {
+    /// Encode this public key as a deterministically-encoded COSE_Key.
+    pub fn to_cose_key(&self) -> [u8; PUBLIC_COSE_KEY_LEN] {
+        let mut out = [0u8; PUBLIC_COSE_KEY_LEN];
+        encode_public(3, self, &mut out);
+        out
+    }
+
+    /// Decode a public key from a COSE_Key.
+    ///
+    /// A private COSE_Key is accepted, in which case only its public part is
+    /// used.  The `x` parameter must be present.
+    pub fn from_cose_key(bytes: &[u8]) -> Result<PublicKey, Error> {
+        let key = CoseKey::decode(bytes)?;
+        let x = key.x.ok_or(CoseError::Malformed)?;
+        Ok(PublicKey::from(*x))
+    }
+}
+
+#[cfg(feature = "static_secrets")]
+impl StaticSecret {
+    /// Encode this secret key, together with its public key, as a
+    /// deterministically-encoded private COSE_Key.
+    pub fn to_cose_key(&self) -> Zeroizing<[u8; PRIVATE_COSE_KEY_LEN]> {
+        let mut out = Zeroizing::new([0u8; PRIVATE_COSE_KEY_LEN]);
+        encode_public(4, &PublicKey::from(self), &mut *out);
+        out[PUBLIC_COSE_KEY_LEN..PUBLIC_COSE_KEY_LEN + 3].copy_from_slice(&[
+            (MAJOR_NEGATIVE << 5) | (-1 - LABEL_D) as u8,
+            (MAJOR_BYTES << 5) | 24,
+            32,
+        ]);
+        out[PUBLIC_COSE_KEY_LEN + 3..].copy_from_slice(self.as_bytes());
+        out
+    }
+
+    /// Decode a secret key from a private COSE_Key.
+    ///
+    /// The `x` parameter is optional, but if present it must match the public
+    /// key derived from the `d` parameter.
+    pub fn from_cose_key(bytes: &[u8]) -> Result<StaticSecret, Error> {
+        let key = CoseKey::decode(bytes)?;
+        let secret = StaticSecret::from(*key.d.ok_or(CoseError::MissingPrivateKey)?);
+
+        match key.x {
+            Some(x) if PublicKey::from(&secret).as_bytes() != x => {
+                Err(CoseError::PublicKeyMismatch.into())
+            }
+            _ => Ok(secret),
+        }
+    }
+}
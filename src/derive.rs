@@ -0,0 +1,46 @@
+// -*- mode: rust; -*-
+//
+// This file is part of x25519-dalek.
+// Copyright (c) 2017-2021 isis lovecruft
+// Copyright (c) 2019-2021 DebugSteven
+// See LICENSE for licensing information.
+//
+// Authors:
+// - isis agora lovecruft <isis@patternsinthevoid.net>
+// - DebugSteven <debugsteven@gmail.com>
+
+//! Deterministic derivation of secret keys from a master seed, using
+//! HKDF-SHA256 as specified in [RFC5869](https://tools.ietf.org/html/rfc5869).
+
+use hkdf::Hkdf;
+use sha2::Sha256;
+use zeroize::Zeroize;
+
+use crate::x25519::StaticSecret;
+
+/// The HKDF salt, separating keys derived by this crate from any other use
+/// of the same seed.
+const DERIVE_FROM_SEED_SALT: &[u8] = b"x25519-dalek derive_from_seed v1";
+
+impl StaticSecret {
+    /// Deterministically derive a secret key from a master `seed` and a
+    /// `label`.
+    ///
+    /// The key is the 32-byte output of HKDF-SHA256, with the salt
+    /// `"x25519-dalek derive_from_seed v1"`, the seed as input keying
+    /// material and the label as info.  Different labels yield independent
+    /// keys, and the same seed and label always yield the same key.
+    ///
+    /// The seed should contain at least 32 bytes of entropy; this function
+    /// does not stretch low-entropy inputs such as passwords.
+    pub fn derive_from_seed(seed: &[u8], label: &[u8]) -> StaticSecret {
+        let mut bytes = [0u8; 32];
+        Hkdf::<Sha256>::new(Some(DERIVE_FROM_SEED_SALT), seed)
+            .expand(label, &mut bytes)
+            .expect("32 bytes is a valid HKDF-SHA256 output length");
+        // Clamping is done later.
+        let secret = StaticSecret::from(bytes);
+        bytes.zeroize();
+        secret
+    }
+}
@@ -0,0 +1,104 @@
+// -*- mode: rust; -*-
+//
+// This file is part of x25519-dalek.
+// Copyright (c) 2017-2021 isis lovecruft
+// Copyright (c) 2019-2021 DebugSteven
+// See LICENSE for licensing information.
+//
+// Authors:
+// - isis agora lovecruft <isis@patternsinthevoid.net>
+// - DebugSteven <debugsteven@gmail.com>
+
+//! `did:key` encoding of X25519 public keys, as specified by the
+//! [did:key method](https://w3c-ccg.github.io/did-method-key/).
+//!
+//! The key is prefixed with the `x25519-pub` multicodec (`0xec`, encoded as
+//! an unsigned varint) and encoded as base58btc multibase, so that every
+//! X25519 `did:key` starts with `did:key:z6LS`.
+
+use alloc::string::String;
+use core::fmt;
+
+use crate::error::Error;
+use crate::x25519::PublicKey;
+
+/// The `did:key` method prefix.
+const DID_KEY_PREFIX: &str = "did:key:";
+
+/// The multibase prefix for base58btc.
+const MULTIBASE_BASE58BTC: char = 'z';
+
+/// The `x25519-pub` multicodec, as an unsigned varint.
+const MULTICODEC_X25519_PUB: [u8; 2] = [0xec, 0x01];
+
+/// Errors which may occur when parsing a `did:key` into a [`PublicKey`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DidKeyError {
+    /// The identifier does not start with `did:key:`, or has a fragment
+    /// which does not name the key itself.
+    InvalidMethod,
+    /// The method-specific identifier is not base58btc multibase.
+    UnsupportedMultibase,
+    /// The method-specific identifier is not valid base58.
+    InvalidEncoding,
+    /// The multicodec is not `x25519-pub`, or the key is not 32 bytes.
+    UnsupportedMulticodec,
+}
+
+impl fmt::Display for DidKeyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DidKeyError::InvalidMethod => f.write_str("identifier is not a did:key"),
+            DidKeyError::UnsupportedMultibase => f.write_str("did:key is not base58btc"),
+            DidKeyError::InvalidEncoding => f.write_str("did:key is not valid base58"),
+            DidKeyError::UnsupportedMulticodec => f.write_str("did:key is not an X25519 key"),
+        }
+    }
+}
+
+impl PublicKey {
+    /// Encode this public key as a `did:key` identifier.
+    pub fn to_did_key(&self) -> String {
+        let mut multicodec = [0u8; 34];
+        multicodec[..2].copy_from_slice(&MULTICODEC_X25519_PUB);
+        multicodec[2..].copy_from_slice(self.as_bytes());
+
+        let mut did = String::from(DID_KEY_PREFIX);
+        did.push(MULTIBASE_BASE58BTC);
+        did.push_str(&bs58::encode(multicodec).into_string());
+        did
+    }
+
+    /// Parse a public key from a `did:key` identifier.
+    ///
+    /// A DID URL whose fragment is the key's own verification method
+    /// (`did:key:z6LS...#z6LS...`) is also accepted.
+    pub fn from_did_key(did: &str) -> Result<PublicKey, Error> {
+        let id = did
+            .strip_prefix(DID_KEY_PREFIX)
+            .ok_or(DidKeyError::InvalidMethod)?;
+        let id = match id.split_once('#') {
+            Some((id, fragment)) if id == fragment => id,
+            Some(_) => return Err(DidKeyError::InvalidMethod.into()),
+            None => id,
+        };
+        let encoded = id
+            .strip_prefix(MULTIBASE_BASE58BTC)
+            .ok_or(DidKeyError::UnsupportedMultibase)?;
+
+        let mut multicodec = [0u8; 34];
+        let len = bs58::decode(encoded)
+            .onto(&mut multicodec[..])
+            .map_err(|err| match err {
+                bs58::decode::Error::BufferTooSmall => DidKeyError::UnsupportedMulticodec,
+                _ => DidKeyError::InvalidEncoding,
+            })?;
+        if len != multicodec.len() || multicodec[..2] != MULTICODEC_X25519_PUB {
+            return Err(DidKeyError::UnsupportedMulticodec.into());
+        }
+
+        let mut bytes = [0u8; 32];
+        bytes.copy_from_slice(&multicodec[2..]);
+        Ok(PublicKey::from(bytes))
+    }
+}
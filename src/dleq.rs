@@ -0,0 +1,214 @@
+// -*- mode: rust; -*-
+//
+// This file is part of x25519-dalek.
+// Copyright (c) 2017-2021 isis lovecruft
+// Copyright (c) 2019-2021 DebugSteven
+// See LICENSE for licensing information.
+//
+// Authors:
+// - isis agora lovecruft <isis@patternsinthevoid.net>
+// - DebugSteven <debugsteven@gmail.com>
+
+//! Verifiable Diffie-Hellman: Chaum-Pedersen proofs of discrete logarithm
+//! equality, showing that a shared secret was computed correctly from a
+//! public key and a peer's public key, without revealing the secret key.
+//!
+//! The proof is over the Edwards forms of the keys, with the cofactor
+//! cleared: for the prover's secret scalar `w` it shows that
+//! `A = w * 8B` and `S = w * 8P` for the basepoint `B`, the peer's public key
+//! `P`, the prover's public key `A`, and the shared point `S`, whose
+//! u-coordinate is the X25519 shared secret.
+//!
+#![cfg_attr(feature = "getrandom", doc = "```")]
+#![cfg_attr(not(feature = "getrandom"), doc = "```ignore")]
+//! use rand_core::OsRng;
+//! use x25519_dalek::{PublicKey, StaticSecret};
+//!
+//! let secret = StaticSecret::random();
+//! let peer = PublicKey::from(&StaticSecret::random());
+//! let (shared, proof) = secret.diffie_hellman_with_proof(&peer, OsRng)?;
+//!
+//! // Anyone can check the shared secret against both public keys.
+//! let public = PublicKey::from(&secret);
+//! assert!(proof.verify(&public, &peer, shared.as_bytes()).is_ok());
+//! # Ok::<(), x25519_dalek::Error>(())
+//! ```
+//!
+//! Peer public keys on the twist of Curve25519 are rejected, since they have
+//! no Edwards form.
+
+use core::fmt;
+
+use curve25519_dalek::edwards::{CompressedEdwardsY, EdwardsPoint};
+use curve25519_dalek::scalar::Scalar;
+use rand_core::{CryptoRng, RngCore};
+use sha2::{Digest, Sha512};
+use subtle::{Choice, ConditionallyNegatable};
+use zeroize::Zeroizing;
+
+use crate::x25519::{cofactor_free_scalar, PublicKey, SharedSecret, StaticSecret};
+
+/// The domain separator for the challenge hash.
+const CHALLENGE_DOMAIN: &[u8] = b"x25519-dalek DLEQ v1";
+
+/// The domain separator for the nonce hash.
+const NONCE_DOMAIN: &[u8] = b"x25519-dalek DLEQ nonce v1";
+
+/// The length of an encoded [`DleqProof`].
+pub const PROOF_LEN: usize = 96;
+
+/// Errors which may occur when proving or verifying a Diffie-Hellman result.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// The proof is malformed, or does not verify against the public keys
+    /// and shared secret.
+    InvalidProof,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::InvalidProof => f.write_str("invalid Diffie-Hellman proof"),
+        }
+    }
+}
+
+/// A proof that a shared secret is the Diffie-Hellman of two public keys.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct DleqProof {
+    shared_point: CompressedEdwardsY,
+    challenge: Scalar,
+    response: Scalar,
+}
+
+impl DleqProof {
+    /// Verify that `shared_secret` is the Diffie-Hellman of the secret key
+    /// for `public` with `their_public`.
+    pub fn verify(
+        &self,
+        public: &PublicKey,
+        their_public: &PublicKey,
+        shared_secret: &[u8; 32],
+    ) -> Result<(), crate::Error> {
+        let public = edwards(public)?;
+        let their_public = edwards(their_public)?.mul_by_cofactor();
+        let shared_point = self.shared_point.decompress().ok_or(Error::InvalidProof)?;
+
+        if shared_point.to_montgomery().as_bytes() != shared_secret {
+            return Err(Error::InvalidProof.into());
+        }
+
+        // Recompute the commitments as s * G - c * A and s * H - c * S,
+        // where G = 8B.
+        let commitments = [
+            EdwardsPoint::vartime_double_scalar_mul_basepoint(
+                &self.challenge,
+                &-public,
+                &(self.response * Scalar::from(8u8)),
+            ),
+            their_public * self.response - shared_point * self.challenge,
+        ];
+        let challenge = challenge(&public, &their_public, &shared_point, &commitments);
+        if challenge != self.challenge {
+            return Err(Error::InvalidProof.into());
+        }
+        Ok(())
+    }
+
+    /// Encode this proof as the compressed shared point, followed by the
+    /// challenge and response scalars.
+    pub fn to_bytes(&self) -> [u8; PROOF_LEN] {
+        let mut bytes = [0u8; PROOF_LEN];
+        bytes[..32].copy_from_slice(self.shared_point.as_bytes());
+        bytes[32..64].copy_from_slice(self.challenge.as_bytes());
+        bytes[64..].copy_from_slice(self.response.as_bytes());
+        bytes
+    }
+
+    /// Decode a proof produced by [`DleqProof::to_bytes`].
+    pub fn from_bytes(bytes: &[u8; PROOF_LEN]) -> Result<DleqProof, crate::Error> {
+        let mut shared_point = [0u8; 32];
+        shared_point.copy_from_slice(&bytes[..32]);
+        Ok(DleqProof {
+            shared_point: CompressedEdwardsY(shared_point),
+            challenge: scalar(&bytes[32..64])?,
+            response: scalar(&bytes[64..])?,
+        })
+    }
+}
+
+impl StaticSecret {
+    /// Perform a Diffie-Hellman key agreement between `self` and
+    /// `their_public` key, together with a proof that the resulting
+    /// [`SharedSecret`] was computed correctly.
+    ///
+    /// The nonce is derived from both the secret key and the RNG, so a weak
+    /// RNG does not leak the secret key.
+    pub fn diffie_hellman_with_proof<T: RngCore + CryptoRng>(
+        &self,
+        their_public: &PublicKey,
+        mut csprng: T,
+    ) -> Result<(SharedSecret, DleqProof), crate::Error> {
+        let generator = EdwardsPoint::mul_base(&Scalar::from(8u8));
+        let their_point = edwards(their_public)?.mul_by_cofactor();
+
+        let mut scalar = Zeroizing::new(cofactor_free_scalar(self.as_bytes()));
+        let public = generator * *scalar;
+        // Negate the scalar if needed so that the Edwards public key has
+        // sign 0, and can be recovered from the u-coordinate alone.
+        let negate = Choice::from(public.compress().as_bytes()[31] >> 7);
+        scalar.conditional_negate(negate);
+        let public = generator * *scalar;
+        let shared_point = their_point * *scalar;
+
+        let mut randomness = Zeroizing::new([0u8; 32]);
+        csprng.fill_bytes(&mut randomness[..]);
+        let nonce = Zeroizing::new(Scalar::from_hash(
+            Sha512::new()
+                .chain_update(NONCE_DOMAIN)
+                .chain_update(scalar.as_bytes())
+                .chain_update(&randomness[..])
+                .chain_update(their_point.compress().as_bytes()),
+        ));
+
+        let commitments = [generator * *nonce, their_point * *nonce];
+        let challenge = challenge(&public, &their_point, &shared_point, &commitments);
+        let proof = DleqProof {
+            shared_point: shared_point.compress(),
+            challenge,
+            response: *nonce + challenge * *scalar,
+        };
+        Ok((SharedSecret(shared_point.to_montgomery()), proof))
+    }
+}
+
+/// The Edwards point with sign 0 corresponding to a public key.
+fn edwards(public: &PublicKey) -> Result<EdwardsPoint, crate::Error> {
+    public.0.to_edwards(0).ok_or(crate::Error::TwistPoint)
+}
+
+/// Decode a canonical scalar.
+fn scalar(bytes: &[u8]) -> Result<Scalar, Error> {
+    let mut array = [0u8; 32];
+    array.copy_from_slice(bytes);
+    Option::from(Scalar::from_canonical_bytes(array)).ok_or(Error::InvalidProof)
+}
+
+/// Compute the Fiat-Shamir challenge binding the public key, the peer's
+/// cofactor-cleared public key, the shared point and the commitments.  The
+/// generator is fixed, and so is bound by the domain separator.
+fn challenge(
+    public: &EdwardsPoint,
+    their_public: &EdwardsPoint,
+    shared_point: &EdwardsPoint,
+    commitments: &[EdwardsPoint; 2],
+) -> Scalar {
+    let mut hash = Sha512::new().chain_update(CHALLENGE_DOMAIN);
+    for point in [public, their_public, shared_point]
+        .into_iter()
+        .chain(commitments.iter())
+    {
+        hash.update(point.compress().as_bytes());
+    }
+    Scalar::from_hash(hash)
+}
@@ -0,0 +1,157 @@
+// -*- mode: rust; -*-
+//
+// This file is part of x25519-dalek.
+// Copyright (c) 2017-2021 isis lovecruft
+// Copyright (c) 2019-2021 DebugSteven
+// See LICENSE for licensing information.
+//
+// Authors:
+// - isis agora lovecruft <isis@patternsinthevoid.net>
+// - DebugSteven <debugsteven@gmail.com>
+
+//! One-shot public-key encryption: an ephemeral X25519 exchange with the
+//! recipient's public key, HKDF-SHA256, and any AEAD implementing the `aead`
+//! crate's traits.
+//!
+//! A sealed message is the sender's ephemeral public key, followed by the
+//! AEAD ciphertext and tag.  The AEAD key and nonce are derived with
+//! HKDF-SHA256 from the shared secret, with the ephemeral and recipient
+//! public keys as salt and `"x25519-dalek ECIES v1 key"` and
+//! `"x25519-dalek ECIES v1 nonce"` as info.  Since every message has a fresh
+//! ephemeral key, every message has a fresh AEAD key and nonce.
+//!
+#![cfg_attr(feature = "getrandom", doc = "```")]
+#![cfg_attr(not(feature = "getrandom"), doc = "```ignore")]
+//! use chacha20poly1305::ChaCha20Poly1305;
+//! use rand_core::OsRng;
+//! use x25519_dalek::{ecies, PublicKey, StaticSecret};
+//!
+//! let recipient = StaticSecret::random();
+//! let public = PublicKey::from(&recipient);
+//!
+//! let sealed = ecies::seal::<ChaCha20Poly1305, _>(&public, b"hello", b"header", OsRng)?;
+//! let opened = ecies::open::<ChaCha20Poly1305>(&recipient, &sealed, b"header")?;
+//! assert_eq!(opened, b"hello");
+//! # Ok::<(), x25519_dalek::Error>(())
+//! ```
+//!
+//! The sender is anonymous: anyone can seal a message to a public key.
+
+use alloc::vec::Vec;
+use core::fmt;
+
+use aead::generic_array::typenum::Unsigned;
+use aead::{AeadInPlace, Key, KeyInit, Nonce};
+use hkdf::Hkdf;
+use rand_core::{CryptoRng, RngCore};
+use sha2::Sha256;
+use zeroize::Zeroize;
+
+use crate::x25519::{EphemeralSecret, PublicKey, SharedSecret, X25519Provider};
+
+/// The HKDF info for the AEAD key.
+const KEY_INFO: &[u8] = b"x25519-dalek ECIES v1 key";
+
+/// The HKDF info for the AEAD nonce.
+const NONCE_INFO: &[u8] = b"x25519-dalek ECIES v1 nonce";
+
+/// The length of the ephemeral public key at the start of a sealed message.
+pub const EPHEMERAL_KEY_LEN: usize = 32;
+
+/// Errors which may occur when sealing or opening a message.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// The plaintext is too long for the AEAD.
+    PlaintextTooLong,
+    /// The sealed message is truncated, or does not decrypt under the
+    /// recipient's secret key and the associated data.
+    InvalidCiphertext,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::PlaintextTooLong => f.write_str("plaintext is too long"),
+            Error::InvalidCiphertext => f.write_str("message could not be decrypted"),
+        }
+    }
+}
+
+/// Encrypt `plaintext` to the `recipient`, authenticating `aad` along with
+/// it, using an ephemeral key from `csprng` and the AEAD `A`.
+pub fn seal<A, T>(
+    recipient: &PublicKey,
+    plaintext: &[u8],
+    aad: &[u8],
+    csprng: T,
+) -> Result<Vec<u8>, crate::Error>
+where
+    A: KeyInit + AeadInPlace,
+    T: RngCore + CryptoRng,
+{
+    let ephemeral = EphemeralSecret::random_from_rng(csprng);
+    let ephemeral_public = PublicKey::from(&ephemeral);
+    let shared = ephemeral.diffie_hellman(recipient);
+    let (cipher, nonce) = cipher::<A>(&shared, &ephemeral_public, recipient)?;
+
+    let mut sealed = Vec::with_capacity(EPHEMERAL_KEY_LEN + plaintext.len() + A::TagSize::USIZE);
+    sealed.extend_from_slice(ephemeral_public.as_bytes());
+    sealed.extend_from_slice(plaintext);
+    let tag = cipher
+        .encrypt_in_place_detached(&nonce, aad, &mut sealed[EPHEMERAL_KEY_LEN..])
+        .map_err(|_| Error::PlaintextTooLong)?;
+    sealed.extend_from_slice(&tag);
+    Ok(sealed)
+}
+
+/// Decrypt a message sealed to the public key of `secret`, with the
+/// associated data `aad` and the AEAD `A`.
+pub fn open<A>(
+    secret: &dyn X25519Provider,
+    sealed: &[u8],
+    aad: &[u8],
+) -> Result<Vec<u8>, crate::Error>
+where
+    A: KeyInit + AeadInPlace,
+{
+    if sealed.len() < EPHEMERAL_KEY_LEN {
+        return Err(Error::InvalidCiphertext.into());
+    }
+    let (ephemeral_public, ciphertext) = sealed.split_at(EPHEMERAL_KEY_LEN);
+    let ephemeral_public = PublicKey::from(<[u8; 32]>::try_from(ephemeral_public).unwrap());
+    let shared = secret.diffie_hellman(&ephemeral_public)?;
+    let (cipher, nonce) = cipher::<A>(&shared, &ephemeral_public, &secret.public())?;
+
+    let mut plaintext = ciphertext.to_vec();
+    cipher
+        .decrypt_in_place(&nonce, aad, &mut plaintext)
+        .map_err(|_| Error::InvalidCiphertext)?;
+    Ok(plaintext)
+}
+
+/// Derive the AEAD and nonce for a message from the shared secret and both
+/// public keys.
+fn cipher<A: KeyInit + AeadInPlace>(
+    shared: &SharedSecret,
+    ephemeral_public: &PublicKey,
+    recipient: &PublicKey,
+) -> Result<(A, Nonce<A>), crate::Error> {
+    if !shared.was_contributory() {
+        return Err(crate::Error::SmallOrderPoint);
+    }
+
+    let mut salt = [0u8; 64];
+    salt[..32].copy_from_slice(ephemeral_public.as_bytes());
+    salt[32..].copy_from_slice(recipient.as_bytes());
+    let hkdf = Hkdf::<Sha256>::new(Some(&salt), shared.as_bytes());
+
+    let mut key = Key::<A>::default();
+    let mut nonce = Nonce::<A>::default();
+    hkdf.expand(KEY_INFO, &mut key)
+        .expect("AEAD keys are valid HKDF-SHA256 output lengths");
+    hkdf.expand(NONCE_INFO, &mut nonce)
+        .expect("AEAD nonces are valid HKDF-SHA256 output lengths");
+    let cipher = A::new(&key);
+    key.as_mut_slice().zeroize();
+    Ok((cipher, nonce))
+}
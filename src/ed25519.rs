@@ -0,0 +1,65 @@
+// -*- mode: rust; -*-
+//
+// This file is part of x25519-dalek.
+// Copyright (c) 2017-2021 isis lovecruft
+// Copyright (c) 2019-2021 DebugSteven
+// See LICENSE for licensing information.
+//
+// Authors:
+// - isis agora lovecruft <isis@patternsinthevoid.net>
+// - DebugSteven <debugsteven@gmail.com>
+
+//! Conversion of Ed25519 keys from `ed25519-dalek` to X25519 keys, through
+//! the birational map from the Edwards curve to the Montgomery curve.
+//!
+//! The conversions match libsodium's `crypto_sign_ed25519_pk_to_curve25519`
+//! and `crypto_sign_ed25519_sk_to_curve25519`, so that a party with an
+//! Ed25519 identity can also be addressed by X25519.
+//!
+//! An Ed25519 secret key is a seed, which RFC8032 expands with SHA-512 into
+//! the secret scalar.  Using the seed itself as an X25519 secret key gives a
+//! key pair unrelated to the Ed25519 one; the conversion from a
+//! [`SigningKey`] uses the expanded scalar instead.
+//!
+//! Using one key pair for both signatures and Diffie-Hellman is discouraged
+//! where it can be avoided; see
+//! [On using the same key pair for Ed25519 and an X25519 based KEM](https://eprint.iacr.org/2021/509).
+
+#[cfg(feature = "static_secrets")]
+use curve25519_dalek::scalar::clamp_integer;
+#[cfg(feature = "static_secrets")]
+use ed25519_dalek::SigningKey;
+use ed25519_dalek::VerifyingKey;
+#[cfg(feature = "static_secrets")]
+use zeroize::Zeroizing;
+
+use crate::x25519::PublicKey;
+#[cfg(feature = "static_secrets")]
+use crate::x25519::StaticSecret;
+
+impl From<VerifyingKey> for PublicKey {
+    /// Convert an Ed25519 public key to the X25519 public key of the same
+    /// secret key, as libsodium's `crypto_sign_ed25519_pk_to_curve25519`.
+    fn from(verifying_key: VerifyingKey) -> PublicKey {
+        PublicKey::from(&verifying_key)
+    }
+}
+
+impl<'a> From<&'a VerifyingKey> for PublicKey {
+    /// Convert an Ed25519 public key to the X25519 public key of the same
+    /// secret key, as libsodium's `crypto_sign_ed25519_pk_to_curve25519`.
+    fn from(verifying_key: &'a VerifyingKey) -> PublicKey {
+        PublicKey(verifying_key.to_montgomery())
+    }
+}
+
+#[cfg(feature = "static_secrets")]
+impl<'a> From<&'a SigningKey> for StaticSecret {
+    /// Convert an Ed25519 secret key to the X25519 secret key of the same
+    /// key pair, as libsodium's `crypto_sign_ed25519_sk_to_curve25519`: the
+    /// first half of the SHA-512 hash of the seed, clamped.
+    fn from(signing_key: &'a SigningKey) -> StaticSecret {
+        let bytes = Zeroizing::new(signing_key.to_scalar_bytes());
+        StaticSecret::from(clamp_integer(*bytes))
+    }
+}
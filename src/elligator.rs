@@ -0,0 +1,236 @@
+// -*- mode: rust; -*-
+//
+// This file is part of x25519-dalek.
+// Copyright (c) 2017-2021 isis lovecruft
+// Copyright (c) 2019-2021 DebugSteven
+// See LICENSE for licensing information.
+//
+// Authors:
+// - isis agora lovecruft <isis@patternsinthevoid.net>
+// - DebugSteven <debugsteven@gmail.com>
+
+//! The [Elligator2](https://elligator.org/) map between uniformly random
+//! strings, called representatives, and public keys.
+//!
+//! Censorship-resistant transports such as obfs4 send representatives in
+//! place of public keys, so that their handshakes look like random noise.
+//! This follows the conventions of [elligator.org](https://elligator.org/)
+//! and Monocypher: the non-square is 2, and a representative is a field
+//! element of at most 254 bits, whose two top bits are random padding.
+//!
+//! Only about half of all public keys have a representative, so
+//! [`EphemeralSecret::generate_elligator`] draws secret keys until one does:
+//!
+#![cfg_attr(feature = "getrandom", doc = "```")]
+#![cfg_attr(not(feature = "getrandom"), doc = "```ignore")]
+//! use rand_core::OsRng;
+//! use x25519_dalek::{EphemeralSecret, PublicKey};
+//!
+//! let (alice_secret, alice_representative) = EphemeralSecret::generate_elligator(OsRng);
+//! let bob_secret = EphemeralSecret::random();
+//! let bob_public = PublicKey::from(&bob_secret);
+//!
+//! // Alice sends the representative, which Bob decodes.
+//! let alice_public = PublicKey::from(&alice_representative);
+//! let bob_shared = bob_secret.diffie_hellman(&alice_public);
+//!
+//! let alice_shared = alice_secret.diffie_hellman(&bob_public);
+//! assert_eq!(alice_shared.as_bytes(), bob_shared.as_bytes());
+//! ```
+
+use curve25519_dalek::constants::EIGHT_TORSION;
+use curve25519_dalek::edwards::EdwardsPoint;
+use curve25519_dalek::montgomery::MontgomeryPoint;
+use rand_core::{CryptoRng, RngCore};
+use subtle::{Choice, ConditionallyNegatable, ConditionallySelectable, ConstantTimeEq, CtOption};
+
+use crate::field::FieldElement;
+use crate::x25519::{EphemeralSecret, PublicKey};
+
+/// \\( \sqrt{-1} \\), the nonnegative one, encoded in little-endian.
+const SQRT_M1: [u8; 32] = [
+    0xb0, 0xa0, 0x0e, 0x4a, 0x27, 0x1b, 0xee, 0xc4, 0x78, 0xe4, 0x2f, 0xad, 0x06, 0x18, 0x43, 0x2f,
+    0xa7, 0xd7, 0xfb, 0x3d, 0x99, 0x00, 0x4d, 0x2b, 0x0b, 0xdf, 0xc1, 0x4f, 0x80, 0x24, 0x83, 0x2b,
+];
+
+/// \\( (p - 5) / 8 \\), the exponent of square roots, in little-endian.
+const P_MINUS_5_DIV_8: [u8; 32] = [
+    0xfd, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x0f,
+];
+
+impl FieldElement {
+    /// Whether this field element is negative, that is, odd when encoded.
+    fn is_negative(&self) -> Choice {
+        Choice::from(self.to_bytes()[0] & 1)
+    }
+
+    /// Compute the nonnegative square root of `u / v`.
+    ///
+    /// Returns `(1, sqrt(u / v))` if `u / v` is a nonzero square or `u` is
+    /// zero, and `(0, sqrt(i * u / v))` otherwise, where `i` is
+    /// \\( \sqrt{-1} \\).  If `v` is zero and `u` is not, the root is zero.
+    fn sqrt_ratio_i(u: &FieldElement, v: &FieldElement) -> (Choice, FieldElement) {
+        let sqrt_m1 = FieldElement::from_bytes(&SQRT_M1);
+
+        let v3 = &v.square() * v;
+        let v7 = &v3.square() * v;
+        let mut r = &(u * &v3) * &(u * &v7).pow(&P_MINUS_5_DIV_8);
+        let check = v * &r.square();
+
+        let neg_u = -u;
+        let correct_sign_sqrt = check.ct_eq(u);
+        let flipped_sign_sqrt = check.ct_eq(&neg_u);
+        let flipped_sign_sqrt_i = check.ct_eq(&(&neg_u * &sqrt_m1));
+
+        let r_prime = &sqrt_m1 * &r;
+        r.conditional_assign(&r_prime, flipped_sign_sqrt | flipped_sign_sqrt_i);
+        let r_is_negative = r.is_negative();
+        r.conditional_negate(r_is_negative);
+
+        (correct_sign_sqrt | flipped_sign_sqrt, r)
+    }
+}
+
+/// The coefficient \\( A \\) of Curve25519, \\( v\^2 = u\^3 + Au\^2 + u \\).
+fn montgomery_a() -> FieldElement {
+    FieldElement::from_u32(486662)
+}
+
+/// The Elligator2 map from the field element encoded by `r`, whose top bit
+/// is ignored, to the encoded \\( u \\)-coordinate of a point on the curve.
+pub(crate) fn map(r: &[u8; 32]) -> [u8; 32] {
+    let a = montgomery_a();
+    let one = FieldElement::from_u32(1);
+
+    // w = -A / (1 + 2r^2)
+    let r_sq = FieldElement::from_bytes(r).square();
+    let denominator = &one + &(&r_sq + &r_sq);
+    let w = &(-&a) * &denominator.invert();
+
+    // The point with u = w is on the curve if w^3 + Aw^2 + w is a square,
+    // and the one with u = -w - A otherwise.
+    let w_sq = w.square();
+    let eps = &w * &(&(&w_sq + &(&a * &w)) + &one);
+    let (eps_is_square, _) = FieldElement::sqrt_ratio_i(&eps, &one);
+    let other = &(-&w) - &a;
+    FieldElement::conditional_select(&other, &w, eps_is_square).to_bytes()
+}
+
+/// The inverse of [`map`]: a field element of at most 254 bits which maps
+/// to `u`, if there is one.
+///
+/// Each such `u` has two, one for each point with that \\( u \\)-coordinate,
+/// and `alternate` chooses between them.
+fn inverse_map(u: &FieldElement, alternate: Choice) -> CtOption<FieldElement> {
+    // r = sqrt(-u / 2(u + A)) or sqrt(-(u + A) / 2u), which exist if
+    // -2u(u + A) is a nonzero square.
+    let u_plus_a = u + &montgomery_a();
+    let two_u = u + u;
+    let denominator = -&(&two_u * &u_plus_a);
+    let (is_square, inv_sqrt) =
+        FieldElement::sqrt_ratio_i(&FieldElement::from_u32(1), &denominator);
+    let numerator = FieldElement::conditional_select(&u_plus_a, u, alternate);
+    let mut r = &numerator * &inv_sqrt;
+
+    // Of r and -r, take the one below (p - 1) / 2, which fits in 254 bits.
+    let r_is_high = (&r + &r).is_negative();
+    r.conditional_negate(r_is_high);
+    CtOption::new(r, is_square)
+}
+
+/// The Elligator2 representative of a public key, which is indistinguishable
+/// from 32 uniformly random bytes.
+///
+/// Any 32 bytes can be converted to a [`Representative`], and decoded into
+/// a [`PublicKey`] with [`PublicKey::from_elligator_representative`] or
+/// [`From`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Representative([u8; 32]);
+
+impl Representative {
+    /// View this representative as a byte array.
+    #[inline]
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+
+    /// Convert this representative to a byte array.
+    #[inline]
+    pub fn to_bytes(&self) -> [u8; 32] {
+        self.0
+    }
+}
+
+impl AsRef<[u8]> for Representative {
+    fn as_ref(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+impl From<[u8; 32]> for Representative {
+    fn from(bytes: [u8; 32]) -> Representative {
+        Representative(bytes)
+    }
+}
+
+impl<'a> From<&'a Representative> for PublicKey {
+    /// Decode the public key of a representative.
+    fn from(representative: &'a Representative) -> PublicKey {
+        PublicKey::from_elligator_representative(representative.as_bytes())
+    }
+}
+
+impl EphemeralSecret {
+    /// Generate a new [`EphemeralSecret`] with the supplied RNG, along with a
+    /// uniformly random [`Representative`] of its public key.
+    ///
+    /// The public key encoded by the representative is
+    /// `PublicKey::from(&secret)` plus a random point of small order, since
+    /// the public keys of clamped secret keys lie in the prime-order
+    /// subgroup and their representatives would be recognizable.  The
+    /// Diffie-Hellman result is the same with either key, but protocols which
+    /// hash public keys must use the one decoded from the representative on
+    /// both sides.
+    ///
+    /// Secret keys are drawn until the public key has a representative, which
+    /// takes two tries on average.  The two top bits of the representative
+    /// are random padding.
+    pub fn generate_elligator<T: RngCore + CryptoRng>(
+        mut csprng: T,
+    ) -> (EphemeralSecret, Representative) {
+        loop {
+            let secret = EphemeralSecret::random_from_rng(&mut csprng);
+            // The low three bits choose the point of small order, the next
+            // one the representative, and the top two are padding.
+            let mut tweak = [0u8; 1];
+            csprng.fill_bytes(&mut tweak);
+            let tweak = tweak[0];
+
+            let point =
+                EdwardsPoint::mul_base_clamped(secret.0) + EIGHT_TORSION[usize::from(tweak & 0x07)];
+            let u = FieldElement::from_bytes(&point.to_montgomery().0);
+            let r: Option<FieldElement> = inverse_map(&u, Choice::from((tweak >> 3) & 1)).into();
+            if let Some(r) = r {
+                let mut bytes = r.to_bytes();
+                bytes[31] |= tweak & 0xc0;
+                return (secret, Representative(bytes));
+            }
+        }
+    }
+}
+
+impl PublicKey {
+    /// Decode a public key from its Elligator2 `representative`.
+    ///
+    /// Every 32-byte string is the representative of some public key, and
+    /// the two top bits are ignored, so uniformly random strings decode to
+    /// public keys indistinguishable from those sent by a peer.  The public
+    /// key may have a low-order component, which a Diffie-Hellman exchange
+    /// with a clamped secret key discards.
+    pub fn from_elligator_representative(representative: &[u8; 32]) -> PublicKey {
+        let mut bytes = *representative;
+        bytes[31] &= 0x3f;
+        PublicKey(MontgomeryPoint(map(&bytes)))
+    }
+}
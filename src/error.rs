@@ -0,0 +1,348 @@
+// -*- mode: rust; -*-
+//
+// This file is part of x25519-dalek.
+// Copyright (c) 2017-2021 isis lovecruft
+// Copyright (c) 2019-2021 DebugSteven
+// See LICENSE for licensing information.
+//
+// Authors:
+// - isis agora lovecruft <isis@patternsinthevoid.net>
+// - DebugSteven <debugsteven@gmail.com>
+
+//! The error type shared by all fallible constructors in this crate.
+
+use core::fmt;
+
+use crate::x25519::ParsePublicKeyError;
+
+#[cfg(feature = "age")]
+use crate::age::AgeKeyError;
+#[cfg(feature = "base64")]
+use crate::base64::Base64Error;
+#[cfg(feature = "cose")]
+use crate::cose::CoseError;
+#[cfg(feature = "did_key")]
+use crate::did_key::DidKeyError;
+#[cfg(feature = "jwk")]
+use crate::jwk::JwkError;
+#[cfg(feature = "tor")]
+use crate::tor::TorKeyError;
+
+/// Errors which may occur when constructing, generating or decoding keys.
+///
+/// Errors from the various key encodings are wrapped in their own variants,
+/// which carry a more specific error describing what went wrong.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Error {
+    /// The input was not the expected number of bytes.
+    InvalidLength {
+        /// The expected length, in bytes.
+        expected: usize,
+        /// The actual length, in bytes.
+        actual: usize,
+    },
+    /// The public key is not the canonical encoding of a field element.
+    NonCanonicalEncoding,
+    /// The public key is a point of small order.
+    SmallOrderPoint,
+    /// The public key is not on the curve but on its twist.
+    TwistPoint,
+    /// The random number generator failed.
+    Rng,
+    /// A newly generated key pair failed its pairwise consistency test.
+    PairwiseConsistency,
+    /// A secret key held outside of memory, by an
+    /// [`X25519Provider`](crate::X25519Provider), could not be used.
+    Provider,
+    /// Page-locked memory for a secret key could not be allocated or locked.
+    #[cfg(feature = "mlock")]
+    MemoryLock,
+    /// A public key could not be parsed from hex.
+    Hex(ParsePublicKeyError),
+    /// A key could not be decoded from PKCS#8 or SPKI.
+    #[cfg(feature = "pkcs8")]
+    Pkcs8(pkcs8::Error),
+    /// A key could not be decoded from a JSON Web Key.
+    #[cfg(feature = "jwk")]
+    Jwk(JwkError),
+    /// A key could not be decoded from a COSE_Key.
+    #[cfg(feature = "cose")]
+    Cose(CoseError),
+    /// A public key could not be parsed from a `did:key`.
+    #[cfg(feature = "did_key")]
+    DidKey(DidKeyError),
+    /// A key could not be decoded from its OpenPGP encoding.
+    #[cfg(feature = "openpgp")]
+    OpenPgp(crate::openpgp::Error),
+    /// A key could not be parsed from an age recipient or identity.
+    #[cfg(feature = "age")]
+    Age(AgeKeyError),
+    /// A key could not be parsed from a Tor client authorization line.
+    #[cfg(feature = "tor")]
+    Tor(TorKeyError),
+    /// A key could not be decoded from base64.
+    #[cfg(feature = "base64")]
+    Base64(Base64Error),
+    /// A key could not be derived from a seed and derivation path.
+    #[cfg(feature = "slip10")]
+    Slip10(crate::slip10::Error),
+    /// A key could not be split into or recovered from shares.
+    #[cfg(feature = "shamir")]
+    Shamir(crate::shamir::Error),
+    /// A threshold Diffie-Hellman operation failed.
+    #[cfg(feature = "threshold")]
+    Threshold(crate::threshold::Error),
+    /// A proof of possession did not verify.
+    #[cfg(feature = "proof_of_possession")]
+    ProofOfPossession(crate::proof_of_possession::Error),
+    /// A Diffie-Hellman proof could not be produced or did not verify.
+    #[cfg(feature = "dleq")]
+    Dleq(crate::dleq::Error),
+    /// Key material could not be derived from a shared secret.
+    #[cfg(feature = "kdf")]
+    Kdf(crate::kdf::Error),
+    /// A message could not be sealed or opened.
+    #[cfg(feature = "ecies")]
+    Ecies(crate::ecies::Error),
+    /// A `crypto_box` could not be sealed or opened.
+    #[cfg(feature = "sodium")]
+    Sodium(crate::sodium::Error),
+    /// A Noise IK handshake failed.
+    #[cfg(feature = "noise_ik")]
+    NoiseIk(crate::noise_ik::Error),
+    /// An X3DH key agreement failed.
+    #[cfg(feature = "x3dh")]
+    X3dh(crate::x3dh::Error),
+    /// A Tor ntor handshake failed.
+    #[cfg(feature = "ntor")]
+    Ntor(crate::ntor::Error),
+    /// A Tor ntor-v3 handshake failed.
+    #[cfg(feature = "ntor_v3")]
+    NtorV3(crate::ntor_v3::Error),
+    /// A SPAKE2 exchange failed.
+    #[cfg(feature = "spake2")]
+    Spake2(crate::pake::spake2::Error),
+    /// An OPAQUE key exchange failed.
+    #[cfg(feature = "opaque")]
+    Opaque(crate::opaque::Error),
+    /// A secret key could not be stored in or loaded from the platform keyring.
+    #[cfg(feature = "keyring")]
+    Keyring(crate::keyring::Error),
+    /// A secret key could not be sealed or unsealed with a passphrase.
+    #[cfg(feature = "passphrase")]
+    Passphrase(crate::passphrase::Error),
+    /// A secret key could not be read from or written to a JSON keystore.
+    #[cfg(feature = "json_keystore")]
+    Keystore(crate::keystore::Error),
+    /// The known-answer self-test failed.
+    #[cfg(feature = "self_test")]
+    SelfTest(crate::self_test::SelfTestError),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::InvalidLength { expected, actual } => {
+                write!(f, "expected {} bytes, got {}", expected, actual)
+            }
+            Error::NonCanonicalEncoding => f.write_str("public key encoding is not canonical"),
+            Error::SmallOrderPoint => f.write_str("public key is a point of small order"),
+            Error::TwistPoint => f.write_str("public key is on the twist of the curve"),
+            Error::Rng => f.write_str("random number generator failed"),
+            Error::PairwiseConsistency => f.write_str("pairwise consistency test failed"),
+            Error::Provider => f.write_str("key agreement provider failed"),
+            #[cfg(feature = "mlock")]
+            Error::MemoryLock => f.write_str("could not lock memory for secret key"),
+            Error::Hex(err) => err.fmt(f),
+            #[cfg(feature = "pkcs8")]
+            Error::Pkcs8(err) => err.fmt(f),
+            #[cfg(feature = "jwk")]
+            Error::Jwk(err) => err.fmt(f),
+            #[cfg(feature = "cose")]
+            Error::Cose(err) => err.fmt(f),
+            #[cfg(feature = "did_key")]
+            Error::DidKey(err) => err.fmt(f),
+            #[cfg(feature = "openpgp")]
+            Error::OpenPgp(err) => err.fmt(f),
+            #[cfg(feature = "age")]
+            Error::Age(err) => err.fmt(f),
+            #[cfg(feature = "tor")]
+            Error::Tor(err) => err.fmt(f),
+            #[cfg(feature = "base64")]
+            Error::Base64(err) => err.fmt(f),
+            #[cfg(feature = "slip10")]
+            Error::Slip10(err) => err.fmt(f),
+            #[cfg(feature = "shamir")]
+            Error::Shamir(err) => err.fmt(f),
+            #[cfg(feature = "threshold")]
+            Error::Threshold(err) => err.fmt(f),
+            #[cfg(feature = "proof_of_possession")]
+            Error::ProofOfPossession(err) => err.fmt(f),
+            #[cfg(feature = "dleq")]
+            Error::Dleq(err) => err.fmt(f),
+            #[cfg(feature = "kdf")]
+            Error::Kdf(err) => err.fmt(f),
+            #[cfg(feature = "ecies")]
+            Error::Ecies(err) => err.fmt(f),
+            #[cfg(feature = "sodium")]
+            Error::Sodium(err) => err.fmt(f),
+            #[cfg(feature = "noise_ik")]
+            Error::NoiseIk(err) => err.fmt(f),
+            #[cfg(feature = "x3dh")]
+            Error::X3dh(err) => err.fmt(f),
+            #[cfg(feature = "ntor")]
+            Error::Ntor(err) => err.fmt(f),
+            #[cfg(feature = "ntor_v3")]
+            Error::NtorV3(err) => err.fmt(f),
+            #[cfg(feature = "spake2")]
+            Error::Spake2(err) => err.fmt(f),
+            #[cfg(feature = "opaque")]
+            Error::Opaque(err) => err.fmt(f),
+            #[cfg(feature = "keyring")]
+            Error::Keyring(err) => err.fmt(f),
+            #[cfg(feature = "passphrase")]
+            Error::Passphrase(err) => err.fmt(f),
+            #[cfg(feature = "json_keystore")]
+            Error::Keystore(err) => err.fmt(f),
+            #[cfg(feature = "self_test")]
+            Error::SelfTest(err) => err.fmt(f),
+        }
+    }
+}
+
+#[cfg(feature = "core_error")]
+impl core::error::Error for Error {}
+
+/// Implement `From` for a wrapped error type, and `core::error::Error` for
+/// the wrapped type itself.
+macro_rules! wrap_error {
+    ($(#[$attr:meta])* $variant:ident($err:ty)) => {
+        $(#[$attr])*
+        impl From<$err> for Error {
+            fn from(err: $err) -> Error {
+                Error::$variant(err)
+            }
+        }
+
+        $(#[$attr])*
+        #[cfg(feature = "core_error")]
+        impl core::error::Error for $err {}
+    };
+}
+
+wrap_error!(Hex(ParsePublicKeyError));
+wrap_error!(
+    #[cfg(feature = "jwk")]
+    Jwk(JwkError)
+);
+wrap_error!(
+    #[cfg(feature = "cose")]
+    Cose(CoseError)
+);
+wrap_error!(
+    #[cfg(feature = "did_key")]
+    DidKey(DidKeyError)
+);
+wrap_error!(
+    #[cfg(feature = "openpgp")]
+    OpenPgp(crate::openpgp::Error)
+);
+wrap_error!(
+    #[cfg(feature = "age")]
+    Age(AgeKeyError)
+);
+wrap_error!(
+    #[cfg(feature = "tor")]
+    Tor(TorKeyError)
+);
+wrap_error!(
+    #[cfg(feature = "base64")]
+    Base64(Base64Error)
+);
+wrap_error!(
+    #[cfg(feature = "slip10")]
+    Slip10(crate::slip10::Error)
+);
+wrap_error!(
+    #[cfg(feature = "shamir")]
+    Shamir(crate::shamir::Error)
+);
+wrap_error!(
+    #[cfg(feature = "threshold")]
+    Threshold(crate::threshold::Error)
+);
+wrap_error!(
+    #[cfg(feature = "proof_of_possession")]
+    ProofOfPossession(crate::proof_of_possession::Error)
+);
+wrap_error!(
+    #[cfg(feature = "dleq")]
+    Dleq(crate::dleq::Error)
+);
+wrap_error!(
+    #[cfg(feature = "kdf")]
+    Kdf(crate::kdf::Error)
+);
+wrap_error!(
+    #[cfg(feature = "ecies")]
+    Ecies(crate::ecies::Error)
+);
+wrap_error!(
+    #[cfg(feature = "sodium")]
+    Sodium(crate::sodium::Error)
+);
+wrap_error!(
+    #[cfg(feature = "noise_ik")]
+    NoiseIk(crate::noise_ik::Error)
+);
+wrap_error!(
+    #[cfg(feature = "x3dh")]
+    X3dh(crate::x3dh::Error)
+);
+wrap_error!(
+    #[cfg(feature = "ntor")]
+    Ntor(crate::ntor::Error)
+);
+wrap_error!(
+    #[cfg(feature = "ntor_v3")]
+    NtorV3(crate::ntor_v3::Error)
+);
+wrap_error!(
+    #[cfg(feature = "spake2")]
+    Spake2(crate::pake::spake2::Error)
+);
+wrap_error!(
+    #[cfg(feature = "opaque")]
+    Opaque(crate::opaque::Error)
+);
+wrap_error!(
+    #[cfg(feature = "keyring")]
+    Keyring(crate::keyring::Error)
+);
+wrap_error!(
+    #[cfg(feature = "passphrase")]
+    Passphrase(crate::passphrase::Error)
+);
+wrap_error!(
+    #[cfg(feature = "json_keystore")]
+    Keystore(crate::keystore::Error)
+);
+wrap_error!(
+    #[cfg(feature = "self_test")]
+    SelfTest(crate::self_test::SelfTestError)
+);
+
+#[cfg(feature = "pkcs8")]
+impl From<pkcs8::Error> for Error {
+    fn from(err: pkcs8::Error) -> Error {
+        Error::Pkcs8(err)
+    }
+}
+
+#[cfg(feature = "pkcs8")]
+impl From<pkcs8::spki::Error> for Error {
+    fn from(err: pkcs8::spki::Error) -> Error {
+        Error::Pkcs8(err.into())
+    }
+}
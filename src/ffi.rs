@@ -0,0 +1,150 @@
+// -*- mode: rust; -*-
+//
+// This file is part of x25519-dalek.
+// Copyright (c) 2017-2021 isis lovecruft
+// Copyright (c) 2019-2021 DebugSteven
+// See LICENSE for licensing information.
+//
+// Authors:
+// - isis agora lovecruft <isis@patternsinthevoid.net>
+// - DebugSteven <debugsteven@gmail.com>
+
+//! A C interface, declared in `include/x25519_dalek.h`.
+//!
+//! Every function takes pointers to 32-byte buffers and returns `0` on
+//! success or `-1` on failure, in which case every output buffer which is
+//! not null is zeroed.  A null pointer is a failure.
+//!
+//! Cargo cannot make the crate type depend on a feature, so build the
+//! shared and static libraries with:
+//!
+//! ```text
+//! cargo rustc --release --features ffi --crate-type cdylib --crate-type staticlib
+//! ```
+
+use std::os::raw::c_int;
+
+use curve25519_dalek::MontgomeryPoint;
+use rand_core::{OsRng, RngCore};
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::x25519::{mul_base_clamped, SharedSecret};
+
+/// The length of secret keys, public keys and shared secrets.
+pub const X25519_KEY_LEN: usize = 32;
+
+/// Copy a key from C, to be wiped when dropped.
+///
+/// # Safety
+///
+/// `key` must be valid for reads of 32 bytes.
+unsafe fn read_key(key: *const u8) -> Zeroizing<[u8; 32]> {
+    let mut bytes = Zeroizing::new([0u8; 32]);
+    core::ptr::copy_nonoverlapping(key, bytes.as_mut_ptr(), 32);
+    bytes
+}
+
+/// Copy a key to C.
+///
+/// # Safety
+///
+/// `out` must be valid for writes of 32 bytes.
+unsafe fn write_key(out: *mut u8, key: &[u8; 32]) {
+    core::ptr::copy_nonoverlapping(key.as_ptr(), out, 32);
+}
+
+/// Zero each of `outs` which is not null, and return the error code.
+///
+/// # Safety
+///
+/// Each of `outs` must be null or valid for writes of 32 bytes.
+unsafe fn fail(outs: &[*mut u8]) -> c_int {
+    for out in outs.iter().filter(|out| !out.is_null()) {
+        write_key(*out, &[0u8; 32]);
+    }
+    -1
+}
+
+/// Generate a secret key with the operating system's RNG, and compute its
+/// public key.
+///
+/// Fails if the RNG fails.
+///
+/// # Safety
+///
+/// `secret_out` and `public_out` must each be null or valid for writes of
+/// 32 bytes.
+#[no_mangle]
+pub unsafe extern "C" fn x25519_keygen(secret_out: *mut u8, public_out: *mut u8) -> c_int {
+    if secret_out.is_null() || public_out.is_null() {
+        return fail(&[secret_out, public_out]);
+    }
+    let mut secret = Zeroizing::new([0u8; 32]);
+    if OsRng.try_fill_bytes(&mut *secret).is_err() {
+        return fail(&[secret_out, public_out]);
+    }
+    write_key(secret_out, &secret);
+    write_key(public_out, &mul_base_clamped(*secret).0);
+    0
+}
+
+/// Compute the public key of a secret key.
+///
+/// # Safety
+///
+/// `public_out` must be null or valid for writes of 32 bytes, and `secret`
+/// must be null or valid for reads of 32 bytes.
+#[no_mangle]
+pub unsafe extern "C" fn x25519_public_from_secret(
+    public_out: *mut u8,
+    secret: *const u8,
+) -> c_int {
+    if public_out.is_null() || secret.is_null() {
+        return fail(&[public_out]);
+    }
+    let secret = read_key(secret);
+    write_key(public_out, &mul_base_clamped(*secret).0);
+    0
+}
+
+/// Compute the shared secret of a secret key and a peer's public key.
+///
+/// As with libsodium's `crypto_scalarmult`, fails if the shared secret is
+/// all zeros, from a public key of small order.
+///
+/// # Safety
+///
+/// `shared_out` must be null or valid for writes of 32 bytes, and `secret`
+/// and `their_public` must each be null or valid for reads of 32 bytes.
+#[no_mangle]
+pub unsafe extern "C" fn x25519_dh(
+    shared_out: *mut u8,
+    secret: *const u8,
+    their_public: *const u8,
+) -> c_int {
+    if shared_out.is_null() || secret.is_null() || their_public.is_null() {
+        return fail(&[shared_out]);
+    }
+    let secret = read_key(secret);
+    let their_public = read_key(their_public);
+    let shared = SharedSecret(MontgomeryPoint(*their_public).mul_clamped(*secret));
+    if !shared.was_contributory() {
+        return fail(&[shared_out]);
+    }
+    write_key(shared_out, shared.as_bytes());
+    0
+}
+
+/// Zero `len` bytes at `buf`, in a way which the compiler will not remove.
+///
+/// Does nothing if `buf` is null.
+///
+/// # Safety
+///
+/// `buf` must be null or valid for writes of `len` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn x25519_zeroize(buf: *mut u8, len: usize) {
+    if !buf.is_null() {
+        core::slice::from_raw_parts_mut(buf, len).zeroize();
+    }
+}
@@ -0,0 +1,262 @@
+// -*- mode: rust; -*-
+//
+// This file is part of x25519-dalek.
+// Copyright (c) 2017-2021 isis lovecruft
+// Copyright (c) 2019-2021 DebugSteven
+// See LICENSE for licensing information.
+//
+// Authors:
+// - isis agora lovecruft <isis@patternsinthevoid.net>
+// - DebugSteven <debugsteven@gmail.com>
+
+//! Field arithmetic for the features which need more than
+//! `curve25519-dalek` exposes, on top of Fiat Crypto.
+
+use core::ops::{Add, Mul, Neg, Sub};
+
+#[cfg(not(target_pointer_width = "64"))]
+use fiat_crypto::curve25519_32 as fiat;
+#[cfg(target_pointer_width = "64")]
+use fiat_crypto::curve25519_64 as fiat;
+use subtle::{Choice, ConditionallySelectable, ConstantTimeEq};
+#[cfg(feature = "zeroize")]
+use zeroize::Zeroize;
+
+/// \\( p - 2 \\), the exponent of inversion, in little-endian.
+pub(crate) const P_MINUS_2: [u8; 32] = [
+    0xeb, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x7f,
+];
+
+/// An element of the field of integers modulo \\( 2\^{255} - 19 \\).
+///
+/// `curve25519-dalek` keeps its field arithmetic private, so this wraps the
+/// formally verified arithmetic generated by
+/// [Fiat Crypto](https://github.com/mit-plv/fiat-crypto), with the limbs
+/// always carried to its tight bounds.  Only exponentiation by a public
+/// exponent is built on top.
+#[derive(Copy, Clone)]
+pub(crate) struct FieldElement(fiat::fiat_25519_tight_field_element);
+
+impl FieldElement {
+    /// Load a field element from its little-endian encoding, ignoring the
+    /// top bit.
+    pub(crate) fn from_bytes(bytes: &[u8; 32]) -> FieldElement {
+        let mut bytes = *bytes;
+        bytes[31] &= 0x7f;
+        let mut limbs = Default::default();
+        fiat::fiat_25519_from_bytes(&mut limbs, &bytes);
+        FieldElement(limbs)
+    }
+
+    /// The field element with the small value `n`.
+    pub(crate) fn from_u32(n: u32) -> FieldElement {
+        let mut bytes = [0u8; 32];
+        bytes[..4].copy_from_slice(&n.to_le_bytes());
+        FieldElement::from_bytes(&bytes)
+    }
+
+    /// The canonical little-endian encoding of this field element.
+    pub(crate) fn to_bytes(self) -> [u8; 32] {
+        let mut bytes = [0u8; 32];
+        fiat::fiat_25519_to_bytes(&mut bytes, &self.0);
+        bytes
+    }
+
+    pub(crate) fn square(&self) -> FieldElement {
+        let mut limbs = Default::default();
+        fiat::fiat_25519_carry_square(&mut limbs, &self.0);
+        FieldElement(limbs)
+    }
+
+    /// The sum of two field elements, left uncarried.
+    #[cfg(any(
+        feature = "batch",
+        feature = "projective_randomization",
+        feature = "resumable"
+    ))]
+    pub(crate) fn add_loose(&self, rhs: &FieldElement) -> LooseFieldElement {
+        let mut loose = Default::default();
+        fiat::fiat_25519_add(&mut loose, &self.0, &rhs.0);
+        LooseFieldElement(loose)
+    }
+
+    /// The difference of two field elements, left uncarried.
+    #[cfg(any(
+        feature = "batch",
+        feature = "projective_randomization",
+        feature = "resumable"
+    ))]
+    pub(crate) fn sub_loose(&self, rhs: &FieldElement) -> LooseFieldElement {
+        let mut loose = Default::default();
+        fiat::fiat_25519_sub(&mut loose, &self.0, &rhs.0);
+        LooseFieldElement(loose)
+    }
+
+    /// This field element, for multiplication with uncarried ones.
+    #[cfg(any(
+        feature = "batch",
+        feature = "projective_randomization",
+        feature = "resumable"
+    ))]
+    pub(crate) fn relax(&self) -> LooseFieldElement {
+        let mut loose = Default::default();
+        fiat::fiat_25519_relax(&mut loose, &self.0);
+        LooseFieldElement(loose)
+    }
+
+    /// Raise this field element to the power of a public exponent, given in
+    /// little-endian.
+    #[cfg(any(
+        feature = "batch",
+        feature = "elligator",
+        feature = "projective_randomization"
+    ))]
+    pub(crate) fn pow(&self, exponent: &[u8; 32]) -> FieldElement {
+        exponent
+            .iter()
+            .rev()
+            .fold(FieldElement::from_u32(1), |acc, byte| {
+                self.pow_byte(&acc, *byte)
+            })
+    }
+
+    /// Continue raising this field element to the power of a public
+    /// exponent, from `acc`, the power of the bytes of the exponent above
+    /// `byte`, to the power of the bytes down to `byte`.
+    pub(crate) fn pow_byte(&self, acc: &FieldElement, byte: u8) -> FieldElement {
+        let mut acc = *acc;
+        for bit in (0..8).rev() {
+            acc = acc.square();
+            if (byte >> bit) & 1 == 1 {
+                acc = &acc * self;
+            }
+        }
+        acc
+    }
+
+    /// The multiplicative inverse of this field element, or zero if it is
+    /// zero.
+    #[cfg(any(
+        feature = "batch",
+        feature = "elligator",
+        feature = "projective_randomization"
+    ))]
+    pub(crate) fn invert(&self) -> FieldElement {
+        self.pow(&P_MINUS_2)
+    }
+
+    /// Carry a sum or difference back to the tight bounds.
+    fn carry(loose: &fiat::fiat_25519_loose_field_element) -> FieldElement {
+        let mut limbs = Default::default();
+        fiat::fiat_25519_carry(&mut limbs, loose);
+        FieldElement(limbs)
+    }
+}
+
+/// A sum or difference of field elements, left at the loose bounds of Fiat
+/// Crypto rather than carried, since the ladder only multiplies them.
+#[cfg(any(
+    feature = "batch",
+    feature = "projective_randomization",
+    feature = "resumable"
+))]
+#[derive(Copy, Clone)]
+pub(crate) struct LooseFieldElement(fiat::fiat_25519_loose_field_element);
+
+#[cfg(any(
+    feature = "batch",
+    feature = "projective_randomization",
+    feature = "resumable"
+))]
+impl LooseFieldElement {
+    pub(crate) fn square(&self) -> FieldElement {
+        let mut limbs = Default::default();
+        fiat::fiat_25519_carry_square(&mut limbs, &self.0);
+        FieldElement(limbs)
+    }
+
+    /// Multiply this field element by \\( (A + 2) / 4 = 121666 \\), the
+    /// constant of the Montgomery ladder.
+    pub(crate) fn mul_a24(&self) -> FieldElement {
+        let mut limbs = Default::default();
+        fiat::fiat_25519_carry_scmul_121666(&mut limbs, &self.0);
+        FieldElement(limbs)
+    }
+}
+
+impl ConstantTimeEq for FieldElement {
+    fn ct_eq(&self, other: &FieldElement) -> Choice {
+        self.to_bytes().ct_eq(&other.to_bytes())
+    }
+}
+
+impl ConditionallySelectable for FieldElement {
+    fn conditional_select(a: &FieldElement, b: &FieldElement, choice: Choice) -> FieldElement {
+        let mut limbs = Default::default();
+        fiat::fiat_25519_selectznz(&mut limbs, choice.unwrap_u8(), &a.0, &b.0);
+        FieldElement(limbs)
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl Zeroize for FieldElement {
+    fn zeroize(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl Add<&FieldElement> for &FieldElement {
+    type Output = FieldElement;
+
+    fn add(self, rhs: &FieldElement) -> FieldElement {
+        let mut loose = Default::default();
+        fiat::fiat_25519_add(&mut loose, &self.0, &rhs.0);
+        FieldElement::carry(&loose)
+    }
+}
+
+impl Sub<&FieldElement> for &FieldElement {
+    type Output = FieldElement;
+
+    fn sub(self, rhs: &FieldElement) -> FieldElement {
+        let mut loose = Default::default();
+        fiat::fiat_25519_sub(&mut loose, &self.0, &rhs.0);
+        FieldElement::carry(&loose)
+    }
+}
+
+impl Neg for &FieldElement {
+    type Output = FieldElement;
+
+    fn neg(self) -> FieldElement {
+        let mut loose = Default::default();
+        fiat::fiat_25519_opp(&mut loose, &self.0);
+        FieldElement::carry(&loose)
+    }
+}
+
+impl Mul<&FieldElement> for &FieldElement {
+    type Output = FieldElement;
+
+    fn mul(self, rhs: &FieldElement) -> FieldElement {
+        let mut limbs = Default::default();
+        fiat::fiat_25519_carry_mul(&mut limbs, &self.0, &rhs.0);
+        FieldElement(limbs)
+    }
+}
+
+#[cfg(any(
+    feature = "batch",
+    feature = "projective_randomization",
+    feature = "resumable"
+))]
+impl Mul<&LooseFieldElement> for &LooseFieldElement {
+    type Output = FieldElement;
+
+    fn mul(self, rhs: &LooseFieldElement) -> FieldElement {
+        let mut limbs = Default::default();
+        fiat::fiat_25519_carry_mul(&mut limbs, &self.0, &rhs.0);
+        FieldElement(limbs)
+    }
+}
@@ -0,0 +1,34 @@
+// -*- mode: rust; -*-
+//
+// This file is part of x25519-dalek.
+// Copyright (c) 2017-2021 isis lovecruft
+// Copyright (c) 2019-2021 DebugSteven
+// See LICENSE for licensing information.
+//
+// Authors:
+// - isis agora lovecruft <isis@patternsinthevoid.net>
+// - DebugSteven <debugsteven@gmail.com>
+
+//! Fingerprints of public keys.
+
+use digest::{Digest, Output};
+use sha2::Sha256;
+
+use crate::x25519::{canonicalize, PublicKey};
+
+impl PublicKey {
+    /// Hash the canonical encoding of this public key with `D`.
+    ///
+    /// Encodings which X25519 treats as the same key, i.e. those differing
+    /// only in the unused high bit or by a multiple of 2^255 - 19, have the
+    /// same fingerprint.
+    pub fn fingerprint<D: Digest>(&self) -> Output<D> {
+        D::digest(canonicalize(self.to_bytes()))
+    }
+
+    /// The SHA-256 [`fingerprint`](PublicKey::fingerprint) of this public
+    /// key.
+    pub fn fingerprint_sha256(&self) -> [u8; 32] {
+        self.fingerprint::<Sha256>().into()
+    }
+}
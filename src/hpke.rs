@@ -0,0 +1,241 @@
+// -*- mode: rust; -*-
+//
+// This file is part of x25519-dalek.
+// Copyright (c) 2017-2021 isis lovecruft
+// Copyright (c) 2019-2021 DebugSteven
+// See LICENSE for licensing information.
+//
+// Authors:
+// - isis agora lovecruft <isis@patternsinthevoid.net>
+// - DebugSteven <debugsteven@gmail.com>
+
+//! The DHKEM(X25519, HKDF-SHA256) key encapsulation mechanism of HPKE, as
+//! specified in [RFC9180](https://www.rfc-editor.org/rfc/rfc9180.html#section-4.1).
+//!
+//! The sender encapsulates a fresh shared secret to the recipient's public
+//! key, producing an encapsulated key `enc` to send alongside; the recipient
+//! decapsulates `enc` with its secret key to recover the same shared secret:
+//!
+#![cfg_attr(feature = "getrandom", doc = "```")]
+#![cfg_attr(not(feature = "getrandom"), doc = "```ignore")]
+//! use rand_core::OsRng;
+//! use x25519_dalek::{hpke, PublicKey, StaticSecret};
+//!
+//! let recipient = StaticSecret::random();
+//! let (shared, enc) = hpke::encap(&PublicKey::from(&recipient), OsRng)?;
+//!
+//! assert_eq!(hpke::decap(&enc, &recipient)?, shared);
+//! # Ok::<(), x25519_dalek::Error>(())
+//! ```
+//!
+//! [`auth_encap`] and [`auth_decap`] additionally authenticate the sender by
+//! its static key, for the HPKE Auth and AuthPSK modes.
+//!
+//! This module provides only the KEM; the key schedule and AEAD of HPKE are
+//! left to the caller.
+//!
+//! With the `kem` feature, [`PublicKey`] implements the `kem` crate's
+//! `Encapsulate` trait and [`StaticSecret`] its `Decapsulate` trait, so that
+//! this KEM can be used by code written generically over KEMs.
+
+use hkdf::Hkdf;
+use rand_core::{CryptoRng, RngCore};
+use sha2::Sha256;
+use zeroize::Zeroizing;
+
+use crate::x25519::{EphemeralSecret, PublicKey, SharedSecret, StaticSecret, X25519Provider};
+
+/// The KEM identifier of DHKEM(X25519, HKDF-SHA256).
+pub const KEM_ID: u16 = 0x0020;
+
+/// The length of a KEM shared secret.
+pub const N_SECRET: usize = 32;
+
+/// The length of an encapsulated key.
+pub const N_ENC: usize = 32;
+
+/// The length of an encoded public key.
+pub const N_PK: usize = 32;
+
+/// The length of an encoded secret key.
+pub const N_SK: usize = 32;
+
+/// The HPKE version label.
+const VERSION_LABEL: &[u8] = b"HPKE-v1";
+
+/// The KEM suite identifier, `"KEM"` followed by [`KEM_ID`].
+const SUITE_ID: [u8; 5] = [b'K', b'E', b'M', (KEM_ID >> 8) as u8, KEM_ID as u8];
+
+/// `LabeledExtract` from RFC9180, with the KEM suite identifier.
+fn labeled_extract(salt: &[u8], label: &[u8], ikm: &[u8]) -> Hkdf<Sha256> {
+    let mut extract = hkdf::HkdfExtract::<Sha256>::new(Some(salt));
+    extract.input_ikm(VERSION_LABEL);
+    extract.input_ikm(&SUITE_ID);
+    extract.input_ikm(label);
+    extract.input_ikm(ikm);
+    extract.finalize().1
+}
+
+/// `LabeledExpand` from RFC9180, with the KEM suite identifier.
+fn labeled_expand(prk: &Hkdf<Sha256>, label: &[u8], info: &[u8], okm: &mut [u8]) {
+    let length = (okm.len() as u16).to_be_bytes();
+    prk.expand_multi_info(&[&length, VERSION_LABEL, &SUITE_ID, label, info], okm)
+        .expect("KEM outputs are valid HKDF-SHA256 output lengths");
+}
+
+/// `ExtractAndExpand` from RFC9180: derive the KEM shared secret from the
+/// Diffie-Hellman results and the KEM context.
+pub fn extract_and_expand(dh: &[u8], kem_context: &[u8]) -> Zeroizing<[u8; N_SECRET]> {
+    let eae_prk = labeled_extract(b"", b"eae_prk", dh);
+    let mut shared = Zeroizing::new([0u8; N_SECRET]);
+    labeled_expand(&eae_prk, b"shared_secret", kem_context, &mut shared[..]);
+    shared
+}
+
+/// `DeriveKeyPair` from RFC9180: deterministically derive a key pair from
+/// the input keying material `ikm`, which should have at least [`N_SK`]
+/// bytes of entropy.
+pub fn derive_key_pair(ikm: &[u8]) -> (StaticSecret, PublicKey) {
+    let dkp_prk = labeled_extract(b"", b"dkp_prk", ikm);
+    let mut sk = Zeroizing::new([0u8; N_SK]);
+    labeled_expand(&dkp_prk, b"sk", b"", &mut sk[..]);
+    let secret = StaticSecret::from(*sk);
+    let public = PublicKey::from(&secret);
+    (secret, public)
+}
+
+/// `Encap` from RFC9180: generate a KEM shared secret for the recipient's
+/// public key `pk_r`, using an ephemeral key from `csprng`.
+///
+/// Returns the shared secret and the encapsulated key to send to the
+/// recipient.
+pub fn encap<T: RngCore + CryptoRng>(
+    pk_r: &PublicKey,
+    csprng: T,
+) -> Result<(Zeroizing<[u8; N_SECRET]>, [u8; N_ENC]), crate::Error> {
+    let sk_e = EphemeralSecret::random_from_rng(csprng);
+    let enc = PublicKey::from(&sk_e).to_bytes();
+    let dh = checked(sk_e.diffie_hellman(pk_r))?;
+    let shared = extract_and_expand(dh.as_bytes(), &kem_context(&enc, pk_r));
+    Ok((shared, enc))
+}
+
+/// `Decap` from RFC9180: recover the KEM shared secret from the
+/// encapsulated key `enc`, using the recipient's secret key `sk_r`.
+pub fn decap(
+    enc: &[u8; N_ENC],
+    sk_r: &dyn X25519Provider,
+) -> Result<Zeroizing<[u8; N_SECRET]>, crate::Error> {
+    let dh = checked(sk_r.diffie_hellman(&PublicKey::from(*enc))?)?;
+    let pk_r = sk_r.public();
+    Ok(extract_and_expand(dh.as_bytes(), &kem_context(enc, &pk_r)))
+}
+
+/// `AuthEncap` from RFC9180: generate a KEM shared secret for the
+/// recipient's public key `pk_r`, authenticated by the sender's secret key
+/// `sk_s`, using an ephemeral key from `csprng`.
+///
+/// This is the KEM for the HPKE Auth and AuthPSK modes.  Only the holder of
+/// the secret key for `pk_r` can recover the shared secret, and only if it
+/// names the sender's public key in [`auth_decap`].
+pub fn auth_encap<T: RngCore + CryptoRng>(
+    pk_r: &PublicKey,
+    sk_s: &dyn X25519Provider,
+    csprng: T,
+) -> Result<(Zeroizing<[u8; N_SECRET]>, [u8; N_ENC]), crate::Error> {
+    let sk_e = EphemeralSecret::random_from_rng(csprng);
+    let enc = PublicKey::from(&sk_e).to_bytes();
+    let dh = auth_dh(
+        checked(sk_e.diffie_hellman(pk_r))?,
+        checked(sk_s.diffie_hellman(pk_r)?)?,
+    );
+    let pk_s = sk_s.public();
+    let shared = extract_and_expand(&dh[..], &auth_kem_context(&enc, pk_r, &pk_s));
+    Ok((shared, enc))
+}
+
+/// `AuthDecap` from RFC9180: recover the KEM shared secret from the
+/// encapsulated key `enc`, using the recipient's secret key `sk_r`, and
+/// authenticate it as coming from the sender's public key `pk_s`.
+///
+/// A sender other than `pk_s` yields an unrelated shared secret, which the
+/// caller's AEAD will then fail to decrypt with.
+pub fn auth_decap(
+    enc: &[u8; N_ENC],
+    sk_r: &dyn X25519Provider,
+    pk_s: &PublicKey,
+) -> Result<Zeroizing<[u8; N_SECRET]>, crate::Error> {
+    let dh = auth_dh(
+        checked(sk_r.diffie_hellman(&PublicKey::from(*enc))?)?,
+        checked(sk_r.diffie_hellman(pk_s)?)?,
+    );
+    let pk_r = sk_r.public();
+    Ok(extract_and_expand(
+        &dh[..],
+        &auth_kem_context(enc, &pk_r, pk_s),
+    ))
+}
+
+/// Reject an all-zero Diffie-Hellman result, as RFC9180 requires.
+fn checked(dh: SharedSecret) -> Result<SharedSecret, crate::Error> {
+    if dh.was_contributory() {
+        Ok(dh)
+    } else {
+        Err(crate::Error::SmallOrderPoint)
+    }
+}
+
+/// Concatenate the ephemeral and static Diffie-Hellman results of the
+/// authenticated KEM.
+fn auth_dh(ephemeral: SharedSecret, static_: SharedSecret) -> Zeroizing<[u8; 64]> {
+    let mut dh = Zeroizing::new([0u8; 64]);
+    dh[..32].copy_from_slice(ephemeral.as_bytes());
+    dh[32..].copy_from_slice(static_.as_bytes());
+    dh
+}
+
+/// The KEM context: the encapsulated key followed by the recipient's public
+/// key.
+fn kem_context(enc: &[u8; N_ENC], pk_r: &PublicKey) -> [u8; N_ENC + N_PK] {
+    let mut context = [0u8; N_ENC + N_PK];
+    context[..N_ENC].copy_from_slice(enc);
+    context[N_ENC..].copy_from_slice(pk_r.as_bytes());
+    context
+}
+
+/// The authenticated KEM context: the encapsulated key followed by the
+/// recipient's and sender's public keys.
+fn auth_kem_context(
+    enc: &[u8; N_ENC],
+    pk_r: &PublicKey,
+    pk_s: &PublicKey,
+) -> [u8; N_ENC + 2 * N_PK] {
+    let mut context = [0u8; N_ENC + 2 * N_PK];
+    context[..N_ENC + N_PK].copy_from_slice(&kem_context(enc, pk_r));
+    context[N_ENC + N_PK..].copy_from_slice(pk_s.as_bytes());
+    context
+}
+
+#[cfg(feature = "kem")]
+impl ::kem::Encapsulate<[u8; N_ENC], Zeroizing<[u8; N_SECRET]>> for PublicKey {
+    type Error = crate::Error;
+
+    /// See [`encap`].
+    fn encapsulate(
+        &self,
+        rng: &mut impl rand_core::CryptoRngCore,
+    ) -> Result<([u8; N_ENC], Zeroizing<[u8; N_SECRET]>), crate::Error> {
+        let (shared, enc) = encap(self, rng)?;
+        Ok((enc, shared))
+    }
+}
+
+#[cfg(feature = "kem")]
+impl ::kem::Decapsulate<[u8; N_ENC], Zeroizing<[u8; N_SECRET]>> for StaticSecret {
+    type Error = crate::Error;
+
+    /// See [`decap`].
+    fn decapsulate(&self, enc: &[u8; N_ENC]) -> Result<Zeroizing<[u8; N_SECRET]>, crate::Error> {
+        decap(enc, self)
+    }
+}
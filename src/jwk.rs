@@ -0,0 +1,172 @@
+// -*- mode: rust; -*-
+//
+// This file is part of x25519-dalek.
+// Copyright (c) 2017-2021 isis lovecruft
+// Copyright (c) 2019-2021 DebugSteven
+// See LICENSE for licensing information.
+//
+// Authors:
+// - isis agora lovecruft <isis@patternsinthevoid.net>
+// - DebugSteven <debugsteven@gmail.com>
+
+//! JSON Web Key encoding of X25519 keys, as specified in
+//! [RFC8037](https://tools.ietf.org/html/rfc8037).
+
+use alloc::string::String;
+use core::fmt;
+
+use base64ct::{Base64UrlUnpadded, Encoding};
+
+#[cfg(feature = "zeroize")]
+use zeroize::Zeroize;
+
+use crate::error::Error;
+use crate::x25519::PublicKey;
+
+#[cfg(feature = "static_secrets")]
+use crate::x25519::StaticSecret;
+
+/// The JWK key type for octet key pairs.
+const KTY_OKP: &str = "OKP";
+
+/// The JWK curve name for X25519.
+const CRV_X25519: &str = "X25519";
+
+/// An X25519 key in JSON Web Key form (`kty: "OKP"`, `crv: "X25519"`).
+///
+/// This type implements `serde::Serialize` and `serde::Deserialize`, so it
+/// can be read from and written to JSON with e.g. `serde_json`.  The fields
+/// hold the base64url (unpadded) encodings of the key material, and are
+/// only validated when converting into a [`PublicKey`] or `StaticSecret`.
+///
+/// The `Debug` implementation does not print the private key, and the
+/// private key is wiped on drop when the `zeroize` feature is enabled.
+#[derive(Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Jwk {
+    /// The key type.  Must be `"OKP"`.
+    pub kty: String,
+    /// The curve.  Must be `"X25519"`.
+    pub crv: String,
+    /// The public key.
+    pub x: String,
+    /// The private key, if this is a private JWK.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub d: Option<String>,
+}
+
+impl fmt::Debug for Jwk {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Jwk")
+            .field("kty", &self.kty)
+            .field("crv", &self.crv)
+            .field("x", &self.x)
+            .field("d", &self.d.as_ref().map(|_| "<redacted>"))
+            .finish()
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl Drop for Jwk {
+    fn drop(&mut self) {
+        self.d.zeroize();
+    }
+}
+
+/// Errors which may occur when converting a [`Jwk`] into a key.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum JwkError {
+    /// The `kty` member is not `"OKP"`.
+    UnsupportedKeyType,
+    /// The `crv` member is not `"X25519"`.
+    UnsupportedCurve,
+    /// The `x` or `d` member is not the unpadded base64url encoding of 32 bytes.
+    InvalidEncoding,
+    /// The `d` member is required but missing.
+    MissingPrivateKey,
+    /// The `x` member does not match the public key derived from `d`.
+    PublicKeyMismatch,
+}
+
+impl fmt::Display for JwkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JwkError::UnsupportedKeyType => f.write_str("JWK key type is not \"OKP\""),
+            JwkError::UnsupportedCurve => f.write_str("JWK curve is not \"X25519\""),
+            JwkError::InvalidEncoding => f.write_str("JWK key material is not 32 base64url bytes"),
+            JwkError::MissingPrivateKey => f.write_str("JWK is missing the private key"),
+            JwkError::PublicKeyMismatch => {
+                f.write_str("JWK public key does not match the private key")
+            }
+        }
+    }
+}
+
+impl Jwk {
+    /// Check the `kty` and `crv` members, and decode the `x` member.
+    fn decode_public(&self) -> Result<[u8; 32], JwkError> {
+        if self.kty != KTY_OKP {
+            return Err(JwkError::UnsupportedKeyType);
+        }
+        if self.crv != CRV_X25519 {
+            return Err(JwkError::UnsupportedCurve);
+        }
+        decode_bytes(&self.x)
+    }
+}
+
+/// Decode the unpadded base64url encoding of exactly 32 bytes.
+fn decode_bytes(encoded: &str) -> Result<[u8; 32], JwkError> {
+    let mut bytes = [0u8; 32];
+    let len = Base64UrlUnpadded::decode(encoded, &mut bytes)
+        .map_err(|_| JwkError::InvalidEncoding)?
+        .len();
+    if len != 32 {
+        return Err(JwkError::InvalidEncoding);
+    }
+    Ok(bytes)
+}
+
+impl PublicKey {
+    /// Encode this public key as a JSON Web Key.
+    pub fn to_jwk(&self) -> Jwk {
+        Jwk {
+            kty: String::from(KTY_OKP),
+            crv: String::from(CRV_X25519),
+            x: Base64UrlUnpadded::encode_string(self.as_bytes()),
+            d: None,
+        }
+    }
+
+    /// Decode a public key from a JSON Web Key.
+    ///
+    /// A private JWK is accepted, in which case only its public part is used.
+    pub fn from_jwk(jwk: &Jwk) -> Result<PublicKey, Error> {
+        Ok(PublicKey::from(jwk.decode_public()?))
+    }
+}
+
+#[cfg(feature = "static_secrets")]
+impl StaticSecret {
+    /// Encode this secret key, together with its public key, as a private
+    /// JSON Web Key.
+    pub fn to_jwk(&self) -> Jwk {
+        let mut jwk = PublicKey::from(self).to_jwk();
+        jwk.d = Some(Base64UrlUnpadded::encode_string(self.as_bytes()));
+        jwk
+    }
+
+    /// Decode a secret key from a private JSON Web Key.
+    ///
+    /// The `x` member must match the public key derived from the `d` member.
+    pub fn from_jwk(jwk: &Jwk) -> Result<StaticSecret, Error> {
+        let public = jwk.decode_public()?;
+        let d = jwk.d.as_ref().ok_or(JwkError::MissingPrivateKey)?;
+        let secret = StaticSecret::from(decode_bytes(d)?);
+
+        if PublicKey::from(&secret).as_bytes() != &public {
+            return Err(JwkError::PublicKeyMismatch.into());
+        }
+
+        Ok(secret)
+    }
+}
@@ -0,0 +1,408 @@
+// -*- mode: rust; -*-
+//
+// This file is part of x25519-dalek.
+// Copyright (c) 2017-2021 isis lovecruft
+// Copyright (c) 2019-2021 DebugSteven
+// See LICENSE for licensing information.
+//
+// Authors:
+// - isis agora lovecruft <isis@patternsinthevoid.net>
+// - DebugSteven <debugsteven@gmail.com>
+
+//! Derivation of symmetric keys from a [`SharedSecret`], using HKDF-SHA256
+//! as specified in [RFC5869](https://tools.ietf.org/html/rfc5869).
+//!
+//! The raw output of Diffie-Hellman is not uniformly random, and should not
+//! be used directly as a key.  [`SharedSecret::extract`] condenses it into a
+//! pseudorandom key, from which any number of independent keys can be
+//! expanded under different `info` strings:
+//!
+#![cfg_attr(feature = "getrandom", doc = "```")]
+#![cfg_attr(not(feature = "getrandom"), doc = "```ignore")]
+//! use x25519_dalek::{EphemeralSecret, PublicKey};
+//!
+//! let alice_secret = EphemeralSecret::random();
+//! let bob_public = PublicKey::from(&EphemeralSecret::random());
+//! let shared = alice_secret.diffie_hellman(&bob_public);
+//!
+//! let prk = shared.extract(b"my protocol v1");
+//! let mut key = [0u8; 32];
+//! prk.expand(b"encryption key", &mut key)?;
+//! # Ok::<(), x25519_dalek::Error>(())
+//! ```
+//!
+//! For a single key, [`SharedSecret::derive_key`] does both steps at once,
+//! with any hash function implementing [`Digest`].  After a handshake,
+//! [`SessionKeys::derive`] produces separate keys and IVs for each direction
+//! of a session, and [`triple_dh`] derives a key from a handshake of both
+//! identity and ephemeral keys.
+
+use core::fmt;
+use core::marker::PhantomData;
+
+use digest::core_api::BlockSizeUser;
+use digest::typenum::Unsigned;
+use digest::{Digest, OutputSizeUser};
+use hkdf::{Hkdf, HkdfExtract, SimpleHkdf};
+use sha2::Sha256;
+use zeroize::{Zeroize, Zeroizing};
+
+#[cfg(all(feature = "kdf", feature = "static_secrets"))]
+use crate::x25519::{EphemeralSecret, StaticSecret};
+use crate::x25519::{PublicKey, SharedSecret};
+
+/// The length of a [`Prk`].
+pub const PRK_LEN: usize = 32;
+
+/// The length of each key in [`SessionKeys`], and of transcript-bound keys.
+pub const KEY_LEN: usize = 32;
+
+/// The length of each IV in [`SessionKeys`].
+pub const IV_LEN: usize = 12;
+
+/// The HKDF salt for [`SharedSecret::derive_key_with_transcript`].
+const TRANSCRIPT_SALT: &[u8] = b"x25519-dalek transcript key v1";
+
+/// The HKDF salt for [`triple_dh`].
+#[cfg(all(feature = "kdf", feature = "static_secrets"))]
+const TRIPLE_DH_SALT: &[u8] = b"x25519-dalek triple DH v1";
+
+/// The HKDF salt for [`SessionKeys::derive`].
+const SESSION_KEYS_SALT: &[u8] = b"x25519-dalek session keys v1";
+
+/// The maximum number of bytes which can be expanded from a [`Prk`].
+pub const MAX_OUTPUT_LEN: usize = 255 * PRK_LEN;
+
+/// A compile-time check that HKDF over `D` can expand `N` bytes, for
+/// [`SharedSecret::derive_key`].
+struct OutputLen<D, const N: usize>(PhantomData<D>);
+
+impl<D: OutputSizeUser, const N: usize> OutputLen<D, N> {
+    const VALID: () = assert!(
+        N <= 255 * D::OutputSize::USIZE,
+        "HKDF can expand at most 255 times the digest output size"
+    );
+}
+
+/// Errors which may occur when deriving keys.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// The requested output is longer than [`MAX_OUTPUT_LEN`] bytes.
+    InvalidOutputLength,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::InvalidOutputLength => f.write_str("requested HKDF output is too long"),
+        }
+    }
+}
+
+/// An HKDF-SHA256 pseudorandom key, extracted from a [`SharedSecret`].
+///
+/// The key is wiped on drop.
+#[derive(Clone, Zeroize)]
+#[zeroize(drop)]
+pub struct Prk([u8; PRK_LEN]);
+
+impl Prk {
+    /// Fill `okm` with key material for the context `info`.
+    ///
+    /// Different `info` strings yield independent output.
+    pub fn expand(&self, info: &[u8], okm: &mut [u8]) -> Result<(), crate::Error> {
+        Hkdf::<Sha256>::from_prk(&self.0)
+            .expect("PRK_LEN is a valid HKDF-SHA256 PRK length")
+            .expand(info, okm)
+            .map_err(|_| Error::InvalidOutputLength.into())
+    }
+
+    /// Mix `input`, such as a pre-shared key, into this pseudorandom key.
+    ///
+    /// This is the `MixKey` operation of the Noise protocol framework and
+    /// WireGuard, over HKDF-SHA256: the new key is HKDF-Extract with this key
+    /// as salt and `input` as input keying material, followed by
+    /// HKDF-Expand with empty info.  The result is secret as long as either
+    /// this key or `input` is, so mixing in a pre-shared key protects the
+    /// session even if X25519 is broken.
+    pub fn mix_key(&self, input: &[u8]) -> Prk {
+        let (prk, _) = Hkdf::<Sha256>::extract(Some(&self.0), input);
+        let mut bytes = [0u8; PRK_LEN];
+        Prk::from_output(prk)
+            .expand(b"", &mut bytes)
+            .expect("PRK_LEN is a valid HKDF-SHA256 output length");
+        Prk(bytes)
+    }
+
+    /// Copy the output of HKDF-Extract, wiping the original.
+    fn from_output(mut prk: digest::Output<Sha256>) -> Prk {
+        let mut bytes = [0u8; PRK_LEN];
+        bytes.copy_from_slice(&prk);
+        prk.as_mut_slice().zeroize();
+        Prk(bytes)
+    }
+
+    /// View this pseudorandom key as a byte array.
+    pub fn as_bytes(&self) -> &[u8; PRK_LEN] {
+        &self.0
+    }
+}
+
+impl SharedSecret {
+    /// Extract a pseudorandom key from this shared secret with HKDF-SHA256,
+    /// using `salt` to separate this use of the secret from any other.
+    ///
+    /// An empty salt is equivalent to HKDF's default of all zeroes.
+    pub fn extract(&self, salt: &[u8]) -> Prk {
+        let (prk, _) = Hkdf::<Sha256>::extract(Some(salt), self.as_bytes());
+        Prk::from_output(prk)
+    }
+
+    /// Extract a pseudorandom key from this shared secret and a pre-shared
+    /// key `psk`.
+    ///
+    /// This is [`SharedSecret::extract`] followed by [`Prk::mix_key`] with
+    /// the pre-shared key.
+    pub fn extract_with_psk(&self, salt: &[u8], psk: &[u8; 32]) -> Prk {
+        self.extract(salt).mix_key(psk)
+    }
+
+    /// Derive an `N`-byte key from this shared secret with HKDF over the
+    /// hash function `D`, bound to `context`.
+    ///
+    /// This is HKDF-Extract with an empty salt, followed by HKDF-Expand with
+    /// `context` as info.  The context should identify the protocol and the
+    /// purpose of the key, so that keys derived for different purposes are
+    /// independent.
+    ///
+    /// HKDF can expand at most 255 times the output size of `D`, and a
+    /// longer `N` fails to compile:
+    ///
+    /// ```compile_fail
+    /// use sha2::Sha256;
+    /// use x25519_dalek::SharedSecret;
+    ///
+    /// let derive = SharedSecret::derive_key::<Sha256, { 255 * 32 + 1 }>;
+    /// # let _ = derive as fn(&SharedSecret, &[u8]) -> _;
+    /// ```
+    pub fn derive_key<D, const N: usize>(&self, context: &[u8]) -> Zeroizing<[u8; N]>
+    where
+        D: Digest + BlockSizeUser + Clone,
+    {
+        let () = OutputLen::<D, N>::VALID;
+
+        let mut key = Zeroizing::new([0u8; N]);
+        SimpleHkdf::<D>::new(None, self.as_bytes())
+            .expand(context, &mut key[..])
+            .expect("OutputLen checks that HKDF can expand N bytes");
+        key
+    }
+
+    /// Derive a key from this shared secret and a handshake `transcript`,
+    /// bound to `context`.
+    ///
+    /// The key is HKDF-SHA256, with the salt `"x25519-dalek transcript key
+    /// v1"`, the shared secret followed by the transcript as input keying
+    /// material, and the context as info.  Binding the transcript into the
+    /// key means that two parties only agree on a key if they agree on the
+    /// transcript, such as which public keys took part in the exchange.
+    pub fn derive_key_with_transcript(
+        &self,
+        transcript: &[u8],
+        context: &[u8],
+    ) -> Zeroizing<[u8; KEY_LEN]> {
+        let mut extract = HkdfExtract::<Sha256>::new(Some(TRANSCRIPT_SALT));
+        extract.input_ikm(self.as_bytes());
+        extract.input_ikm(transcript);
+        let (prk, _) = extract.finalize();
+
+        let mut key = Zeroizing::new([0u8; KEY_LEN]);
+        Prk::from_output(prk)
+            .expand(context, &mut key[..])
+            .expect("32 bytes is a valid HKDF-SHA256 output length");
+        key
+    }
+
+    /// Derive a key from this shared secret, bound to both parties' public
+    /// keys and to `context`.
+    ///
+    /// This is [`SharedSecret::derive_key_with_transcript`], with the
+    /// transcript formed from the two public keys in ascending byte order, so
+    /// that both parties derive the same key regardless of which is which.
+    /// Binding the public keys protects against unknown key-share attacks,
+    /// where a party is misled about whom it shares a key with.
+    pub fn derive_bound_key(
+        &self,
+        public: &PublicKey,
+        their_public: &PublicKey,
+        context: &[u8],
+    ) -> Zeroizing<[u8; KEY_LEN]> {
+        let (low, high) = if public.as_bytes() <= their_public.as_bytes() {
+            (public, their_public)
+        } else {
+            (their_public, public)
+        };
+        let mut transcript = [0u8; 64];
+        transcript[..32].copy_from_slice(low.as_bytes());
+        transcript[32..].copy_from_slice(high.as_bytes());
+        self.derive_key_with_transcript(&transcript, context)
+    }
+}
+
+/// Which side of a handshake a party played.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Role {
+    /// The party which sent the first handshake message.
+    Initiator,
+    /// The party which responded to the initiator.
+    Responder,
+}
+
+/// The key and IV for one direction of a session.
+#[derive(Clone, Zeroize)]
+#[zeroize(drop)]
+struct DirectionalKeys {
+    key: [u8; KEY_LEN],
+    iv: [u8; IV_LEN],
+}
+
+impl DirectionalKeys {
+    fn from_bytes(bytes: &[u8]) -> DirectionalKeys {
+        let mut keys = DirectionalKeys {
+            key: [0u8; KEY_LEN],
+            iv: [0u8; IV_LEN],
+        };
+        keys.key.copy_from_slice(&bytes[..KEY_LEN]);
+        keys.iv.copy_from_slice(&bytes[KEY_LEN..]);
+        keys
+    }
+}
+
+/// Separate keys and IVs for sending and receiving, derived from a
+/// [`SharedSecret`] after a handshake.
+///
+/// Both parties derive the same initiator-to-responder and
+/// responder-to-initiator keys, so that the initiator's sending keys are the
+/// responder's receiving keys and vice versa, and the two directions never
+/// share a key and nonce stream.
+///
+/// The keys are wiped on drop.
+#[derive(Clone)]
+pub struct SessionKeys {
+    send: DirectionalKeys,
+    receive: DirectionalKeys,
+}
+
+impl SessionKeys {
+    /// Derive the session keys for the party playing `role`, from the
+    /// `shared` secret and the handshake `transcript`.
+    ///
+    /// The keys are 88 bytes expanded with HKDF-SHA256, with the salt
+    /// `"x25519-dalek session keys v1"` and the transcript as info, and
+    /// split into the initiator-to-responder key and IV followed by the
+    /// responder-to-initiator key and IV.  The transcript should contain
+    /// everything both parties agreed on during the handshake, such as their
+    /// public keys.
+    pub fn derive(shared: &SharedSecret, role: Role, transcript: &[u8]) -> SessionKeys {
+        let mut okm = Zeroizing::new([0u8; 2 * (KEY_LEN + IV_LEN)]);
+        shared
+            .extract(SESSION_KEYS_SALT)
+            .expand(transcript, &mut okm[..])
+            .expect("88 bytes is a valid HKDF-SHA256 output length");
+
+        let (initiator, responder) = okm.split_at(KEY_LEN + IV_LEN);
+        let initiator = DirectionalKeys::from_bytes(initiator);
+        let responder = DirectionalKeys::from_bytes(responder);
+        match role {
+            Role::Initiator => SessionKeys {
+                send: initiator,
+                receive: responder,
+            },
+            Role::Responder => SessionKeys {
+                send: responder,
+                receive: initiator,
+            },
+        }
+    }
+
+    /// The key for encrypting messages to the peer.
+    pub fn send_key(&self) -> &[u8; KEY_LEN] {
+        &self.send.key
+    }
+
+    /// The IV for encrypting messages to the peer.
+    pub fn send_iv(&self) -> &[u8; IV_LEN] {
+        &self.send.iv
+    }
+
+    /// The key for decrypting messages from the peer.
+    pub fn receive_key(&self) -> &[u8; KEY_LEN] {
+        &self.receive.key
+    }
+
+    /// The IV for decrypting messages from the peer.
+    pub fn receive_iv(&self) -> &[u8; IV_LEN] {
+        &self.receive.iv
+    }
+}
+
+/// Derive a key from a triple Diffie-Hellman handshake between two parties,
+/// each with a static identity key and an ephemeral key, as in Signal-like
+/// and OPAQUE-like protocols.
+///
+/// The three Diffie-Hellman results are the two ephemeral keys together,
+/// and each party's identity key with the other's ephemeral key.  The
+/// parties are ordered by their public keys in ascending byte order, so
+/// that both derive the same key regardless of which is which.  The key is
+/// HKDF-SHA256 with the salt `"x25519-dalek triple DH v1"`, the three
+/// results as input keying material, and both parties' identity and
+/// ephemeral public keys as info.
+///
+/// The ephemeral secret is consumed, so that it cannot be reused for a
+/// second handshake.
+///
+/// Fails with [`Error::SmallOrderPoint`](crate::Error::SmallOrderPoint) if
+/// any Diffie-Hellman result is all zeroes.
+///
+/// Only available with the `static_secrets` feature as well as `kdf`, which
+/// does not enable it.
+#[cfg(all(feature = "kdf", feature = "static_secrets"))]
+pub fn triple_dh(
+    my_identity: &StaticSecret,
+    my_ephemeral: EphemeralSecret,
+    their_identity: &PublicKey,
+    their_ephemeral: &PublicKey,
+) -> Result<Zeroizing<[u8; KEY_LEN]>, crate::Error> {
+    let mine = [PublicKey::from(my_identity), PublicKey::from(&my_ephemeral)];
+    let theirs = [*their_identity, *their_ephemeral];
+    let ephemeral = SharedSecret(their_ephemeral.0.mul_clamped(my_ephemeral.0));
+    let my_static = my_identity.diffie_hellman(their_ephemeral);
+    let their_static = SharedSecret(their_identity.0.mul_clamped(my_ephemeral.0));
+
+    let ((low, high), (low_static, high_static)) = if [mine[0].as_bytes(), mine[1].as_bytes()]
+        <= [theirs[0].as_bytes(), theirs[1].as_bytes()]
+    {
+        ((mine, theirs), (my_static, their_static))
+    } else {
+        ((theirs, mine), (their_static, my_static))
+    };
+
+    let mut extract = HkdfExtract::<Sha256>::new(Some(TRIPLE_DH_SALT));
+    for dh in [&ephemeral, &low_static, &high_static] {
+        if !dh.was_contributory() {
+            return Err(crate::Error::SmallOrderPoint);
+        }
+        extract.input_ikm(dh.as_bytes());
+    }
+    let (prk, _) = extract.finalize();
+
+    let mut transcript = [0u8; 128];
+    for (chunk, public) in transcript.chunks_mut(32).zip(low.iter().chain(high.iter())) {
+        chunk.copy_from_slice(public.as_bytes());
+    }
+    let mut key = Zeroizing::new([0u8; KEY_LEN]);
+    Prk::from_output(prk)
+        .expand(&transcript, &mut key[..])
+        .expect("32 bytes is a valid HKDF-SHA256 output length");
+    Ok(key)
+}
@@ -0,0 +1,75 @@
+// -*- mode: rust; -*-
+//
+// This file is part of x25519-dalek.
+// Copyright (c) 2017-2021 isis lovecruft
+// Copyright (c) 2019-2021 DebugSteven
+// See LICENSE for licensing information.
+//
+// Authors:
+// - isis agora lovecruft <isis@patternsinthevoid.net>
+// - DebugSteven <debugsteven@gmail.com>
+
+//! Multiplicative blinding of key pairs, for deriving unlinkable public keys
+//! from a single long-term key pair.
+
+use curve25519_dalek::scalar::Scalar;
+use sha2::{Digest, Sha512};
+use subtle::{Choice, ConditionallyNegatable};
+use zeroize::Zeroizing;
+
+use crate::x25519::{cofactor_free_scalar, PublicKey, StaticSecret};
+
+/// The domain separator for hashing a blinding factor to a scalar.
+const BLINDING_DOMAIN: &[u8] = b"x25519-dalek key blinding v1";
+
+/// Hash a blinding factor to a scalar.
+fn blinding_scalar(factor: &[u8]) -> Zeroizing<Scalar> {
+    Zeroizing::new(Scalar::from_hash(
+        Sha512::new()
+            .chain_update(BLINDING_DOMAIN)
+            .chain_update(factor),
+    ))
+}
+
+impl StaticSecret {
+    /// Blind this secret key by `factor`, such that the public key of the
+    /// result is [`PublicKey::blind`] of this key's public key by the same
+    /// factor.
+    ///
+    /// The factor is hashed to a scalar `b`, and the blinded secret key is
+    /// the clamped scalar multiplied by `b`, re-encoded so that X25519's
+    /// clamping leaves it unchanged.  Diffie-Hellman between blinded and
+    /// unblinded counterparts agrees as usual.  With probability below
+    /// 2^-124 over the choice of factor, no such encoding exists and the
+    /// result is unrelated to this key.
+    pub fn blind(&self, factor: &[u8]) -> StaticSecret {
+        let blinded =
+            Zeroizing::new(cofactor_free_scalar(self.as_bytes()) * *blinding_scalar(factor));
+
+        // The blinded secret key is 8y, where y must lie in [2^251, 2^252)
+        // to survive clamping.  Either y = blinded or y = -blinded will do,
+        // since a point and its negation share a u-coordinate, and one of
+        // them lies in range unless blinded is within 2^125 of 0 or l.
+        let bytes = blinded.as_bytes();
+        let in_range = Choice::from((bytes[31] >> 3) & 1) & !Choice::from((bytes[31] >> 4) & 1);
+        let mut y = Zeroizing::new(*blinded);
+        y.conditional_negate(!in_range);
+
+        let y = Zeroizing::new(y.to_bytes());
+        let mut x = Zeroizing::new([0u8; 32]);
+        for i in 0..32 {
+            let low = if i == 0 { 0 } else { y[i - 1] >> 5 };
+            x[i] = (y[i] << 3) | low;
+        }
+        StaticSecret::from(*x)
+    }
+}
+
+impl PublicKey {
+    /// Blind this public key by `factor`.
+    ///
+    /// See [`StaticSecret::blind`].
+    pub fn blind(&self, factor: &[u8]) -> PublicKey {
+        PublicKey(self.0 * *blinding_scalar(factor))
+    }
+}
@@ -0,0 +1,108 @@
+// -*- mode: rust; -*-
+//
+// This file is part of x25519-dalek.
+// Copyright (c) 2017-2021 isis lovecruft
+// Copyright (c) 2019-2021 DebugSteven
+// See LICENSE for licensing information.
+//
+// Authors:
+// - isis agora lovecruft <isis@patternsinthevoid.net>
+// - DebugSteven <debugsteven@gmail.com>
+
+//! Storage of [`StaticSecret`]s in the platform keyring, through the
+//! [`keyring`](https://docs.rs/keyring) crate.
+//!
+//! A secret key is stored as the 32 bytes of a keyring entry named by a
+//! service and a name of the caller's choosing:
+//!
+//! ```ignore
+//! use x25519_dalek::{keyring, StaticSecret};
+//!
+//! let secret = StaticSecret::random();
+//! keyring::store("com.example.app", "identity", &secret)?;
+//! let loaded = keyring::load("com.example.app", "identity")?;
+//! ```
+//!
+//! The `keyring` crate only stores entries in memory unless a platform's
+//! store is enabled through its features, such as `apple-native` for the
+//! macOS Keychain, `windows-native` for the Windows Credential Manager, or
+//! `sync-secret-service` for the Secret Service of Linux desktops.  These
+//! are left to the application, which adds `keyring` to its own
+//! dependencies with the features of its targets.
+
+use core::fmt;
+
+use ::keyring::Entry;
+use zeroize::Zeroizing;
+
+use crate::x25519::StaticSecret;
+
+/// Errors which may occur when storing or loading a secret key.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// There is no keyring entry for the service and name.
+    NoEntry,
+    /// The service or name is not accepted by the platform keyring.
+    InvalidName,
+    /// The platform keyring failed, could not be accessed, or holds more than
+    /// one entry for the service and name.
+    Storage,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::NoEntry => f.write_str("no keyring entry for the service and name"),
+            Error::InvalidName => f.write_str("service or name not accepted by the keyring"),
+            Error::Storage => f.write_str("platform keyring failed"),
+        }
+    }
+}
+
+impl From<::keyring::Error> for Error {
+    fn from(err: ::keyring::Error) -> Error {
+        match err {
+            ::keyring::Error::NoEntry => Error::NoEntry,
+            ::keyring::Error::TooLong(..) | ::keyring::Error::Invalid(..) => Error::InvalidName,
+            _ => Error::Storage,
+        }
+    }
+}
+
+impl From<::keyring::Error> for crate::Error {
+    fn from(err: ::keyring::Error) -> crate::Error {
+        Error::from(err).into()
+    }
+}
+
+/// Store `secret` in the platform keyring under `service` and `name`,
+/// replacing any secret key already stored there.
+pub fn store(service: &str, name: &str, secret: &StaticSecret) -> Result<(), crate::Error> {
+    Entry::new(service, name)?.set_secret(secret.as_bytes())?;
+    Ok(())
+}
+
+/// Load the secret key stored in the platform keyring under `service` and
+/// `name`.
+///
+/// The bytes read from the keyring are wiped once copied into the
+/// [`StaticSecret`]. Fails with
+/// [`Error::InvalidLength`](crate::Error::InvalidLength) if the entry does
+/// not hold 32 bytes.
+pub fn load(service: &str, name: &str) -> Result<StaticSecret, crate::Error> {
+    let bytes = Zeroizing::new(Entry::new(service, name)?.get_secret()?);
+    let bytes = Zeroizing::new(<[u8; 32]>::try_from(&bytes[..]).map_err(|_| {
+        crate::Error::InvalidLength {
+            expected: 32,
+            actual: bytes.len(),
+        }
+    })?);
+    Ok(StaticSecret::from(*bytes))
+}
+
+/// Delete the secret key stored in the platform keyring under `service` and
+/// `name`.
+pub fn delete(service: &str, name: &str) -> Result<(), crate::Error> {
+    Entry::new(service, name)?.delete_credential()?;
+    Ok(())
+}
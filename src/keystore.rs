@@ -0,0 +1,368 @@
+// -*- mode: rust; -*-
+//
+// This file is part of x25519-dalek.
+// Copyright (c) 2017-2021 isis lovecruft
+// Copyright (c) 2019-2021 DebugSteven
+// See LICENSE for licensing information.
+//
+// Authors:
+// - isis agora lovecruft <isis@patternsinthevoid.net>
+// - DebugSteven <debugsteven@gmail.com>
+
+//! Ethereum-style JSON keystores, as specified by the
+//! [Web3 Secret Storage Definition](https://ethereum.org/en/developers/docs/data-structures-and-encoding/web3-secret-storage/),
+//! holding an X25519 secret key.
+//!
+//! ```no_run
+//! use rand_core::OsRng;
+//! use x25519_dalek::StaticSecret;
+//! use x25519_dalek::keystore::{Keystore, Params};
+//!
+//! let secret = StaticSecret::random_from_rng(OsRng);
+//! let keystore = secret.to_keystore(b"correct horse", &Params::default(), OsRng)?;
+//! let json = serde_json::to_string(&keystore).unwrap();
+//!
+//! let keystore: Keystore = serde_json::from_str(&json).unwrap();
+//! let decrypted = StaticSecret::from_keystore(&keystore, b"correct horse")?;
+//! assert_eq!(decrypted.to_bytes(), secret.to_bytes());
+//! # Ok::<(), x25519_dalek::Error>(())
+//! ```
+//!
+//! The secret key is encrypted with AES-128-CTR under the first half of a
+//! key derived from the password with scrypt, and authenticated by the
+//! Keccak-256 hash of the second half of that key and the ciphertext.  Only
+//! version 3 keystores with scrypt are read; those with PBKDF2 are rejected
+//! with [`Error::UnsupportedKdf`], and those whose scrypt parameters break
+//! the `n < 2^(16 * r)` of RFC7914, such as the definition's own test vector,
+//! with [`Error::InvalidParams`].  The format does not record the type of
+//! the key, so any keystore holding 32 bytes, such as that of an Ethereum
+//! account, decrypts to a `StaticSecret`.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::{self, Write};
+
+use aes::Aes128;
+use ctr::cipher::{KeyIvInit, StreamCipher};
+use rand_core::{CryptoRng, RngCore};
+use sha3::{Digest, Keccak256};
+use subtle::ConstantTimeEq;
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::x25519::StaticSecret;
+
+/// The keystore format version.
+pub const VERSION: u32 = 3;
+
+/// The largest scrypt memory size accepted, `128 * r * n` bytes: 1 GiB,
+/// four times the default.
+pub const MAX_MEMORY: u64 = 1 << 30;
+
+/// The largest scrypt parallelism accepted.
+pub const MAX_PARALLELISM: u32 = 16;
+
+/// The cipher name of AES-128 in counter mode.
+const CIPHER_AES_128_CTR: &str = "aes-128-ctr";
+
+/// The key derivation function name of scrypt.
+const KDF_SCRYPT: &str = "scrypt";
+
+/// The length of the key derived from the password.
+const DKLEN: u32 = 32;
+
+/// The length of the random salt.
+const SALT_LEN: usize = 32;
+
+type Aes128Ctr = ctr::Ctr128BE<Aes128>;
+
+/// Errors which may occur when reading or writing a [`Keystore`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// The keystore is of a version other than [`VERSION`].
+    UnsupportedVersion,
+    /// The cipher is not `"aes-128-ctr"`.
+    UnsupportedCipher,
+    /// The key derivation function is not `"scrypt"`.
+    UnsupportedKdf,
+    /// The scrypt parameters are out of range, or above [`MAX_MEMORY`] or
+    /// [`MAX_PARALLELISM`].
+    InvalidParams,
+    /// A hex-encoded member is not valid hex, or the IV is not 16 bytes.
+    InvalidEncoding,
+    /// The MAC does not match, because the password is wrong or the keystore
+    /// has been modified.
+    InvalidMac,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::UnsupportedVersion => f.write_str("unsupported keystore version"),
+            Error::UnsupportedCipher => f.write_str("keystore cipher is not \"aes-128-ctr\""),
+            Error::UnsupportedKdf => f.write_str("keystore key derivation is not \"scrypt\""),
+            Error::InvalidParams => f.write_str("invalid scrypt parameters"),
+            Error::InvalidEncoding => f.write_str("keystore member is not valid hex"),
+            Error::InvalidMac => f.write_str("wrong password or corrupted keystore"),
+        }
+    }
+}
+
+/// A version 3 JSON keystore.
+///
+/// This type implements `serde::Serialize` and `serde::Deserialize`, so it
+/// can be read from and written to JSON with e.g. `serde_json`.  Members
+/// other than these, such as the `address` of Ethereum keystores, are
+/// ignored when reading.  The hex-encoded members are only validated when
+/// the keystore is decrypted.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Keystore {
+    /// The format version.  Must be `3`.
+    pub version: u32,
+    /// A random (version 4) UUID identifying the keystore.
+    pub id: String,
+    /// The encrypted secret key and the parameters to decrypt it.
+    #[serde(alias = "Crypto")]
+    pub crypto: Crypto,
+}
+
+/// The `crypto` member of a [`Keystore`].
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Crypto {
+    /// The cipher.  Must be `"aes-128-ctr"`.
+    pub cipher: String,
+    /// The cipher parameters.
+    pub cipherparams: CipherParams,
+    /// The hex-encoded encrypted secret key.
+    pub ciphertext: String,
+    /// The key derivation function.  Must be `"scrypt"`.
+    pub kdf: String,
+    /// The key derivation parameters.
+    pub kdfparams: KdfParams,
+    /// The hex-encoded Keccak-256 MAC.
+    pub mac: String,
+}
+
+/// The `cipherparams` member of a [`Crypto`].
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct CipherParams {
+    /// The hex-encoded 16-byte initial counter block.
+    pub iv: String,
+}
+
+/// The `kdfparams` member of a [`Crypto`], for scrypt.
+///
+/// The scrypt members default to zero when absent, so that a keystore with
+/// another key derivation function can be read, and is then rejected on
+/// decryption.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct KdfParams {
+    /// The length of the derived key.  Must be `32`.
+    pub dklen: u32,
+    /// The CPU and memory cost, a power of two.
+    #[serde(default)]
+    pub n: u64,
+    /// The block size.
+    #[serde(default)]
+    pub r: u32,
+    /// The parallelism.
+    #[serde(default)]
+    pub p: u32,
+    /// The hex-encoded salt.
+    pub salt: String,
+}
+
+/// The scrypt cost parameters for writing a keystore.
+///
+/// The default is that of `geth`, `n = 2^18`, `r = 8` and `p = 1`, which
+/// uses 256 MiB of memory and takes around a second on current computers.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Params {
+    /// The base two logarithm of the CPU and memory cost `n`.
+    pub log_n: u8,
+    /// The block size.
+    pub r: u32,
+    /// The parallelism.
+    pub p: u32,
+}
+
+impl Default for Params {
+    fn default() -> Params {
+        Params {
+            log_n: 18,
+            r: 8,
+            p: 1,
+        }
+    }
+}
+
+/// Derive the 32-byte key from `password`, `salt` and `params`.
+fn derive_key(password: &[u8], salt: &[u8], params: &Params) -> Result<Zeroizing<[u8; 32]>, Error> {
+    if params.log_n >= 64
+        || (128 * u128::from(params.r)) << params.log_n > MAX_MEMORY.into()
+        || params.p > MAX_PARALLELISM
+    {
+        return Err(Error::InvalidParams);
+    }
+    let params = scrypt::Params::new(params.log_n, params.r, params.p, DKLEN as usize)
+        .map_err(|_| Error::InvalidParams)?;
+    let mut key = Zeroizing::new([0u8; 32]);
+    scrypt::scrypt(password, salt, &params, &mut key[..]).map_err(|_| Error::InvalidParams)?;
+    Ok(key)
+}
+
+/// The Keccak-256 MAC of `ciphertext` under the second half of `key`.
+fn mac(key: &[u8; 32], ciphertext: &[u8]) -> [u8; 32] {
+    let mut hasher = Keccak256::new();
+    hasher.update(&key[16..]);
+    hasher.update(ciphertext);
+    hasher.finalize().into()
+}
+
+/// Encode `bytes` as lowercase hex.
+fn encode_hex(bytes: &[u8]) -> String {
+    let mut hex = String::with_capacity(2 * bytes.len());
+    for byte in bytes {
+        write!(hex, "{:02x}", byte).expect("writing to a String does not fail");
+    }
+    hex
+}
+
+/// Decode hex, of either case.
+fn decode_hex(hex: &str) -> Result<Vec<u8>, Error> {
+    fn digit(c: u8) -> Result<u8, Error> {
+        match c {
+            b'0'..=b'9' => Ok(c - b'0'),
+            b'a'..=b'f' => Ok(c - b'a' + 10),
+            b'A'..=b'F' => Ok(c - b'A' + 10),
+            _ => Err(Error::InvalidEncoding),
+        }
+    }
+    let hex = hex.as_bytes();
+    if hex.len() % 2 != 0 {
+        return Err(Error::InvalidEncoding);
+    }
+    hex.chunks(2)
+        .map(|pair| Ok(digit(pair[0])? << 4 | digit(pair[1])?))
+        .collect()
+}
+
+/// Format 16 random bytes as a version 4 UUID.
+fn uuid_v4(mut bytes: [u8; 16]) -> String {
+    bytes[6] = bytes[6] & 0x0f | 0x40;
+    bytes[8] = bytes[8] & 0x3f | 0x80;
+    let hex = encode_hex(&bytes);
+    let mut uuid = String::with_capacity(36);
+    for (i, range) in [0..8, 8..12, 12..16, 16..20, 20..32]
+        .into_iter()
+        .enumerate()
+    {
+        if i > 0 {
+            uuid.push('-');
+        }
+        uuid.push_str(&hex[range]);
+    }
+    uuid
+}
+
+impl StaticSecret {
+    /// Encrypt this secret key into a version 3 keystore under `password`,
+    /// with a key derived by scrypt with `params`, and a salt, IV and UUID
+    /// from `csprng`.
+    ///
+    /// Parameters above [`MAX_MEMORY`] or [`MAX_PARALLELISM`] are rejected,
+    /// as they are by [`from_keystore`](StaticSecret::from_keystore).
+    pub fn to_keystore<T: RngCore + CryptoRng>(
+        &self,
+        password: &[u8],
+        params: &Params,
+        mut csprng: T,
+    ) -> Result<Keystore, crate::Error> {
+        let mut salt = [0u8; SALT_LEN];
+        let mut iv = [0u8; 16];
+        let mut id = [0u8; 16];
+        for buf in [&mut salt[..], &mut iv[..], &mut id[..]] {
+            csprng.try_fill_bytes(buf).map_err(|_| crate::Error::Rng)?;
+        }
+
+        let key = derive_key(password, &salt, params)?;
+        let mut ciphertext = self.to_bytes();
+        Aes128Ctr::new(key[..16].into(), &iv.into()).apply_keystream(&mut ciphertext);
+        let mac = mac(&key, &ciphertext);
+
+        Ok(Keystore {
+            version: VERSION,
+            id: uuid_v4(id),
+            crypto: Crypto {
+                cipher: String::from(CIPHER_AES_128_CTR),
+                cipherparams: CipherParams {
+                    iv: encode_hex(&iv),
+                },
+                ciphertext: encode_hex(&ciphertext),
+                kdf: String::from(KDF_SCRYPT),
+                kdfparams: KdfParams {
+                    dklen: DKLEN,
+                    n: 1 << params.log_n,
+                    r: params.r,
+                    p: params.p,
+                    salt: encode_hex(&salt),
+                },
+                mac: encode_hex(&mac),
+            },
+        })
+    }
+
+    /// Decrypt a secret key from a version 3 keystore under `password`.
+    ///
+    /// The scrypt parameters come from the keystore, before it can be
+    /// authenticated, so they are limited to [`MAX_MEMORY`] and
+    /// [`MAX_PARALLELISM`], which bound the memory and time a keystore from
+    /// an untrusted source can make this function use.
+    pub fn from_keystore(
+        keystore: &Keystore,
+        password: &[u8],
+    ) -> Result<StaticSecret, crate::Error> {
+        let crypto = &keystore.crypto;
+        if keystore.version != VERSION {
+            return Err(Error::UnsupportedVersion.into());
+        }
+        if crypto.cipher != CIPHER_AES_128_CTR {
+            return Err(Error::UnsupportedCipher.into());
+        }
+        if crypto.kdf != KDF_SCRYPT {
+            return Err(Error::UnsupportedKdf.into());
+        }
+        let kdfparams = &crypto.kdfparams;
+        if kdfparams.dklen != DKLEN || !kdfparams.n.is_power_of_two() || kdfparams.n < 2 {
+            return Err(Error::InvalidParams.into());
+        }
+        let params = Params {
+            log_n: kdfparams.n.trailing_zeros() as u8,
+            r: kdfparams.r,
+            p: kdfparams.p,
+        };
+
+        let salt = decode_hex(&kdfparams.salt)?;
+        let iv: [u8; 16] = decode_hex(&crypto.cipherparams.iv)?
+            .try_into()
+            .map_err(|_| Error::InvalidEncoding)?;
+        let expected_mac = decode_hex(&crypto.mac)?;
+        let ciphertext = decode_hex(&crypto.ciphertext)?;
+        let mut bytes: [u8; 32] =
+            ciphertext
+                .as_slice()
+                .try_into()
+                .map_err(|_| crate::Error::InvalidLength {
+                    expected: 32,
+                    actual: ciphertext.len(),
+                })?;
+
+        let key = derive_key(password, &salt, &params)?;
+        if !bool::from(mac(&key, &bytes)[..].ct_eq(&expected_mac)) {
+            return Err(Error::InvalidMac.into());
+        }
+        Aes128Ctr::new(key[..16].into(), &iv.into()).apply_keystream(&mut bytes);
+        let secret = StaticSecret::from(bytes);
+        bytes.zeroize();
+        Ok(secret)
+    }
+}
@@ -0,0 +1,111 @@
+// -*- mode: rust; -*-
+//
+// This file is part of x25519-dalek.
+// Copyright (c) 2017-2021 isis lovecruft
+// Copyright (c) 2019-2021 DebugSteven
+// See LICENSE for licensing information.
+//
+// Authors:
+// - isis agora lovecruft <isis@patternsinthevoid.net>
+// - DebugSteven <debugsteven@gmail.com>
+
+//! The Montgomery ladder of RFC 7748 over the field arithmetic of
+//! [`crate::field`], for the features which need its projective result.
+//!
+//! `MontgomeryPoint::mul_clamped` runs the same ladder inside
+//! `curve25519-dalek`, but only returns the affine result.
+
+use subtle::{Choice, ConditionallySelectable};
+#[cfg(feature = "zeroize")]
+use zeroize::Zeroize;
+
+use crate::field::FieldElement;
+
+/// The state of a ladder multiplying the point with the u-coordinate `u`
+/// by a scalar, one bit at a time from the top.
+#[cfg_attr(feature = "zeroize", derive(Zeroize))]
+#[cfg_attr(feature = "zeroize", zeroize(drop))]
+pub(crate) struct Ladder {
+    u: FieldElement,
+    x2: FieldElement,
+    z2: FieldElement,
+    x3: FieldElement,
+    z3: FieldElement,
+    /// Whether the two points are swapped, as a `Choice`, which cannot be
+    /// wiped.
+    swap: u8,
+}
+
+impl Ladder {
+    /// Start a ladder over the point with the encoded u-coordinate `u`,
+    /// whose top bit is ignored.
+    pub(crate) fn new(u: &[u8; 32]) -> Ladder {
+        let u = FieldElement::from_bytes(u);
+        Ladder {
+            u,
+            x2: FieldElement::from_u32(1),
+            z2: FieldElement::from_u32(0),
+            x3: u,
+            z3: FieldElement::from_u32(1),
+            swap: 0,
+        }
+    }
+
+    /// Scale the projective coordinates of the two points of the ladder by
+    /// the nonzero `lambda2` and `lambda3`, before any step has been run.
+    ///
+    /// The points stay the same, but the values the ladder computes on are
+    /// no longer predictable from the input point.
+    #[cfg(feature = "projective_randomization")]
+    pub(crate) fn randomize(&mut self, lambda2: &FieldElement, lambda3: &FieldElement) {
+        self.x2 = &self.x2 * lambda2;
+        self.z2 = &self.z2 * lambda2;
+        self.x3 = &self.x3 * lambda3;
+        self.z3 = &self.z3 * lambda3;
+    }
+
+    /// Run one step of the ladder for the next `bit` of the scalar.
+    pub(crate) fn step(&mut self, bit: Choice) {
+        let swap = Choice::from(self.swap) ^ bit;
+        FieldElement::conditional_swap(&mut self.x2, &mut self.x3, swap);
+        FieldElement::conditional_swap(&mut self.z2, &mut self.z3, swap);
+        self.swap = bit.unwrap_u8();
+
+        // Sums and differences stay uncarried, as they are only multiplied.
+        let a = self.x2.add_loose(&self.z2);
+        let aa = a.square();
+        let b = self.x2.sub_loose(&self.z2);
+        let bb = b.square();
+        let e = aa.sub_loose(&bb);
+        let c = self.x3.add_loose(&self.z3);
+        let d = self.x3.sub_loose(&self.z3);
+        let da = &d * &a;
+        let cb = &c * &b;
+        self.x3 = da.add_loose(&cb).square();
+        self.z3 = &self.u.relax() * &da.sub_loose(&cb).square().relax();
+        self.x2 = &aa * &bb;
+        self.z2 = &e * &bb.add_loose(&e.mul_a24());
+    }
+
+    /// The product in projective coordinates \\( (X : Z) \\), once a step
+    /// has been run for every bit of the scalar.
+    ///
+    /// The point at infinity has \\( Z = 0 \\).
+    pub(crate) fn finish(&mut self) -> (FieldElement, FieldElement) {
+        let swap = Choice::from(self.swap);
+        FieldElement::conditional_swap(&mut self.x2, &mut self.x3, swap);
+        FieldElement::conditional_swap(&mut self.z2, &mut self.z3, swap);
+        self.swap = 0;
+        (self.x2, self.z2)
+    }
+
+    /// Run the ladder for the clamped `scalar` and return the product in
+    /// projective coordinates, as [`Ladder::finish`] does.
+    #[cfg(any(feature = "batch", feature = "projective_randomization"))]
+    pub(crate) fn mul_clamped(&mut self, scalar: &[u8; 32]) -> (FieldElement, FieldElement) {
+        for i in (0..255).rev() {
+            self.step(Choice::from((scalar[i >> 3] >> (i & 7)) & 1));
+        }
+        self.finish()
+    }
+}
@@ -15,7 +15,6 @@
 // README.md as the crate documentation.
 
 #![no_std]
-#![cfg_attr(feature = "bench", feature(test))]
 #![cfg_attr(docsrs, feature(doc_auto_cfg, doc_cfg, doc_cfg_hide))]
 #![cfg_attr(docsrs, doc(cfg_hide(docsrs)))]
 #![deny(missing_docs)]
@@ -24,10 +23,235 @@
 )]
 #![doc = include_str!("../README.md")]
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+// The C interface is built as a library with a panic handler, from `std`.
+#[cfg(feature = "ffi")]
+extern crate std;
+
 //------------------------------------------------------------------------
 // x25519-dalek public API
 //------------------------------------------------------------------------
 
+mod error;
 mod x25519;
 
+#[cfg(feature = "pkcs8")]
+mod asn1;
+
+#[cfg(feature = "jwk")]
+mod jwk;
+
+#[cfg(feature = "cose")]
+mod cose;
+
+#[cfg(feature = "did_key")]
+mod did_key;
+
+#[cfg(feature = "openpgp")]
+pub mod openpgp;
+
+#[cfg(feature = "wireguard")]
+mod wireguard;
+
+#[cfg(feature = "age")]
+mod age;
+
+#[cfg(feature = "tor")]
+mod tor;
+
+#[cfg(feature = "base64")]
+mod base64;
+
+#[cfg(feature = "rand_core_0_9")]
+mod rng_0_9;
+
+#[cfg(feature = "derive_from_seed")]
+mod derive;
+
+#[cfg(feature = "slip10")]
+pub mod slip10;
+
+#[cfg(feature = "fingerprint")]
+mod fingerprint;
+
+#[cfg(feature = "shamir")]
+pub mod shamir;
+
+#[cfg(feature = "threshold")]
+pub mod threshold;
+
+#[cfg(feature = "proof_of_possession")]
+pub mod proof_of_possession;
+
+#[cfg(feature = "dleq")]
+pub mod dleq;
+
+#[cfg(feature = "key_blinding")]
+mod key_blinding;
+
+#[cfg(feature = "scalar_blinding")]
+mod scalar_blinding;
+
+#[cfg(feature = "projective_randomization")]
+mod projective_randomization;
+
+#[cfg(feature = "resumable")]
+mod resumable;
+
+#[cfg(feature = "kdf")]
+pub mod kdf;
+
+#[cfg(feature = "hpke")]
+pub mod hpke;
+
+#[cfg(feature = "x25519_mlkem768")]
+pub mod x25519_mlkem768;
+
+#[cfg(feature = "ecies")]
+pub mod ecies;
+
+#[cfg(feature = "sodium")]
+pub mod sodium;
+
+#[cfg(feature = "scalarmult")]
+pub mod scalarmult;
+
+#[cfg(feature = "noise")]
+pub mod noise;
+
+#[cfg(feature = "noise_ik")]
+pub mod noise_ik;
+
+#[cfg(feature = "x3dh")]
+pub mod x3dh;
+
+#[cfg(feature = "ratchet")]
+pub mod ratchet;
+
+#[cfg(feature = "ed25519")]
+mod ed25519;
+
+#[cfg(feature = "batch")]
+mod batch;
+
+#[cfg(feature = "parallel")]
+mod parallel;
+
+#[cfg(feature = "precomputed-tables")]
+mod precomputed;
+
+#[cfg(feature = "session_cache")]
+mod session_cache;
+
+#[cfg(feature = "keyring")]
+pub mod keyring;
+
+#[cfg(feature = "mlock")]
+mod locked;
+
+#[cfg(feature = "passphrase")]
+pub mod passphrase;
+
+#[cfg(feature = "json_keystore")]
+pub mod keystore;
+
+#[cfg(any(
+    feature = "batch",
+    feature = "elligator",
+    feature = "projective_randomization",
+    feature = "resumable"
+))]
+mod field;
+
+#[cfg(any(
+    feature = "batch",
+    feature = "projective_randomization",
+    feature = "resumable"
+))]
+mod ladder;
+
+#[cfg(feature = "elligator")]
+mod elligator;
+
+#[cfg(feature = "ntor")]
+pub mod ntor;
+
+#[cfg(feature = "ntor_v3")]
+pub mod ntor_v3;
+
+#[cfg(any(feature = "cpace", feature = "spake2"))]
+pub mod pake;
+
+#[cfg(feature = "opaque")]
+pub mod opaque;
+
+#[cfg(feature = "test-vectors")]
+pub mod test_vectors;
+
+#[cfg(feature = "self_test")]
+mod self_test;
+
+#[cfg(feature = "ffi")]
+pub mod ffi;
+
+#[cfg(feature = "agreement")]
+pub mod agreement;
+
+pub use crate::error::Error;
 pub use crate::x25519::*;
+
+#[cfg(feature = "jwk")]
+pub use crate::jwk::{Jwk, JwkError};
+
+#[cfg(feature = "cose")]
+pub use crate::cose::CoseError;
+
+#[cfg(feature = "did_key")]
+pub use crate::did_key::DidKeyError;
+
+#[cfg(feature = "age")]
+pub use crate::age::AgeKeyError;
+
+#[cfg(feature = "tor")]
+pub use crate::tor::TorKeyError;
+
+#[cfg(feature = "base64")]
+pub use crate::base64::Base64Error;
+
+#[cfg(feature = "self_test")]
+pub use crate::self_test::{self_test, SelfTestError};
+
+#[cfg(feature = "elligator")]
+pub use crate::elligator::Representative;
+
+#[cfg(feature = "precomputed-tables")]
+pub use crate::precomputed::PrecomputedPublicKey;
+
+#[cfg(feature = "session_cache")]
+pub use crate::session_cache::SessionCache;
+
+#[cfg(feature = "resumable")]
+pub use crate::resumable::DhOperation;
+
+#[cfg(feature = "mlock")]
+pub use crate::locked::LockedSecret;
+
+#[cfg(feature = "pkcs8")]
+pub use pkcs8;
+
+#[cfg(any(feature = "fingerprint", feature = "kdf"))]
+pub use digest;
+
+#[cfg(feature = "kem")]
+pub use kem;
+
+#[cfg(feature = "ecies")]
+pub use aead;
+
+#[cfg(feature = "noise")]
+pub use snow;
+
+#[cfg(feature = "ed25519")]
+pub use ed25519_dalek;
@@ -0,0 +1,148 @@
+// -*- mode: rust; -*-
+//
+// This file is part of x25519-dalek.
+// Copyright (c) 2017-2021 isis lovecruft
+// Copyright (c) 2019-2021 DebugSteven
+// See LICENSE for licensing information.
+//
+// Authors:
+// - isis agora lovecruft <isis@patternsinthevoid.net>
+// - DebugSteven <debugsteven@gmail.com>
+
+//! Static secrets in page-locked memory.
+//!
+//! The secret key of a [`LockedSecret`] lives in pages of its own, allocated
+//! with `memsec`'s guarded allocator, which are locked into RAM with `mlock`
+//! or `VirtualLock` so that they are never written to swap, and on Linux and
+//! FreeBSD are excluded from core dumps.  Guard pages on either side catch
+//! overruns, and the pages are wiped when the secret is dropped.
+
+use core::mem;
+use core::ptr::{self, NonNull};
+
+use rand_core::{CryptoRng, RngCore};
+
+use crate::error::Error;
+use crate::x25519::{PublicKey, SharedSecret, StaticSecret, X25519Provider};
+
+/// A [`StaticSecret`] held in page-locked memory, for long-lived identity
+/// keys which must not be swapped to disk or captured in core dumps.
+///
+/// Each secret takes a locked page, two guard pages and a header page, and
+/// the locked pages a process may hold are limited, by `RLIMIT_MEMLOCK` on
+/// Unix, so this suits a few long-term keys rather than every key of a
+/// program.  The secret key is generated or copied directly into the locked
+/// memory, without passing through the stack.
+pub struct LockedSecret {
+    secret: NonNull<StaticSecret>,
+}
+
+// The `StaticSecret` is owned by the `LockedSecret` alone.
+unsafe impl Send for LockedSecret {}
+unsafe impl Sync for LockedSecret {}
+
+impl LockedSecret {
+    /// Allocate and lock memory for an all-zero secret key.
+    fn zeroed() -> Result<LockedSecret, Error> {
+        // SAFETY: `malloc` returns memory for a `StaticSecret`, or `None`,
+        // and the `StaticSecret` is written before the `LockedSecret` which
+        // drops it exists.
+        unsafe {
+            let secret = memsec::malloc::<StaticSecret>().ok_or(Error::MemoryLock)?;
+            secret.as_ptr().write(StaticSecret([0u8; 32]));
+            let locked = LockedSecret { secret };
+
+            // `malloc` locks the pages but ignores failure, for instance
+            // beyond `RLIMIT_MEMLOCK`, so lock them again to find out.
+            let size = mem::size_of::<StaticSecret>();
+            if !memsec::mlock(secret.as_ptr().cast(), size) {
+                return Err(Error::MemoryLock);
+            }
+            Ok(locked)
+        }
+    }
+
+    /// Generate a new [`LockedSecret`] with the supplied RNG.
+    ///
+    /// Returns [`Error::MemoryLock`] if the memory could not be allocated or
+    /// locked, and [`Error::Rng`] if the RNG fails.
+    pub fn random_from_rng<T: RngCore + CryptoRng>(mut csprng: T) -> Result<Self, Error> {
+        let mut locked = LockedSecret::zeroed()?;
+        // The secret is wiped on drop if the RNG fails part way through.
+        csprng
+            .try_fill_bytes(&mut locked.secret_mut().0)
+            .map_err(|_| Error::Rng)?;
+        Ok(locked)
+    }
+
+    /// Generate a new [`LockedSecret`].
+    #[cfg(feature = "getrandom")]
+    pub fn random() -> Result<Self, Error> {
+        Self::random_from_rng(rand_core::OsRng)
+    }
+
+    /// The secret key.
+    #[inline]
+    pub fn secret(&self) -> &StaticSecret {
+        // SAFETY: `secret` points to a `StaticSecret` until `self` is dropped.
+        unsafe { self.secret.as_ref() }
+    }
+
+    fn secret_mut(&mut self) -> &mut StaticSecret {
+        // SAFETY: as for `secret`, and `&mut self` is unique.
+        unsafe { self.secret.as_mut() }
+    }
+
+    /// Perform a Diffie-Hellman key agreement between the secret key and
+    /// `their_public` key to produce a `SharedSecret`.
+    pub fn diffie_hellman(&self, their_public: &PublicKey) -> SharedSecret {
+        self.secret().diffie_hellman(their_public)
+    }
+}
+
+impl TryFrom<&[u8]> for LockedSecret {
+    type Error = Error;
+
+    /// Copy a secret key of exactly 32 bytes into locked memory.
+    ///
+    /// The caller remains responsible for wiping `bytes`.
+    fn try_from(bytes: &[u8]) -> Result<LockedSecret, Error> {
+        if bytes.len() != 32 {
+            return Err(Error::InvalidLength {
+                expected: 32,
+                actual: bytes.len(),
+            });
+        }
+        let mut locked = LockedSecret::zeroed()?;
+        locked.secret_mut().0.copy_from_slice(bytes);
+        Ok(locked)
+    }
+}
+
+impl Drop for LockedSecret {
+    fn drop(&mut self) {
+        // SAFETY: `secret` was allocated by `memsec::malloc` and holds a
+        // `StaticSecret`, which is dropped, and so wiped, exactly once.
+        unsafe {
+            ptr::drop_in_place(self.secret.as_ptr());
+            memsec::free(self.secret);
+        }
+    }
+}
+
+impl<'a> From<&'a LockedSecret> for PublicKey {
+    /// Compute the public key of the locked secret key.
+    fn from(locked: &'a LockedSecret) -> PublicKey {
+        PublicKey::from(locked.secret())
+    }
+}
+
+impl X25519Provider for LockedSecret {
+    fn public(&self) -> PublicKey {
+        PublicKey::from(self)
+    }
+
+    fn diffie_hellman(&self, their_public: &PublicKey) -> Result<SharedSecret, Error> {
+        Ok(LockedSecret::diffie_hellman(self, their_public))
+    }
+}
@@ -0,0 +1,144 @@
+// -*- mode: rust; -*-
+//
+// This file is part of x25519-dalek.
+// Copyright (c) 2017-2021 isis lovecruft
+// Copyright (c) 2019-2021 DebugSteven
+// See LICENSE for licensing information.
+//
+// Authors:
+// - isis agora lovecruft <isis@patternsinthevoid.net>
+// - DebugSteven <debugsteven@gmail.com>
+
+//! The `25519` DH function of the [Noise protocol
+//! framework](https://noiseprotocol.org/noise.html), for the `snow` crate.
+//!
+//! [`Dh25519`] implements `snow`'s `Dh` trait, and [`Resolver`] is a
+//! `CryptoResolver` which provides it for Noise patterns naming `25519`.  The
+//! resolver provides no other primitives, so it is meant to be combined with
+//! one which does through `snow`'s `FallbackResolver`:
+//!
+//! ```ignore
+//! use snow::resolvers::{DefaultResolver, FallbackResolver};
+//! use x25519_dalek::noise;
+//!
+//! let resolver = FallbackResolver::new(Box::new(noise::Resolver), Box::new(DefaultResolver));
+//! let builder = snow::Builder::with_resolver(
+//!     "Noise_XX_25519_ChaChaPoly_BLAKE2s".parse()?,
+//!     Box::new(resolver),
+//! );
+//! ```
+//!
+//! Unlike `snow`'s own implementation, [`Dh25519`] rejects public keys of
+//! small order, for which the Diffie-Hellman result is all zeroes.
+
+use alloc::boxed::Box;
+
+use snow::params::{CipherChoice, DHChoice, HashChoice};
+use snow::resolvers::CryptoResolver;
+use snow::types::{Cipher, Dh, Hash, Random};
+
+use crate::x25519::{PublicKey, StaticSecret};
+
+/// The length of a `25519` public key, `DHLEN` in the Noise specification.
+pub const DH_LEN: usize = 32;
+
+/// An X25519 key pair for `snow`.
+///
+/// The default key pair has an all-zero secret key, which `snow` replaces
+/// through [`Dh::set`] or [`Dh::generate`] before use.
+pub struct Dh25519 {
+    secret: StaticSecret,
+    public: PublicKey,
+}
+
+impl Default for Dh25519 {
+    fn default() -> Self {
+        Self::from(StaticSecret::from([0u8; 32]))
+    }
+}
+
+impl From<StaticSecret> for Dh25519 {
+    /// Use an existing secret key as a Noise key pair.
+    fn from(secret: StaticSecret) -> Self {
+        let public = PublicKey::from(&secret);
+        Dh25519 { secret, public }
+    }
+}
+
+impl Dh for Dh25519 {
+    fn name(&self) -> &'static str {
+        "25519"
+    }
+
+    fn pub_len(&self) -> usize {
+        DH_LEN
+    }
+
+    fn priv_len(&self) -> usize {
+        32
+    }
+
+    /// Set the secret key.
+    ///
+    /// # Panics
+    ///
+    /// If `privkey` is not 32 bytes long.
+    fn set(&mut self, privkey: &[u8]) {
+        let bytes = <[u8; 32]>::try_from(privkey).expect("X25519 secret keys are 32 bytes long");
+        *self = Self::from(StaticSecret::from(bytes));
+    }
+
+    fn generate(&mut self, rng: &mut dyn Random) {
+        *self = Self::from(StaticSecret::random_from_rng(rng));
+    }
+
+    fn pubkey(&self) -> &[u8] {
+        self.public.as_bytes()
+    }
+
+    fn privkey(&self) -> &[u8] {
+        self.secret.as_bytes()
+    }
+
+    /// Compute the Diffie-Hellman result with `pubkey` into the first
+    /// [`DH_LEN`] bytes of `out`.
+    ///
+    /// Fails if `pubkey` is not [`DH_LEN`] bytes long or is a point of small
+    /// order, or if `out` is too short.
+    fn dh(&self, pubkey: &[u8], out: &mut [u8]) -> Result<(), snow::Error> {
+        let pubkey = <[u8; DH_LEN]>::try_from(pubkey).map_err(|_| snow::Error::Dh)?;
+        let out = out.get_mut(..DH_LEN).ok_or(snow::Error::Dh)?;
+        let shared = self.secret.diffie_hellman(&PublicKey::from(pubkey));
+        if !shared.was_contributory() {
+            return Err(snow::Error::Dh);
+        }
+        out.copy_from_slice(shared.as_bytes());
+        Ok(())
+    }
+}
+
+/// A `snow` resolver providing [`Dh25519`] for the `25519` DH function, and
+/// nothing else.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Resolver;
+
+impl CryptoResolver for Resolver {
+    fn resolve_rng(&self) -> Option<Box<dyn Random>> {
+        None
+    }
+
+    fn resolve_dh(&self, choice: &DHChoice) -> Option<Box<dyn Dh>> {
+        match choice {
+            DHChoice::Curve25519 => Some(Box::<Dh25519>::default()),
+            _ => None,
+        }
+    }
+
+    fn resolve_hash(&self, _choice: &HashChoice) -> Option<Box<dyn Hash>> {
+        None
+    }
+
+    fn resolve_cipher(&self, _choice: &CipherChoice) -> Option<Box<dyn Cipher>> {
+        None
+    }
+}
@@ -0,0 +1,390 @@
+// -*- mode: rust; -*-
+//
+// This file is part of x25519-dalek.
+// Copyright (c) 2017-2021 isis lovecruft
+// Copyright (c) 2019-2021 DebugSteven
+// See LICENSE for licensing information.
+//
+// Authors:
+// - isis agora lovecruft <isis@patternsinthevoid.net>
+// - DebugSteven <debugsteven@gmail.com>
+
+//! The two-message `Noise_IK_25519_ChaChaPoly_BLAKE2s` handshake of the
+//! [Noise protocol framework](https://noiseprotocol.org/noise.html), the
+//! pattern underlying WireGuard:
+//!
+//! ```text
+//! IK:
+//!   <- s
+//!   ...
+//!   -> e, es, s, ss
+//!   <- e, ee, se
+//! ```
+//!
+//! The initiator knows the responder's static public key in advance, and
+//! sends its own static public key encrypted in the first message.  Each
+//! message may carry a payload, which is encrypted and authenticated by the
+//! keys derived so far.  At the end of the handshake both parties hold the
+//! same [`TransportKeys`]:
+//!
+#![cfg_attr(feature = "getrandom", doc = "```")]
+#![cfg_attr(not(feature = "getrandom"), doc = "```ignore")]
+//! use x25519_dalek::{noise_ik, PublicKey, StaticSecret};
+//!
+//! let initiator_static = StaticSecret::random();
+//! let responder_static = StaticSecret::random();
+//! let responder_public = PublicKey::from(&responder_static);
+//!
+//! let (initiator, message) = noise_ik::initiate(
+//!     b"my protocol v1",
+//!     &initiator_static,
+//!     &StaticSecret::random(),
+//!     &responder_public,
+//!     b"hello",
+//! )?;
+//!
+//! let responder = noise_ik::accept(b"my protocol v1", &responder_static, &message)?;
+//! assert_eq!(responder.remote_static(), &PublicKey::from(&initiator_static));
+//! assert_eq!(responder.payload(), b"hello");
+//! let (responder_keys, message) = responder.finish(&StaticSecret::random(), b"")?;
+//!
+//! let (initiator_keys, payload) = initiator.finish(&message)?;
+//! assert_eq!(initiator_keys.send_key(), responder_keys.receive_key());
+//! assert_eq!(initiator_keys.receive_key(), responder_keys.send_key());
+//! # Ok::<(), x25519_dalek::Error>(())
+//! ```
+//!
+//! The ephemeral secrets must be freshly generated for every handshake.  As
+//! in WireGuard, Diffie-Hellman with a public key of small order is
+//! rejected.  The transport keys are for ChaCha20-Poly1305 with the
+//! 64-bit little-endian message counter as nonce, starting from zero, which
+//! is left to the caller.
+
+use alloc::vec::Vec;
+use core::fmt;
+
+use blake2::{Blake2s256, Digest};
+use chacha20poly1305::aead::generic_array::GenericArray;
+use chacha20poly1305::{AeadInPlace, ChaCha20Poly1305, KeyInit};
+use hkdf::SimpleHkdf;
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::x25519::{PublicKey, SharedSecret, StaticSecret};
+
+/// The Noise protocol name of this handshake.
+pub const PROTOCOL_NAME: &[u8] = b"Noise_IK_25519_ChaChaPoly_BLAKE2s";
+
+/// The length of a transport key.
+pub const KEY_LEN: usize = 32;
+
+/// The length of a ChaCha20-Poly1305 tag.
+const TAG_LEN: usize = 16;
+
+/// The length of the first message, besides its payload: the initiator's
+/// ephemeral public key, its encrypted static public key, and the payload's
+/// tag.
+pub const INITIATION_OVERHEAD: usize = 32 + (32 + TAG_LEN) + TAG_LEN;
+
+/// The length of the second message, besides its payload: the responder's
+/// ephemeral public key and the payload's tag.
+pub const RESPONSE_OVERHEAD: usize = 32 + TAG_LEN;
+
+/// Errors which may occur during the handshake.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// A handshake message is truncated, or does not decrypt under the keys
+    /// derived so far.
+    InvalidMessage,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::InvalidMessage => f.write_str("handshake message could not be decrypted"),
+        }
+    }
+}
+
+/// The Noise `SymmetricState`, with the chaining key, handshake hash and
+/// current cipher key.
+#[derive(Zeroize)]
+#[zeroize(drop)]
+struct SymmetricState {
+    ck: [u8; 32],
+    h: [u8; 32],
+    k: [u8; KEY_LEN],
+    n: u64,
+}
+
+impl SymmetricState {
+    /// `InitializeSymmetric` with [`PROTOCOL_NAME`], followed by `MixHash`
+    /// of the prologue and of the responder's static public key.
+    fn new(prologue: &[u8], responder_static: &PublicKey) -> SymmetricState {
+        let h: [u8; 32] = Blake2s256::digest(PROTOCOL_NAME).into();
+        let mut state = SymmetricState {
+            ck: h,
+            h,
+            k: [0u8; KEY_LEN],
+            n: 0,
+        };
+        state.mix_hash(prologue);
+        state.mix_hash(responder_static.as_bytes());
+        state
+    }
+
+    fn mix_hash(&mut self, data: &[u8]) {
+        self.h = Blake2s256::new()
+            .chain_update(self.h)
+            .chain_update(data)
+            .finalize()
+            .into();
+    }
+
+    /// `MixKey` with a Diffie-Hellman result, rejecting an all-zero one.
+    fn mix_dh(&mut self, dh: SharedSecret) -> Result<(), crate::Error> {
+        if !dh.was_contributory() {
+            return Err(crate::Error::SmallOrderPoint);
+        }
+        let mut okm = self.hkdf(dh.as_bytes());
+        self.ck.copy_from_slice(&okm[..32]);
+        self.k.copy_from_slice(&okm[32..]);
+        self.n = 0;
+        okm.zeroize();
+        Ok(())
+    }
+
+    /// The Noise `HKDF` with two outputs, which is HKDF-BLAKE2s with the
+    /// chaining key as salt and empty info.
+    fn hkdf(&self, ikm: &[u8]) -> Zeroizing<[u8; 64]> {
+        let mut okm = Zeroizing::new([0u8; 64]);
+        SimpleHkdf::<Blake2s256>::new(Some(&self.ck), ikm)
+            .expand(&[], &mut okm[..])
+            .expect("64 bytes is a valid HKDF-BLAKE2s output length");
+        okm
+    }
+
+    /// `EncryptAndHash`, appending the ciphertext to `out`.
+    fn encrypt_and_hash(&mut self, plaintext: &[u8], out: &mut Vec<u8>) {
+        let start = out.len();
+        out.extend_from_slice(plaintext);
+        let tag = ChaCha20Poly1305::new(GenericArray::from_slice(&self.k))
+            .encrypt_in_place_detached(&nonce(self.n), &self.h, &mut out[start..])
+            .expect("handshake messages are shorter than the ChaCha20-Poly1305 limit");
+        out.extend_from_slice(&tag);
+        self.n += 1;
+        self.mix_hash(&out[start..]);
+    }
+
+    /// `DecryptAndHash`.
+    fn decrypt_and_hash(&mut self, ciphertext: &[u8]) -> Result<Vec<u8>, Error> {
+        if ciphertext.len() < TAG_LEN {
+            return Err(Error::InvalidMessage);
+        }
+        let (body, tag) = ciphertext.split_at(ciphertext.len() - TAG_LEN);
+        let mut plaintext = body.to_vec();
+        ChaCha20Poly1305::new(GenericArray::from_slice(&self.k))
+            .decrypt_in_place_detached(
+                &nonce(self.n),
+                &self.h,
+                &mut plaintext,
+                GenericArray::from_slice(tag),
+            )
+            .map_err(|_| Error::InvalidMessage)?;
+        self.n += 1;
+        self.mix_hash(ciphertext);
+        Ok(plaintext)
+    }
+
+    /// `Split`, into the initiator's and responder's sending keys.
+    fn split(&self) -> TransportKeys {
+        let okm = self.hkdf(&[]);
+        let mut keys = TransportKeys {
+            send: [0u8; KEY_LEN],
+            receive: [0u8; KEY_LEN],
+            handshake_hash: self.h,
+        };
+        keys.send.copy_from_slice(&okm[..KEY_LEN]);
+        keys.receive.copy_from_slice(&okm[KEY_LEN..]);
+        keys
+    }
+}
+
+/// The ChaCha20-Poly1305 nonce for the counter `n`: four zero bytes followed
+/// by `n` in little-endian.
+fn nonce(n: u64) -> GenericArray<u8, chacha20poly1305::consts::U12> {
+    let mut nonce = GenericArray::default();
+    nonce[4..].copy_from_slice(&n.to_le_bytes());
+    nonce
+}
+
+/// Read a public key from the start of `message`.
+fn read_public(message: &[u8]) -> Result<(PublicKey, &[u8]), Error> {
+    if message.len() < 32 {
+        return Err(Error::InvalidMessage);
+    }
+    let (public, rest) = message.split_at(32);
+    Ok((PublicKey::from(<[u8; 32]>::try_from(public).unwrap()), rest))
+}
+
+/// The keys for the transport phase, after a completed handshake.
+///
+/// The keys are wiped on drop.
+#[derive(Clone, Zeroize)]
+#[zeroize(drop)]
+pub struct TransportKeys {
+    send: [u8; KEY_LEN],
+    receive: [u8; KEY_LEN],
+    handshake_hash: [u8; 32],
+}
+
+impl TransportKeys {
+    /// The ChaCha20-Poly1305 key for sending messages to the peer.
+    pub fn send_key(&self) -> &[u8; KEY_LEN] {
+        &self.send
+    }
+
+    /// The ChaCha20-Poly1305 key for receiving messages from the peer.
+    pub fn receive_key(&self) -> &[u8; KEY_LEN] {
+        &self.receive
+    }
+
+    /// The final handshake hash, which uniquely identifies the handshake and
+    /// can be used for channel binding.
+    pub fn handshake_hash(&self) -> &[u8; 32] {
+        &self.handshake_hash
+    }
+}
+
+/// The initiator's state between sending the first message and receiving
+/// the second.
+pub struct Initiator {
+    state: SymmetricState,
+    local_static: StaticSecret,
+    local_ephemeral: StaticSecret,
+}
+
+/// Start a handshake with the responder whose static public key is
+/// `remote_static`, using the initiator's static and ephemeral secrets.
+///
+/// Returns the initiator's state and the first message, which carries
+/// `payload`.  The `prologue` must be the same for both parties.
+pub fn initiate(
+    prologue: &[u8],
+    local_static: &StaticSecret,
+    local_ephemeral: &StaticSecret,
+    remote_static: &PublicKey,
+    payload: &[u8],
+) -> Result<(Initiator, Vec<u8>), crate::Error> {
+    let mut state = SymmetricState::new(prologue, remote_static);
+    let mut message = Vec::with_capacity(INITIATION_OVERHEAD + payload.len());
+
+    let ephemeral_public = PublicKey::from(local_ephemeral);
+    message.extend_from_slice(ephemeral_public.as_bytes());
+    state.mix_hash(ephemeral_public.as_bytes());
+    state.mix_dh(local_ephemeral.diffie_hellman(remote_static))?;
+    state.encrypt_and_hash(PublicKey::from(local_static).as_bytes(), &mut message);
+    state.mix_dh(local_static.diffie_hellman(remote_static))?;
+    state.encrypt_and_hash(payload, &mut message);
+
+    let initiator = Initiator {
+        state,
+        local_static: local_static.clone(),
+        local_ephemeral: local_ephemeral.clone(),
+    };
+    Ok((initiator, message))
+}
+
+impl Initiator {
+    /// Complete the handshake with the responder's `message`.
+    ///
+    /// Returns the initiator's transport keys and the responder's payload.
+    pub fn finish(mut self, message: &[u8]) -> Result<(TransportKeys, Vec<u8>), crate::Error> {
+        let (remote_ephemeral, rest) = read_public(message)?;
+        self.state.mix_hash(remote_ephemeral.as_bytes());
+        self.state
+            .mix_dh(self.local_ephemeral.diffie_hellman(&remote_ephemeral))?;
+        self.state
+            .mix_dh(self.local_static.diffie_hellman(&remote_ephemeral))?;
+        let payload = self.state.decrypt_and_hash(rest)?;
+        Ok((self.state.split(), payload))
+    }
+}
+
+/// The responder's state between receiving the first message and sending
+/// the second.
+pub struct Responder {
+    state: SymmetricState,
+    remote_static: PublicKey,
+    remote_ephemeral: PublicKey,
+    payload: Vec<u8>,
+}
+
+/// Accept the initiator's first `message` with the responder's static
+/// secret.
+///
+/// The returned state gives the initiator's static public key and payload,
+/// so that the responder can decide whether to continue.  The `prologue`
+/// must be the same for both parties.
+pub fn accept(
+    prologue: &[u8],
+    local_static: &StaticSecret,
+    message: &[u8],
+) -> Result<Responder, crate::Error> {
+    let mut state = SymmetricState::new(prologue, &PublicKey::from(local_static));
+
+    let (remote_ephemeral, rest) = read_public(message)?;
+    state.mix_hash(remote_ephemeral.as_bytes());
+    state.mix_dh(local_static.diffie_hellman(&remote_ephemeral))?;
+    if rest.len() < 32 + TAG_LEN {
+        return Err(Error::InvalidMessage.into());
+    }
+    let (encrypted_static, rest) = rest.split_at(32 + TAG_LEN);
+    let remote_static = state.decrypt_and_hash(encrypted_static)?;
+    let remote_static = PublicKey::from(<[u8; 32]>::try_from(&remote_static[..]).unwrap());
+    state.mix_dh(local_static.diffie_hellman(&remote_static))?;
+    let payload = state.decrypt_and_hash(rest)?;
+
+    Ok(Responder {
+        state,
+        remote_static,
+        remote_ephemeral,
+        payload,
+    })
+}
+
+impl Responder {
+    /// The initiator's static public key.
+    pub fn remote_static(&self) -> &PublicKey {
+        &self.remote_static
+    }
+
+    /// The payload of the initiator's message.
+    pub fn payload(&self) -> &[u8] {
+        &self.payload
+    }
+
+    /// Complete the handshake with the responder's ephemeral secret.
+    ///
+    /// Returns the responder's transport keys and the second message, which
+    /// carries `payload`.
+    pub fn finish(
+        mut self,
+        local_ephemeral: &StaticSecret,
+        payload: &[u8],
+    ) -> Result<(TransportKeys, Vec<u8>), crate::Error> {
+        let mut message = Vec::with_capacity(RESPONSE_OVERHEAD + payload.len());
+
+        let ephemeral_public = PublicKey::from(local_ephemeral);
+        message.extend_from_slice(ephemeral_public.as_bytes());
+        self.state.mix_hash(ephemeral_public.as_bytes());
+        self.state
+            .mix_dh(local_ephemeral.diffie_hellman(&self.remote_ephemeral))?;
+        self.state
+            .mix_dh(local_ephemeral.diffie_hellman(&self.remote_static))?;
+        self.state.encrypt_and_hash(payload, &mut message);
+
+        let mut keys = self.state.split();
+        core::mem::swap(&mut keys.send, &mut keys.receive);
+        Ok((keys, message))
+    }
+}
@@ -0,0 +1,272 @@
+// -*- mode: rust; -*-
+//
+// This file is part of x25519-dalek.
+// Copyright (c) 2017-2021 isis lovecruft
+// Copyright (c) 2019-2021 DebugSteven
+// See LICENSE for licensing information.
+//
+// Authors:
+// - isis agora lovecruft <isis@patternsinthevoid.net>
+// - DebugSteven <debugsteven@gmail.com>
+
+//! The ntor circuit-extension handshake of Tor, as specified in section
+//! 5.1.4 of the [Tor protocol specification](https://spec.torproject.org/tor-spec/create-created-cells.html#ntor).
+//!
+//! A client extends a circuit to a relay known by its 20-byte identity
+//! digest and its ntor onion key.  The client sends a [`client_handshake`]
+//! message in a `CREATE2` cell, the relay answers with a
+//! [`server_handshake`] message in a `CREATED2` cell, and both derive the
+//! same key material from a [`KeyGenerator`]:
+//!
+#![cfg_attr(feature = "getrandom", doc = "```")]
+#![cfg_attr(not(feature = "getrandom"), doc = "```ignore")]
+//! use rand_core::OsRng;
+//! use x25519_dalek::{ntor, PublicKey, StaticSecret};
+//!
+//! let relay_id = [0x42; ntor::NODE_ID_LEN];
+//! let relay_onion_key = StaticSecret::random();
+//!
+//! let (client, create) =
+//!     ntor::client_handshake(&relay_id, &PublicKey::from(&relay_onion_key), OsRng);
+//! let (relay_keys, created) =
+//!     ntor::server_handshake(&relay_id, &relay_onion_key, &create, OsRng)?;
+//! let client_keys = client.finish(&created)?;
+//!
+//! // Digests and keys for relay cell encryption.
+//! let mut client_material = [0u8; 72];
+//! let mut relay_material = [0u8; 72];
+//! client_keys.expand(&mut client_material);
+//! relay_keys.expand(&mut relay_material);
+//! assert_eq!(client_material, relay_material);
+//! # Ok::<(), x25519_dalek::Error>(())
+//! ```
+
+use core::fmt;
+
+use hkdf::Hkdf;
+use hmac::{Hmac, Mac};
+use rand_core::{CryptoRng, RngCore};
+use sha2::Sha256;
+use subtle::ConstantTimeEq;
+use zeroize::Zeroizing;
+
+use crate::x25519::{PublicKey, SharedSecret, StaticSecret};
+
+/// The protocol identifier, `PROTOID`.
+pub const PROTOID: &[u8] = b"ntor-curve25519-sha256-1";
+
+/// The length of a relay's identity digest, `ID`.
+pub const NODE_ID_LEN: usize = 20;
+
+/// The length of the client's handshake message: the relay's identity
+/// digest, its onion key, and the client's ephemeral key.
+pub const CLIENT_HANDSHAKE_LEN: usize = NODE_ID_LEN + 32 + 32;
+
+/// The length of the relay's handshake message: its ephemeral key and the
+/// authenticator.
+pub const SERVER_HANDSHAKE_LEN: usize = 32 + 32;
+
+const T_MAC: &[u8] = b"ntor-curve25519-sha256-1:mac";
+const T_KEY: &[u8] = b"ntor-curve25519-sha256-1:key_extract";
+const T_VERIFY: &[u8] = b"ntor-curve25519-sha256-1:verify";
+const M_EXPAND: &[u8] = b"ntor-curve25519-sha256-1:key_expand";
+
+/// Errors which may occur in the handshake.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// The client's message names another relay or another onion key.
+    UnknownKey,
+    /// The relay's authenticator is wrong.
+    AuthenticationFailed,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::UnknownKey => f.write_str("handshake is for another relay or onion key"),
+            Error::AuthenticationFailed => f.write_str("ntor server authentication failed"),
+        }
+    }
+}
+
+/// The source of the key material of a completed handshake.
+///
+/// The key seed is wiped on drop.
+pub struct KeyGenerator {
+    key_seed: Zeroizing<[u8; 32]>,
+}
+
+impl KeyGenerator {
+    /// Fill `okm` with key material, the HKDF-SHA256 expansion of the key
+    /// seed.
+    ///
+    /// For relay cell encryption, this is 72 bytes: the forward and
+    /// backward digest seeds, followed by the forward and backward keys.
+    ///
+    /// # Panics
+    ///
+    /// If `okm` is longer than 8160 bytes.
+    pub fn expand(&self, okm: &mut [u8]) {
+        Hkdf::<Sha256>::from_prk(&self.key_seed[..])
+            .expect("32 bytes is a valid HKDF-SHA256 PRK length")
+            .expand(M_EXPAND, okm)
+            .expect("at most 8160 bytes of key material");
+    }
+}
+
+/// The client's state between sending its handshake message and receiving
+/// the relay's.
+///
+/// The ephemeral secret key is wiped on drop.
+pub struct ClientState {
+    node_id: [u8; NODE_ID_LEN],
+    server_key: PublicKey,
+    secret: StaticSecret,
+    public: PublicKey,
+}
+
+/// Start a handshake with the relay whose identity digest is `node_id` and
+/// whose ntor onion key is `server_key`, using an ephemeral key from
+/// `csprng`.
+///
+/// Returns the client's state and the message to send to the relay.
+pub fn client_handshake<T: RngCore + CryptoRng>(
+    node_id: &[u8; NODE_ID_LEN],
+    server_key: &PublicKey,
+    csprng: T,
+) -> (ClientState, [u8; CLIENT_HANDSHAKE_LEN]) {
+    let secret = StaticSecret::random_from_rng(csprng);
+    let public = PublicKey::from(&secret);
+
+    let mut message = [0u8; CLIENT_HANDSHAKE_LEN];
+    message[..NODE_ID_LEN].copy_from_slice(node_id);
+    message[NODE_ID_LEN..NODE_ID_LEN + 32].copy_from_slice(server_key.as_bytes());
+    message[NODE_ID_LEN + 32..].copy_from_slice(public.as_bytes());
+
+    let state = ClientState {
+        node_id: *node_id,
+        server_key: *server_key,
+        secret,
+        public,
+    };
+    (state, message)
+}
+
+impl ClientState {
+    /// Complete the handshake with the relay's `message`.
+    ///
+    /// Fails if the relay's ephemeral key is a point of small order, or if
+    /// its authenticator, which is compared in constant time, is wrong.
+    pub fn finish(
+        self,
+        message: &[u8; SERVER_HANDSHAKE_LEN],
+    ) -> Result<KeyGenerator, crate::Error> {
+        let mut server_public = [0u8; 32];
+        server_public.copy_from_slice(&message[..32]);
+        let server_public = PublicKey::from(server_public);
+
+        let (keys, auth) = derive(
+            &self.secret.diffie_hellman(&server_public),
+            &self.secret.diffie_hellman(&self.server_key),
+            &self.node_id,
+            &self.server_key,
+            &self.public,
+            &server_public,
+        )?;
+        if !bool::from(auth.ct_eq(&message[32..])) {
+            return Err(Error::AuthenticationFailed.into());
+        }
+        Ok(keys)
+    }
+}
+
+/// Answer a client's handshake `message` as the relay whose identity digest
+/// is `node_id` and whose ntor onion key is `server_secret`, using an
+/// ephemeral key from `csprng`.
+///
+/// Returns the key material and the message to send to the client.  Fails
+/// with [`Error::UnknownKey`] if the message names another identity digest
+/// or onion key, in which case a relay which still accepts its previous
+/// onion key can retry with it.
+pub fn server_handshake<T: RngCore + CryptoRng>(
+    node_id: &[u8; NODE_ID_LEN],
+    server_secret: &StaticSecret,
+    message: &[u8; CLIENT_HANDSHAKE_LEN],
+    csprng: T,
+) -> Result<(KeyGenerator, [u8; SERVER_HANDSHAKE_LEN]), crate::Error> {
+    let server_key = PublicKey::from(server_secret);
+    if message[..NODE_ID_LEN] != node_id[..]
+        || message[NODE_ID_LEN..NODE_ID_LEN + 32] != server_key.as_bytes()[..]
+    {
+        return Err(Error::UnknownKey.into());
+    }
+    let mut client_public = [0u8; 32];
+    client_public.copy_from_slice(&message[NODE_ID_LEN + 32..]);
+    let client_public = PublicKey::from(client_public);
+
+    let secret = StaticSecret::random_from_rng(csprng);
+    let public = PublicKey::from(&secret);
+    let (keys, auth) = derive(
+        &secret.diffie_hellman(&client_public),
+        &server_secret.diffie_hellman(&client_public),
+        node_id,
+        &server_key,
+        &client_public,
+        &public,
+    )?;
+
+    let mut reply = [0u8; SERVER_HANDSHAKE_LEN];
+    reply[..32].copy_from_slice(public.as_bytes());
+    reply[32..].copy_from_slice(&auth[..]);
+    Ok((keys, reply))
+}
+
+/// `H(x, t)`: HMAC-SHA256 of the concatenated `parts` with the key `tweak`.
+fn hmac(tweak: &[u8], parts: &[&[u8]]) -> Zeroizing<[u8; 32]> {
+    let mut mac =
+        <Hmac<Sha256> as Mac>::new_from_slice(tweak).expect("HMAC accepts keys of any length");
+    for part in parts {
+        mac.update(part);
+    }
+    Zeroizing::new(mac.finalize().into_bytes().into())
+}
+
+/// Derive the key generator and the relay's authenticator from the two
+/// Diffie-Hellman results, `EXP(X, y)` and `EXP(X, b)`, and the public keys.
+fn derive(
+    exp_xy: &SharedSecret,
+    exp_xb: &SharedSecret,
+    node_id: &[u8; NODE_ID_LEN],
+    server_key: &PublicKey,
+    client_public: &PublicKey,
+    server_public: &PublicKey,
+) -> Result<(KeyGenerator, Zeroizing<[u8; 32]>), crate::Error> {
+    if !exp_xy.was_contributory() || !exp_xb.was_contributory() {
+        return Err(crate::Error::SmallOrderPoint);
+    }
+
+    let secret_input: [&[u8]; 7] = [
+        exp_xy.as_bytes(),
+        exp_xb.as_bytes(),
+        node_id,
+        server_key.as_bytes(),
+        client_public.as_bytes(),
+        server_public.as_bytes(),
+        PROTOID,
+    ];
+    let key_seed = hmac(T_KEY, &secret_input);
+    let verify = hmac(T_VERIFY, &secret_input);
+    let auth = hmac(
+        T_MAC,
+        &[
+            &verify[..],
+            node_id,
+            server_key.as_bytes(),
+            server_public.as_bytes(),
+            client_public.as_bytes(),
+            PROTOID,
+            b"Server",
+        ],
+    );
+    Ok((KeyGenerator { key_seed }, auth))
+}
@@ -0,0 +1,484 @@
+// -*- mode: rust; -*-
+//
+// This file is part of x25519-dalek.
+// Copyright (c) 2017-2021 isis lovecruft
+// Copyright (c) 2019-2021 DebugSteven
+// See LICENSE for licensing information.
+//
+// Authors:
+// - isis agora lovecruft <isis@patternsinthevoid.net>
+// - DebugSteven <debugsteven@gmail.com>
+
+//! The ntor-v3 circuit-extension handshake of Tor, as specified in the
+//! [Tor protocol specification](https://spec.torproject.org/tor-spec/create-created-cells.html#ntor-v3).
+//!
+//! Like [ntor](crate::ntor), ntor-v3 authenticates a relay by its onion key
+//! with two Diffie-Hellman exchanges, but it identifies the relay by its
+//! Ed25519 identity, uses SHA3-256 and SHAKE-256 in its key derivation, and
+//! carries an encrypted message in each direction, such as the negotiation
+//! of congestion control.  Both parties also bind the handshake to a
+//! verification string which they agree on in advance.
+//!
+//! The relay reads the client's message before choosing its reply:
+//!
+#![cfg_attr(feature = "getrandom", doc = "```")]
+#![cfg_attr(not(feature = "getrandom"), doc = "```ignore")]
+//! use rand_core::OsRng;
+//! use x25519_dalek::{ntor_v3, PublicKey, StaticSecret};
+//!
+//! let relay_id = [0x42; ntor_v3::NODE_ID_LEN];
+//! let relay_onion_key = StaticSecret::random();
+//!
+//! let (client, create) = ntor_v3::client_handshake(
+//!     &relay_id,
+//!     &PublicKey::from(&relay_onion_key),
+//!     b"circuit extend",
+//!     b"client extensions",
+//!     OsRng,
+//! )?;
+//!
+//! let relay = ntor_v3::server_handshake(&relay_id, &relay_onion_key, b"circuit extend", &create)?;
+//! assert_eq!(relay.client_message(), b"client extensions");
+//! let (relay_keys, created) = relay.finish(b"relay extensions", OsRng);
+//!
+//! let (client_keys, relay_message) = client.finish(&created)?;
+//! assert_eq!(relay_message, b"relay extensions");
+//!
+//! let mut client_material = [0u8; 72];
+//! let mut relay_material = [0u8; 72];
+//! client_keys.expand(&mut client_material);
+//! relay_keys.expand(&mut relay_material);
+//! assert_eq!(client_material, relay_material);
+//! # Ok::<(), x25519_dalek::Error>(())
+//! ```
+
+use alloc::vec::Vec;
+use core::fmt;
+
+use aes::Aes256;
+use ctr::cipher::{KeyIvInit, StreamCipher};
+use rand_core::{CryptoRng, RngCore};
+use sha3::digest::{ExtendableOutput, FixedOutput, Update, XofReader};
+use sha3::{Sha3_256, Shake256};
+use subtle::ConstantTimeEq;
+use zeroize::Zeroizing;
+
+use crate::x25519::{PublicKey, SharedSecret, StaticSecret};
+
+/// The protocol identifier, `PROTOID`.
+pub const PROTOID: &[u8] = b"ntor3-curve25519-sha3_256-1";
+
+/// The length of a relay's Ed25519 identity, `ID`.
+pub const NODE_ID_LEN: usize = 32;
+
+/// The length of the client's handshake message, beyond its encrypted
+/// message: the relay's identity and onion key, the client's ephemeral
+/// key, and a MAC.
+pub const CLIENT_OVERHEAD: usize = NODE_ID_LEN + 32 + 32 + DIGEST_LEN;
+
+/// The length of the relay's handshake message, beyond its encrypted
+/// message: its ephemeral key and the authenticator.
+pub const SERVER_OVERHEAD: usize = 32 + DIGEST_LEN;
+
+const DIGEST_LEN: usize = 32;
+const ENC_KEY_LEN: usize = 32;
+
+const T_MSGKDF: &[u8] = b"ntor3-curve25519-sha3_256-1:kdf_phase1";
+const T_MSGMAC: &[u8] = b"ntor3-curve25519-sha3_256-1:msg_mac";
+const T_KEY_SEED: &[u8] = b"ntor3-curve25519-sha3_256-1:key_seed";
+const T_VERIFY: &[u8] = b"ntor3-curve25519-sha3_256-1:verify";
+const T_FINAL: &[u8] = b"ntor3-curve25519-sha3_256-1:kdf_final";
+const T_AUTH: &[u8] = b"ntor3-curve25519-sha3_256-1:auth_final";
+
+type Aes256Ctr = ctr::Ctr128BE<Aes256>;
+
+/// Errors which may occur in the handshake.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// The client's message names another relay or another onion key.
+    UnknownKey,
+    /// A handshake message is too short, or the client's MAC is wrong.
+    InvalidMessage,
+    /// The relay's authenticator is wrong.
+    AuthenticationFailed,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::UnknownKey => f.write_str("handshake is for another relay or onion key"),
+            Error::InvalidMessage => f.write_str("invalid ntor-v3 handshake message"),
+            Error::AuthenticationFailed => f.write_str("ntor-v3 server authentication failed"),
+        }
+    }
+}
+
+/// The source of the key material of a completed handshake.
+///
+/// The key seed is wiped on drop.
+pub struct KeyGenerator {
+    key_seed: Zeroizing<[u8; DIGEST_LEN]>,
+}
+
+impl KeyGenerator {
+    /// Fill `okm` with key material, the SHAKE-256 output of the key seed
+    /// following the key of the relay's encrypted message.
+    ///
+    /// For relay cell encryption, this is 72 bytes: the forward and
+    /// backward digest seeds, followed by the forward and backward keys.
+    pub fn expand(&self, okm: &mut [u8]) {
+        let mut reader = self.reader();
+        let mut enc_key = Zeroizing::new([0u8; ENC_KEY_LEN]);
+        reader.read(&mut enc_key[..]);
+        reader.read(okm);
+    }
+
+    /// The key which encrypts the relay's message.
+    fn enc_key(&self) -> Zeroizing<[u8; ENC_KEY_LEN]> {
+        let mut enc_key = Zeroizing::new([0u8; ENC_KEY_LEN]);
+        self.reader().read(&mut enc_key[..]);
+        enc_key
+    }
+
+    /// `KDF_final(ntor_key_seed)`.
+    fn reader(&self) -> impl XofReader {
+        kdf(T_FINAL, &[&self.key_seed[..]])
+    }
+}
+
+/// The client's state between sending its handshake message and receiving
+/// the relay's.
+///
+/// The ephemeral secret key is wiped on drop.
+pub struct ClientState {
+    node_id: [u8; NODE_ID_LEN],
+    server_key: PublicKey,
+    secret: StaticSecret,
+    public: PublicKey,
+    verification: Vec<u8>,
+    msg_mac: [u8; DIGEST_LEN],
+}
+
+/// Start a handshake with the relay whose Ed25519 identity is `node_id` and
+/// whose ntor onion key is `server_key`, sending it `message`.
+///
+/// Returns the client's state and the handshake message to send to the
+/// relay, using an ephemeral key from `csprng`.  The `verification` string
+/// must be the one the relay expects.  Fails if `server_key` is a point of
+/// small order.
+pub fn client_handshake<T: RngCore + CryptoRng>(
+    node_id: &[u8; NODE_ID_LEN],
+    server_key: &PublicKey,
+    verification: &[u8],
+    message: &[u8],
+    csprng: T,
+) -> Result<(ClientState, Vec<u8>), crate::Error> {
+    let secret = StaticSecret::random_from_rng(csprng);
+    let public = PublicKey::from(&secret);
+    let exp_bx = secret.diffie_hellman(server_key);
+    if !exp_bx.was_contributory() {
+        return Err(crate::Error::SmallOrderPoint);
+    }
+
+    let mut handshake = Vec::with_capacity(CLIENT_OVERHEAD + message.len());
+    handshake.extend_from_slice(node_id);
+    handshake.extend_from_slice(server_key.as_bytes());
+    handshake.extend_from_slice(public.as_bytes());
+    handshake.extend_from_slice(message);
+    let keys = phase1_keys(&exp_bx, node_id, server_key, &public, verification);
+    let (enc_key, mac_key) = keys.split_at(ENC_KEY_LEN);
+    apply_keystream(enc_key, &mut handshake[NODE_ID_LEN + 64..]);
+    let msg_mac = msg_mac(mac_key, &handshake);
+    handshake.extend_from_slice(&msg_mac);
+
+    let state = ClientState {
+        node_id: *node_id,
+        server_key: *server_key,
+        secret,
+        public,
+        verification: verification.to_vec(),
+        msg_mac,
+    };
+    Ok((state, handshake))
+}
+
+impl ClientState {
+    /// Complete the handshake with the relay's handshake `message`.
+    ///
+    /// Returns the key material and the relay's decrypted message.  Fails if
+    /// the relay's ephemeral key is a point of small order, or if its
+    /// authenticator, which is compared in constant time, is wrong.
+    pub fn finish(self, message: &[u8]) -> Result<(KeyGenerator, Vec<u8>), crate::Error> {
+        if message.len() < SERVER_OVERHEAD {
+            return Err(Error::InvalidMessage.into());
+        }
+        let mut server_public = [0u8; 32];
+        server_public.copy_from_slice(&message[..32]);
+        let server_public = PublicKey::from(server_public);
+        let exp_yx = self.secret.diffie_hellman(&server_public);
+        if !exp_yx.was_contributory() {
+            return Err(crate::Error::SmallOrderPoint);
+        }
+
+        let (keys, verify) = derive(
+            &exp_yx,
+            &self.secret.diffie_hellman(&self.server_key),
+            &self.node_id,
+            &self.server_key,
+            &self.public,
+            &server_public,
+            &self.verification,
+        );
+        let encrypted_msg = &message[SERVER_OVERHEAD..];
+        let auth = auth(
+            &verify,
+            &self.node_id,
+            &self.server_key,
+            &server_public,
+            &self.public,
+            &self.msg_mac,
+            encrypted_msg,
+        );
+        if !bool::from(auth.ct_eq(&message[32..SERVER_OVERHEAD])) {
+            return Err(Error::AuthenticationFailed.into());
+        }
+
+        let mut server_message = encrypted_msg.to_vec();
+        apply_keystream(&keys.enc_key()[..], &mut server_message);
+        Ok((keys, server_message))
+    }
+}
+
+/// The relay's state between reading the client's handshake message and
+/// replying to it.
+pub struct ServerState {
+    node_id: [u8; NODE_ID_LEN],
+    server_key: PublicKey,
+    client_public: PublicKey,
+    exp_xb: SharedSecret,
+    verification: Vec<u8>,
+    msg_mac: [u8; DIGEST_LEN],
+    client_message: Vec<u8>,
+}
+
+/// Read a client's handshake `message` as the relay whose Ed25519 identity
+/// is `node_id` and whose ntor onion key is `server_secret`.
+///
+/// Fails with [`Error::UnknownKey`] if the message names another identity
+/// or onion key, in which case a relay which still accepts its previous
+/// onion key can retry with it, and with [`Error::InvalidMessage`] if the
+/// message is malformed or its MAC is wrong, which is checked in constant
+/// time.
+pub fn server_handshake(
+    node_id: &[u8; NODE_ID_LEN],
+    server_secret: &StaticSecret,
+    verification: &[u8],
+    message: &[u8],
+) -> Result<ServerState, crate::Error> {
+    if message.len() < CLIENT_OVERHEAD {
+        return Err(Error::InvalidMessage.into());
+    }
+    let server_key = PublicKey::from(server_secret);
+    if message[..NODE_ID_LEN] != node_id[..]
+        || message[NODE_ID_LEN..NODE_ID_LEN + 32] != server_key.as_bytes()[..]
+    {
+        return Err(Error::UnknownKey.into());
+    }
+    let mut client_public = [0u8; 32];
+    client_public.copy_from_slice(&message[NODE_ID_LEN + 32..NODE_ID_LEN + 64]);
+    let client_public = PublicKey::from(client_public);
+    let exp_xb = server_secret.diffie_hellman(&client_public);
+    if !exp_xb.was_contributory() {
+        return Err(crate::Error::SmallOrderPoint);
+    }
+
+    let keys = phase1_keys(&exp_xb, node_id, &server_key, &client_public, verification);
+    let (enc_key, mac_key) = keys.split_at(ENC_KEY_LEN);
+    let (body, mac) = message.split_at(message.len() - DIGEST_LEN);
+    let msg_mac = msg_mac(mac_key, body);
+    if !bool::from(msg_mac.ct_eq(mac)) {
+        return Err(Error::InvalidMessage.into());
+    }
+    let mut client_message = body[NODE_ID_LEN + 64..].to_vec();
+    apply_keystream(enc_key, &mut client_message);
+
+    Ok(ServerState {
+        node_id: *node_id,
+        server_key,
+        client_public,
+        exp_xb,
+        verification: verification.to_vec(),
+        msg_mac,
+        client_message,
+    })
+}
+
+impl ServerState {
+    /// The client's decrypted message.
+    pub fn client_message(&self) -> &[u8] {
+        &self.client_message
+    }
+
+    /// Complete the handshake, sending `message` to the client with an
+    /// ephemeral key from `csprng`.
+    ///
+    /// Returns the key material and the handshake message to send to the
+    /// client.
+    pub fn finish<T: RngCore + CryptoRng>(
+        self,
+        message: &[u8],
+        csprng: T,
+    ) -> (KeyGenerator, Vec<u8>) {
+        let secret = StaticSecret::random_from_rng(csprng);
+        let public = PublicKey::from(&secret);
+        // The client's key is not of small order, since EXP(X, b) was
+        // contributory, so neither is EXP(X, y).
+        let (keys, verify) = derive(
+            &secret.diffie_hellman(&self.client_public),
+            &self.exp_xb,
+            &self.node_id,
+            &self.server_key,
+            &self.client_public,
+            &public,
+            &self.verification,
+        );
+
+        let mut encrypted_msg = message.to_vec();
+        apply_keystream(&keys.enc_key()[..], &mut encrypted_msg);
+        let auth = auth(
+            &verify,
+            &self.node_id,
+            &self.server_key,
+            &public,
+            &self.client_public,
+            &self.msg_mac,
+            &encrypted_msg,
+        );
+
+        let mut handshake = Vec::with_capacity(SERVER_OVERHEAD + message.len());
+        handshake.extend_from_slice(public.as_bytes());
+        handshake.extend_from_slice(&auth[..]);
+        handshake.extend_from_slice(&encrypted_msg);
+        (keys, handshake)
+    }
+}
+
+/// `ENCAP(s)`: the length of `s` as a 64-bit big-endian integer, which
+/// precedes `s`.
+fn encap_len(s: &[u8]) -> [u8; 8] {
+    (s.len() as u64).to_be_bytes()
+}
+
+/// `H(t, s)`: SHA3-256 of `ENCAP(t)` followed by the concatenated `parts`.
+fn hash(tweak: &[u8], parts: &[&[u8]]) -> Zeroizing<[u8; DIGEST_LEN]> {
+    let mut hasher = Sha3_256::default();
+    hasher.update(&encap_len(tweak));
+    hasher.update(tweak);
+    for part in parts {
+        hasher.update(part);
+    }
+    Zeroizing::new(hasher.finalize_fixed().into())
+}
+
+/// `KDF(s, t)`: SHAKE-256 of `ENCAP(t)` followed by the concatenated
+/// `parts`.
+fn kdf(tweak: &[u8], parts: &[&[u8]]) -> impl XofReader {
+    let mut hasher = Shake256::default();
+    hasher.update(&encap_len(tweak));
+    hasher.update(tweak);
+    for part in parts {
+        hasher.update(part);
+    }
+    hasher.finalize_xof()
+}
+
+/// `ENC(k, m)`: encrypt or decrypt `buf` in place with AES-256-CTR.
+fn apply_keystream(key: &[u8], buf: &mut [u8]) {
+    Aes256Ctr::new(key.into(), &[0u8; 16].into()).apply_keystream(buf);
+}
+
+/// The key which encrypts the client's message, followed by the key of its
+/// MAC, derived from `EXP(B, x)` and the public keys.
+fn phase1_keys(
+    exp_bx: &SharedSecret,
+    node_id: &[u8; NODE_ID_LEN],
+    server_key: &PublicKey,
+    client_public: &PublicKey,
+    verification: &[u8],
+) -> Zeroizing<[u8; ENC_KEY_LEN + DIGEST_LEN]> {
+    let mut keys = Zeroizing::new([0u8; ENC_KEY_LEN + DIGEST_LEN]);
+    kdf(
+        T_MSGKDF,
+        &[
+            exp_bx.as_bytes(),
+            node_id,
+            client_public.as_bytes(),
+            server_key.as_bytes(),
+            PROTOID,
+            &encap_len(verification),
+            verification,
+        ],
+    )
+    .read(&mut keys[..]);
+    keys
+}
+
+/// The MAC of the client's handshake message up to the MAC, which covers the
+/// encrypted message.
+fn msg_mac(mac_key: &[u8], handshake: &[u8]) -> [u8; DIGEST_LEN] {
+    *hash(T_MSGMAC, &[&encap_len(mac_key), mac_key, handshake])
+}
+
+/// Derive the key generator and `verify` from the two Diffie-Hellman
+/// results, `EXP(X, y)` and `EXP(X, b)`, and the public keys.
+fn derive(
+    exp_xy: &SharedSecret,
+    exp_xb: &SharedSecret,
+    node_id: &[u8; NODE_ID_LEN],
+    server_key: &PublicKey,
+    client_public: &PublicKey,
+    server_public: &PublicKey,
+    verification: &[u8],
+) -> (KeyGenerator, Zeroizing<[u8; DIGEST_LEN]>) {
+    let secret_input: [&[u8]; 9] = [
+        exp_xy.as_bytes(),
+        exp_xb.as_bytes(),
+        node_id,
+        server_key.as_bytes(),
+        client_public.as_bytes(),
+        server_public.as_bytes(),
+        PROTOID,
+        &encap_len(verification),
+        verification,
+    ];
+    let key_seed = hash(T_KEY_SEED, &secret_input);
+    let verify = hash(T_VERIFY, &secret_input);
+    (KeyGenerator { key_seed }, verify)
+}
+
+/// The relay's authenticator, `AUTH`.
+fn auth(
+    verify: &[u8; DIGEST_LEN],
+    node_id: &[u8; NODE_ID_LEN],
+    server_key: &PublicKey,
+    server_public: &PublicKey,
+    client_public: &PublicKey,
+    msg_mac: &[u8; DIGEST_LEN],
+    encrypted_msg: &[u8],
+) -> Zeroizing<[u8; DIGEST_LEN]> {
+    hash(
+        T_AUTH,
+        &[
+            verify,
+            node_id,
+            server_key.as_bytes(),
+            server_public.as_bytes(),
+            client_public.as_bytes(),
+            msg_mac,
+            &encap_len(encrypted_msg),
+            encrypted_msg,
+            PROTOID,
+            b"Server",
+        ],
+    )
+}
@@ -0,0 +1,287 @@
+// -*- mode: rust; -*-
+//
+// This file is part of x25519-dalek.
+// Copyright (c) 2017-2021 isis lovecruft
+// Copyright (c) 2019-2021 DebugSteven
+// See LICENSE for licensing information.
+//
+// Authors:
+// - isis agora lovecruft <isis@patternsinthevoid.net>
+// - DebugSteven <debugsteven@gmail.com>
+
+//! The 3DH authenticated key exchange of OPAQUE, as specified in section 6
+//! of [RFC 9807](https://www.rfc-editor.org/rfc/rfc9807), with X25519 and
+//! SHA-512.
+//!
+//! This is only the AKE: the OPRF, the envelope and the message encodings
+//! belong to the OPAQUE implementation, which passes the encoded messages
+//! in a [`Preamble`].  The client's and server's static keys are those
+//! recovered from, and stored in, the registration record.
+//!
+#![cfg_attr(feature = "getrandom", doc = "```")]
+#![cfg_attr(not(feature = "getrandom"), doc = "```ignore")]
+//! use x25519_dalek::{opaque, PublicKey, StaticSecret};
+//!
+//! let client_secret = StaticSecret::random();
+//! let server_secret = StaticSecret::random();
+//! let client_ephemeral = StaticSecret::random();
+//! let server_ephemeral = StaticSecret::random();
+//! let client_public = PublicKey::from(&client_secret);
+//! let server_public = PublicKey::from(&server_secret);
+//!
+//! // The encoded KE1 and credential response of the OPAQUE implementation.
+//! let ke1 = [0u8; 96];
+//! let credential_response = [0u8; 208];
+//! let preamble = opaque::Preamble {
+//!     context: b"example",
+//!     client_identity: client_public.as_bytes(),
+//!     ke1: &ke1,
+//!     server_identity: server_public.as_bytes(),
+//!     credential_response: &credential_response,
+//!     server_nonce: &[0x42; opaque::NONCE_LEN],
+//!     server_keyshare: &PublicKey::from(&server_ephemeral),
+//! };
+//!
+//! let (server, server_mac) = opaque::server_respond(
+//!     &server_secret,
+//!     &server_ephemeral,
+//!     &client_public,
+//!     &PublicKey::from(&client_ephemeral),
+//!     &preamble,
+//! )?;
+//! let (client_mac, client_key) = opaque::client_finalize(
+//!     &client_secret,
+//!     &client_ephemeral,
+//!     &server_public,
+//!     &preamble,
+//!     &server_mac,
+//! )?;
+//! let server_key = server.finish(&client_mac)?;
+//! assert_eq!(client_key, server_key);
+//! # Ok::<(), x25519_dalek::Error>(())
+//! ```
+
+use core::fmt;
+
+use hkdf::Hkdf;
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha512};
+use subtle::ConstantTimeEq;
+use zeroize::Zeroizing;
+
+use crate::x25519::{PublicKey, SharedSecret, StaticSecret};
+
+/// The length of the client's and server's nonces, `Nn`.
+pub const NONCE_LEN: usize = 32;
+
+/// The length of the MACs, `Nm`.
+pub const MAC_LEN: usize = 64;
+
+/// The length of the session key, `Nx`.
+pub const SESSION_KEY_LEN: usize = 64;
+
+/// Errors which may occur in the key exchange.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// The peer's MAC is wrong.
+    AuthenticationFailed,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::AuthenticationFailed => f.write_str("OPAQUE authentication failed"),
+        }
+    }
+}
+
+/// The messages and identities which the key schedule binds, in the order
+/// of the `preamble`.
+///
+/// An identity which is not configured is the party's static public key.
+#[derive(Copy, Clone, Debug)]
+pub struct Preamble<'a> {
+    /// The application context.
+    pub context: &'a [u8],
+    /// The client's identity.
+    pub client_identity: &'a [u8],
+    /// The encoded `KE1` message.
+    pub ke1: &'a [u8],
+    /// The server's identity.
+    pub server_identity: &'a [u8],
+    /// The encoded `CredentialResponse`.
+    pub credential_response: &'a [u8],
+    /// The server's nonce.
+    pub server_nonce: &'a [u8; NONCE_LEN],
+    /// The server's ephemeral public key.
+    pub server_keyshare: &'a PublicKey,
+}
+
+impl Preamble<'_> {
+    /// A SHA-512 hasher which has absorbed the preamble.
+    fn hasher(&self) -> Sha512 {
+        let mut hasher = Sha512::new();
+        hasher.update(b"OPAQUEv1-");
+        update_prefixed(&mut hasher, self.context);
+        update_prefixed(&mut hasher, self.client_identity);
+        hasher.update(self.ke1);
+        update_prefixed(&mut hasher, self.server_identity);
+        hasher.update(self.credential_response);
+        hasher.update(self.server_nonce);
+        hasher.update(self.server_keyshare.as_bytes());
+        hasher
+    }
+}
+
+/// The server's state between sending `KE2` and receiving `KE3`.
+///
+/// The session key is wiped on drop.
+pub struct ServerState {
+    session_key: Zeroizing<[u8; SESSION_KEY_LEN]>,
+    client_mac: Zeroizing<[u8; MAC_LEN]>,
+}
+
+impl ServerState {
+    /// Check the client's MAC from `KE3` in constant time, returning the
+    /// session key if it is correct.
+    pub fn finish(
+        self,
+        client_mac: &[u8; MAC_LEN],
+    ) -> Result<Zeroizing<[u8; SESSION_KEY_LEN]>, crate::Error> {
+        if !bool::from(self.client_mac.ct_eq(client_mac)) {
+            return Err(Error::AuthenticationFailed.into());
+        }
+        Ok(self.session_key)
+    }
+}
+
+/// `AuthServerRespond`: derive the keys as the server with the static
+/// secret `server_secret` and the ephemeral secret `server_ephemeral`,
+/// whose public key must be the preamble's `server_keyshare`, from the
+/// client's static key `client_public` and key share `client_keyshare`.
+///
+/// Returns the server's state and the server MAC to send in `KE2`.
+pub fn server_respond(
+    server_secret: &StaticSecret,
+    server_ephemeral: &StaticSecret,
+    client_public: &PublicKey,
+    client_keyshare: &PublicKey,
+    preamble: &Preamble<'_>,
+) -> Result<(ServerState, [u8; MAC_LEN]), crate::Error> {
+    let keys = derive_keys(
+        &[
+            server_ephemeral.diffie_hellman(client_keyshare),
+            server_secret.diffie_hellman(client_keyshare),
+            server_ephemeral.diffie_hellman(client_public),
+        ],
+        preamble,
+    )?;
+    let state = ServerState {
+        session_key: keys.session_key,
+        client_mac: keys.client_mac,
+    };
+    Ok((state, *keys.server_mac))
+}
+
+/// `AuthClientFinalize`: derive the keys as the client with the static
+/// secret `client_secret` and the ephemeral secret `client_ephemeral`,
+/// from the server's static key `server_public` and the preamble's
+/// `server_keyshare`, and check the `server_mac` from `KE2` in constant
+/// time.
+///
+/// Returns the client MAC to send in `KE3` and the session key.
+pub fn client_finalize(
+    client_secret: &StaticSecret,
+    client_ephemeral: &StaticSecret,
+    server_public: &PublicKey,
+    preamble: &Preamble<'_>,
+    server_mac: &[u8; MAC_LEN],
+) -> Result<([u8; MAC_LEN], Zeroizing<[u8; SESSION_KEY_LEN]>), crate::Error> {
+    let keys = derive_keys(
+        &[
+            client_ephemeral.diffie_hellman(preamble.server_keyshare),
+            client_ephemeral.diffie_hellman(server_public),
+            client_secret.diffie_hellman(preamble.server_keyshare),
+        ],
+        preamble,
+    )?;
+    if !bool::from(keys.server_mac.ct_eq(server_mac)) {
+        return Err(Error::AuthenticationFailed.into());
+    }
+    Ok((*keys.client_mac, keys.session_key))
+}
+
+/// The output of the key schedule.
+struct Keys {
+    session_key: Zeroizing<[u8; SESSION_KEY_LEN]>,
+    server_mac: Zeroizing<[u8; MAC_LEN]>,
+    client_mac: Zeroizing<[u8; MAC_LEN]>,
+}
+
+/// `DeriveKeys` and the MACs over the preamble, from the three
+/// Diffie-Hellman results in the order of the input key material.
+fn derive_keys(dh: &[SharedSecret; 3], preamble: &Preamble<'_>) -> Result<Keys, crate::Error> {
+    if dh.iter().any(|shared| !shared.was_contributory()) {
+        return Err(crate::Error::SmallOrderPoint);
+    }
+
+    let mut ikm = Zeroizing::new([0u8; 96]);
+    for (chunk, shared) in ikm.chunks_mut(32).zip(dh) {
+        chunk.copy_from_slice(shared.as_bytes());
+    }
+    let hkdf = Hkdf::<Sha512>::new(None, &ikm[..]);
+
+    let hasher = preamble.hasher();
+    let preamble_hash = hasher.clone().finalize();
+    let handshake_secret = expand_label(&hkdf, b"HandshakeSecret", &preamble_hash);
+    let session_key = expand_label(&hkdf, b"SessionKey", &preamble_hash);
+    let handshake = Hkdf::<Sha512>::from_prk(&handshake_secret[..])
+        .expect("64 bytes is a valid HKDF-SHA512 PRK length");
+    let km2 = expand_label(&handshake, b"ServerMAC", b"");
+    let km3 = expand_label(&handshake, b"ClientMAC", b"");
+
+    let server_mac = mac(&km2[..], &preamble_hash);
+    let client_mac = mac(&km3[..], &hasher.chain_update(&server_mac[..]).finalize());
+    Ok(Keys {
+        session_key,
+        server_mac,
+        client_mac,
+    })
+}
+
+/// `Expand-Label`: expand `Nx` bytes with the label `"OPAQUE-" || label`
+/// and the `context`.
+fn expand_label(
+    hkdf: &Hkdf<Sha512>,
+    label: &[u8],
+    context: &[u8],
+) -> Zeroizing<[u8; SESSION_KEY_LEN]> {
+    let mut okm = Zeroizing::new([0u8; SESSION_KEY_LEN]);
+    hkdf.expand_multi_info(
+        &[
+            &(SESSION_KEY_LEN as u16).to_be_bytes(),
+            &[(7 + label.len()) as u8],
+            b"OPAQUE-",
+            label,
+            &[context.len() as u8],
+            context,
+        ],
+        &mut okm[..],
+    )
+    .expect("64 bytes is a valid HKDF-SHA512 output length");
+    okm
+}
+
+/// HMAC-SHA512 of `data` with `key`.
+fn mac(key: &[u8], data: &[u8]) -> Zeroizing<[u8; MAC_LEN]> {
+    let mut mac =
+        <Hmac<Sha512> as Mac>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(data);
+    Zeroizing::new(mac.finalize().into_bytes().into())
+}
+
+/// Hash `data` preceded by its length as a big-endian 16-bit integer.
+fn update_prefixed(hasher: &mut Sha512, data: &[u8]) {
+    hasher.update((data.len() as u16).to_be_bytes());
+    hasher.update(data);
+}
@@ -0,0 +1,347 @@
+// -*- mode: rust; -*-
+//
+// This file is part of x25519-dalek.
+// Copyright (c) 2017-2021 isis lovecruft
+// Copyright (c) 2019-2021 DebugSteven
+// See LICENSE for licensing information.
+//
+// Authors:
+// - isis agora lovecruft <isis@patternsinthevoid.net>
+// - DebugSteven <debugsteven@gmail.com>
+
+//! Building blocks for OpenPGP ECDH with Curve25519 (`cv25519`) subkeys, as
+//! specified in [RFC6637](https://tools.ietf.org/html/rfc6637) and the
+//! `draft-ietf-openpgp-rfc4880bis` Curve25519 extension.
+//!
+//! An OpenPGP implementation encrypts a session key to a `cv25519` subkey
+//! by performing a Diffie-Hellman exchange against it, deriving a
+//! [`KeyEncryptionKey`] from the [`SharedSecret`], and wrapping the
+//! (already formatted) session key with it:
+//!
+#![cfg_attr(feature = "getrandom", doc = "```")]
+#![cfg_attr(not(feature = "getrandom"), doc = "```ignore")]
+//! use x25519_dalek::{EphemeralSecret, PublicKey, StaticSecret};
+//! use x25519_dalek::openpgp::{KdfHash, KeyEncryptionKey, KeyWrapAlgorithm};
+//!
+//! # let recipient_secret = StaticSecret::random();
+//! # let recipient = PublicKey::from(&recipient_secret);
+//! let fingerprint = [0x42u8; 20];
+//! let session_key = [0x09u8; 35];
+//!
+//! // Sender: the ephemeral public key is sent alongside the wrapped key.
+//! let ephemeral = EphemeralSecret::random();
+//! let ephemeral_public = PublicKey::from(&ephemeral).to_openpgp_point();
+//! let kek = KeyEncryptionKey::derive(
+//!     &ephemeral.diffie_hellman(&recipient),
+//!     KdfHash::Sha256,
+//!     KeyWrapAlgorithm::Aes128,
+//!     &fingerprint,
+//! );
+//! let wrapped = kek.wrap(&session_key)?;
+//!
+//! // Recipient.
+//! let ephemeral_public = PublicKey::from_openpgp_point(&ephemeral_public)?;
+//! let kek = KeyEncryptionKey::derive(
+//!     &recipient_secret.diffie_hellman(&ephemeral_public),
+//!     KdfHash::Sha256,
+//!     KeyWrapAlgorithm::Aes128,
+//!     &fingerprint,
+//! );
+//! assert_eq!(&kek.unwrap(&wrapped)?[..], &session_key[..]);
+//! # Ok::<(), x25519_dalek::Error>(())
+//! ```
+//!
+//! Formatting the session key (algorithm identifier and checksum) is left to
+//! the caller.
+
+use alloc::vec::Vec;
+use core::fmt;
+
+use aes_kw::{KekAes128, KekAes192, KekAes256};
+use sha2::{Digest, Sha256, Sha384, Sha512};
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::x25519::{PublicKey, SharedSecret, StaticSecret};
+
+/// The DER-encoded object identifier of `Curve25519` (1.3.6.1.4.1.3029.1.5.1),
+/// without its tag and length, as it appears in OpenPGP key packets.
+pub const CV25519_OID: [u8; 10] = [0x2b, 0x06, 0x01, 0x04, 0x01, 0x97, 0x55, 0x01, 0x05, 0x01];
+
+/// The OpenPGP public key algorithm identifier for ECDH.
+pub const PUBLIC_KEY_ALGORITHM_ECDH: u8 = 18;
+
+/// The prefix of a native (`0x40`-prefixed) Curve25519 point.
+const NATIVE_POINT_PREFIX: u8 = 0x40;
+
+/// The fixed "Anonymous Sender" string of the KDF parameters.
+const ANONYMOUS_SENDER: &[u8; 20] = b"Anonymous Sender    ";
+
+/// The hash algorithm used by the ECDH KDF.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum KdfHash {
+    /// SHA2-256.
+    Sha256,
+    /// SHA2-384.
+    Sha384,
+    /// SHA2-512.
+    Sha512,
+}
+
+impl KdfHash {
+    /// The OpenPGP hash algorithm identifier.
+    pub fn id(&self) -> u8 {
+        match self {
+            KdfHash::Sha256 => 8,
+            KdfHash::Sha384 => 9,
+            KdfHash::Sha512 => 10,
+        }
+    }
+
+    /// Look up a hash algorithm by its OpenPGP identifier.
+    pub fn from_id(id: u8) -> Option<KdfHash> {
+        match id {
+            8 => Some(KdfHash::Sha256),
+            9 => Some(KdfHash::Sha384),
+            10 => Some(KdfHash::Sha512),
+            _ => None,
+        }
+    }
+}
+
+/// The AES key wrap algorithm used to wrap the session key.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum KeyWrapAlgorithm {
+    /// AES key wrap with a 128-bit key.
+    Aes128,
+    /// AES key wrap with a 192-bit key.
+    Aes192,
+    /// AES key wrap with a 256-bit key.
+    Aes256,
+}
+
+impl KeyWrapAlgorithm {
+    /// The OpenPGP symmetric algorithm identifier.
+    pub fn id(&self) -> u8 {
+        match self {
+            KeyWrapAlgorithm::Aes128 => 7,
+            KeyWrapAlgorithm::Aes192 => 8,
+            KeyWrapAlgorithm::Aes256 => 9,
+        }
+    }
+
+    /// Look up a key wrap algorithm by its OpenPGP symmetric algorithm
+    /// identifier.
+    pub fn from_id(id: u8) -> Option<KeyWrapAlgorithm> {
+        match id {
+            7 => Some(KeyWrapAlgorithm::Aes128),
+            8 => Some(KeyWrapAlgorithm::Aes192),
+            9 => Some(KeyWrapAlgorithm::Aes256),
+            _ => None,
+        }
+    }
+
+    /// The length of the key encryption key, in bytes.
+    pub fn key_len(&self) -> usize {
+        match self {
+            KeyWrapAlgorithm::Aes128 => 16,
+            KeyWrapAlgorithm::Aes192 => 24,
+            KeyWrapAlgorithm::Aes256 => 32,
+        }
+    }
+}
+
+/// Errors which may occur in OpenPGP ECDH operations.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// The session key is too short to be wrapped.
+    InvalidSessionKey,
+    /// The wrapped session key failed its integrity check or is malformed.
+    UnwrapFailed,
+    /// The public key is not a `0x40`-prefixed native point.
+    InvalidPoint,
+    /// The secret key MPI is longer than 32 bytes.
+    InvalidSecretKey,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::InvalidSessionKey => f.write_str("session key is too short to wrap"),
+            Error::UnwrapFailed => f.write_str("failed to unwrap session key"),
+            Error::InvalidPoint => f.write_str("public key is not a native Curve25519 point"),
+            Error::InvalidSecretKey => f.write_str("secret key MPI is longer than 32 bytes"),
+        }
+    }
+}
+
+/// A key encryption key, derived from an ECDH shared secret with the KDF of
+/// RFC6637 section 7.
+///
+/// The key is wiped on drop.
+pub struct KeyEncryptionKey {
+    bytes: Zeroizing<[u8; 32]>,
+    algorithm: KeyWrapAlgorithm,
+}
+
+impl KeyEncryptionKey {
+    /// Derive a key encryption key from a shared secret.
+    ///
+    /// The `hash` and `algorithm` are the KDF parameters of the recipient's
+    /// subkey, and `fingerprint` is the recipient subkey's fingerprint.
+    pub fn derive(
+        shared_secret: &SharedSecret,
+        hash: KdfHash,
+        algorithm: KeyWrapAlgorithm,
+        fingerprint: &[u8],
+    ) -> KeyEncryptionKey {
+        let mut bytes = Zeroizing::new([0u8; 32]);
+        let len = algorithm.key_len();
+        match hash {
+            KdfHash::Sha256 => {
+                let digest = kdf::<Sha256>(shared_secret, hash, algorithm, fingerprint);
+                bytes[..len].copy_from_slice(&digest[..len]);
+            }
+            KdfHash::Sha384 => {
+                let digest = kdf::<Sha384>(shared_secret, hash, algorithm, fingerprint);
+                bytes[..len].copy_from_slice(&digest[..len]);
+            }
+            KdfHash::Sha512 => {
+                let digest = kdf::<Sha512>(shared_secret, hash, algorithm, fingerprint);
+                bytes[..len].copy_from_slice(&digest[..len]);
+            }
+        }
+        KeyEncryptionKey { bytes, algorithm }
+    }
+
+    /// The key wrap algorithm this key is for.
+    pub fn algorithm(&self) -> KeyWrapAlgorithm {
+        self.algorithm
+    }
+
+    /// View this key as a byte slice.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes[..self.algorithm.key_len()]
+    }
+
+    /// Pad the session key to a multiple of 8 bytes as described in PKCS#5,
+    /// and wrap it with RFC3394 AES key wrap.
+    ///
+    /// RFC3394 requires at least two 64-bit blocks, so the session key must
+    /// be at least 8 bytes long.
+    pub fn wrap(&self, session_key: &[u8]) -> Result<Vec<u8>, Error> {
+        if session_key.len() < 8 {
+            return Err(Error::InvalidSessionKey);
+        }
+        let pad = 8 - session_key.len() % 8;
+        let mut padded = Zeroizing::new(Vec::with_capacity(session_key.len() + pad));
+        padded.extend_from_slice(session_key);
+        padded.resize(session_key.len() + pad, pad as u8);
+
+        let result = match self.algorithm {
+            KeyWrapAlgorithm::Aes128 => {
+                KekAes128::try_from(self.as_bytes()).and_then(|kek| kek.wrap_vec(&padded))
+            }
+            KeyWrapAlgorithm::Aes192 => {
+                KekAes192::try_from(self.as_bytes()).and_then(|kek| kek.wrap_vec(&padded))
+            }
+            KeyWrapAlgorithm::Aes256 => {
+                KekAes256::try_from(self.as_bytes()).and_then(|kek| kek.wrap_vec(&padded))
+            }
+        };
+        result.map_err(|_| Error::InvalidSessionKey)
+    }
+
+    /// Unwrap a session key wrapped with [`KeyEncryptionKey::wrap`], removing
+    /// its PKCS#5 padding.
+    pub fn unwrap(&self, wrapped: &[u8]) -> Result<Zeroizing<Vec<u8>>, Error> {
+        let result = match self.algorithm {
+            KeyWrapAlgorithm::Aes128 => {
+                KekAes128::try_from(self.as_bytes()).and_then(|kek| kek.unwrap_vec(wrapped))
+            }
+            KeyWrapAlgorithm::Aes192 => {
+                KekAes192::try_from(self.as_bytes()).and_then(|kek| kek.unwrap_vec(wrapped))
+            }
+            KeyWrapAlgorithm::Aes256 => {
+                KekAes256::try_from(self.as_bytes()).and_then(|kek| kek.unwrap_vec(wrapped))
+            }
+        };
+        let mut padded = Zeroizing::new(result.map_err(|_| Error::UnwrapFailed)?);
+
+        let pad = *padded.last().ok_or(Error::UnwrapFailed)? as usize;
+        if pad == 0 || pad > 8 || pad > padded.len() {
+            return Err(Error::UnwrapFailed);
+        }
+        let len = padded.len() - pad;
+        if padded[len..].iter().any(|&byte| byte as usize != pad) {
+            return Err(Error::UnwrapFailed);
+        }
+        padded[len..].zeroize();
+        padded.truncate(len);
+        Ok(padded)
+    }
+}
+
+/// Compute `Hash(00 00 00 01 || ZZ || Param)`.
+fn kdf<D: Digest>(
+    shared_secret: &SharedSecret,
+    hash: KdfHash,
+    algorithm: KeyWrapAlgorithm,
+    fingerprint: &[u8],
+) -> Zeroizing<Vec<u8>> {
+    let digest = D::new()
+        .chain_update([0, 0, 0, 1])
+        .chain_update(shared_secret.as_bytes())
+        .chain_update([CV25519_OID.len() as u8])
+        .chain_update(CV25519_OID)
+        .chain_update([PUBLIC_KEY_ALGORITHM_ECDH, 3, 1, hash.id(), algorithm.id()])
+        .chain_update(ANONYMOUS_SENDER)
+        .chain_update(fingerprint)
+        .finalize();
+    Zeroizing::new(digest.to_vec())
+}
+
+impl PublicKey {
+    /// Encode this public key as an OpenPGP native point, i.e. prefixed with
+    /// `0x40`.
+    pub fn to_openpgp_point(&self) -> [u8; 33] {
+        let mut point = [NATIVE_POINT_PREFIX; 33];
+        point[1..].copy_from_slice(self.as_bytes());
+        point
+    }
+
+    /// Decode a public key from an OpenPGP native point.
+    pub fn from_openpgp_point(point: &[u8]) -> Result<PublicKey, crate::Error> {
+        match point.split_first() {
+            Some((&NATIVE_POINT_PREFIX, bytes)) => <[u8; 32]>::try_from(bytes)
+                .map(PublicKey::from)
+                .map_err(|_| Error::InvalidPoint.into()),
+            _ => Err(Error::InvalidPoint.into()),
+        }
+    }
+}
+
+impl StaticSecret {
+    /// Encode this secret key as the value of an OpenPGP MPI.
+    ///
+    /// OpenPGP stores `cv25519` secret keys in big-endian order, i.e. with
+    /// the bytes of [`StaticSecret::to_bytes`] reversed.
+    pub fn to_openpgp_mpi(&self) -> Zeroizing<[u8; 32]> {
+        let mut bytes = Zeroizing::new(self.to_bytes());
+        bytes.reverse();
+        bytes
+    }
+
+    /// Decode a secret key from the value of an OpenPGP MPI.
+    ///
+    /// Since MPIs are stored without leading zero bytes, values shorter than
+    /// 32 bytes are accepted.
+    pub fn from_openpgp_mpi(mpi: &[u8]) -> Result<StaticSecret, crate::Error> {
+        if mpi.len() > 32 {
+            return Err(Error::InvalidSecretKey.into());
+        }
+        let mut bytes = Zeroizing::new([0u8; 32]);
+        bytes[32 - mpi.len()..].copy_from_slice(mpi);
+        bytes.reverse();
+        Ok(StaticSecret::from(*bytes))
+    }
+}
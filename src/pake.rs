@@ -0,0 +1,19 @@
+// -*- mode: rust; -*-
+//
+// This file is part of x25519-dalek.
+// Copyright (c) 2017-2021 isis lovecruft
+// Copyright (c) 2019-2021 DebugSteven
+// See LICENSE for licensing information.
+//
+// Authors:
+// - isis agora lovecruft <isis@patternsinthevoid.net>
+// - DebugSteven <debugsteven@gmail.com>
+
+//! Password-authenticated key exchanges, which derive a strong shared key
+//! from a low-entropy secret such as a PIN.
+
+#[cfg(feature = "cpace")]
+pub mod cpace;
+
+#[cfg(feature = "spake2")]
+pub mod spake2;
@@ -0,0 +1,221 @@
+// -*- mode: rust; -*-
+//
+// This file is part of x25519-dalek.
+// Copyright (c) 2017-2021 isis lovecruft
+// Copyright (c) 2019-2021 DebugSteven
+// See LICENSE for licensing information.
+//
+// Authors:
+// - isis agora lovecruft <isis@patternsinthevoid.net>
+// - DebugSteven <debugsteven@gmail.com>
+
+//! The CPace balanced PAKE, as specified in
+//! [draft-irtf-cfrg-cpace](https://datatracker.ietf.org/doc/draft-irtf-cfrg-cpace/),
+//! with X25519 and SHA-512 (`CPACE-X25519-SHA512`).
+//!
+//! Both parties hash the password-related string `PRS` with an optional
+//! channel identifier `CI` and session identifier `sid` onto a generator of
+//! the curve, through the Elligator2 map, and then perform a Diffie-Hellman
+//! exchange over it.  Each party sends its [`CPace::share`] along with
+//! optional associated data, and both derive the same intermediate session
+//! key `ISK` only if they used the same `PRS`:
+//!
+#![cfg_attr(feature = "getrandom", doc = "```")]
+#![cfg_attr(not(feature = "getrandom"), doc = "```ignore")]
+//! use rand_core::OsRng;
+//! use x25519_dalek::pake::cpace::{CPace, Role};
+//!
+//! let sid = [0x42; 16];
+//! let alice = CPace::new(b"123456", b"alice-bob", &sid, OsRng);
+//! let bob = CPace::new(b"123456", b"alice-bob", &sid, OsRng);
+//! let alice_share = *alice.share();
+//! let bob_share = *bob.share();
+//!
+//! let alice_isk = alice.finish(Role::Initiator, b"", &bob_share, b"")?;
+//! let bob_isk = bob.finish(Role::Responder, b"", &alice_share, b"")?;
+//! assert_eq!(alice_isk, bob_isk);
+//! # Ok::<(), x25519_dalek::Error>(())
+//! ```
+//!
+//! The `sid` should be fresh for each session, for example chosen by one
+//! party and sent in the clear.  The `ISK` is not authenticated: the
+//! parties learn that their passwords matched only once it is used, such as
+//! by exchanging MACs under keys derived from it.
+
+use curve25519_dalek::montgomery::MontgomeryPoint;
+use rand_core::{CryptoRng, RngCore};
+use sha2::{Digest, Sha512};
+use zeroize::Zeroizing;
+
+use crate::elligator;
+use crate::error::Error;
+use crate::x25519::{PublicKey, StaticSecret};
+
+/// The domain separation identifier of the X25519 group, `G.DSI`.
+pub const DSI: &[u8] = b"CPace255";
+
+/// The length of the intermediate session key.
+pub const ISK_LEN: usize = 64;
+
+/// The input block size of SHA-512, `H.s_in_bytes`.
+const HASH_BLOCK_LEN: usize = 128;
+
+/// How a party's share and associated data are ordered in the transcript.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Role {
+    /// The party whose share comes first.
+    Initiator,
+    /// The party whose share comes second.
+    Responder,
+    /// Either party, when neither takes a distinguished role: the shares
+    /// are ordered by their encodings.
+    Symmetric,
+}
+
+/// The state of one party between sending its share and receiving the
+/// peer's.
+///
+/// The secret scalar is wiped on drop.
+pub struct CPace {
+    secret: StaticSecret,
+    share: PublicKey,
+    isk_hasher: Sha512,
+}
+
+impl CPace {
+    /// Start the exchange with the password-related string `prs`, the
+    /// channel identifier `ci` and the session identifier `sid`, using a
+    /// secret scalar from `csprng`.
+    pub fn new<T: RngCore + CryptoRng>(prs: &[u8], ci: &[u8], sid: &[u8], csprng: T) -> CPace {
+        let secret = StaticSecret::random_from_rng(csprng);
+        let share = PublicKey::from(secret.diffie_hellman(&generator(prs, ci, sid)).to_bytes());
+
+        // The transcript hash starts with lv_cat(DSI || "_ISK", sid), which
+        // is absorbed now since sid need not be kept.
+        let mut isk_hasher = Sha512::new();
+        update_len(&mut isk_hasher, DSI.len() + 4);
+        isk_hasher.update(DSI);
+        isk_hasher.update(b"_ISK");
+        update_lv(&mut isk_hasher, sid);
+        CPace {
+            secret,
+            share,
+            isk_hasher,
+        }
+    }
+
+    /// This party's share, `Ya` or `Yb`, to send to the peer.
+    pub fn share(&self) -> &PublicKey {
+        &self.share
+    }
+
+    /// Complete the exchange with the peer's share, deriving the
+    /// intermediate session key from the transcript of both shares and
+    /// their associated data, `ad` for this party and `peer_ad` for the
+    /// peer.
+    ///
+    /// Fails with [`Error::SmallOrderPoint`] if the Diffie-Hellman result
+    /// is all zeroes.
+    pub fn finish(
+        self,
+        role: Role,
+        ad: &[u8],
+        peer_share: &PublicKey,
+        peer_ad: &[u8],
+    ) -> Result<Zeroizing<[u8; ISK_LEN]>, Error> {
+        let k = self.secret.diffie_hellman(peer_share);
+        if !k.was_contributory() {
+            return Err(Error::SmallOrderPoint);
+        }
+
+        let mut hasher = self.isk_hasher;
+        update_lv(&mut hasher, k.as_bytes());
+        let ours = (self.share.as_bytes(), ad);
+        let theirs = (peer_share.as_bytes(), peer_ad);
+        let (first, second) = match role {
+            Role::Initiator => (ours, theirs),
+            Role::Responder => (theirs, ours),
+            Role::Symmetric => {
+                hasher.update(b"oc");
+                if lv_encoding(ours).cmp(lv_encoding(theirs)).is_gt() {
+                    (ours, theirs)
+                } else {
+                    (theirs, ours)
+                }
+            }
+        };
+        for (share, ad) in [first, second] {
+            update_lv(&mut hasher, share);
+            update_lv(&mut hasher, ad);
+        }
+
+        let mut isk = Zeroizing::new([0u8; ISK_LEN]);
+        isk.copy_from_slice(&hasher.finalize());
+        Ok(isk)
+    }
+}
+
+/// Compute the generator `g` from the password-related string `prs`, the
+/// channel identifier `ci` and the session identifier `sid`, as the
+/// Elligator2 map of the SHA-512 hash of the generator string.
+pub fn generator(prs: &[u8], ci: &[u8], sid: &[u8]) -> PublicKey {
+    // The zero padding fills the first hash block with DSI and PRS, so
+    // that the password is absorbed in a single block.
+    let zero_pad_len = HASH_BLOCK_LEN
+        .saturating_sub(1 + leb128_len(prs.len()) + prs.len() + leb128_len(DSI.len()) + DSI.len());
+
+    let mut hasher = Sha512::new();
+    update_lv(&mut hasher, DSI);
+    update_lv(&mut hasher, prs);
+    update_len(&mut hasher, zero_pad_len);
+    hasher.update(&[0u8; HASH_BLOCK_LEN][..zero_pad_len]);
+    update_lv(&mut hasher, ci);
+    update_lv(&mut hasher, sid);
+
+    let mut u = Zeroizing::new([0u8; 32]);
+    u.copy_from_slice(&hasher.finalize()[..32]);
+    PublicKey(MontgomeryPoint(elligator::map(&u)))
+}
+
+/// The number of bytes in the LEB128 encoding of `len`.
+fn leb128_len(mut len: usize) -> usize {
+    let mut n = 1;
+    while len >= 0x80 {
+        len >>= 7;
+        n += 1;
+    }
+    n
+}
+
+/// `prepend_len`: hash the LEB128 encoding of `len`.
+fn update_len(hasher: &mut Sha512, mut len: usize) {
+    while len >= 0x80 {
+        hasher.update([(len as u8 & 0x7f) | 0x80]);
+        len >>= 7;
+    }
+    hasher.update([len as u8]);
+}
+
+/// `lv_cat`: hash `data` preceded by its length.
+fn update_lv(hasher: &mut Sha512, data: &[u8]) {
+    update_len(hasher, data.len());
+    hasher.update(data);
+}
+
+/// The bytes of `lv_cat(share, ad)`, for the ordering of the symmetric
+/// transcript.
+fn lv_encoding<'a>((share, ad): (&'a [u8; 32], &'a [u8])) -> impl Iterator<Item = u8> + 'a {
+    let mut ad_len = [0u8; 10];
+    let mut n = 0;
+    let mut len = ad.len();
+    while len >= 0x80 {
+        ad_len[n] = (len as u8 & 0x7f) | 0x80;
+        len >>= 7;
+        n += 1;
+    }
+    ad_len[n] = len as u8;
+    core::iter::once(32u8)
+        .chain(share.iter().copied())
+        .chain(ad_len.into_iter().take(n + 1))
+        .chain(ad.iter().copied())
+}
@@ -0,0 +1,257 @@
+// -*- mode: rust; -*-
+//
+// This file is part of x25519-dalek.
+// Copyright (c) 2017-2021 isis lovecruft
+// Copyright (c) 2019-2021 DebugSteven
+// See LICENSE for licensing information.
+//
+// Authors:
+// - isis agora lovecruft <isis@patternsinthevoid.net>
+// - DebugSteven <debugsteven@gmail.com>
+
+//! The SPAKE2 balanced PAKE, as specified in
+//! [RFC 9382](https://www.rfc-editor.org/rfc/rfc9382), with the
+//! `SPAKE2-edwards25519-SHA256-HKDF-HMAC` ciphersuite.
+//!
+//! SPAKE2 blinds each party's Diffie-Hellman share with the password scalar
+//! `w` times one of two fixed points, `M` for party A and `N` for party B,
+//! which needs the group law of the Edwards form of Curve25519 rather than
+//! the X25519 ladder.  Both parties derive the same shared key `Ke` only if
+//! they used the same `w`, and exchange key confirmation MACs before using
+//! it:
+//!
+#![cfg_attr(feature = "getrandom", doc = "```")]
+#![cfg_attr(not(feature = "getrandom"), doc = "```ignore")]
+//! use rand_core::OsRng;
+//! use x25519_dalek::pake::spake2::{Role, Spake2};
+//!
+//! // The output of a memory-hard function of the password.
+//! let w = [0x42; 64];
+//! let alice = Spake2::new(Role::A, &w, OsRng);
+//! let bob = Spake2::new(Role::B, &w, OsRng);
+//! let alice_share = *alice.share();
+//! let bob_share = *bob.share();
+//!
+//! let alice_keys = alice.finish(b"alice", b"bob", b"", &bob_share)?;
+//! let bob_keys = bob.finish(b"alice", b"bob", b"", &alice_share)?;
+//! let alice_mac = *alice_keys.mac();
+//! let alice_ke = alice_keys.verify(bob_keys.mac())?;
+//! let bob_ke = bob_keys.verify(&alice_mac)?;
+//! assert_eq!(alice_ke, bob_ke);
+//! # Ok::<(), x25519_dalek::Error>(())
+//! ```
+//!
+//! The password scalar is reduced from 64 bytes, which should be the output
+//! of a memory-hard function such as scrypt or Argon2 of the password, so
+//! that an attacker who records a session cannot cheaply test guesses.
+
+use core::fmt;
+
+use curve25519_dalek::edwards::{CompressedEdwardsY, EdwardsPoint};
+use curve25519_dalek::scalar::Scalar;
+use curve25519_dalek::traits::IsIdentity;
+use hkdf::Hkdf;
+use hmac::{Hmac, Mac};
+use rand_core::{CryptoRng, RngCore};
+use sha2::{Digest, Sha256};
+use subtle::ConstantTimeEq;
+use zeroize::Zeroizing;
+
+/// The point `M` which blinds party A's share.
+pub const M: [u8; 32] = [
+    0xd0, 0x48, 0x03, 0x2c, 0x6e, 0xa0, 0xb6, 0xd6, 0x97, 0xdd, 0xc2, 0xe8, 0x6b, 0xda, 0x85, 0xa3,
+    0x3a, 0xda, 0xc9, 0x20, 0xf1, 0xbf, 0x18, 0xe1, 0xb0, 0xc6, 0xd1, 0x66, 0xa5, 0xce, 0xcd, 0xaf,
+];
+
+/// The point `N` which blinds party B's share.
+pub const N: [u8; 32] = [
+    0xd3, 0xbf, 0xb5, 0x18, 0xf4, 0x4f, 0x34, 0x30, 0xf2, 0x9d, 0x0c, 0x92, 0xaf, 0x50, 0x38, 0x65,
+    0xa1, 0xed, 0x32, 0x81, 0xdc, 0x69, 0xb3, 0x5d, 0xd8, 0x68, 0xba, 0x85, 0xf8, 0x86, 0xc4, 0xab,
+];
+
+/// The length of a share, a compressed Edwards point.
+pub const SHARE_LEN: usize = 32;
+
+/// The length of the shared key `Ke` and of the confirmation keys.
+pub const KEY_LEN: usize = 16;
+
+/// The length of a key confirmation MAC.
+pub const MAC_LEN: usize = 32;
+
+/// Errors which may occur in the exchange.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// The peer's share is not the encoding of a point, or it makes the
+    /// shared point the identity.
+    InvalidShare,
+    /// The peer's key confirmation MAC is wrong, which is what happens when
+    /// the parties used different passwords.
+    ConfirmationFailed,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::InvalidShare => f.write_str("invalid SPAKE2 share"),
+            Error::ConfirmationFailed => f.write_str("SPAKE2 key confirmation failed"),
+        }
+    }
+}
+
+/// Which side of the exchange a party takes.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Role {
+    /// Party A, whose share is blinded by `M`.
+    A,
+    /// Party B, whose share is blinded by `N`.
+    B,
+}
+
+/// The state of one party between sending its share and receiving the
+/// peer's.
+///
+/// The secret and password scalars are wiped on drop.
+pub struct Spake2 {
+    role: Role,
+    secret: Zeroizing<Scalar>,
+    w: Zeroizing<Scalar>,
+    share: [u8; SHARE_LEN],
+}
+
+impl Spake2 {
+    /// Start the exchange as `role` with the password scalar reduced from
+    /// `w`, using a secret scalar from `csprng`.
+    pub fn new<T: RngCore + CryptoRng>(role: Role, w: &[u8; 64], mut csprng: T) -> Spake2 {
+        let w = Zeroizing::new(Scalar::from_bytes_mod_order_wide(w));
+        let mut bytes = Zeroizing::new([0u8; 64]);
+        csprng.fill_bytes(&mut bytes[..]);
+        let secret = Zeroizing::new(Scalar::from_bytes_mod_order_wide(&bytes));
+
+        let share = EdwardsPoint::mul_base(&secret) + blinding_point(role) * *w;
+        Spake2 {
+            role,
+            secret,
+            w,
+            share: share.compress().to_bytes(),
+        }
+    }
+
+    /// This party's share, `pA` or `pB`, to send to the peer.
+    pub fn share(&self) -> &[u8; SHARE_LEN] {
+        &self.share
+    }
+
+    /// Complete the exchange with the peer's share, deriving the keys from
+    /// the transcript of the identities `id_a` of party A and `id_b` of
+    /// party B, both shares, and the shared point.  The associated data
+    /// `aad` is bound to the confirmation keys.
+    ///
+    /// The identities may be empty if they are not known to the
+    /// application.
+    pub fn finish(
+        self,
+        id_a: &[u8],
+        id_b: &[u8],
+        aad: &[u8],
+        peer_share: &[u8; SHARE_LEN],
+    ) -> Result<Confirmation, crate::Error> {
+        let peer = CompressedEdwardsY(*peer_share)
+            .decompress()
+            .ok_or(Error::InvalidShare)?;
+        let peer_role = match self.role {
+            Role::A => Role::B,
+            Role::B => Role::A,
+        };
+        let k = ((peer - blinding_point(peer_role) * *self.w) * *self.secret).mul_by_cofactor();
+        if k.is_identity() {
+            return Err(Error::InvalidShare.into());
+        }
+
+        let k = Zeroizing::new(k.compress().to_bytes());
+        let (share_a, share_b) = match self.role {
+            Role::A => (&self.share, peer_share),
+            Role::B => (peer_share, &self.share),
+        };
+        let transcript: [&[u8]; 6] = [id_a, id_b, share_a, share_b, &k[..], self.w.as_bytes()];
+
+        let mut hasher = Sha256::new();
+        update_transcript(&transcript, |part| hasher.update(part));
+        let hash = Zeroizing::new(<[u8; 32]>::from(hasher.finalize()));
+        let (ke, ka) = hash.split_at(KEY_LEN);
+
+        let mut kc = Zeroizing::new([0u8; 2 * KEY_LEN]);
+        Hkdf::<Sha256>::new(None, ka)
+            .expand_multi_info(&[b"ConfirmationKeys", aad], &mut kc[..])
+            .expect("32 bytes is a valid HKDF-SHA256 output length");
+        let (kc_a, kc_b) = kc.split_at(KEY_LEN);
+        let (kc_own, kc_peer) = match self.role {
+            Role::A => (kc_a, kc_b),
+            Role::B => (kc_b, kc_a),
+        };
+
+        let mut shared_key = Zeroizing::new([0u8; KEY_LEN]);
+        shared_key.copy_from_slice(ke);
+        Ok(Confirmation {
+            shared_key,
+            mac: mac(kc_own, &transcript),
+            peer_mac: mac(kc_peer, &transcript),
+        })
+    }
+}
+
+/// The keys of a completed exchange, held back until the peer's key
+/// confirmation MAC is checked.
+///
+/// The shared key is wiped on drop.
+pub struct Confirmation {
+    shared_key: Zeroizing<[u8; KEY_LEN]>,
+    mac: [u8; MAC_LEN],
+    peer_mac: [u8; MAC_LEN],
+}
+
+impl Confirmation {
+    /// This party's key confirmation MAC, `cA` or `cB`, to send to the peer.
+    pub fn mac(&self) -> &[u8; MAC_LEN] {
+        &self.mac
+    }
+
+    /// Check the peer's key confirmation MAC in constant time, returning
+    /// the shared key `Ke` if it is correct.
+    pub fn verify(
+        self,
+        peer_mac: &[u8; MAC_LEN],
+    ) -> Result<Zeroizing<[u8; KEY_LEN]>, crate::Error> {
+        if !bool::from(self.peer_mac.ct_eq(peer_mac)) {
+            return Err(Error::ConfirmationFailed.into());
+        }
+        Ok(self.shared_key)
+    }
+}
+
+/// The point which blinds the share of the party taking `role`.
+fn blinding_point(role: Role) -> EdwardsPoint {
+    let point = match role {
+        Role::A => M,
+        Role::B => N,
+    };
+    CompressedEdwardsY(point)
+        .decompress()
+        .expect("M and N are valid points")
+}
+
+/// Feed the transcript `TT` to `update`: each part preceded by its length
+/// as a little-endian 64-bit integer.
+fn update_transcript(parts: &[&[u8]; 6], mut update: impl FnMut(&[u8])) {
+    for part in parts {
+        update(&(part.len() as u64).to_le_bytes());
+        update(part);
+    }
+}
+
+/// HMAC-SHA256 of the transcript with the confirmation key `key`.
+fn mac(key: &[u8], transcript: &[&[u8]; 6]) -> [u8; MAC_LEN] {
+    let mut mac =
+        <Hmac<Sha256> as Mac>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    update_transcript(transcript, |part| mac.update(part));
+    mac.finalize().into_bytes().into()
+}
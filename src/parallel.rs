@@ -0,0 +1,42 @@
+// -*- mode: rust; -*-
+//
+// This file is part of x25519-dalek.
+// Copyright (c) 2017-2021 isis lovecruft
+// Copyright (c) 2019-2021 DebugSteven
+// See LICENSE for licensing information.
+//
+// Authors:
+// - isis agora lovecruft <isis@patternsinthevoid.net>
+// - DebugSteven <debugsteven@gmail.com>
+
+//! Diffie-Hellman over large batches, spread across the threads of the
+//! global rayon thread pool.
+
+use alloc::vec::Vec;
+
+use rayon::prelude::*;
+
+use crate::x25519::{PublicKey, SharedSecret, StaticSecret};
+
+impl StaticSecret {
+    /// Perform a Diffie-Hellman key agreement between `self` and each of
+    /// `their_publics` on all threads of the rayon thread pool, producing
+    /// the same shared secrets as [`StaticSecret::diffie_hellman`] in the
+    /// same order.
+    pub fn par_diffie_hellman_batch(&self, their_publics: &[PublicKey]) -> Vec<SharedSecret> {
+        their_publics
+            .par_iter()
+            .map(|their_public| self.diffie_hellman(their_public))
+            .collect()
+    }
+
+    /// Perform the Diffie-Hellman key agreement of each pair of a secret
+    /// key and a peer's public key on all threads of the rayon thread pool,
+    /// producing the shared secrets in the order of the pairs.
+    pub fn par_diffie_hellman_pairs(pairs: &[(StaticSecret, PublicKey)]) -> Vec<SharedSecret> {
+        pairs
+            .par_iter()
+            .map(|(secret, their_public)| secret.diffie_hellman(their_public))
+            .collect()
+    }
+}
@@ -0,0 +1,219 @@
+// -*- mode: rust; -*-
+//
+// This file is part of x25519-dalek.
+// Copyright (c) 2017-2021 isis lovecruft
+// Copyright (c) 2019-2021 DebugSteven
+// See LICENSE for licensing information.
+//
+// Authors:
+// - isis agora lovecruft <isis@patternsinthevoid.net>
+// - DebugSteven <debugsteven@gmail.com>
+
+//! Passphrase encryption of secret keys for storage at rest, with Argon2id
+//! ([RFC9106](https://www.rfc-editor.org/rfc/rfc9106)) and
+//! ChaCha20-Poly1305.
+//!
+//! ```no_run
+//! use rand_core::OsRng;
+//! use x25519_dalek::StaticSecret;
+//! use x25519_dalek::passphrase::Params;
+//!
+//! let secret = StaticSecret::random_from_rng(OsRng);
+//! let params = Params::default();
+//! let sealed = secret.seal_with_passphrase(b"correct horse", &params, OsRng)?;
+//!
+//! let unsealed = StaticSecret::unseal_with_passphrase(&sealed, b"correct horse")?;
+//! assert_eq!(unsealed.to_bytes(), secret.to_bytes());
+//! # Ok::<(), x25519_dalek::Error>(())
+//! ```
+//!
+//! A sealed key is [`SEALED_LEN`] bytes: a version byte, the Argon2id memory
+//! size, iterations and parallelism as big-endian `u32`s, a 16-byte random
+//! salt, and the secret key encrypted with ChaCha20-Poly1305 under the key
+//! derived from the passphrase and salt, with its tag.  The header before
+//! the ciphertext is authenticated as associated data.  Each sealing draws
+//! a new salt, and so a new key, so the nonce is fixed at zero.
+
+use core::fmt;
+
+use argon2::{Algorithm, Argon2, Version};
+use chacha20poly1305::aead::generic_array::GenericArray;
+use chacha20poly1305::{AeadInPlace, ChaCha20Poly1305, KeyInit};
+use rand_core::{CryptoRng, RngCore};
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::x25519::StaticSecret;
+
+/// The version of the sealed key format.
+pub const VERSION: u8 = 1;
+
+/// The largest Argon2id memory size accepted, in KiB: 1 GiB, sixteen
+/// times the default.
+pub const MAX_MEMORY_KIB: u32 = 1 << 20;
+
+/// The largest number of Argon2id iterations accepted.
+pub const MAX_ITERATIONS: u32 = 16;
+
+/// The length of the random salt.
+const SALT_LEN: usize = 16;
+
+/// The offset of the salt, after the version and the Argon2id parameters.
+const SALT_OFFSET: usize = 1 + 3 * 4;
+
+/// The length of the header, which is authenticated but not encrypted.
+const HEADER_LEN: usize = SALT_OFFSET + SALT_LEN;
+
+/// The length of a ChaCha20-Poly1305 tag.
+const TAG_LEN: usize = 16;
+
+/// The length of a sealed secret key.
+pub const SEALED_LEN: usize = HEADER_LEN + 32 + TAG_LEN;
+
+/// Errors which may occur when sealing or unsealing a secret key.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// The sealed key is of a version other than [`VERSION`].
+    UnsupportedVersion,
+    /// The Argon2id parameters are out of range, or above [`MAX_MEMORY_KIB`]
+    /// or [`MAX_ITERATIONS`].
+    InvalidParams,
+    /// The sealed key does not decrypt under the passphrase, or has been
+    /// modified.
+    InvalidPassphrase,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::UnsupportedVersion => f.write_str("unsupported sealed key version"),
+            Error::InvalidParams => f.write_str("invalid Argon2id parameters"),
+            Error::InvalidPassphrase => f.write_str("wrong passphrase or corrupted sealed key"),
+        }
+    }
+}
+
+/// The Argon2id cost parameters.
+///
+/// The default is the second recommended option of RFC9106, 64 MiB of
+/// memory, three iterations and four lanes, which takes a fraction of a
+/// second on current computers.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Params {
+    /// The memory size, in KiB.
+    pub memory_kib: u32,
+    /// The number of passes over the memory.
+    pub iterations: u32,
+    /// The number of lanes.
+    pub parallelism: u32,
+}
+
+impl Default for Params {
+    fn default() -> Params {
+        Params {
+            memory_kib: 64 * 1024,
+            iterations: 3,
+            parallelism: 4,
+        }
+    }
+}
+
+/// Derive the encryption key from `passphrase`, `salt` and `params`.
+fn derive_key(passphrase: &[u8], salt: &[u8], params: &Params) -> Result<ChaCha20Poly1305, Error> {
+    if params.memory_kib > MAX_MEMORY_KIB || params.iterations > MAX_ITERATIONS {
+        return Err(Error::InvalidParams);
+    }
+    let params = argon2::Params::new(
+        params.memory_kib,
+        params.iterations,
+        params.parallelism,
+        Some(32),
+    )
+    .map_err(|_| Error::InvalidParams)?;
+    let mut key = Zeroizing::new([0u8; 32]);
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(passphrase, salt, &mut key[..])
+        .map_err(|_| Error::InvalidParams)?;
+    Ok(ChaCha20Poly1305::new(GenericArray::from_slice(&key[..])))
+}
+
+impl StaticSecret {
+    /// Encrypt this secret key under `passphrase`, with a key derived by
+    /// Argon2id with `params` and a salt from `csprng`.
+    ///
+    /// Parameters above [`MAX_MEMORY_KIB`] or [`MAX_ITERATIONS`] are
+    /// rejected, as they are by
+    /// [`unseal_with_passphrase`](StaticSecret::unseal_with_passphrase).
+    pub fn seal_with_passphrase<T: RngCore + CryptoRng>(
+        &self,
+        passphrase: &[u8],
+        params: &Params,
+        mut csprng: T,
+    ) -> Result<[u8; SEALED_LEN], crate::Error> {
+        let mut sealed = [0u8; SEALED_LEN];
+        sealed[0] = VERSION;
+        sealed[1..5].copy_from_slice(&params.memory_kib.to_be_bytes());
+        sealed[5..9].copy_from_slice(&params.iterations.to_be_bytes());
+        sealed[9..13].copy_from_slice(&params.parallelism.to_be_bytes());
+        csprng
+            .try_fill_bytes(&mut sealed[SALT_OFFSET..HEADER_LEN])
+            .map_err(|_| crate::Error::Rng)?;
+
+        let cipher = derive_key(passphrase, &sealed[SALT_OFFSET..HEADER_LEN], params)?;
+        let (header, body) = sealed.split_at_mut(HEADER_LEN);
+        let (ciphertext, tag) = body.split_at_mut(32);
+        ciphertext.copy_from_slice(self.as_bytes());
+        let computed = cipher
+            .encrypt_in_place_detached(&Default::default(), header, ciphertext)
+            .expect("32 bytes is within the ChaCha20-Poly1305 length limit");
+        tag.copy_from_slice(&computed);
+        Ok(sealed)
+    }
+
+    /// Decrypt a secret key sealed by
+    /// [`seal_with_passphrase`](StaticSecret::seal_with_passphrase).
+    ///
+    /// The Argon2id parameters are read from `sealed`, before it can be
+    /// authenticated, so they are limited to [`MAX_MEMORY_KIB`] and
+    /// [`MAX_ITERATIONS`], which bound the memory and time a sealed key from
+    /// an untrusted source can make this function use.
+    pub fn unseal_with_passphrase(
+        sealed: &[u8],
+        passphrase: &[u8],
+    ) -> Result<StaticSecret, crate::Error> {
+        if sealed.len() != SEALED_LEN {
+            return Err(crate::Error::InvalidLength {
+                expected: SEALED_LEN,
+                actual: sealed.len(),
+            });
+        }
+        if sealed[0] != VERSION {
+            return Err(Error::UnsupportedVersion.into());
+        }
+        let u32_at = |i: usize| u32::from_be_bytes(sealed[i..i + 4].try_into().unwrap());
+        let params = Params {
+            memory_kib: u32_at(1),
+            iterations: u32_at(5),
+            parallelism: u32_at(9),
+        };
+
+        let cipher = derive_key(passphrase, &sealed[SALT_OFFSET..HEADER_LEN], &params)?;
+        let (header, body) = sealed.split_at(HEADER_LEN);
+        let (ciphertext, tag) = body.split_at(32);
+        let mut bytes = [0u8; 32];
+        bytes.copy_from_slice(ciphertext);
+        if cipher
+            .decrypt_in_place_detached(
+                &Default::default(),
+                header,
+                &mut bytes,
+                GenericArray::from_slice(tag),
+            )
+            .is_err()
+        {
+            return Err(Error::InvalidPassphrase.into());
+        }
+        let secret = StaticSecret::from(bytes);
+        bytes.zeroize();
+        Ok(secret)
+    }
+}
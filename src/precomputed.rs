@@ -0,0 +1,119 @@
+// -*- mode: rust; -*-
+//
+// This file is part of x25519-dalek.
+// Copyright (c) 2017-2021 isis lovecruft
+// Copyright (c) 2019-2021 DebugSteven
+// See LICENSE for licensing information.
+//
+// Authors:
+// - isis agora lovecruft <isis@patternsinthevoid.net>
+// - DebugSteven <debugsteven@gmail.com>
+
+//! Diffie-Hellman against a public key with a precomputed table of its
+//! multiples.
+//!
+//! X25519 only depends on the u-coordinate of the peer's point, which is
+//! shared by a point on the Edwards form of the curve and its negation, so
+//! the peer's key can be lifted to either of them and the secret scalar
+//! applied with the constant-time fixed-base multiplication of
+//! `curve25519-dalek`.  This is the same multiplication which computes
+//! public keys, and is several times faster than the Montgomery ladder once
+//! the table is built.
+
+use core::fmt;
+
+use curve25519_dalek::edwards::EdwardsBasepointTable;
+use curve25519_dalek::montgomery::MontgomeryPoint;
+use curve25519_dalek::traits::BasepointTable;
+
+use crate::x25519::{EphemeralSecret, PublicKey, SharedSecret};
+
+#[cfg(feature = "reusable_secrets")]
+use crate::x25519::ReusableSecret;
+
+#[cfg(feature = "static_secrets")]
+use crate::x25519::StaticSecret;
+
+/// A [`PublicKey`] with a table of multiples of its point, for performing
+/// many Diffie-Hellman key agreements with the same peer.
+///
+/// Each key agreement is about three times faster than with the
+/// [`PublicKey`], but building the table costs about as much as twenty of
+/// them, so this pays off only for a peer which is used many times.  The
+/// table takes 30 KiB, so consider boxing this type.
+///
+/// Public keys which are not on the curve but on its twist, which honest
+/// peers never send, have no table and fall back to the Montgomery ladder.
+#[derive(Clone)]
+pub struct PrecomputedPublicKey {
+    public: PublicKey,
+    table: Option<EdwardsBasepointTable>,
+}
+
+impl PrecomputedPublicKey {
+    /// The public key which this table was built for.
+    pub fn public_key(&self) -> &PublicKey {
+        &self.public
+    }
+
+    /// Multiply the public key by the clamped `secret`.
+    fn mul_clamped(&self, secret: [u8; 32]) -> MontgomeryPoint {
+        match &self.table {
+            Some(table) => table.mul_base_clamped(secret).to_montgomery(),
+            None => self.public.0.mul_clamped(secret),
+        }
+    }
+}
+
+impl<'a> From<&'a PublicKey> for PrecomputedPublicKey {
+    /// Build the table of multiples of `public`.
+    fn from(public: &'a PublicKey) -> PrecomputedPublicKey {
+        PrecomputedPublicKey {
+            public: *public,
+            table: public
+                .0
+                .to_edwards(0)
+                .map(|point| EdwardsBasepointTable::create(&point)),
+        }
+    }
+}
+
+impl fmt::Debug for PrecomputedPublicKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PrecomputedPublicKey")
+            .field("public", &self.public)
+            .finish_non_exhaustive()
+    }
+}
+
+impl EphemeralSecret {
+    /// Perform a Diffie-Hellman key agreement between `self` and the
+    /// precomputed `their_public` key.
+    ///
+    /// The result is the same as [`EphemeralSecret::diffie_hellman`].
+    pub fn diffie_hellman_precomputed(self, their_public: &PrecomputedPublicKey) -> SharedSecret {
+        SharedSecret(their_public.mul_clamped(self.0))
+    }
+}
+
+#[cfg(feature = "reusable_secrets")]
+impl ReusableSecret {
+    /// Perform a Diffie-Hellman key agreement between `self` and the
+    /// precomputed `their_public` key.
+    ///
+    /// The result is the same as [`ReusableSecret::diffie_hellman`].
+    pub fn diffie_hellman_precomputed(&self, their_public: &PrecomputedPublicKey) -> SharedSecret {
+        SharedSecret(their_public.mul_clamped(self.0))
+    }
+}
+
+#[cfg(feature = "static_secrets")]
+impl StaticSecret {
+    /// Perform a Diffie-Hellman key agreement between `self` and the
+    /// precomputed `their_public` key.
+    ///
+    /// The result is the same as [`StaticSecret::diffie_hellman`].
+    pub fn diffie_hellman_precomputed(&self, their_public: &PrecomputedPublicKey) -> SharedSecret {
+        SharedSecret(their_public.mul_clamped(self.0))
+    }
+}
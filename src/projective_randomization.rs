@@ -0,0 +1,121 @@
+// -*- mode: rust; -*-
+//
+// This file is part of x25519-dalek.
+// Copyright (c) 2017-2021 isis lovecruft
+// Copyright (c) 2019-2021 DebugSteven
+// See LICENSE for licensing information.
+//
+// Authors:
+// - isis agora lovecruft <isis@patternsinthevoid.net>
+// - DebugSteven <debugsteven@gmail.com>
+
+//! Diffie-Hellman with randomized projective coordinates, as a
+//! countermeasure against differential power analysis.
+//!
+//! The Montgomery ladder works on points in projective coordinates
+//! \\( (X : Z) \\), in which \\( (\lambda X : \lambda Z) \\) is the same
+//! point for every nonzero \\( \lambda \\).  The randomized variants draw a
+//! fresh \\( \lambda \\) for each of the ladder's two starting points on
+//! every operation, so that the field elements the ladder computes on
+//! cannot be predicted from the peer's public key, even by a peer who
+//! chooses it.  The result is identical to the unrandomized computation.
+//!
+//! `curve25519-dalek` does not expose the projective coordinates of its
+//! ladder, so these variants run the ladder of this crate over the Fiat
+//! Crypto field arithmetic instead.  The secret scalar itself is not
+//! blinded; that is what the `scalar_blinding` feature does, on the ladder
+//! of `curve25519-dalek`.
+
+use curve25519_dalek::montgomery::MontgomeryPoint;
+use curve25519_dalek::scalar::clamp_integer;
+use rand_core::{CryptoRng, RngCore};
+use subtle::ConstantTimeEq;
+use zeroize::Zeroizing;
+
+use crate::field::FieldElement;
+use crate::ladder::Ladder;
+use crate::x25519::{EphemeralSecret, PublicKey, SharedSecret};
+
+#[cfg(feature = "reusable_secrets")]
+use crate::x25519::ReusableSecret;
+
+#[cfg(feature = "static_secrets")]
+use crate::x25519::StaticSecret;
+
+/// A random nonzero field element from `csprng`.
+fn random_nonzero<T: RngCore + CryptoRng>(csprng: &mut T) -> FieldElement {
+    let zero = FieldElement::from_u32(0);
+    loop {
+        let mut bytes = Zeroizing::new([0u8; 32]);
+        csprng.fill_bytes(&mut bytes[..]);
+        let lambda = FieldElement::from_bytes(&bytes);
+        if !bool::from(lambda.ct_eq(&zero)) {
+            return lambda;
+        }
+    }
+}
+
+/// Multiply `point` by the clamped `secret`, randomizing the projective
+/// coordinates of the ladder with randomness from `csprng`.
+fn randomized_mul<T: RngCore + CryptoRng>(
+    point: &MontgomeryPoint,
+    secret: &[u8; 32],
+    mut csprng: T,
+) -> MontgomeryPoint {
+    let scalar = Zeroizing::new(clamp_integer(*secret));
+
+    let mut ladder = Ladder::new(&point.0);
+    ladder.randomize(&random_nonzero(&mut csprng), &random_nonzero(&mut csprng));
+    let (x, z) = ladder.mul_clamped(&scalar);
+
+    // The point at infinity has Z = 0, whose inverse is taken as 0, so it
+    // is encoded as zero as in `MontgomeryPoint::mul_clamped`.
+    MontgomeryPoint((&x * &z.invert()).to_bytes())
+}
+
+impl EphemeralSecret {
+    /// Perform a Diffie-Hellman key agreement between `self` and
+    /// `their_public` key, randomizing the projective coordinates of the
+    /// ladder with randomness from `csprng`.
+    ///
+    /// The result is the same as [`EphemeralSecret::diffie_hellman`].
+    pub fn diffie_hellman_randomized<T: RngCore + CryptoRng>(
+        self,
+        their_public: &PublicKey,
+        csprng: T,
+    ) -> SharedSecret {
+        SharedSecret(randomized_mul(&their_public.0, &self.0, csprng))
+    }
+}
+
+#[cfg(feature = "reusable_secrets")]
+impl ReusableSecret {
+    /// Perform a Diffie-Hellman key agreement between `self` and
+    /// `their_public` key, randomizing the projective coordinates of the
+    /// ladder with randomness from `csprng`.
+    ///
+    /// The result is the same as [`ReusableSecret::diffie_hellman`].
+    pub fn diffie_hellman_randomized<T: RngCore + CryptoRng>(
+        &self,
+        their_public: &PublicKey,
+        csprng: T,
+    ) -> SharedSecret {
+        SharedSecret(randomized_mul(&their_public.0, &self.0, csprng))
+    }
+}
+
+#[cfg(feature = "static_secrets")]
+impl StaticSecret {
+    /// Perform a Diffie-Hellman key agreement between `self` and
+    /// `their_public` key, randomizing the projective coordinates of the
+    /// ladder with randomness from `csprng`.
+    ///
+    /// The result is the same as [`StaticSecret::diffie_hellman`].
+    pub fn diffie_hellman_randomized<T: RngCore + CryptoRng>(
+        &self,
+        their_public: &PublicKey,
+        csprng: T,
+    ) -> SharedSecret {
+        SharedSecret(randomized_mul(&their_public.0, self.as_bytes(), csprng))
+    }
+}
@@ -0,0 +1,188 @@
+// -*- mode: rust; -*-
+//
+// This file is part of x25519-dalek.
+// Copyright (c) 2017-2021 isis lovecruft
+// Copyright (c) 2019-2021 DebugSteven
+// See LICENSE for licensing information.
+//
+// Authors:
+// - isis agora lovecruft <isis@patternsinthevoid.net>
+// - DebugSteven <debugsteven@gmail.com>
+
+//! Schnorr proofs that the holder of a [`StaticSecret`] knows the discrete
+//! logarithm of its [`PublicKey`].
+//!
+//! An X25519 public key is only the Montgomery u-coordinate of a point, which
+//! corresponds to two Edwards points of opposite sign.  As in XEdDSA, the
+//! prover negates its secret scalar if necessary so that its Edwards public
+//! key has sign bit zero, which the verifier can then recover from the
+//! u-coordinate alone.
+//!
+//! Proofs are bound to a caller-supplied context, such as a registration
+//! request identifier, so that they cannot be replayed elsewhere:
+//!
+#![cfg_attr(feature = "getrandom", doc = "```")]
+#![cfg_attr(not(feature = "getrandom"), doc = "```ignore")]
+//! use rand_core::OsRng;
+//! use x25519_dalek::{PublicKey, StaticSecret};
+//!
+//! let secret = StaticSecret::random();
+//! let proof = secret.prove_possession(b"registration 42", OsRng);
+//!
+//! let public = PublicKey::from(&secret);
+//! assert!(public.verify_possession(&proof, b"registration 42").is_ok());
+//! assert!(public.verify_possession(&proof, b"registration 43").is_err());
+//! ```
+
+use core::fmt;
+
+use curve25519_dalek::edwards::{CompressedEdwardsY, EdwardsPoint};
+use curve25519_dalek::scalar::{clamp_integer, Scalar};
+use rand_core::{CryptoRng, RngCore};
+use sha2::{Digest, Sha512};
+use subtle::{Choice, ConditionallyNegatable};
+use zeroize::Zeroizing;
+
+use crate::x25519::{PublicKey, StaticSecret};
+
+/// The domain separator for the challenge hash.
+const CHALLENGE_DOMAIN: &[u8] = b"x25519-dalek proof of possession v1";
+
+/// The domain separator for the nonce hash.
+const NONCE_DOMAIN: &[u8] = b"x25519-dalek proof of possession nonce v1";
+
+/// The length of an encoded [`ProofOfPossession`].
+pub const PROOF_LEN: usize = 64;
+
+/// Errors which may occur when verifying a proof of possession.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// The public key is on the twist, or has a small-order component.
+    InvalidPublicKey,
+    /// The proof is malformed or does not verify.
+    InvalidProof,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::InvalidPublicKey => f.write_str("public key is not in the prime-order subgroup"),
+            Error::InvalidProof => f.write_str("invalid proof of possession"),
+        }
+    }
+}
+
+/// A Schnorr proof of knowledge of the secret key for a [`PublicKey`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ProofOfPossession {
+    commitment: CompressedEdwardsY,
+    response: Scalar,
+}
+
+impl ProofOfPossession {
+    /// Encode this proof as the compressed commitment point followed by the
+    /// response scalar.
+    pub fn to_bytes(&self) -> [u8; PROOF_LEN] {
+        let mut bytes = [0u8; PROOF_LEN];
+        bytes[..32].copy_from_slice(self.commitment.as_bytes());
+        bytes[32..].copy_from_slice(self.response.as_bytes());
+        bytes
+    }
+
+    /// Decode a proof produced by [`ProofOfPossession::to_bytes`].
+    pub fn from_bytes(bytes: &[u8; PROOF_LEN]) -> Result<ProofOfPossession, crate::Error> {
+        let mut commitment = [0u8; 32];
+        let mut response = [0u8; 32];
+        commitment.copy_from_slice(&bytes[..32]);
+        response.copy_from_slice(&bytes[32..]);
+
+        let response =
+            Option::from(Scalar::from_canonical_bytes(response)).ok_or(Error::InvalidProof)?;
+        Ok(ProofOfPossession {
+            commitment: CompressedEdwardsY(commitment),
+            response,
+        })
+    }
+}
+
+impl StaticSecret {
+    /// Prove knowledge of this secret key, bound to `context`.
+    ///
+    /// The nonce is derived from both the secret key and the RNG, so a weak
+    /// RNG does not leak the secret key.
+    pub fn prove_possession<T: RngCore + CryptoRng>(
+        &self,
+        context: &[u8],
+        mut csprng: T,
+    ) -> ProofOfPossession {
+        let mut scalar =
+            Zeroizing::new(Scalar::from_bytes_mod_order(clamp_integer(self.to_bytes())));
+        let public = EdwardsPoint::mul_base(&scalar).compress();
+        // Negate the scalar if needed so the Edwards public key has sign 0.
+        scalar.conditional_negate(Choice::from(public.as_bytes()[31] >> 7));
+        let public = EdwardsPoint::mul_base(&scalar).compress();
+
+        let mut randomness = Zeroizing::new([0u8; 32]);
+        csprng.fill_bytes(&mut randomness[..]);
+        let nonce = Zeroizing::new(Scalar::from_hash(
+            Sha512::new()
+                .chain_update(NONCE_DOMAIN)
+                .chain_update(scalar.as_bytes())
+                .chain_update(&randomness[..])
+                .chain_update(context),
+        ));
+
+        let commitment = EdwardsPoint::mul_base(&nonce).compress();
+        let challenge = challenge(&public, &commitment, context);
+        ProofOfPossession {
+            commitment,
+            response: *nonce + challenge * *scalar,
+        }
+    }
+}
+
+impl PublicKey {
+    /// Verify a proof that the holder of this public key knows its secret
+    /// key, bound to `context`.
+    ///
+    /// Public keys outside the prime-order subgroup are rejected, since no
+    /// [`StaticSecret`] produces them.
+    pub fn verify_possession(
+        &self,
+        proof: &ProofOfPossession,
+        context: &[u8],
+    ) -> Result<(), crate::Error> {
+        let public = self.0.to_edwards(0).ok_or(Error::InvalidPublicKey)?;
+        if public.is_small_order() || !public.is_torsion_free() {
+            return Err(Error::InvalidPublicKey.into());
+        }
+        let public_compressed = public.compress();
+
+        let challenge = challenge(&public_compressed, &proof.commitment, context);
+        // response * B - challenge * A should equal the commitment.
+        let expected = EdwardsPoint::vartime_double_scalar_mul_basepoint(
+            &challenge,
+            &-public,
+            &proof.response,
+        );
+        if expected.compress() != proof.commitment {
+            return Err(Error::InvalidProof.into());
+        }
+        Ok(())
+    }
+}
+
+/// Compute the Fiat-Shamir challenge for a public key and commitment.
+fn challenge(
+    public: &CompressedEdwardsY,
+    commitment: &CompressedEdwardsY,
+    context: &[u8],
+) -> Scalar {
+    Scalar::from_hash(
+        Sha512::new()
+            .chain_update(CHALLENGE_DOMAIN)
+            .chain_update(public.as_bytes())
+            .chain_update(commitment.as_bytes())
+            .chain_update(context),
+    )
+}
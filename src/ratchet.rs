@@ -0,0 +1,179 @@
+// -*- mode: rust; -*-
+//
+// This file is part of x25519-dalek.
+// Copyright (c) 2017-2021 isis lovecruft
+// Copyright (c) 2019-2021 DebugSteven
+// See LICENSE for licensing information.
+//
+// Authors:
+// - isis agora lovecruft <isis@patternsinthevoid.net>
+// - DebugSteven <debugsteven@gmail.com>
+
+//! The Diffie-Hellman ratchet of the
+//! [Double Ratchet algorithm](https://signal.org/docs/specifications/doubleratchet/).
+//!
+//! Each party keeps a ratchet key pair and a root key.  Whenever a message
+//! arrives with a new ratchet public key from the peer, [`DhRatchet::step`]
+//! performs two Diffie-Hellman exchanges with it, one before and one after
+//! replacing the local ratchet key pair, and feeds each result through the
+//! root KDF to derive a new receiving and sending chain key.
+//!
+//! The root KDF, `KDF_RK` in the specification, is supplied by the caller
+//! as a [`RootKdf`], which closures implement:
+//!
+#![cfg_attr(feature = "getrandom", doc = "```")]
+#![cfg_attr(not(feature = "getrandom"), doc = "```ignore")]
+//! use rand_core::OsRng;
+//! use x25519_dalek::ratchet::{DhRatchet, Key, KEY_LEN};
+//! use x25519_dalek::{PublicKey, SharedSecret, StaticSecret};
+//!
+//! fn kdf_rk(root_key: &[u8; KEY_LEN], dh_output: &SharedSecret) -> (Key, Key) {
+//!     // An HKDF of the root key and the Diffie-Hellman output in practice.
+//! #   let mut root = Key::new(*root_key);
+//! #   for (r, d) in root.iter_mut().zip(dh_output.as_bytes()) {
+//! #       *r ^= d;
+//! #   }
+//! #   let chain = root.clone();
+//! #   (root, chain)
+//! }
+//!
+//! // Both parties start from a shared key, such as the output of X3DH, and
+//! // the initiator knows the responder's first ratchet public key.
+//! let shared_key = [0x42; KEY_LEN];
+//! let bob_ratchet = StaticSecret::random();
+//! let bob_public = PublicKey::from(&bob_ratchet);
+//!
+//! let (mut alice, alice_sending) =
+//!     DhRatchet::initiator(&shared_key, &bob_public, kdf_rk, OsRng)?;
+//! let mut bob = DhRatchet::responder(&shared_key, bob_ratchet, kdf_rk);
+//!
+//! // Each message carries its sender's ratchet public key, and each new
+//! // key received advances the receiver's ratchet.
+//! let (bob_receiving, bob_sending) = bob.step(alice.public_key(), OsRng)?;
+//! assert_eq!(alice_sending, bob_receiving);
+//! let (alice_receiving, _) = alice.step(bob.public_key(), OsRng)?;
+//! assert_eq!(alice_receiving, bob_sending);
+//! # Ok::<(), x25519_dalek::Error>(())
+//! ```
+//!
+//! The symmetric-key ratchet, which turns chain keys into message keys, is
+//! left to the caller.
+
+use rand_core::{CryptoRng, RngCore};
+use zeroize::Zeroizing;
+
+use crate::error::Error;
+use crate::x25519::{PublicKey, SharedSecret, StaticSecret};
+
+/// The length of root and chain keys.
+pub const KEY_LEN: usize = 32;
+
+/// A root or chain key, which is wiped on drop.
+pub type Key = Zeroizing<[u8; KEY_LEN]>;
+
+/// The root KDF of the Double Ratchet, `KDF_RK`.
+pub trait RootKdf {
+    /// Derive a new root key and chain key from the current `root_key` and
+    /// the Diffie-Hellman output of a ratchet step.
+    fn derive(&self, root_key: &[u8; KEY_LEN], dh_output: &SharedSecret) -> (Key, Key);
+}
+
+impl<F> RootKdf for F
+where
+    F: Fn(&[u8; KEY_LEN], &SharedSecret) -> (Key, Key),
+{
+    fn derive(&self, root_key: &[u8; KEY_LEN], dh_output: &SharedSecret) -> (Key, Key) {
+        self(root_key, dh_output)
+    }
+}
+
+/// One party's state of the Diffie-Hellman ratchet: its ratchet key pair,
+/// the peer's latest ratchet public key, and the root key.
+///
+/// The secret key and root key are wiped on drop.
+pub struct DhRatchet<K: RootKdf> {
+    secret: StaticSecret,
+    public: PublicKey,
+    remote_public: Option<PublicKey>,
+    root_key: Key,
+    kdf: K,
+}
+
+impl<K: RootKdf> DhRatchet<K> {
+    /// Start the ratchet as the party sending the first message, from the
+    /// `shared_key` agreed with the peer and the peer's ratchet public key.
+    ///
+    /// Returns the ratchet and the first sending chain key.
+    pub fn initiator<T: RngCore + CryptoRng>(
+        shared_key: &[u8; KEY_LEN],
+        remote_public: &PublicKey,
+        kdf: K,
+        csprng: T,
+    ) -> Result<(Self, Key), Error> {
+        let mut ratchet =
+            DhRatchet::responder(shared_key, StaticSecret::random_from_rng(csprng), kdf);
+        ratchet.remote_public = Some(*remote_public);
+        let sending = ratchet.ratchet(remote_public)?;
+        Ok((ratchet, sending))
+    }
+
+    /// Start the ratchet as the party receiving the first message, from the
+    /// `shared_key` agreed with the peer and the ratchet key pair whose
+    /// public key the peer already knows.
+    pub fn responder(shared_key: &[u8; KEY_LEN], secret: StaticSecret, kdf: K) -> Self {
+        let public = PublicKey::from(&secret);
+        DhRatchet {
+            secret,
+            public,
+            remote_public: None,
+            root_key: Zeroizing::new(*shared_key),
+            kdf,
+        }
+    }
+
+    /// The current ratchet public key, to send in message headers.
+    pub fn public_key(&self) -> &PublicKey {
+        &self.public
+    }
+
+    /// The peer's latest ratchet public key, if any message has been received
+    /// from it.
+    pub fn remote_public_key(&self) -> Option<&PublicKey> {
+        self.remote_public.as_ref()
+    }
+
+    /// Advance the ratchet with the peer's new ratchet public key, replacing
+    /// the local ratchet key pair with one from `csprng`.
+    ///
+    /// Returns the new receiving chain key followed by the new sending chain
+    /// key.  This should be called only when a message header carries a
+    /// ratchet public key different from [`DhRatchet::remote_public_key`].
+    ///
+    /// Fails with [`Error::SmallOrderPoint`] if `remote_public` is a point of
+    /// small order, in which case the ratchet is unchanged.  Since the first
+    /// exchange would already fail for such a key, the second cannot.
+    pub fn step<T: RngCore + CryptoRng>(
+        &mut self,
+        remote_public: &PublicKey,
+        csprng: T,
+    ) -> Result<(Key, Key), Error> {
+        let receiving = self.ratchet(remote_public)?;
+        self.secret = StaticSecret::random_from_rng(csprng);
+        self.public = PublicKey::from(&self.secret);
+        self.remote_public = Some(*remote_public);
+        let sending = self.ratchet(remote_public)?;
+        Ok((receiving, sending))
+    }
+
+    /// Mix the Diffie-Hellman output with `remote_public` into the root key,
+    /// returning the new chain key.
+    fn ratchet(&mut self, remote_public: &PublicKey) -> Result<Key, Error> {
+        let dh_output = self.secret.diffie_hellman(remote_public);
+        if !dh_output.was_contributory() {
+            return Err(Error::SmallOrderPoint);
+        }
+        let (root_key, chain_key) = self.kdf.derive(&self.root_key, &dh_output);
+        self.root_key = root_key;
+        Ok(chain_key)
+    }
+}
@@ -0,0 +1,165 @@
+// -*- mode: rust; -*-
+//
+// This file is part of x25519-dalek.
+// Copyright (c) 2017-2021 isis lovecruft
+// Copyright (c) 2019-2021 DebugSteven
+// See LICENSE for licensing information.
+//
+// Authors:
+// - isis agora lovecruft <isis@patternsinthevoid.net>
+// - DebugSteven <debugsteven@gmail.com>
+
+//! Diffie-Hellman in bounded steps, for cooperative schedulers.
+//!
+//! On small microcontrollers a key agreement takes milliseconds, which a
+//! cooperative executor such as Embassy's cannot spend without yielding.
+//! A [`DhOperation`] runs the key agreement one step at a time, each of
+//! about the cost of one step of the Montgomery ladder, so that the task
+//! can yield between steps:
+//!
+//! ```
+//! use core::task::Poll;
+//! use x25519_dalek::{DhOperation, EphemeralSecret, PublicKey};
+//! # use rand_core::OsRng;
+//!
+//! # let alice_secret = EphemeralSecret::random_from_rng(OsRng);
+//! # let bob_public = PublicKey::from(&EphemeralSecret::random_from_rng(OsRng));
+//! let mut operation = DhOperation::new(alice_secret, &bob_public);
+//! let shared = loop {
+//!     if let Poll::Ready(shared) = operation.poll_step() {
+//!         break shared;
+//!     }
+//!     // Yield to the executor here, for example with
+//!     // `embassy_futures::yield_now().await`.
+//! };
+//! ```
+//!
+//! `curve25519-dalek` runs its ladder to completion, so the steps are those
+//! of the ladder of this crate, over the Fiat Crypto field arithmetic.  The
+//! number of steps is the same for every key agreement, [`DhOperation::STEPS`].
+
+use core::task::Poll;
+
+use curve25519_dalek::montgomery::MontgomeryPoint;
+use curve25519_dalek::scalar::clamp_integer;
+use subtle::Choice;
+use zeroize::Zeroize;
+
+use crate::field::{FieldElement, P_MINUS_2};
+use crate::ladder::Ladder;
+use crate::x25519::{EphemeralSecret, PublicKey, SharedSecret};
+
+#[cfg(feature = "reusable_secrets")]
+use crate::x25519::ReusableSecret;
+
+#[cfg(feature = "static_secrets")]
+use crate::x25519::StaticSecret;
+
+/// The number of steps of the ladder, one for each bit of a clamped scalar
+/// below the top one.
+const LADDER_STEPS: usize = 255;
+
+/// The number of steps of the final inversion, one for each byte of its
+/// exponent.
+const INVERSION_STEPS: usize = 32;
+
+/// A Diffie-Hellman key agreement which runs in bounded steps, as returned
+/// by [`DhOperation::new`].
+///
+/// The secret scalar and all intermediate values are wiped when the
+/// operation is dropped.
+#[derive(Zeroize)]
+#[zeroize(drop)]
+pub struct DhOperation {
+    /// The clamped secret scalar.
+    scalar: [u8; 32],
+    ladder: Ladder,
+    /// The number of steps run so far.
+    step: usize,
+    /// The projective coordinates of the result, once the ladder is done.
+    x: FieldElement,
+    z: FieldElement,
+    /// The inverse of `z`, as far as the steps of the inversion have
+    /// computed it.
+    z_inv: FieldElement,
+}
+
+impl DhOperation {
+    /// The number of calls to [`DhOperation::poll_step`] which complete a
+    /// key agreement, the last of which returns the shared secret.
+    pub const STEPS: usize = LADDER_STEPS + INVERSION_STEPS;
+
+    /// Start a Diffie-Hellman key agreement between `secret` and
+    /// `their_public` key, without running any of it.
+    ///
+    /// The result is the same as [`EphemeralSecret::diffie_hellman`].
+    pub fn new(secret: EphemeralSecret, their_public: &PublicKey) -> DhOperation {
+        DhOperation::from_bytes(&secret.0, their_public)
+    }
+
+    /// Start a Diffie-Hellman key agreement between `secret` and
+    /// `their_public` key, without running any of it.
+    ///
+    /// The result is the same as [`ReusableSecret::diffie_hellman`].
+    #[cfg(feature = "reusable_secrets")]
+    pub fn new_reusable(secret: &ReusableSecret, their_public: &PublicKey) -> DhOperation {
+        DhOperation::from_bytes(&secret.0, their_public)
+    }
+
+    /// Start a Diffie-Hellman key agreement between `secret` and
+    /// `their_public` key, without running any of it.
+    ///
+    /// The result is the same as [`StaticSecret::diffie_hellman`].
+    #[cfg(feature = "static_secrets")]
+    pub fn new_static(secret: &StaticSecret, their_public: &PublicKey) -> DhOperation {
+        DhOperation::from_bytes(secret.as_bytes(), their_public)
+    }
+
+    fn from_bytes(secret: &[u8; 32], their_public: &PublicKey) -> DhOperation {
+        let one = FieldElement::from_u32(1);
+        DhOperation {
+            scalar: clamp_integer(*secret),
+            ladder: Ladder::new(their_public.as_bytes()),
+            step: 0,
+            x: one,
+            z: one,
+            z_inv: one,
+        }
+    }
+
+    /// Run the next step of the key agreement, returning the shared secret
+    /// once the last one has been run.
+    ///
+    /// Each call does about the work of one step of the Montgomery ladder,
+    /// and [`DhOperation::STEPS`] calls complete the key agreement.  Calls
+    /// after that return the same shared secret again.
+    pub fn poll_step(&mut self) -> Poll<SharedSecret> {
+        if self.step < LADDER_STEPS {
+            let i = LADDER_STEPS - 1 - self.step;
+            self.ladder
+                .step(Choice::from((self.scalar[i >> 3] >> (i & 7)) & 1));
+            if self.step == LADDER_STEPS - 1 {
+                let (x, z) = self.ladder.finish();
+                self.x = x;
+                self.z = z;
+            }
+        } else if self.step < Self::STEPS {
+            // The inversion raises z to the power of p - 2, from the top
+            // byte of the exponent down, as `FieldElement::invert` does.
+            let byte = P_MINUS_2[Self::STEPS - 1 - self.step];
+            self.z_inv = self.z.pow_byte(&self.z_inv, byte);
+        }
+
+        if self.step < Self::STEPS {
+            self.step += 1;
+        }
+        if self.step < Self::STEPS {
+            return Poll::Pending;
+        }
+
+        // The point at infinity has Z = 0, whose inverse is 0, so it is
+        // encoded as zero as in `MontgomeryPoint::mul_clamped`.
+        let u = &self.x * &self.z_inv;
+        Poll::Ready(SharedSecret(MontgomeryPoint(u.to_bytes())))
+    }
+}
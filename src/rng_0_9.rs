@@ -0,0 +1,92 @@
+// -*- mode: rust; -*-
+//
+// This file is part of x25519-dalek.
+// Copyright (c) 2017-2021 isis lovecruft
+// Copyright (c) 2019-2021 DebugSteven
+// See LICENSE for licensing information.
+//
+// Authors:
+// - isis agora lovecruft <isis@patternsinthevoid.net>
+// - DebugSteven <debugsteven@gmail.com>
+
+//! Key generation with RNGs implementing the `rand_core` 0.9 traits.
+//!
+//! These mirror the `random_from_rng` and `try_random_from_rng` constructors,
+//! which take RNGs implementing the `rand_core` 0.6 traits.
+
+use rand_core_0_9::{CryptoRng, TryCryptoRng};
+
+use crate::error::Error;
+use crate::x25519::EphemeralSecret;
+
+#[cfg(feature = "reusable_secrets")]
+use crate::x25519::ReusableSecret;
+
+#[cfg(feature = "static_secrets")]
+use crate::x25519::StaticSecret;
+
+#[cfg(feature = "zeroize")]
+use zeroize::Zeroize;
+
+/// Fill 32 bytes from a `rand_core` 0.9 RNG.
+fn random_bytes<T: CryptoRng>(mut csprng: T) -> [u8; 32] {
+    let mut bytes = [0u8; 32];
+    csprng.fill_bytes(&mut bytes);
+    bytes
+}
+
+/// Fill 32 bytes from a fallible `rand_core` 0.9 RNG.
+fn try_random_bytes<T: TryCryptoRng>(mut csprng: T) -> Result<[u8; 32], Error> {
+    let mut bytes = [0u8; 32];
+    if csprng.try_fill_bytes(&mut bytes).is_err() {
+        // The RNG may have filled part of the buffer before failing.
+        #[cfg(feature = "zeroize")]
+        bytes.zeroize();
+        return Err(Error::Rng);
+    }
+    Ok(bytes)
+}
+
+impl EphemeralSecret {
+    /// Generate a new [`EphemeralSecret`] with the supplied `rand_core` 0.9 RNG.
+    pub fn random_from_rng_0_9<T: CryptoRng>(csprng: T) -> Self {
+        // The secret key is random bytes. Clamping is done later.
+        EphemeralSecret(random_bytes(csprng))
+    }
+
+    /// Generate a new [`EphemeralSecret`] with the supplied fallible
+    /// `rand_core` 0.9 RNG, returning an error if the RNG fails.
+    pub fn try_random_from_rng_0_9<T: TryCryptoRng>(csprng: T) -> Result<Self, Error> {
+        try_random_bytes(csprng).map(EphemeralSecret)
+    }
+}
+
+#[cfg(feature = "reusable_secrets")]
+impl ReusableSecret {
+    /// Generate a new [`ReusableSecret`] with the supplied `rand_core` 0.9 RNG.
+    pub fn random_from_rng_0_9<T: CryptoRng>(csprng: T) -> Self {
+        // The secret key is random bytes. Clamping is done later.
+        ReusableSecret(random_bytes(csprng))
+    }
+
+    /// Generate a new [`ReusableSecret`] with the supplied fallible
+    /// `rand_core` 0.9 RNG, returning an error if the RNG fails.
+    pub fn try_random_from_rng_0_9<T: TryCryptoRng>(csprng: T) -> Result<Self, Error> {
+        try_random_bytes(csprng).map(ReusableSecret)
+    }
+}
+
+#[cfg(feature = "static_secrets")]
+impl StaticSecret {
+    /// Generate a new [`StaticSecret`] with the supplied `rand_core` 0.9 RNG.
+    pub fn random_from_rng_0_9<T: CryptoRng>(csprng: T) -> Self {
+        // The secret key is random bytes. Clamping is done later.
+        StaticSecret::from(random_bytes(csprng))
+    }
+
+    /// Generate a new [`StaticSecret`] with the supplied fallible
+    /// `rand_core` 0.9 RNG, returning an error if the RNG fails.
+    pub fn try_random_from_rng_0_9<T: TryCryptoRng>(csprng: T) -> Result<Self, Error> {
+        try_random_bytes(csprng).map(StaticSecret::from)
+    }
+}
@@ -0,0 +1,127 @@
+// -*- mode: rust; -*-
+//
+// This file is part of x25519-dalek.
+// Copyright (c) 2017-2021 isis lovecruft
+// Copyright (c) 2019-2021 DebugSteven
+// See LICENSE for licensing information.
+//
+// Authors:
+// - isis agora lovecruft <isis@patternsinthevoid.net>
+// - DebugSteven <debugsteven@gmail.com>
+
+//! Diffie-Hellman with per-operation scalar blinding, as a countermeasure
+//! against power and electromagnetic side-channel analysis.
+//!
+//! For a peer public key `P` on the curve, X25519 computes `w * 8P`, where
+//! `w` is the clamped secret scalar divided by the cofactor.  The blinded
+//! variants instead draw a fresh random scalar `r` for every operation and
+//! compute `(w / r) * (r * 8P)`, so that neither ladder processes the secret
+//! scalar itself.  The result is identical to the unblinded computation.
+//!
+//! This also randomizes the input point of the ladder which depends on the
+//! secret: `r * 8P` is a fresh, unpredictable point for every operation, so
+//! an attacker who chooses `P` cannot predict the ladder's intermediate
+//! values.  Randomizing the projective coordinates of the input point within
+//! a single ladder is left to the `projective_randomization` feature, since
+//! the ladder of `curve25519-dalek` which these variants use keeps its
+//! projective representation internal.
+//!
+//! Points on the twist of Curve25519 have no such decomposition, so they
+//! are rejected with [`Error::TwistPoint`] rather than multiplied without
+//! blinding, which would let a peer switch the countermeasure off.  Honest
+//! peers never send such points.
+
+use curve25519_dalek::montgomery::MontgomeryPoint;
+use curve25519_dalek::scalar::Scalar;
+use rand_core::{CryptoRng, RngCore};
+use zeroize::Zeroizing;
+
+use crate::error::Error;
+use crate::x25519::{cofactor_free_scalar, EphemeralSecret, PublicKey, SharedSecret};
+
+#[cfg(feature = "reusable_secrets")]
+use crate::x25519::ReusableSecret;
+
+#[cfg(feature = "static_secrets")]
+use crate::x25519::StaticSecret;
+
+/// Multiply `point` by the clamped `secret`, blinding the scalar with
+/// randomness from `csprng`.
+fn blinded_mul<T: RngCore + CryptoRng>(
+    point: &MontgomeryPoint,
+    secret: &[u8; 32],
+    mut csprng: T,
+) -> Result<MontgomeryPoint, Error> {
+    if point.to_edwards(0).is_none() {
+        return Err(Error::TwistPoint);
+    }
+
+    let blinding = loop {
+        let mut bytes = Zeroizing::new([0u8; 64]);
+        csprng.fill_bytes(&mut bytes[..]);
+        let blinding = Zeroizing::new(Scalar::from_bytes_mod_order_wide(&bytes));
+        if *blinding != Scalar::ZERO {
+            break blinding;
+        }
+    };
+    let unblinding = Zeroizing::new(cofactor_free_scalar(secret) * blinding.invert());
+
+    // Clearing the cofactor puts the point in the prime-order subgroup,
+    // where scalars may be reduced modulo the group order.
+    let cleared = point * Scalar::from(8u8);
+    let masked = cleared * *blinding;
+    Ok(masked * *unblinding)
+}
+
+impl EphemeralSecret {
+    /// Perform a Diffie-Hellman key agreement between `self` and
+    /// `their_public` key, blinding the secret scalar with randomness from
+    /// `csprng`.
+    ///
+    /// The result is the same as [`EphemeralSecret::diffie_hellman`], except that
+    /// public keys on the twist of the curve are rejected with
+    /// [`Error::TwistPoint`].
+    pub fn diffie_hellman_blinded<T: RngCore + CryptoRng>(
+        self,
+        their_public: &PublicKey,
+        csprng: T,
+    ) -> Result<SharedSecret, Error> {
+        blinded_mul(&their_public.0, &self.0, csprng).map(SharedSecret)
+    }
+}
+
+#[cfg(feature = "reusable_secrets")]
+impl ReusableSecret {
+    /// Perform a Diffie-Hellman key agreement between `self` and
+    /// `their_public` key, blinding the secret scalar with randomness from
+    /// `csprng`.
+    ///
+    /// The result is the same as [`ReusableSecret::diffie_hellman`], except that
+    /// public keys on the twist of the curve are rejected with
+    /// [`Error::TwistPoint`].
+    pub fn diffie_hellman_blinded<T: RngCore + CryptoRng>(
+        &self,
+        their_public: &PublicKey,
+        csprng: T,
+    ) -> Result<SharedSecret, Error> {
+        blinded_mul(&their_public.0, &self.0, csprng).map(SharedSecret)
+    }
+}
+
+#[cfg(feature = "static_secrets")]
+impl StaticSecret {
+    /// Perform a Diffie-Hellman key agreement between `self` and
+    /// `their_public` key, blinding the secret scalar with randomness from
+    /// `csprng`.
+    ///
+    /// The result is the same as [`StaticSecret::diffie_hellman`], except that
+    /// public keys on the twist of the curve are rejected with
+    /// [`Error::TwistPoint`].
+    pub fn diffie_hellman_blinded<T: RngCore + CryptoRng>(
+        &self,
+        their_public: &PublicKey,
+        csprng: T,
+    ) -> Result<SharedSecret, Error> {
+        blinded_mul(&their_public.0, self.as_bytes(), csprng).map(SharedSecret)
+    }
+}
@@ -0,0 +1,65 @@
+// -*- mode: rust; -*-
+//
+// This file is part of x25519-dalek.
+// Copyright (c) 2017-2021 isis lovecruft
+// Copyright (c) 2019-2021 DebugSteven
+// See LICENSE for licensing information.
+//
+// Authors:
+// - isis agora lovecruft <isis@patternsinthevoid.net>
+// - DebugSteven <debugsteven@gmail.com>
+
+//! X25519 on raw bytes, with the semantics of NaCl and libsodium's
+//! `crypto_scalarmult` and `crypto_scalarmult_base`.
+//!
+//! These exist for ports of C code written against that API, and need no
+//! dependencies beyond those of the crate itself.  The typed API of this crate
+//! should be preferred otherwise.
+//!
+//! ```
+//! use x25519_dalek::scalarmult::{scalarmult, scalarmult_base};
+//!
+//! let alice = [0x11u8; 32];
+//! let bob = [0x22u8; 32];
+//! let shared = scalarmult(&alice, &scalarmult_base(&bob))?;
+//! assert_eq!(*shared, *scalarmult(&bob, &scalarmult_base(&alice))?);
+//! # Ok::<(), x25519_dalek::Error>(())
+//! ```
+
+use zeroize::Zeroizing;
+
+use curve25519_dalek::MontgomeryPoint;
+
+use crate::error::Error;
+use crate::x25519::{mul_base_clamped, SharedSecret};
+
+/// The length of a scalar, `crypto_scalarmult_SCALARBYTES`.
+pub const SCALAR_LEN: usize = 32;
+
+/// The length of a point, `crypto_scalarmult_BYTES`.
+pub const POINT_LEN: usize = 32;
+
+/// Multiply the point `p` by the clamped scalar `n`, as libsodium's
+/// `crypto_scalarmult`.
+///
+/// As with libsodium, an all-zero result, from a point `p` of small order, is
+/// rejected.
+pub fn scalarmult(
+    n: &[u8; SCALAR_LEN],
+    p: &[u8; POINT_LEN],
+) -> Result<Zeroizing<[u8; POINT_LEN]>, Error> {
+    let q = SharedSecret(MontgomeryPoint(*p).mul_clamped(*n));
+    if !q.was_contributory() {
+        return Err(Error::SmallOrderPoint);
+    }
+    Ok(Zeroizing::new(q.to_bytes()))
+}
+
+/// Multiply the basepoint by the clamped scalar `n`, as libsodium's
+/// `crypto_scalarmult_base`, giving the public key for the secret key `n`.
+///
+/// Unlike [`scalarmult`], this cannot fail: a clamped scalar times the
+/// basepoint is never zero.
+pub fn scalarmult_base(n: &[u8; SCALAR_LEN]) -> [u8; POINT_LEN] {
+    mul_base_clamped(*n).to_bytes()
+}
@@ -0,0 +1,60 @@
+// -*- mode: rust; -*-
+//
+// This file is part of x25519-dalek.
+// Copyright (c) 2017-2021 isis lovecruft
+// Copyright (c) 2019-2021 DebugSteven
+// See LICENSE for licensing information.
+//
+// Authors:
+// - isis agora lovecruft <isis@patternsinthevoid.net>
+// - DebugSteven <debugsteven@gmail.com>
+
+//! A known-answer self-test, for products which must check their
+//! cryptographic functions at start-up.
+
+use core::fmt;
+
+use curve25519_dalek::MontgomeryPoint;
+
+use crate::test_vectors::RFC7748_DIFFIE_HELLMAN;
+use crate::x25519::mul_base_clamped;
+
+/// Errors which may occur in [`self_test`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SelfTestError {
+    /// A public key was not the one expected for its secret key.
+    PublicKey,
+    /// A shared secret was not the one expected.
+    DiffieHellman,
+}
+
+impl fmt::Display for SelfTestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SelfTestError::PublicKey => f.write_str("public key self-test failed"),
+            SelfTestError::DiffieHellman => f.write_str("Diffie-Hellman self-test failed"),
+        }
+    }
+}
+
+/// Check public key derivation and Diffie-Hellman against the exchange of
+/// RFC7748 section 6.1, through the same code as the secret types of this
+/// crate.
+///
+/// This takes two public key derivations and two key agreements.  On
+/// failure, the implementation must not be used.
+pub fn self_test() -> Result<(), SelfTestError> {
+    let v = RFC7748_DIFFIE_HELLMAN;
+
+    if mul_base_clamped(v.alice_private).0 != v.alice_public
+        || mul_base_clamped(v.bob_private).0 != v.bob_public
+    {
+        return Err(SelfTestError::PublicKey);
+    }
+    if MontgomeryPoint(v.bob_public).mul_clamped(v.alice_private).0 != v.shared
+        || MontgomeryPoint(v.alice_public).mul_clamped(v.bob_private).0 != v.shared
+    {
+        return Err(SelfTestError::DiffieHellman);
+    }
+    Ok(())
+}
@@ -0,0 +1,151 @@
+// -*- mode: rust; -*-
+//
+// This file is part of x25519-dalek.
+// Copyright (c) 2017-2021 isis lovecruft
+// Copyright (c) 2019-2021 DebugSteven
+// See LICENSE for licensing information.
+//
+// Authors:
+// - isis agora lovecruft <isis@patternsinthevoid.net>
+// - DebugSteven <debugsteven@gmail.com>
+
+//! A bounded cache of the shared secrets of a static secret key with its
+//! peers, evicting the least recently used.
+
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use core::num::NonZeroUsize;
+
+use crate::x25519::{PublicKey, SharedSecret, StaticSecret};
+
+/// A cached shared secret and the tick at which it was last used.
+///
+/// The shared secret is boxed so that it stays put while the map moves its
+/// entries around, which would otherwise leave copies behind to be freed
+/// without being wiped.
+struct Entry {
+    shared: Box<SharedSecret>,
+    last_used: u64,
+}
+
+/// A cache of the [`SharedSecret`]s of a [`StaticSecret`] with the
+/// [`PublicKey`]s of its peers, so that a server whose peers reconnect with
+/// the same static keys computes each key agreement only once.
+///
+/// At most `capacity` shared secrets are kept, and when a new one is added
+/// to a full cache the least recently used is evicted.  Shared secrets are
+/// wiped when they are evicted, removed, or dropped with the cache, as is
+/// the secret key.
+///
+/// Whether a shared secret is cached shows in the time taken to get it, so
+/// an observer who can time the server learns whether a public key was seen
+/// recently.
+///
+/// ```
+/// use core::num::NonZeroUsize;
+/// use x25519_dalek::{PublicKey, SessionCache, StaticSecret};
+///
+/// let server = StaticSecret::from([0x42; 32]);
+/// let client = StaticSecret::from([0x24; 32]);
+/// let client_public = PublicKey::from(&client);
+///
+/// let expected = server.diffie_hellman(&client_public);
+/// let mut cache = SessionCache::new(server, NonZeroUsize::new(1024).unwrap());
+/// assert_eq!(cache.diffie_hellman(&client_public).as_bytes(), expected.as_bytes());
+/// // Computed once, then found in the cache.
+/// assert_eq!(cache.diffie_hellman(&client_public).as_bytes(), expected.as_bytes());
+/// assert_eq!(cache.len(), 1);
+/// ```
+pub struct SessionCache {
+    secret: StaticSecret,
+    capacity: NonZeroUsize,
+    entries: BTreeMap<[u8; 32], Entry>,
+    recency: BTreeMap<u64, [u8; 32]>,
+    tick: u64,
+}
+
+impl SessionCache {
+    /// Create an empty cache for the shared secrets of `secret`, holding at
+    /// most `capacity` of them.
+    pub fn new(secret: StaticSecret, capacity: NonZeroUsize) -> SessionCache {
+        SessionCache {
+            secret,
+            capacity,
+            entries: BTreeMap::new(),
+            recency: BTreeMap::new(),
+            tick: 0,
+        }
+    }
+
+    /// The shared secret with `their_public`, from the cache if it is there,
+    /// or else computed and added to the cache, evicting the least recently
+    /// used shared secret if the cache is full.
+    pub fn diffie_hellman(&mut self, their_public: &PublicKey) -> &SharedSecret {
+        let key = their_public.to_bytes();
+        self.tick += 1;
+
+        if let Some(entry) = self.entries.get_mut(&key) {
+            self.recency.remove(&entry.last_used);
+            entry.last_used = self.tick;
+        } else {
+            if self.entries.len() == self.capacity.get() {
+                if let Some((&oldest_used, &oldest)) = self.recency.iter().next() {
+                    self.recency.remove(&oldest_used);
+                    self.entries.remove(&oldest);
+                }
+            }
+            self.entries.insert(
+                key,
+                Entry {
+                    shared: Box::new(self.secret.diffie_hellman(their_public)),
+                    last_used: self.tick,
+                },
+            );
+        }
+        self.recency.insert(self.tick, key);
+        &self.entries[&key].shared
+    }
+
+    /// The cached shared secret with `their_public`, if there is one.
+    ///
+    /// This does not count as a use for eviction.
+    pub fn get(&self, their_public: &PublicKey) -> Option<&SharedSecret> {
+        self.entries
+            .get(their_public.as_bytes())
+            .map(|entry| &*entry.shared)
+    }
+
+    /// Remove the shared secret with `their_public` from the cache, for
+    /// example when the peer's key is revoked.  Returns whether it was
+    /// cached.
+    pub fn remove(&mut self, their_public: &PublicKey) -> bool {
+        match self.entries.remove(their_public.as_bytes()) {
+            Some(entry) => {
+                self.recency.remove(&entry.last_used);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Remove all shared secrets from the cache.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.recency.clear();
+    }
+
+    /// The number of cached shared secrets.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether no shared secrets are cached.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The maximum number of cached shared secrets.
+    pub fn capacity(&self) -> NonZeroUsize {
+        self.capacity
+    }
+}
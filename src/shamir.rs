@@ -0,0 +1,206 @@
+// -*- mode: rust; -*-
+//
+// This file is part of x25519-dalek.
+// Copyright (c) 2017-2021 isis lovecruft
+// Copyright (c) 2019-2021 DebugSteven
+// See LICENSE for licensing information.
+//
+// Authors:
+// - isis agora lovecruft <isis@patternsinthevoid.net>
+// - DebugSteven <debugsteven@gmail.com>
+
+//! Shamir secret sharing of [`StaticSecret`]s over GF(2^8).
+//!
+//! Each byte of the secret key is shared independently with a random
+//! polynomial of degree `t - 1` over GF(2^8), using the AES reduction
+//! polynomial `x^8 + x^4 + x^3 + x + 1`.  Any `t` of the `n` shares recover
+//! the secret key, while fewer reveal nothing about it:
+//!
+#![cfg_attr(feature = "getrandom", doc = "```")]
+#![cfg_attr(not(feature = "getrandom"), doc = "```ignore")]
+//! use rand_core::OsRng;
+//! use x25519_dalek::StaticSecret;
+//!
+//! let secret = StaticSecret::random();
+//! let shares = secret.split(5, 3, OsRng)?;
+//!
+//! let recovered = StaticSecret::recover(&shares[1..4])?;
+//! assert_eq!(recovered.to_bytes(), secret.to_bytes());
+//! # Ok::<(), x25519_dalek::Error>(())
+//! ```
+
+use alloc::vec::Vec;
+use core::fmt;
+
+use rand_core::{CryptoRng, RngCore};
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::x25519::StaticSecret;
+
+/// The length of an encoded [`Share`].
+pub const SHARE_LEN: usize = 33;
+
+/// Errors which may occur when splitting or recovering a secret key.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// The threshold is zero or greater than the number of shares.
+    InvalidThreshold,
+    /// No shares were given.
+    NoShares,
+    /// A share has index zero, which would reveal the secret key itself.
+    InvalidShareIndex,
+    /// Two shares have the same index.
+    DuplicateShare,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::InvalidThreshold => {
+                f.write_str("threshold must be between 1 and the number of shares")
+            }
+            Error::NoShares => f.write_str("no shares given"),
+            Error::InvalidShareIndex => f.write_str("share index must not be zero"),
+            Error::DuplicateShare => f.write_str("shares have duplicate indices"),
+        }
+    }
+}
+
+/// One share of a secret key: the evaluation of the sharing polynomials at a
+/// non-zero index.
+///
+/// The share is wiped on drop.
+#[derive(Clone, Zeroize)]
+#[zeroize(drop)]
+pub struct Share {
+    index: u8,
+    value: [u8; 32],
+}
+
+impl Share {
+    /// The index at which this share was evaluated, between 1 and 255.
+    pub fn index(&self) -> u8 {
+        self.index
+    }
+
+    /// Encode this share as its index followed by its 32-byte value.
+    pub fn to_bytes(&self) -> [u8; SHARE_LEN] {
+        let mut bytes = [0u8; SHARE_LEN];
+        bytes[0] = self.index;
+        bytes[1..].copy_from_slice(&self.value);
+        bytes
+    }
+
+    /// Decode a share produced by [`Share::to_bytes`].
+    pub fn from_bytes(bytes: &[u8; SHARE_LEN]) -> Result<Share, crate::Error> {
+        if bytes[0] == 0 {
+            return Err(Error::InvalidShareIndex.into());
+        }
+        let mut value = [0u8; 32];
+        value.copy_from_slice(&bytes[1..]);
+        Ok(Share {
+            index: bytes[0],
+            value,
+        })
+    }
+}
+
+impl StaticSecret {
+    /// Split this secret key into `n` shares, any `threshold` of which
+    /// recover it with [`StaticSecret::recover`].
+    pub fn split<T: RngCore + CryptoRng>(
+        &self,
+        n: u8,
+        threshold: u8,
+        mut csprng: T,
+    ) -> Result<Vec<Share>, crate::Error> {
+        if threshold == 0 || threshold > n {
+            return Err(Error::InvalidThreshold.into());
+        }
+
+        // The coefficients of x, x^2, ..., x^(t-1); the constant term is the
+        // secret key itself.
+        let mut coefficients = Zeroizing::new(Vec::with_capacity(threshold as usize - 1));
+        for _ in 1..threshold {
+            let mut coefficient = [0u8; 32];
+            csprng.fill_bytes(&mut coefficient);
+            coefficients.push(coefficient);
+        }
+
+        let secret = self.as_bytes();
+        let shares = (1..=n)
+            .map(|index| {
+                let mut value = [0u8; 32];
+                for (k, y) in value.iter_mut().enumerate() {
+                    // Horner's rule, from the highest degree coefficient down.
+                    for coefficient in coefficients.iter().rev() {
+                        *y = gf256_mul(*y, index) ^ coefficient[k];
+                    }
+                    *y = gf256_mul(*y, index) ^ secret[k];
+                }
+                Share { index, value }
+            })
+            .collect();
+        Ok(shares)
+    }
+
+    /// Recover a secret key from at least as many shares as the threshold it
+    /// was split with.
+    ///
+    /// Recovering from fewer shares than the threshold does not fail, but
+    /// yields an unrelated key.
+    pub fn recover(shares: &[Share]) -> Result<StaticSecret, crate::Error> {
+        if shares.is_empty() {
+            return Err(Error::NoShares.into());
+        }
+        for (i, share) in shares.iter().enumerate() {
+            if share.index == 0 {
+                return Err(Error::InvalidShareIndex.into());
+            }
+            if shares[..i].iter().any(|other| other.index == share.index) {
+                return Err(Error::DuplicateShare.into());
+            }
+        }
+
+        let mut secret = Zeroizing::new([0u8; 32]);
+        for share in shares {
+            // The Lagrange basis polynomial for this share, evaluated at 0.
+            // Subtraction in GF(2^8) is XOR.
+            let mut numerator = 1u8;
+            let mut denominator = 1u8;
+            for other in shares.iter().filter(|other| other.index != share.index) {
+                numerator = gf256_mul(numerator, other.index);
+                denominator = gf256_mul(denominator, other.index ^ share.index);
+            }
+            let basis = gf256_mul(numerator, gf256_inv(denominator));
+
+            for (s, y) in secret.iter_mut().zip(share.value.iter()) {
+                *s ^= gf256_mul(basis, *y);
+            }
+        }
+        Ok(StaticSecret::from(*secret))
+    }
+}
+
+/// Multiply in GF(2^8) modulo `x^8 + x^4 + x^3 + x + 1`, in constant time.
+fn gf256_mul(mut a: u8, mut b: u8) -> u8 {
+    let mut product = 0u8;
+    for _ in 0..8 {
+        product ^= a & 0u8.wrapping_sub(b & 1);
+        let carry = 0u8.wrapping_sub(a >> 7);
+        a = (a << 1) ^ (carry & 0x1b);
+        b >>= 1;
+    }
+    product
+}
+
+/// Invert a non-zero element of GF(2^8), as `a^254`.
+fn gf256_inv(a: u8) -> u8 {
+    let mut result = 1u8;
+    let mut power = a;
+    for _ in 0..7 {
+        power = gf256_mul(power, power);
+        result = gf256_mul(result, power);
+    }
+    result
+}
@@ -0,0 +1,184 @@
+// -*- mode: rust; -*-
+//
+// This file is part of x25519-dalek.
+// Copyright (c) 2017-2021 isis lovecruft
+// Copyright (c) 2019-2021 DebugSteven
+// See LICENSE for licensing information.
+//
+// Authors:
+// - isis agora lovecruft <isis@patternsinthevoid.net>
+// - DebugSteven <debugsteven@gmail.com>
+
+//! Hierarchical deterministic derivation of X25519 secret keys, as specified
+//! for the `curve25519` curve in
+//! [SLIP-0010](https://github.com/satoshilabs/slips/blob/master/slip-0010.md).
+//!
+//! Only hardened derivation is defined for Curve25519, so every component of
+//! a derivation path must be hardened:
+//!
+//! ```
+//! use x25519_dalek::PublicKey;
+//! use x25519_dalek::slip10::ExtendedSecret;
+//!
+//! let seed = [0x42u8; 32];
+//! let key = ExtendedSecret::from_seed_and_path(&seed, "m/44'/0'/1'")?;
+//! let public = PublicKey::from(key.secret());
+//!
+//! // The same key can be reached one step at a time.
+//! let master = ExtendedSecret::from_seed(&seed)?;
+//! let child = master.derive_child(44 | x25519_dalek::slip10::HARDENED_OFFSET)?;
+//! let child = child.derive_path("m/0'/1'")?;
+//! assert_eq!(PublicKey::from(child.secret()), public);
+//! # Ok::<(), x25519_dalek::Error>(())
+//! ```
+
+use core::fmt;
+
+use hmac::{Hmac, Mac};
+use sha2::Sha512;
+use zeroize::Zeroizing;
+
+use crate::x25519::StaticSecret;
+
+/// The HMAC key used to derive the master key from a seed.
+const MASTER_HMAC_KEY: &[u8] = b"curve25519 seed";
+
+/// The offset added to an index to mark it as hardened.
+pub const HARDENED_OFFSET: u32 = 0x8000_0000;
+
+/// The shortest seed accepted, in bytes.
+const MIN_SEED_LEN: usize = 16;
+
+/// The longest seed accepted, in bytes.
+const MAX_SEED_LEN: usize = 64;
+
+/// Errors which may occur during hierarchical key derivation.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// The seed is shorter than 16 or longer than 64 bytes.
+    InvalidSeedLength,
+    /// The derivation path is not of the form `m/a'/b'/...`.
+    InvalidPath,
+    /// A derivation index is not hardened.
+    NonHardenedIndex,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::InvalidSeedLength => f.write_str("seed must be between 16 and 64 bytes"),
+            Error::InvalidPath => f.write_str("invalid derivation path"),
+            Error::NonHardenedIndex => f.write_str("curve25519 only supports hardened derivation"),
+        }
+    }
+}
+
+/// A secret key together with the chain code used to derive its children.
+///
+/// Both the secret key and the chain code are wiped on drop.
+#[derive(Clone)]
+pub struct ExtendedSecret {
+    secret: StaticSecret,
+    chain_code: Zeroizing<[u8; 32]>,
+}
+
+impl ExtendedSecret {
+    /// Derive the master key from a seed of between 16 and 64 bytes.
+    pub fn from_seed(seed: &[u8]) -> Result<ExtendedSecret, crate::Error> {
+        if !(MIN_SEED_LEN..=MAX_SEED_LEN).contains(&seed.len()) {
+            return Err(Error::InvalidSeedLength.into());
+        }
+        Ok(ExtendedSecret::from_hmac(MASTER_HMAC_KEY, &[seed]))
+    }
+
+    /// Derive the master key from a seed, followed by the key at `path`.
+    ///
+    /// This is equivalent to calling [`ExtendedSecret::from_seed`] followed
+    /// by [`ExtendedSecret::derive_path`].
+    pub fn from_seed_and_path(seed: &[u8], path: &str) -> Result<ExtendedSecret, crate::Error> {
+        ExtendedSecret::from_seed(seed)?.derive_path(path)
+    }
+
+    /// Derive the child key at `index`, which must include
+    /// [`HARDENED_OFFSET`].
+    pub fn derive_child(&self, index: u32) -> Result<ExtendedSecret, crate::Error> {
+        if index < HARDENED_OFFSET {
+            return Err(Error::NonHardenedIndex.into());
+        }
+        Ok(ExtendedSecret::from_hmac(
+            &self.chain_code[..],
+            &[&[0x00], self.secret.as_bytes(), &index.to_be_bytes()],
+        ))
+    }
+
+    /// Derive the descendant key at `path`, relative to this key.
+    ///
+    /// The path is written as `m/44'/0'/1'`, where `m` stands for this key.
+    /// Hardened components may be marked with `'`, `h` or `H`, and must not
+    /// exceed `2^31 - 1`.
+    pub fn derive_path(&self, path: &str) -> Result<ExtendedSecret, crate::Error> {
+        let mut components = path.split('/');
+        if components.next() != Some("m") {
+            return Err(Error::InvalidPath.into());
+        }
+
+        let mut key = self.clone();
+        for component in components {
+            key = key.derive_child(parse_component(component)?)?;
+        }
+        Ok(key)
+    }
+
+    /// The secret key.
+    pub fn secret(&self) -> &StaticSecret {
+        &self.secret
+    }
+
+    /// The chain code.
+    pub fn chain_code(&self) -> &[u8; 32] {
+        &self.chain_code
+    }
+
+    /// Convert this extended key into its secret key, discarding the chain
+    /// code.
+    pub fn into_secret(self) -> StaticSecret {
+        self.secret
+    }
+
+    /// Split `HMAC-SHA512(key, data)` into a secret key and chain code.
+    fn from_hmac(key: &[u8], data: &[&[u8]]) -> ExtendedSecret {
+        let mut mac = Hmac::<Sha512>::new_from_slice(key).expect("HMAC accepts keys of any length");
+        for part in data {
+            mac.update(part);
+        }
+        let output = Zeroizing::new(<[u8; 64]>::from(mac.finalize().into_bytes()));
+
+        let mut secret = Zeroizing::new([0u8; 32]);
+        let mut chain_code = Zeroizing::new([0u8; 32]);
+        secret.copy_from_slice(&output[..32]);
+        chain_code.copy_from_slice(&output[32..]);
+
+        ExtendedSecret {
+            secret: StaticSecret::from(*secret),
+            chain_code,
+        }
+    }
+}
+
+/// Parse a single hardened path component, such as `44'`, into an index.
+fn parse_component(component: &str) -> Result<u32, Error> {
+    let (index, hardened) = match component.strip_suffix(|c| matches!(c, '\'' | 'h' | 'H')) {
+        Some(index) => (index, true),
+        None => (component, false),
+    };
+    if index.is_empty() || !index.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(Error::InvalidPath);
+    }
+    if !hardened {
+        return Err(Error::NonHardenedIndex);
+    }
+    match index.parse::<u32>() {
+        Ok(index) if index < HARDENED_OFFSET => Ok(index | HARDENED_OFFSET),
+        _ => Err(Error::InvalidPath),
+    }
+}
@@ -0,0 +1,292 @@
+// -*- mode: rust; -*-
+//
+// This file is part of x25519-dalek.
+// Copyright (c) 2017-2021 isis lovecruft
+// Copyright (c) 2019-2021 DebugSteven
+// See LICENSE for licensing information.
+//
+// Authors:
+// - isis agora lovecruft <isis@patternsinthevoid.net>
+// - DebugSteven <debugsteven@gmail.com>
+
+//! Public-key authenticated encryption compatible with NaCl and libsodium's
+//! `crypto_box` (`crypto_box_curve25519xsalsa20poly1305`).
+//!
+//! The box key is HSalsa20 of the X25519 shared secret, as computed by
+//! `crypto_box_beforenm`, and messages are encrypted with XSalsa20-Poly1305.
+//! [`seal`] and [`open`] produce and consume the same bytes as libsodium's
+//! `crypto_box_easy` and `crypto_box_open_easy`: the 16-byte Poly1305 tag
+//! followed by the ciphertext.
+//!
+#![cfg_attr(feature = "getrandom", doc = "```")]
+#![cfg_attr(not(feature = "getrandom"), doc = "```ignore")]
+//! use rand_core::{OsRng, RngCore};
+//! use x25519_dalek::{sodium, PublicKey, StaticSecret};
+//!
+//! let alice = StaticSecret::random();
+//! let bob = StaticSecret::random();
+//!
+//! let mut nonce = [0u8; sodium::NONCE_LEN];
+//! OsRng.fill_bytes(&mut nonce);
+//! let boxed = sodium::seal(b"hello", &nonce, &PublicKey::from(&bob), &alice)?;
+//! let opened = sodium::open(&boxed, &nonce, &PublicKey::from(&alice), &bob)?;
+//! assert_eq!(opened, b"hello");
+//! # Ok::<(), x25519_dalek::Error>(())
+//! ```
+//!
+//! As with libsodium, a nonce must never be reused for the same pair of
+//! keys.
+//!
+//! [`box_seal`] and [`box_seal_open`] implement libsodium's anonymous sealed
+//! boxes, `crypto_box_seal` and `crypto_box_seal_open`: the sender boxes the
+//! message with a fresh ephemeral key, and the nonce is the BLAKE2b-192 hash
+//! of the ephemeral and recipient public keys.
+//!
+//! [`kx_client_session_keys`] and [`kx_server_session_keys`] implement
+//! libsodium's `crypto_kx` key exchange, deriving a pair of session keys from
+//! BLAKE2b-512 of the shared secret and both public keys.
+
+use alloc::vec::Vec;
+use core::fmt;
+
+use blake2::digest::consts::U24;
+use blake2::{Blake2b, Blake2b512, Digest};
+use crypto_secretbox::aead::generic_array::GenericArray;
+use crypto_secretbox::{AeadInPlace, KeyInit, XSalsa20Poly1305};
+use rand_core::{CryptoRng, RngCore};
+use salsa20::cipher::consts::U10;
+use salsa20::hsalsa;
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::x25519::{EphemeralSecret, PublicKey, SharedSecret, StaticSecret};
+
+/// The length of a `crypto_box` key, `crypto_box_BEFORENMBYTES`.
+pub const KEY_LEN: usize = 32;
+
+/// The length of a `crypto_box` nonce, `crypto_box_NONCEBYTES`.
+pub const NONCE_LEN: usize = 24;
+
+/// The length of a `crypto_box` tag, `crypto_box_MACBYTES`.
+pub const MAC_LEN: usize = 16;
+
+/// The overhead of a sealed box, `crypto_box_SEALBYTES`: the ephemeral public
+/// key and the tag.
+pub const SEAL_LEN: usize = 32 + MAC_LEN;
+
+/// The length of each `crypto_kx` session key,
+/// `crypto_kx_SESSIONKEYBYTES`.
+pub const SESSION_KEY_LEN: usize = 32;
+
+/// Errors which may occur when sealing or opening a box.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// The box is truncated, or does not decrypt under the keys and nonce.
+    InvalidCiphertext,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::InvalidCiphertext => f.write_str("box could not be opened"),
+        }
+    }
+}
+
+/// Compute the box key shared between `secret` and `their_public`, as
+/// libsodium's `crypto_box_beforenm`.
+pub fn beforenm(
+    their_public: &PublicKey,
+    secret: &StaticSecret,
+) -> Result<Zeroizing<[u8; KEY_LEN]>, crate::Error> {
+    box_key(secret.diffie_hellman(their_public))
+}
+
+/// Derive the box key from an X25519 shared secret with HSalsa20.
+fn box_key(shared: SharedSecret) -> Result<Zeroizing<[u8; KEY_LEN]>, crate::Error> {
+    if !shared.was_contributory() {
+        return Err(crate::Error::SmallOrderPoint);
+    }
+    let mut key = hsalsa::<U10>(
+        GenericArray::from_slice(shared.as_bytes()),
+        &GenericArray::default(),
+    );
+    let mut bytes = Zeroizing::new([0u8; KEY_LEN]);
+    bytes.copy_from_slice(&key);
+    key.as_mut_slice().zeroize();
+    Ok(bytes)
+}
+
+/// Encrypt and authenticate `plaintext` from `secret` to `their_public`, as
+/// libsodium's `crypto_box_easy`.
+pub fn seal(
+    plaintext: &[u8],
+    nonce: &[u8; NONCE_LEN],
+    their_public: &PublicKey,
+    secret: &StaticSecret,
+) -> Result<Vec<u8>, crate::Error> {
+    let key = beforenm(their_public, secret)?;
+    let mut boxed = Vec::with_capacity(MAC_LEN + plaintext.len());
+    seal_into(&mut boxed, plaintext, nonce, &key);
+    Ok(boxed)
+}
+
+/// Verify and decrypt a box from `their_public` to `secret`, as libsodium's
+/// `crypto_box_open_easy`.
+pub fn open(
+    boxed: &[u8],
+    nonce: &[u8; NONCE_LEN],
+    their_public: &PublicKey,
+    secret: &StaticSecret,
+) -> Result<Vec<u8>, crate::Error> {
+    if boxed.len() < MAC_LEN {
+        return Err(Error::InvalidCiphertext.into());
+    }
+    let key = beforenm(their_public, secret)?;
+    Ok(open_with(boxed, nonce, &key)?)
+}
+
+/// Anonymously encrypt `plaintext` to `recipient`, using an ephemeral key
+/// from `csprng`, as libsodium's `crypto_box_seal`.
+///
+/// The recipient can decrypt the message, but cannot tell who sent it.
+pub fn box_seal<T: RngCore + CryptoRng>(
+    plaintext: &[u8],
+    recipient: &PublicKey,
+    csprng: T,
+) -> Result<Vec<u8>, crate::Error> {
+    let ephemeral = EphemeralSecret::random_from_rng(csprng);
+    let ephemeral_public = PublicKey::from(&ephemeral);
+    let key = box_key(ephemeral.diffie_hellman(recipient))?;
+    let nonce = seal_nonce(&ephemeral_public, recipient);
+
+    let mut sealed = Vec::with_capacity(SEAL_LEN + plaintext.len());
+    sealed.extend_from_slice(ephemeral_public.as_bytes());
+    seal_into(&mut sealed, plaintext, &nonce, &key);
+    Ok(sealed)
+}
+
+/// Decrypt a sealed box to the public key of `secret`, as libsodium's
+/// `crypto_box_seal_open`.
+pub fn box_seal_open(sealed: &[u8], secret: &StaticSecret) -> Result<Vec<u8>, crate::Error> {
+    if sealed.len() < SEAL_LEN {
+        return Err(Error::InvalidCiphertext.into());
+    }
+    let (ephemeral_public, boxed) = sealed.split_at(32);
+    let ephemeral_public = PublicKey::from(<[u8; 32]>::try_from(ephemeral_public).unwrap());
+    let key = box_key(secret.diffie_hellman(&ephemeral_public))?;
+    let nonce = seal_nonce(&ephemeral_public, &PublicKey::from(secret));
+    Ok(open_with(boxed, &nonce, &key)?)
+}
+
+/// The nonce of a sealed box: BLAKE2b-192 of the ephemeral public key
+/// followed by the recipient's public key.
+fn seal_nonce(ephemeral_public: &PublicKey, recipient: &PublicKey) -> [u8; NONCE_LEN] {
+    Blake2b::<U24>::new()
+        .chain_update(ephemeral_public.as_bytes())
+        .chain_update(recipient.as_bytes())
+        .finalize()
+        .into()
+}
+
+/// Append the tag and ciphertext of `plaintext` to `out`.
+fn seal_into(out: &mut Vec<u8>, plaintext: &[u8], nonce: &[u8; NONCE_LEN], key: &[u8; KEY_LEN]) {
+    let start = out.len();
+    out.extend_from_slice(&[0u8; MAC_LEN]);
+    out.extend_from_slice(plaintext);
+    let tag = XSalsa20Poly1305::new(key.into())
+        .encrypt_in_place_detached(nonce.into(), b"", &mut out[start + MAC_LEN..])
+        .expect("XSalsa20-Poly1305 accepts any plaintext which fits in memory");
+    out[start..start + MAC_LEN].copy_from_slice(&tag);
+}
+
+/// Verify and decrypt a tag followed by a ciphertext, which must be at
+/// least [`MAC_LEN`] bytes.
+fn open_with(boxed: &[u8], nonce: &[u8; NONCE_LEN], key: &[u8; KEY_LEN]) -> Result<Vec<u8>, Error> {
+    let (tag, ciphertext) = boxed.split_at(MAC_LEN);
+    let mut plaintext = ciphertext.to_vec();
+    XSalsa20Poly1305::new(key.into())
+        .decrypt_in_place_detached(nonce.into(), b"", &mut plaintext, tag.into())
+        .map_err(|_| Error::InvalidCiphertext)?;
+    Ok(plaintext)
+}
+
+/// The session keys of one side of a `crypto_kx` key exchange.
+///
+/// The keys are wiped on drop.
+#[derive(Clone, Zeroize)]
+#[zeroize(drop)]
+pub struct KxSessionKeys {
+    rx: [u8; SESSION_KEY_LEN],
+    tx: [u8; SESSION_KEY_LEN],
+}
+
+impl KxSessionKeys {
+    /// The key for receiving data from the peer.
+    pub fn rx(&self) -> &[u8; SESSION_KEY_LEN] {
+        &self.rx
+    }
+
+    /// The key for sending data to the peer.
+    pub fn tx(&self) -> &[u8; SESSION_KEY_LEN] {
+        &self.tx
+    }
+}
+
+/// Compute the client's session keys with the server's public key, as
+/// libsodium's `crypto_kx_client_session_keys`.
+pub fn kx_client_session_keys(
+    client_secret: &StaticSecret,
+    server_public: &PublicKey,
+) -> Result<KxSessionKeys, crate::Error> {
+    let client_public = PublicKey::from(client_secret);
+    kx_keys(
+        client_secret.diffie_hellman(server_public),
+        &client_public,
+        server_public,
+    )
+}
+
+/// Compute the server's session keys with the client's public key, as
+/// libsodium's `crypto_kx_server_session_keys`.
+///
+/// The server's receiving key is the client's sending key, and vice versa.
+pub fn kx_server_session_keys(
+    server_secret: &StaticSecret,
+    client_public: &PublicKey,
+) -> Result<KxSessionKeys, crate::Error> {
+    let server_public = PublicKey::from(server_secret);
+    let client = kx_keys(
+        server_secret.diffie_hellman(client_public),
+        client_public,
+        &server_public,
+    )?;
+    Ok(KxSessionKeys {
+        rx: client.tx,
+        tx: client.rx,
+    })
+}
+
+/// Split BLAKE2b-512 of the shared secret and both public keys into the
+/// client's session keys.
+fn kx_keys(
+    shared: SharedSecret,
+    client_public: &PublicKey,
+    server_public: &PublicKey,
+) -> Result<KxSessionKeys, crate::Error> {
+    if !shared.was_contributory() {
+        return Err(crate::Error::SmallOrderPoint);
+    }
+    let mut hash = Blake2b512::new()
+        .chain_update(shared.as_bytes())
+        .chain_update(client_public.as_bytes())
+        .chain_update(server_public.as_bytes())
+        .finalize();
+    let mut keys = KxSessionKeys {
+        rx: [0u8; SESSION_KEY_LEN],
+        tx: [0u8; SESSION_KEY_LEN],
+    };
+    keys.rx.copy_from_slice(&hash[..SESSION_KEY_LEN]);
+    keys.tx.copy_from_slice(&hash[SESSION_KEY_LEN..]);
+    hash.as_mut_slice().zeroize();
+    Ok(keys)
+}
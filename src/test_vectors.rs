@@ -0,0 +1,328 @@
+// -*- mode: rust; -*-
+//
+// This file is part of x25519-dalek.
+// Copyright (c) 2017-2021 isis lovecruft
+// Copyright (c) 2019-2021 DebugSteven
+// See LICENSE for licensing information.
+//
+// Authors:
+// - isis agora lovecruft <isis@patternsinthevoid.net>
+// - DebugSteven <debugsteven@gmail.com>
+
+//! Known-answer test vectors for X25519, for the tests of other crates.
+//!
+//! ```
+//! use x25519_dalek::test_vectors::RFC7748_X25519;
+//! use x25519_dalek::x25519;
+//!
+//! for vector in RFC7748_X25519.iter() {
+//!     assert_eq!(x25519(vector.scalar, vector.u), vector.result);
+//! }
+//! ```
+
+/// A test vector of the X25519 function: `x25519(scalar, u) == result`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct X25519Vector {
+    /// The scalar, before clamping.
+    pub scalar: [u8; 32],
+    /// The u-coordinate.
+    pub u: [u8; 32],
+    /// The u-coordinate of the result.
+    pub result: [u8; 32],
+}
+
+/// A Diffie-Hellman exchange between Alice and Bob.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DiffieHellmanVector {
+    /// Alice's private key.
+    pub alice_private: [u8; 32],
+    /// Alice's public key.
+    pub alice_public: [u8; 32],
+    /// Bob's private key.
+    pub bob_private: [u8; 32],
+    /// Bob's public key.
+    pub bob_public: [u8; 32],
+    /// The shared secret.
+    pub shared: [u8; 32],
+}
+
+/// A public key in one of the categories of Google's Wycheproof X25519 tests,
+/// with the shared secret it gives with [`EDGE_CASE_PRIVATE`].
+///
+/// Every public key is accepted by X25519 itself, as RFC7748 requires, but
+/// the flags tell which are rejected by stricter validation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EdgeCase {
+    /// The public key.
+    pub public: [u8; 32],
+    /// The shared secret, which is all zeros for points of small order.
+    pub shared: [u8; 32],
+    /// Whether the public key is a point of small order.
+    pub low_order: bool,
+    /// Whether the public key is on the twist rather than the curve.
+    pub twist: bool,
+    /// Whether the public key is a non-canonical encoding, with the high bit
+    /// set or of a value of at least 2^255 - 19.
+    pub non_canonical: bool,
+}
+
+/// The two test vectors of RFC7748 section 5.2.
+pub const RFC7748_X25519: [X25519Vector; 2] = [
+    X25519Vector {
+        scalar: [
+            0xa5, 0x46, 0xe3, 0x6b, 0xf0, 0x52, 0x7c, 0x9d, 0x3b, 0x16, 0x15, 0x4b, 0x82, 0x46,
+            0x5e, 0xdd, 0x62, 0x14, 0x4c, 0x0a, 0xc1, 0xfc, 0x5a, 0x18, 0x50, 0x6a, 0x22, 0x44,
+            0xba, 0x44, 0x9a, 0xc4,
+        ],
+        u: [
+            0xe6, 0xdb, 0x68, 0x67, 0x58, 0x30, 0x30, 0xdb, 0x35, 0x94, 0xc1, 0xa4, 0x24, 0xb1,
+            0x5f, 0x7c, 0x72, 0x66, 0x24, 0xec, 0x26, 0xb3, 0x35, 0x3b, 0x10, 0xa9, 0x03, 0xa6,
+            0xd0, 0xab, 0x1c, 0x4c,
+        ],
+        result: [
+            0xc3, 0xda, 0x55, 0x37, 0x9d, 0xe9, 0xc6, 0x90, 0x8e, 0x94, 0xea, 0x4d, 0xf2, 0x8d,
+            0x08, 0x4f, 0x32, 0xec, 0xcf, 0x03, 0x49, 0x1c, 0x71, 0xf7, 0x54, 0xb4, 0x07, 0x55,
+            0x77, 0xa2, 0x85, 0x52,
+        ],
+    },
+    X25519Vector {
+        scalar: [
+            0x4b, 0x66, 0xe9, 0xd4, 0xd1, 0xb4, 0x67, 0x3c, 0x5a, 0xd2, 0x26, 0x91, 0x95, 0x7d,
+            0x6a, 0xf5, 0xc1, 0x1b, 0x64, 0x21, 0xe0, 0xea, 0x01, 0xd4, 0x2c, 0xa4, 0x16, 0x9e,
+            0x79, 0x18, 0xba, 0x0d,
+        ],
+        u: [
+            0xe5, 0x21, 0x0f, 0x12, 0x78, 0x68, 0x11, 0xd3, 0xf4, 0xb7, 0x95, 0x9d, 0x05, 0x38,
+            0xae, 0x2c, 0x31, 0xdb, 0xe7, 0x10, 0x6f, 0xc0, 0x3c, 0x3e, 0xfc, 0x4c, 0xd5, 0x49,
+            0xc7, 0x15, 0xa4, 0x93,
+        ],
+        result: [
+            0x95, 0xcb, 0xde, 0x94, 0x76, 0xe8, 0x90, 0x7d, 0x7a, 0xad, 0xe4, 0x5c, 0xb4, 0xb8,
+            0x73, 0xf8, 0x8b, 0x59, 0x5a, 0x68, 0x79, 0x9f, 0xa1, 0x52, 0xe6, 0xf8, 0xf7, 0x64,
+            0x7a, 0xac, 0x79, 0x57,
+        ],
+    },
+];
+
+/// The iterated test of RFC7748 section 5.2, as pairs of the number of
+/// iterations and the resulting `k`.
+///
+/// Starting with `k` and `u` both the basepoint, each iteration sets `k` to
+/// `x25519(k, u)` and `u` to the old `k`.
+pub const RFC7748_ITERATED: [(usize, [u8; 32]); 3] = [
+    (
+        1,
+        [
+            0x42, 0x2c, 0x8e, 0x7a, 0x62, 0x27, 0xd7, 0xbc, 0xa1, 0x35, 0x0b, 0x3e, 0x2b, 0xb7,
+            0x27, 0x9f, 0x78, 0x97, 0xb8, 0x7b, 0xb6, 0x85, 0x4b, 0x78, 0x3c, 0x60, 0xe8, 0x03,
+            0x11, 0xae, 0x30, 0x79,
+        ],
+    ),
+    (
+        1_000,
+        [
+            0x68, 0x4c, 0xf5, 0x9b, 0xa8, 0x33, 0x09, 0x55, 0x28, 0x00, 0xef, 0x56, 0x6f, 0x2f,
+            0x4d, 0x3c, 0x1c, 0x38, 0x87, 0xc4, 0x93, 0x60, 0xe3, 0x87, 0x5f, 0x2e, 0xb9, 0x4d,
+            0x99, 0x53, 0x2c, 0x51,
+        ],
+    ),
+    (
+        1_000_000,
+        [
+            0x7c, 0x39, 0x11, 0xe0, 0xab, 0x25, 0x86, 0xfd, 0x86, 0x44, 0x97, 0x29, 0x7e, 0x57,
+            0x5e, 0x6f, 0x3b, 0xc6, 0x01, 0xc0, 0x88, 0x3c, 0x30, 0xdf, 0x5f, 0x4d, 0xd2, 0xd2,
+            0x4f, 0x66, 0x54, 0x24,
+        ],
+    ),
+];
+
+/// The Diffie-Hellman exchange of RFC7748 section 6.1.
+pub const RFC7748_DIFFIE_HELLMAN: DiffieHellmanVector = DiffieHellmanVector {
+    alice_private: [
+        0x77, 0x07, 0x6d, 0x0a, 0x73, 0x18, 0xa5, 0x7d, 0x3c, 0x16, 0xc1, 0x72, 0x51, 0xb2, 0x66,
+        0x45, 0xdf, 0x4c, 0x2f, 0x87, 0xeb, 0xc0, 0x99, 0x2a, 0xb1, 0x77, 0xfb, 0xa5, 0x1d, 0xb9,
+        0x2c, 0x2a,
+    ],
+    alice_public: [
+        0x85, 0x20, 0xf0, 0x09, 0x89, 0x30, 0xa7, 0x54, 0x74, 0x8b, 0x7d, 0xdc, 0xb4, 0x3e, 0xf7,
+        0x5a, 0x0d, 0xbf, 0x3a, 0x0d, 0x26, 0x38, 0x1a, 0xf4, 0xeb, 0xa4, 0xa9, 0x8e, 0xaa, 0x9b,
+        0x4e, 0x6a,
+    ],
+    bob_private: [
+        0x5d, 0xab, 0x08, 0x7e, 0x62, 0x4a, 0x8a, 0x4b, 0x79, 0xe1, 0x7f, 0x8b, 0x83, 0x80, 0x0e,
+        0xe6, 0x6f, 0x3b, 0xb1, 0x29, 0x26, 0x18, 0xb6, 0xfd, 0x1c, 0x2f, 0x8b, 0x27, 0xff, 0x88,
+        0xe0, 0xeb,
+    ],
+    bob_public: [
+        0xde, 0x9e, 0xdb, 0x7d, 0x7b, 0x7d, 0xc1, 0xb4, 0xd3, 0x5b, 0x61, 0xc2, 0xec, 0xe4, 0x35,
+        0x37, 0x3f, 0x83, 0x43, 0xc8, 0x5b, 0x78, 0x67, 0x4d, 0xad, 0xfc, 0x7e, 0x14, 0x6f, 0x88,
+        0x2b, 0x4f,
+    ],
+    shared: [
+        0x4a, 0x5d, 0x9d, 0x5b, 0xa4, 0xce, 0x2d, 0xe1, 0x72, 0x8e, 0x3b, 0xf4, 0x80, 0x35, 0x0f,
+        0x25, 0xe0, 0x7e, 0x21, 0xc9, 0x47, 0xd1, 0x9e, 0x33, 0x76, 0xf0, 0x9b, 0x3c, 0x1e, 0x16,
+        0x17, 0x42,
+    ],
+};
+
+/// The private key of every [`EDGE_CASES`] vector, from RFC7748 section 5.2.
+pub const EDGE_CASE_PRIVATE: [u8; 32] = [
+    0xc8, 0xa9, 0xd5, 0xa9, 0x10, 0x91, 0xad, 0x85, 0x1c, 0x66, 0x8b, 0x07, 0x36, 0xc1, 0xc9, 0xa0,
+    0x29, 0x36, 0xc0, 0xd3, 0xad, 0x62, 0x67, 0x08, 0x58, 0x08, 0x80, 0x47, 0xba, 0x05, 0x74, 0x75,
+];
+
+/// Public keys of small order, on the twist, and non-canonically encoded,
+/// computed with the ladder of RFC7748 section 5 and checked against
+/// OpenSSL.
+pub const EDGE_CASES: [EdgeCase; 10] = [
+    EdgeCase {
+        public: [
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00,
+        ],
+        shared: [
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00,
+        ],
+        low_order: true,
+        twist: false,
+        non_canonical: false,
+    },
+    EdgeCase {
+        public: [
+            0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00,
+        ],
+        shared: [
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00,
+        ],
+        low_order: true,
+        twist: false,
+        non_canonical: false,
+    },
+    EdgeCase {
+        public: [
+            0xe0, 0xeb, 0x7a, 0x7c, 0x3b, 0x41, 0xb8, 0xae, 0x16, 0x56, 0xe3, 0xfa, 0xf1, 0x9f,
+            0xc4, 0x6a, 0xda, 0x09, 0x8d, 0xeb, 0x9c, 0x32, 0xb1, 0xfd, 0x86, 0x62, 0x05, 0x16,
+            0x5f, 0x49, 0xb8, 0x00,
+        ],
+        shared: [
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00,
+        ],
+        low_order: true,
+        twist: false,
+        non_canonical: false,
+    },
+    EdgeCase {
+        public: [
+            0x5f, 0x9c, 0x95, 0xbc, 0xa3, 0x50, 0x8c, 0x24, 0xb1, 0xd0, 0xb1, 0x55, 0x9c, 0x83,
+            0xef, 0x5b, 0x04, 0x44, 0x5c, 0xc4, 0x58, 0x1c, 0x8e, 0x86, 0xd8, 0x22, 0x4e, 0xdd,
+            0xd0, 0x9f, 0x11, 0x57,
+        ],
+        shared: [
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00,
+        ],
+        low_order: true,
+        twist: false,
+        non_canonical: false,
+    },
+    EdgeCase {
+        public: [
+            0xec, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+            0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+            0xff, 0xff, 0xff, 0x7f,
+        ],
+        shared: [
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00,
+        ],
+        low_order: true,
+        twist: true,
+        non_canonical: false,
+    },
+    EdgeCase {
+        public: [
+            0xed, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+            0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+            0xff, 0xff, 0xff, 0x7f,
+        ],
+        shared: [
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00,
+        ],
+        low_order: true,
+        twist: false,
+        non_canonical: true,
+    },
+    EdgeCase {
+        public: [
+            0xee, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+            0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+            0xff, 0xff, 0xff, 0x7f,
+        ],
+        shared: [
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00,
+        ],
+        low_order: true,
+        twist: false,
+        non_canonical: true,
+    },
+    EdgeCase {
+        public: [
+            0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00,
+        ],
+        shared: [
+            0xa5, 0xf0, 0x0a, 0x22, 0x0f, 0x82, 0xac, 0x43, 0xd0, 0xd4, 0x12, 0x02, 0x11, 0x7a,
+            0x67, 0x66, 0x37, 0x23, 0x33, 0x09, 0x0c, 0x42, 0xc9, 0x4c, 0xf0, 0xa1, 0xb9, 0x91,
+            0xc2, 0xd6, 0xc7, 0x73,
+        ],
+        low_order: false,
+        twist: true,
+        non_canonical: false,
+    },
+    EdgeCase {
+        public: [
+            0xef, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+            0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+            0xff, 0xff, 0xff, 0x7f,
+        ],
+        shared: [
+            0xa5, 0xf0, 0x0a, 0x22, 0x0f, 0x82, 0xac, 0x43, 0xd0, 0xd4, 0x12, 0x02, 0x11, 0x7a,
+            0x67, 0x66, 0x37, 0x23, 0x33, 0x09, 0x0c, 0x42, 0xc9, 0x4c, 0xf0, 0xa1, 0xb9, 0x91,
+            0xc2, 0xd6, 0xc7, 0x73,
+        ],
+        low_order: false,
+        twist: true,
+        non_canonical: true,
+    },
+    EdgeCase {
+        public: [
+            0x09, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x80,
+        ],
+        shared: [
+            0x5f, 0x64, 0xb4, 0x1c, 0xce, 0x8a, 0x6b, 0x3d, 0x6a, 0x38, 0x76, 0x30, 0x88, 0xf6,
+            0x15, 0xa4, 0x97, 0x7d, 0x42, 0x22, 0x88, 0xae, 0x42, 0xb4, 0x9a, 0xb3, 0xa5, 0x7e,
+            0x2f, 0xcd, 0x6f, 0x6d,
+        ],
+        low_order: false,
+        twist: false,
+        non_canonical: true,
+    },
+];
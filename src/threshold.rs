@@ -0,0 +1,260 @@
+// -*- mode: rust; -*-
+//
+// This file is part of x25519-dalek.
+// Copyright (c) 2017-2021 isis lovecruft
+// Copyright (c) 2019-2021 DebugSteven
+// See LICENSE for licensing information.
+//
+// Authors:
+// - isis agora lovecruft <isis@patternsinthevoid.net>
+// - DebugSteven <debugsteven@gmail.com>
+
+//! Threshold Diffie-Hellman: t-of-n sharing of a [`StaticSecret`] such that
+//! shareholders can jointly compute a [`SharedSecret`] without ever
+//! reconstructing the secret key.
+//!
+//! The clamped secret scalar, divided by the cofactor, is Shamir-shared over
+//! the scalar field.  Each shareholder computes a [`PartialSharedSecret`]
+//! against the peer's public key, and any `t` partial results combine, by
+//! Lagrange interpolation in the exponent, into exactly the shared secret
+//! that [`StaticSecret::diffie_hellman`] would have produced:
+//!
+#![cfg_attr(feature = "getrandom", doc = "```")]
+#![cfg_attr(not(feature = "getrandom"), doc = "```ignore")]
+//! use rand_core::OsRng;
+//! use x25519_dalek::{PublicKey, StaticSecret};
+//! use x25519_dalek::threshold::PartialSharedSecret;
+//!
+//! let secret = StaticSecret::random();
+//! let shareholders = secret.split_threshold(3, 2, OsRng)?;
+//!
+//! let peer = PublicKey::from(&StaticSecret::random());
+//! let partials = [
+//!     shareholders[0].partial_diffie_hellman(&peer)?,
+//!     shareholders[2].partial_diffie_hellman(&peer)?,
+//! ];
+//! let shared = PartialSharedSecret::combine(&partials)?;
+//! assert_eq!(shared.as_bytes(), secret.diffie_hellman(&peer).as_bytes());
+//! # Ok::<(), x25519_dalek::Error>(())
+//! ```
+//!
+//! Unlike X25519 itself, partial Diffie-Hellman rejects public keys which
+//! lie on the twist of Curve25519, since these cannot be combined.
+
+use alloc::vec::Vec;
+use core::fmt;
+
+use curve25519_dalek::edwards::{CompressedEdwardsY, EdwardsPoint};
+use curve25519_dalek::scalar::Scalar;
+use curve25519_dalek::traits::Identity;
+use rand_core::{CryptoRng, RngCore};
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::x25519::{cofactor_free_scalar, PublicKey, SharedSecret, StaticSecret};
+
+/// The length of an encoded [`ShareholderSecret`] or [`PartialSharedSecret`].
+pub const SHARE_LEN: usize = 33;
+
+/// Errors which may occur in threshold Diffie-Hellman.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// The threshold is zero or greater than the number of shares.
+    InvalidThreshold,
+    /// No partial results were given.
+    NoShares,
+    /// A share has index zero.
+    InvalidShareIndex,
+    /// Two shares have the same index.
+    DuplicateShare,
+    /// A shareholder secret is not a canonically-encoded scalar.
+    InvalidScalar,
+    /// A partial result is not the encoding of a point.
+    InvalidPoint,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::InvalidThreshold => {
+                f.write_str("threshold must be between 1 and the number of shares")
+            }
+            Error::NoShares => f.write_str("no partial results given"),
+            Error::InvalidShareIndex => f.write_str("share index must not be zero"),
+            Error::DuplicateShare => f.write_str("shares have duplicate indices"),
+            Error::InvalidScalar => f.write_str("shareholder secret is not a canonical scalar"),
+            Error::InvalidPoint => f.write_str("partial result is not a valid point"),
+        }
+    }
+}
+
+/// One shareholder's share of a secret key.
+///
+/// The share is wiped on drop.
+#[derive(Clone, Zeroize)]
+#[zeroize(drop)]
+pub struct ShareholderSecret {
+    index: u8,
+    scalar: Scalar,
+}
+
+/// One shareholder's contribution to a shared secret.
+///
+/// Any `threshold` of these reconstruct the shared secret, so they are
+/// wiped on drop.
+#[derive(Clone, Zeroize)]
+#[zeroize(drop)]
+pub struct PartialSharedSecret {
+    index: u8,
+    point: EdwardsPoint,
+}
+
+impl StaticSecret {
+    /// Split this secret key between `n` shareholders, any `threshold` of
+    /// which can jointly compute Diffie-Hellman with it.
+    pub fn split_threshold<T: RngCore + CryptoRng>(
+        &self,
+        n: u8,
+        threshold: u8,
+        mut csprng: T,
+    ) -> Result<Vec<ShareholderSecret>, crate::Error> {
+        if threshold == 0 || threshold > n {
+            return Err(Error::InvalidThreshold.into());
+        }
+
+        // The polynomial's constant term is the secret scalar, followed by
+        // random coefficients of x, x^2, ..., x^(t-1).
+        let mut coefficients = Zeroizing::new(Vec::with_capacity(threshold as usize));
+        coefficients.push(cofactor_free_scalar(self.as_bytes()));
+        for _ in 1..threshold {
+            let mut bytes = Zeroizing::new([0u8; 64]);
+            csprng.fill_bytes(&mut bytes[..]);
+            coefficients.push(Scalar::from_bytes_mod_order_wide(&bytes));
+        }
+
+        let shares = (1..=n)
+            .map(|index| {
+                let x = Scalar::from(index);
+                // Horner's rule, from the highest degree coefficient down.
+                let scalar = coefficients
+                    .iter()
+                    .rev()
+                    .fold(Scalar::ZERO, |y, coefficient| y * x + coefficient);
+                ShareholderSecret { index, scalar }
+            })
+            .collect();
+        Ok(shares)
+    }
+}
+
+impl ShareholderSecret {
+    /// The index of this share, between 1 and 255.
+    pub fn index(&self) -> u8 {
+        self.index
+    }
+
+    /// Compute this shareholder's contribution to the shared secret with
+    /// `their_public` key.
+    pub fn partial_diffie_hellman(
+        &self,
+        their_public: &PublicKey,
+    ) -> Result<PartialSharedSecret, crate::Error> {
+        // Either sign will do: the shared secret is the u-coordinate of the
+        // combined point, which is the same for a point and its negation.
+        let point = their_public
+            .0
+            .to_edwards(0)
+            .ok_or(crate::Error::TwistPoint)?
+            .mul_by_cofactor();
+        Ok(PartialSharedSecret {
+            index: self.index,
+            point: point * self.scalar,
+        })
+    }
+
+    /// Encode this share as its index followed by its 32-byte scalar.
+    pub fn to_bytes(&self) -> [u8; SHARE_LEN] {
+        let mut bytes = [0u8; SHARE_LEN];
+        bytes[0] = self.index;
+        bytes[1..].copy_from_slice(self.scalar.as_bytes());
+        bytes
+    }
+
+    /// Decode a share produced by [`ShareholderSecret::to_bytes`].
+    pub fn from_bytes(bytes: &[u8; SHARE_LEN]) -> Result<ShareholderSecret, crate::Error> {
+        let index = share_index(bytes[0])?;
+        let mut scalar_bytes = Zeroizing::new([0u8; 32]);
+        scalar_bytes.copy_from_slice(&bytes[1..]);
+        let scalar = Option::from(Scalar::from_canonical_bytes(*scalar_bytes))
+            .ok_or(Error::InvalidScalar)?;
+        Ok(ShareholderSecret { index, scalar })
+    }
+}
+
+impl PartialSharedSecret {
+    /// The index of the share which produced this partial result.
+    pub fn index(&self) -> u8 {
+        self.index
+    }
+
+    /// Combine partial results from at least as many shareholders as the
+    /// threshold into the shared secret.
+    ///
+    /// Combining fewer partial results than the threshold does not fail, but
+    /// yields an unrelated value.
+    pub fn combine(partials: &[PartialSharedSecret]) -> Result<SharedSecret, crate::Error> {
+        if partials.is_empty() {
+            return Err(Error::NoShares.into());
+        }
+        for (i, partial) in partials.iter().enumerate() {
+            if partials[..i]
+                .iter()
+                .any(|other| other.index == partial.index)
+            {
+                return Err(Error::DuplicateShare.into());
+            }
+        }
+
+        let mut combined = Zeroizing::new(EdwardsPoint::identity());
+        for partial in partials {
+            // The Lagrange basis polynomial for this share, evaluated at 0.
+            let x = Scalar::from(partial.index);
+            let (numerator, denominator) = partials
+                .iter()
+                .filter(|other| other.index != partial.index)
+                .fold((Scalar::ONE, Scalar::ONE), |(num, den), other| {
+                    let other_x = Scalar::from(other.index);
+                    (num * other_x, den * (other_x - x))
+                });
+            let term = Zeroizing::new(partial.point * (numerator * denominator.invert()));
+            *combined += &*term;
+        }
+        Ok(SharedSecret(combined.to_montgomery()))
+    }
+
+    /// Encode this partial result as its index followed by the compressed
+    /// Edwards point.
+    pub fn to_bytes(&self) -> [u8; SHARE_LEN] {
+        let mut bytes = [0u8; SHARE_LEN];
+        bytes[0] = self.index;
+        bytes[1..].copy_from_slice(self.point.compress().as_bytes());
+        bytes
+    }
+
+    /// Decode a partial result produced by [`PartialSharedSecret::to_bytes`].
+    pub fn from_bytes(bytes: &[u8; SHARE_LEN]) -> Result<PartialSharedSecret, crate::Error> {
+        let index = share_index(bytes[0])?;
+        let point = CompressedEdwardsY::from_slice(&bytes[1..])
+            .ok()
+            .and_then(|point| point.decompress())
+            .ok_or(Error::InvalidPoint)?;
+        Ok(PartialSharedSecret { index, point })
+    }
+}
+
+/// Check that a decoded share index is non-zero.
+fn share_index(index: u8) -> Result<u8, Error> {
+    match index {
+        0 => Err(Error::InvalidShareIndex),
+        index => Ok(index),
+    }
+}
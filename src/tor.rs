@@ -0,0 +1,191 @@
+// -*- mode: rust; -*-
+//
+// This file is part of x25519-dalek.
+// Copyright (c) 2017-2021 isis lovecruft
+// Copyright (c) 2019-2021 DebugSteven
+// See LICENSE for licensing information.
+//
+// Authors:
+// - isis agora lovecruft <isis@patternsinthevoid.net>
+// - DebugSteven <debugsteven@gmail.com>
+
+//! Encoding of X25519 keys in Tor onion service client authorization files,
+//! as specified in the "Client authorization" section of the
+//! [Tor manual](https://2019.www.torproject.org/docs/tor-manual.html.en).
+//!
+//! An onion service lists its authorized clients in `*.auth` files, each
+//! holding a `descriptor:x25519:<base32-public-key>` line, while a client
+//! keeps its keys in `*.auth_private` files, each holding a
+//! `<onion-address>:descriptor:x25519:<base32-private-key>` line.
+//!
+//! Keys use the RFC4648 base32 alphabet without padding, and are written in
+//! uppercase.  Decoding is case-insensitive and constant-time with respect
+//! to the key material.
+
+use alloc::string::String;
+use core::fmt;
+
+use zeroize::Zeroizing;
+
+use crate::error::Error;
+use crate::x25519::PublicKey;
+
+#[cfg(feature = "static_secrets")]
+use crate::x25519::StaticSecret;
+
+/// The prefix of a key in an authorization line.
+const KEY_PREFIX: &str = "descriptor:x25519:";
+
+/// The length of a base32-encoded key.
+const ENCODED_LEN: usize = 52;
+
+/// The length of a v3 onion address, without the `.onion` suffix.
+#[cfg(feature = "static_secrets")]
+const ONION_ADDRESS_LEN: usize = 56;
+
+/// The RFC4648 base32 alphabet, in uppercase.
+const ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+/// Errors which may occur when parsing a Tor client authorization line.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TorKeyError {
+    /// The line is not of the form `descriptor:x25519:<key>`, or the onion
+    /// address of a private key line is not 56 base32 characters.
+    InvalidFormat,
+    /// The key is not the unpadded base32 encoding of 32 bytes.
+    InvalidEncoding,
+}
+
+impl fmt::Display for TorKeyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TorKeyError::InvalidFormat => f.write_str("not a Tor x25519 client authorization line"),
+            TorKeyError::InvalidEncoding => f.write_str("key is not 52 base32 characters"),
+        }
+    }
+}
+
+/// Encode 32 bytes as unpadded uppercase base32.
+fn encode(bytes: &[u8; 32], out: &mut String) {
+    let mut acc = 0u16;
+    let mut bits = 0;
+    for &byte in bytes {
+        acc = (acc << 8) | byte as u16;
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            out.push(ALPHABET[((acc >> bits) & 0x1f) as usize] as char);
+        }
+    }
+    out.push(ALPHABET[((acc << (5 - bits)) & 0x1f) as usize] as char);
+}
+
+/// Decode a single base32 character, in constant time, returning -1 if it
+/// is not in the (case-insensitive) alphabet.
+fn decode_char(c: u8) -> i16 {
+    let c = c as i16;
+    let mut value = -1;
+    // 'A'..='Z'
+    value += (((0x40 - c) & (c - 0x5b)) >> 8) & (c - 0x40);
+    // 'a'..='z'
+    value += (((0x60 - c) & (c - 0x7b)) >> 8) & (c - 0x60);
+    // '2'..='7'
+    value += (((0x31 - c) & (c - 0x38)) >> 8) & (c - 0x17);
+    value
+}
+
+/// Decode the unpadded base32 encoding of exactly 32 bytes, in constant
+/// time.  Non-zero trailing bits are rejected.
+fn decode(encoded: &str) -> Result<Zeroizing<[u8; 32]>, TorKeyError> {
+    if encoded.len() != ENCODED_LEN {
+        return Err(TorKeyError::InvalidEncoding);
+    }
+
+    let mut bytes = Zeroizing::new([0u8; 32]);
+    let mut acc = 0u16;
+    let mut bits = 0;
+    let mut invalid = 0i16;
+    let mut i = 0;
+    for &c in encoded.as_bytes() {
+        let value = decode_char(c);
+        invalid |= value;
+        acc = (acc << 5) | (value as u16 & 0x1f);
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            bytes[i] = (acc >> bits) as u8;
+            i += 1;
+        }
+    }
+    // Any invalid character sets the sign bit, and the remaining bits must
+    // be zero.
+    invalid |= -((acc & ((1 << bits) - 1)) as i16);
+
+    if invalid < 0 {
+        return Err(TorKeyError::InvalidEncoding);
+    }
+    Ok(bytes)
+}
+
+impl PublicKey {
+    /// Encode this public key as the `descriptor:x25519:<base32>` line of an
+    /// onion service's `*.auth` client authorization file.
+    pub fn to_tor_auth_key(&self) -> String {
+        let mut line = String::with_capacity(KEY_PREFIX.len() + ENCODED_LEN);
+        line.push_str(KEY_PREFIX);
+        encode(self.as_bytes(), &mut line);
+        line
+    }
+
+    /// Parse a public key from a `descriptor:x25519:<base32>` line.
+    ///
+    /// Surrounding whitespace, such as a trailing newline, is ignored.
+    pub fn from_tor_auth_key(line: &str) -> Result<PublicKey, Error> {
+        let encoded = line
+            .trim()
+            .strip_prefix(KEY_PREFIX)
+            .ok_or(TorKeyError::InvalidFormat)?;
+        Ok(PublicKey::from(*decode(encoded)?))
+    }
+}
+
+#[cfg(feature = "static_secrets")]
+impl StaticSecret {
+    /// Encode this secret key as the
+    /// `<onion-address>:descriptor:x25519:<base32>` line of a client's
+    /// `*.auth_private` file.
+    ///
+    /// The `onion_address` is the address of the service, without the
+    /// `.onion` suffix.
+    pub fn to_tor_auth_private(&self, onion_address: &str) -> Zeroizing<String> {
+        let mut line = Zeroizing::new(String::with_capacity(
+            onion_address.len() + 1 + KEY_PREFIX.len() + ENCODED_LEN,
+        ));
+        line.push_str(onion_address);
+        line.push(':');
+        line.push_str(KEY_PREFIX);
+        encode(self.as_bytes(), &mut line);
+        line
+    }
+
+    /// Parse a secret key from a `<onion-address>:descriptor:x25519:<base32>`
+    /// line, returning the onion address together with the key.
+    ///
+    /// Surrounding whitespace, such as a trailing newline, is ignored.
+    pub fn from_tor_auth_private(line: &str) -> Result<(&str, StaticSecret), Error> {
+        let (onion_address, key) = line
+            .trim()
+            .split_once(':')
+            .ok_or(TorKeyError::InvalidFormat)?;
+        if onion_address.len() != ONION_ADDRESS_LEN
+            || !onion_address.bytes().all(|c| decode_char(c) >= 0)
+        {
+            return Err(TorKeyError::InvalidFormat.into());
+        }
+        let encoded = key
+            .strip_prefix(KEY_PREFIX)
+            .ok_or(TorKeyError::InvalidFormat)?;
+        let secret = StaticSecret::from(*decode(encoded)?);
+        Ok((onion_address, secret))
+    }
+}
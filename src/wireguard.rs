@@ -0,0 +1,58 @@
+// -*- mode: rust; -*-
+//
+// This file is part of x25519-dalek.
+// Copyright (c) 2017-2021 isis lovecruft
+// Copyright (c) 2019-2021 DebugSteven
+// See LICENSE for licensing information.
+//
+// Authors:
+// - isis agora lovecruft <isis@patternsinthevoid.net>
+// - DebugSteven <debugsteven@gmail.com>
+
+//! WireGuard-style base64 encoding of X25519 keys, as used by `wg(8)` and
+//! `wg-quick(8)` configuration files.
+//!
+//! WireGuard keys are 44 characters of standard, padded base64, so these
+//! are the [`base64`](crate::PublicKey::to_base64) encodings under the names
+//! WireGuard users look for, and report errors as
+//! [`Base64Error`](crate::Base64Error).
+
+use alloc::string::String;
+
+use crate::error::Error;
+use crate::x25519::PublicKey;
+
+#[cfg(feature = "static_secrets")]
+use zeroize::Zeroizing;
+
+#[cfg(feature = "static_secrets")]
+use crate::x25519::StaticSecret;
+
+impl PublicKey {
+    /// Encode this public key as WireGuard-style base64, the same as
+    /// [`PublicKey::to_base64`].
+    pub fn to_wireguard_base64(&self) -> String {
+        self.to_base64()
+    }
+
+    /// Parse a public key from WireGuard-style base64, the same as
+    /// [`PublicKey::from_base64`].
+    pub fn from_wireguard_base64(encoded: &str) -> Result<PublicKey, Error> {
+        PublicKey::from_base64(encoded)
+    }
+}
+
+#[cfg(feature = "static_secrets")]
+impl StaticSecret {
+    /// Encode this secret key as WireGuard-style base64, the same as
+    /// [`StaticSecret::to_base64`].
+    pub fn to_wireguard_base64(&self) -> Zeroizing<String> {
+        self.to_base64()
+    }
+
+    /// Parse a secret key from WireGuard-style base64, in constant time, the
+    /// same as [`StaticSecret::from_base64`].
+    pub fn from_wireguard_base64(encoded: &str) -> Result<StaticSecret, Error> {
+        StaticSecret::from_base64(encoded)
+    }
+}
@@ -14,11 +14,17 @@
 //! This implements x25519 key exchange as specified by Mike Hamburg
 //! and Adam Langley in [RFC7748](https://tools.ietf.org/html/rfc7748).
 
-use curve25519_dalek::{edwards::EdwardsPoint, montgomery::MontgomeryPoint, traits::IsIdentity};
+use curve25519_dalek::{
+    edwards::EdwardsPoint, montgomery::MontgomeryPoint, scalar::Scalar, traits::IsIdentity,
+};
 
 use rand_core::CryptoRng;
 use rand_core::RngCore;
 
+use subtle::{Choice, ConstantTimeEq};
+
+use crate::error::Error;
+
 #[cfg(feature = "zeroize")]
 use zeroize::Zeroize;
 
@@ -28,7 +34,6 @@ use zeroize::Zeroize;
 /// should they wish to erase public keys from memory.  Note that this erasure
 /// (in this crate) does *not* automatically happen, but either must be derived
 /// for Drop or explicitly called.
-#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[cfg_attr(feature = "zeroize", derive(Zeroize))]
 #[derive(PartialEq, Eq, Hash, Copy, Clone, Debug)]
 pub struct PublicKey(pub(crate) MontgomeryPoint);
@@ -52,6 +57,106 @@ impl PublicKey {
     pub fn as_bytes(&self) -> &[u8; 32] {
         self.0.as_bytes()
     }
+
+    /// Construct a public key from its canonical encoding, rejecting points
+    /// of small order.
+    ///
+    /// Unlike `From<[u8; 32]>`, which accepts any 32 bytes, this rejects
+    /// encodings with the high bit set or of a value of at least 2^255 - 19,
+    /// as well as points whose Diffie-Hellman output would be the identity
+    /// regardless of the secret key.
+    pub fn from_canonical_bytes(bytes: [u8; 32]) -> Result<PublicKey, Error> {
+        if !is_canonical(&bytes) {
+            return Err(Error::NonCanonicalEncoding);
+        }
+        let point = MontgomeryPoint(bytes);
+        if (point * Scalar::from(8u8)).is_identity() {
+            return Err(Error::SmallOrderPoint);
+        }
+        Ok(PublicKey(point))
+    }
+
+    /// Convert this public key to a point on the Edwards form of the curve,
+    /// through the birational map `y = (u - 1) / (u + 1)`.
+    ///
+    /// An X25519 public key is only a `u`-coordinate, which determines the
+    /// Edwards `y`-coordinate but not the sign of `x`, so each key is
+    /// equally the point `P` and `-P`.  The `sign` selects between them: the
+    /// returned point has an `x`-coordinate whose least significant bit is
+    /// `sign & 1`, which is also the top bit of its compressed encoding.
+    /// XEdDSA, for example, uses the point with sign `0`.
+    ///
+    /// Returns `None` if `u = -1`, where the map is undefined, or if the key
+    /// is not on the curve but on its quadratic twist.
+    pub fn to_edwards(&self, sign: u8) -> Option<EdwardsPoint> {
+        self.0.to_edwards(sign & 1)
+    }
+}
+
+/// Check whether `bytes` is the canonical encoding of a field element, i.e.
+/// a little-endian integer less than 2^255 - 19.
+fn is_canonical(bytes: &[u8; 32]) -> bool {
+    // Compare against 2^255 - 19 from the most significant byte down.
+    let mut modulus = [0xff; 32];
+    modulus[0] = 0xed;
+    modulus[31] = 0x7f;
+    for (byte, p) in bytes.iter().zip(modulus.iter()).rev() {
+        if byte != p {
+            return byte < p;
+        }
+    }
+    false
+}
+
+/// Reduce `bytes` to the canonical encoding of the field element it
+/// represents, ignoring the unused high bit as X25519 itself does.
+#[cfg(feature = "fingerprint")]
+pub(crate) fn canonicalize(mut bytes: [u8; 32]) -> [u8; 32] {
+    bytes[31] &= 0x7f;
+    if !is_canonical(&bytes) {
+        // The only remaining non-canonical values are p, ..., p + 18.
+        let low = bytes[0] - 0xed;
+        bytes = [0u8; 32];
+        bytes[0] = low;
+    }
+    bytes
+}
+
+/// The clamped secret scalar divided by the cofactor, 8.
+///
+/// Clamping clears the low three bits, so this is exact, and multiplying the
+/// peer's public key by 8 and then by this scalar is the same as X25519.
+#[cfg(any(
+    feature = "threshold",
+    feature = "dleq",
+    feature = "key_blinding",
+    feature = "scalar_blinding"
+))]
+pub(crate) fn cofactor_free_scalar(bytes: &[u8; 32]) -> Scalar {
+    let clamped = zeroize::Zeroizing::new(curve25519_dalek::scalar::clamp_integer(*bytes));
+    let mut shifted = zeroize::Zeroizing::new([0u8; 32]);
+    for i in 0..32 {
+        let high = clamped.get(i + 1).map_or(0, |byte| byte << 5);
+        shifted[i] = (clamped[i] >> 3) | high;
+    }
+    Scalar::from_bytes_mod_order(*shifted)
+}
+
+impl TryFrom<&[u8]> for PublicKey {
+    type Error = Error;
+
+    /// Construct a public key from a slice of exactly 32 bytes.
+    ///
+    /// Like `From<[u8; 32]>`, this accepts any 32 bytes; use
+    /// [`PublicKey::from_canonical_bytes`] to validate the key.
+    fn try_from(bytes: &[u8]) -> Result<PublicKey, Error> {
+        <[u8; 32]>::try_from(bytes)
+            .map(PublicKey::from)
+            .map_err(|_| Error::InvalidLength {
+                expected: 32,
+                actual: bytes.len(),
+            })
+    }
 }
 
 impl AsRef<[u8]> for PublicKey {
@@ -62,6 +167,82 @@ impl AsRef<[u8]> for PublicKey {
     }
 }
 
+impl ConstantTimeEq for PublicKey {
+    /// Compare the encodings of two public keys in constant time.
+    fn ct_eq(&self, other: &PublicKey) -> Choice {
+        self.as_bytes().ct_eq(other.as_bytes())
+    }
+}
+
+impl core::fmt::Display for PublicKey {
+    /// Format this public key as 64 lowercase hexadecimal digits.
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        core::fmt::LowerHex::fmt(self, f)
+    }
+}
+
+impl core::fmt::LowerHex for PublicKey {
+    /// Format this public key as 64 lowercase hexadecimal digits, prefixed
+    /// with `0x` when the alternate flag (`{:#x}`) is set.
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        if f.alternate() {
+            f.write_str("0x")?;
+        }
+        for byte in self.as_bytes() {
+            write!(f, "{:02x}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+impl core::str::FromStr for PublicKey {
+    type Err = Error;
+
+    /// Parse a public key from 64 hexadecimal digits, in either case.
+    fn from_str(s: &str) -> Result<PublicKey, Error> {
+        if s.len() != 64 {
+            return Err(ParsePublicKeyError::InvalidLength.into());
+        }
+        let mut bytes = [0u8; 32];
+        for (byte, pair) in bytes.iter_mut().zip(s.as_bytes().chunks_exact(2)) {
+            *byte = (hex_digit(pair[0])? << 4) | hex_digit(pair[1])?;
+        }
+        Ok(PublicKey::from(bytes))
+    }
+}
+
+/// Decode a single hexadecimal digit.
+fn hex_digit(c: u8) -> Result<u8, ParsePublicKeyError> {
+    match c {
+        b'0'..=b'9' => Ok(c - b'0'),
+        b'a'..=b'f' => Ok(c - b'a' + 10),
+        b'A'..=b'F' => Ok(c - b'A' + 10),
+        _ => Err(ParsePublicKeyError::InvalidCharacter),
+    }
+}
+
+/// Errors which may occur when parsing a [`PublicKey`] from a hex string.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ParsePublicKeyError {
+    /// The string is not 64 characters long.
+    InvalidLength,
+    /// The string contains a character which is not a hexadecimal digit.
+    InvalidCharacter,
+}
+
+impl core::fmt::Display for ParsePublicKeyError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ParsePublicKeyError::InvalidLength => {
+                f.write_str("public key is not 64 hexadecimal digits")
+            }
+            ParsePublicKeyError::InvalidCharacter => {
+                f.write_str("public key contains a non-hexadecimal character")
+            }
+        }
+    }
+}
+
 /// A short-lived Diffie-Hellman secret key that can only be used to compute a single
 /// [`SharedSecret`].
 ///
@@ -81,6 +262,23 @@ impl EphemeralSecret {
         SharedSecret(their_public.0.mul_clamped(self.0))
     }
 
+    /// Perform a Diffie-Hellman key agreement between `self` and
+    /// `their_public` key, writing the shared secret into `out`, such as a
+    /// buffer in secure or DMA-accessible memory.
+    ///
+    /// The result is the same as [`EphemeralSecret::diffie_hellman`].  An all-zero
+    /// result is not contributory, as described at
+    /// [`SharedSecret::was_contributory`].  The compiler may still hold the
+    /// result in registers or on the stack on its way to `out`.
+    pub fn diffie_hellman_into(self, their_public: &PublicKey, out: &mut [u8; 32]) {
+        *out = their_public.0.mul_clamped(self.0).0;
+    }
+
+    /// Compute the public key of `self`, writing it into `out`.
+    pub fn public_key_into(&self, out: &mut [u8; 32]) {
+        *out = mul_base_clamped(self.0).0;
+    }
+
     /// Generate a new [`EphemeralSecret`] with the supplied RNG.
     #[deprecated(
         since = "2.0.0",
@@ -98,17 +296,31 @@ impl EphemeralSecret {
         EphemeralSecret(bytes)
     }
 
+    /// Generate a new [`EphemeralSecret`] with the supplied RNG, returning an
+    /// error instead of panicking if the RNG fails.
+    pub fn try_random_from_rng<T: RngCore + CryptoRng>(mut csprng: T) -> Result<Self, Error> {
+        // The secret key is random bytes. Clamping is done later.
+        let mut bytes = [0u8; 32];
+        if csprng.try_fill_bytes(&mut bytes).is_err() {
+            // The RNG may have filled part of the buffer before failing.
+            #[cfg(feature = "zeroize")]
+            bytes.zeroize();
+            return Err(Error::Rng);
+        }
+        Ok(EphemeralSecret(bytes))
+    }
+
     /// Generate a new [`EphemeralSecret`].
     #[cfg(feature = "getrandom")]
     pub fn random() -> Self {
-        Self::random_from_rng(&mut rand_core::OsRng)
+        Self::random_from_rng(rand_core::OsRng)
     }
 }
 
 impl<'a> From<&'a EphemeralSecret> for PublicKey {
     /// Given an x25519 [`EphemeralSecret`] key, compute its corresponding [`PublicKey`].
     fn from(secret: &'a EphemeralSecret) -> PublicKey {
-        PublicKey(EdwardsPoint::mul_base_clamped(secret.0).to_montgomery())
+        PublicKey(mul_base_clamped(secret.0))
     }
 }
 
@@ -144,6 +356,23 @@ impl ReusableSecret {
         SharedSecret(their_public.0.mul_clamped(self.0))
     }
 
+    /// Perform a Diffie-Hellman key agreement between `self` and
+    /// `their_public` key, writing the shared secret into `out`, such as a
+    /// buffer in secure or DMA-accessible memory.
+    ///
+    /// The result is the same as [`ReusableSecret::diffie_hellman`].  An all-zero
+    /// result is not contributory, as described at
+    /// [`SharedSecret::was_contributory`].  The compiler may still hold the
+    /// result in registers or on the stack on its way to `out`.
+    pub fn diffie_hellman_into(&self, their_public: &PublicKey, out: &mut [u8; 32]) {
+        *out = their_public.0.mul_clamped(self.0).0;
+    }
+
+    /// Compute the public key of `self`, writing it into `out`.
+    pub fn public_key_into(&self, out: &mut [u8; 32]) {
+        *out = mul_base_clamped(self.0).0;
+    }
+
     /// Generate a new [`ReusableSecret`] with the supplied RNG.
     #[deprecated(
         since = "2.0.0",
@@ -161,10 +390,24 @@ impl ReusableSecret {
         ReusableSecret(bytes)
     }
 
+    /// Generate a new [`ReusableSecret`] with the supplied RNG, returning an
+    /// error instead of panicking if the RNG fails.
+    pub fn try_random_from_rng<T: RngCore + CryptoRng>(mut csprng: T) -> Result<Self, Error> {
+        // The secret key is random bytes. Clamping is done later.
+        let mut bytes = [0u8; 32];
+        if csprng.try_fill_bytes(&mut bytes).is_err() {
+            // The RNG may have filled part of the buffer before failing.
+            #[cfg(feature = "zeroize")]
+            bytes.zeroize();
+            return Err(Error::Rng);
+        }
+        Ok(ReusableSecret(bytes))
+    }
+
     /// Generate a new [`ReusableSecret`].
     #[cfg(feature = "getrandom")]
     pub fn random() -> Self {
-        Self::random_from_rng(&mut rand_core::OsRng)
+        Self::random_from_rng(rand_core::OsRng)
     }
 }
 
@@ -172,7 +415,7 @@ impl ReusableSecret {
 impl<'a> From<&'a ReusableSecret> for PublicKey {
     /// Given an x25519 [`ReusableSecret`] key, compute its corresponding [`PublicKey`].
     fn from(secret: &'a ReusableSecret) -> PublicKey {
-        PublicKey(EdwardsPoint::mul_base_clamped(secret.0).to_montgomery())
+        PublicKey(mul_base_clamped(secret.0))
     }
 }
 
@@ -191,11 +434,10 @@ impl<'a> From<&'a ReusableSecret> for PublicKey {
 /// secret keys are never reused, which can have very serious security
 /// implications for many protocols.
 #[cfg(feature = "static_secrets")]
-#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[cfg_attr(feature = "zeroize", derive(Zeroize))]
 #[cfg_attr(feature = "zeroize", zeroize(drop))]
 #[derive(Clone)]
-pub struct StaticSecret([u8; 32]);
+pub struct StaticSecret(pub(crate) [u8; 32]);
 
 #[cfg(feature = "static_secrets")]
 impl StaticSecret {
@@ -205,6 +447,23 @@ impl StaticSecret {
         SharedSecret(their_public.0.mul_clamped(self.0))
     }
 
+    /// Perform a Diffie-Hellman key agreement between `self` and
+    /// `their_public` key, writing the shared secret into `out`, such as a
+    /// buffer in secure or DMA-accessible memory.
+    ///
+    /// The result is the same as [`StaticSecret::diffie_hellman`].  An all-zero
+    /// result is not contributory, as described at
+    /// [`SharedSecret::was_contributory`].  The compiler may still hold the
+    /// result in registers or on the stack on its way to `out`.
+    pub fn diffie_hellman_into(&self, their_public: &PublicKey, out: &mut [u8; 32]) {
+        *out = their_public.0.mul_clamped(self.0).0;
+    }
+
+    /// Compute the public key of `self`, writing it into `out`.
+    pub fn public_key_into(&self, out: &mut [u8; 32]) {
+        *out = mul_base_clamped(self.0).0;
+    }
+
     /// Generate a new [`StaticSecret`] with the supplied RNG.
     #[deprecated(
         since = "2.0.0",
@@ -222,10 +481,24 @@ impl StaticSecret {
         StaticSecret(bytes)
     }
 
+    /// Generate a new [`StaticSecret`] with the supplied RNG, returning an
+    /// error instead of panicking if the RNG fails.
+    pub fn try_random_from_rng<T: RngCore + CryptoRng>(mut csprng: T) -> Result<Self, Error> {
+        // The secret key is random bytes. Clamping is done later.
+        let mut bytes = [0u8; 32];
+        if csprng.try_fill_bytes(&mut bytes).is_err() {
+            // The RNG may have filled part of the buffer before failing.
+            #[cfg(feature = "zeroize")]
+            bytes.zeroize();
+            return Err(Error::Rng);
+        }
+        Ok(StaticSecret(bytes))
+    }
+
     /// Generate a new [`StaticSecret`].
     #[cfg(feature = "getrandom")]
     pub fn random() -> Self {
-        Self::random_from_rng(&mut rand_core::OsRng)
+        Self::random_from_rng(rand_core::OsRng)
     }
 
     /// Extract this key's bytes for serialization.
@@ -249,11 +522,26 @@ impl From<[u8; 32]> for StaticSecret {
     }
 }
 
+#[cfg(feature = "static_secrets")]
+impl TryFrom<&[u8]> for StaticSecret {
+    type Error = Error;
+
+    /// Load a secret key from a slice of exactly 32 bytes.
+    fn try_from(bytes: &[u8]) -> Result<StaticSecret, Error> {
+        <[u8; 32]>::try_from(bytes)
+            .map(StaticSecret::from)
+            .map_err(|_| Error::InvalidLength {
+                expected: 32,
+                actual: bytes.len(),
+            })
+    }
+}
+
 #[cfg(feature = "static_secrets")]
 impl<'a> From<&'a StaticSecret> for PublicKey {
     /// Given an x25519 [`StaticSecret`] key, compute its corresponding [`PublicKey`].
     fn from(secret: &'a StaticSecret) -> PublicKey {
-        PublicKey(EdwardsPoint::mul_base_clamped(secret.0).to_montgomery())
+        PublicKey(mul_base_clamped(secret.0))
     }
 }
 
@@ -266,6 +554,149 @@ impl AsRef<[u8]> for StaticSecret {
     }
 }
 
+/// A [`StaticSecret`] together with its [`PublicKey`], which is computed
+/// once when the key pair is created rather than on every use.
+///
+/// This suits long-lived server secrets whose public key is sent in every
+/// handshake.
+#[cfg(feature = "static_secrets")]
+#[derive(Clone)]
+pub struct Keypair {
+    secret: StaticSecret,
+    public: PublicKey,
+}
+
+#[cfg(feature = "static_secrets")]
+impl Keypair {
+    /// Generate a new [`Keypair`] with the supplied RNG.
+    pub fn random_from_rng<T: RngCore + CryptoRng>(csprng: T) -> Self {
+        Keypair::from(StaticSecret::random_from_rng(csprng))
+    }
+
+    /// Generate a new [`Keypair`].
+    #[cfg(feature = "getrandom")]
+    pub fn random() -> Self {
+        Keypair::from(StaticSecret::random())
+    }
+
+    /// Generate a new [`Keypair`] with the supplied RNG, and check it with a
+    /// pairwise consistency test, as some certification regimes require of
+    /// key generation.
+    ///
+    /// The test performs a Diffie-Hellman key agreement between the new key
+    /// pair and a fresh [`EphemeralSecret`], from both sides, and returns
+    /// [`Error::PairwiseConsistency`] if the two shared secrets differ or are
+    /// all zeros.  It returns [`Error::Rng`] if the RNG fails.
+    pub fn random_checked_from_rng<T: RngCore + CryptoRng>(mut csprng: T) -> Result<Self, Error> {
+        let keypair = Keypair::from(StaticSecret::try_random_from_rng(&mut csprng)?);
+        let ephemeral = EphemeralSecret::try_random_from_rng(&mut csprng)?;
+        let ephemeral_public = PublicKey::from(&ephemeral);
+
+        let ours = keypair.diffie_hellman(&ephemeral_public);
+        let theirs = ephemeral.diffie_hellman(&keypair.public);
+        if !bool::from(ours.ct_eq(&theirs)) || !ours.was_contributory() {
+            return Err(Error::PairwiseConsistency);
+        }
+        Ok(keypair)
+    }
+
+    /// Generate a new [`Keypair`], and check it with a pairwise consistency
+    /// test, as [`random_checked_from_rng`](Keypair::random_checked_from_rng).
+    #[cfg(feature = "getrandom")]
+    pub fn random_checked() -> Result<Self, Error> {
+        Self::random_checked_from_rng(rand_core::OsRng)
+    }
+
+    /// The secret key.
+    #[inline]
+    pub fn secret(&self) -> &StaticSecret {
+        &self.secret
+    }
+
+    /// The public key of the secret key.
+    #[inline]
+    pub fn public_key(&self) -> &PublicKey {
+        &self.public
+    }
+
+    /// Perform a Diffie-Hellman key agreement between the secret key and
+    /// `their_public` key to produce a `SharedSecret`.
+    pub fn diffie_hellman(&self, their_public: &PublicKey) -> SharedSecret {
+        self.secret.diffie_hellman(their_public)
+    }
+}
+
+#[cfg(feature = "static_secrets")]
+impl From<StaticSecret> for Keypair {
+    /// Compute the public key of `secret`.
+    fn from(secret: StaticSecret) -> Keypair {
+        let public = PublicKey::from(&secret);
+        Keypair { secret, public }
+    }
+}
+
+#[cfg(feature = "static_secrets")]
+impl<'a> From<&'a Keypair> for PublicKey {
+    /// The cached public key of the key pair.
+    fn from(keypair: &'a Keypair) -> PublicKey {
+        keypair.public
+    }
+}
+
+/// A secret key which can perform X25519 key agreements, wherever it is
+/// held.
+///
+/// This is implemented by [`StaticSecret`], [`ReusableSecret`] and
+/// [`Keypair`], and can be implemented for secret keys held outside of
+/// memory, such as in a hardware token, which construct their
+/// [`SharedSecret`]s with `SharedSecret::from`.  The higher-level protocols
+/// of this crate which take a long-term secret key, such as HPKE's
+/// decapsulation and ECIES's `open`, accept any `X25519Provider`.
+pub trait X25519Provider {
+    /// The public key of this secret key.
+    fn public(&self) -> PublicKey;
+
+    /// Perform a Diffie-Hellman key agreement between this secret key and
+    /// `their_public` key.
+    ///
+    /// The secret keys of this crate never fail, but those held elsewhere
+    /// may, with [`Error::Provider`].
+    fn diffie_hellman(&self, their_public: &PublicKey) -> Result<SharedSecret, Error>;
+}
+
+#[cfg(feature = "static_secrets")]
+impl X25519Provider for StaticSecret {
+    fn public(&self) -> PublicKey {
+        PublicKey::from(self)
+    }
+
+    fn diffie_hellman(&self, their_public: &PublicKey) -> Result<SharedSecret, Error> {
+        Ok(StaticSecret::diffie_hellman(self, their_public))
+    }
+}
+
+#[cfg(feature = "reusable_secrets")]
+impl X25519Provider for ReusableSecret {
+    fn public(&self) -> PublicKey {
+        PublicKey::from(self)
+    }
+
+    fn diffie_hellman(&self, their_public: &PublicKey) -> Result<SharedSecret, Error> {
+        Ok(ReusableSecret::diffie_hellman(self, their_public))
+    }
+}
+
+#[cfg(feature = "static_secrets")]
+impl X25519Provider for Keypair {
+    fn public(&self) -> PublicKey {
+        self.public
+    }
+
+    fn diffie_hellman(&self, their_public: &PublicKey) -> Result<SharedSecret, Error> {
+        Ok(Keypair::diffie_hellman(self, their_public))
+    }
+}
+
 /// The result of a Diffie-Hellman key exchange.
 ///
 /// Each party computes this using their [`EphemeralSecret`] or [`StaticSecret`] and their
@@ -326,6 +757,30 @@ impl SharedSecret {
     }
 }
 
+impl From<[u8; 32]> for SharedSecret {
+    /// Wrap the result of a key agreement performed elsewhere, for
+    /// implementations of [`X25519Provider`].
+    fn from(bytes: [u8; 32]) -> SharedSecret {
+        SharedSecret(MontgomeryPoint(bytes))
+    }
+}
+
+impl ConstantTimeEq for SharedSecret {
+    /// Compare two shared secrets in constant time.
+    fn ct_eq(&self, other: &SharedSecret) -> Choice {
+        self.as_bytes().ct_eq(other.as_bytes())
+    }
+}
+
+impl PartialEq for SharedSecret {
+    /// Compare two shared secrets in constant time.
+    fn eq(&self, other: &SharedSecret) -> bool {
+        self.ct_eq(other).into()
+    }
+}
+
+impl Eq for SharedSecret {}
+
 impl AsRef<[u8]> for SharedSecret {
     /// View this shared secret key as a byte array.
     #[inline]
@@ -334,6 +789,120 @@ impl AsRef<[u8]> for SharedSecret {
     }
 }
 
+// ------------------------------------------------------------------------
+// Serde support
+// ------------------------------------------------------------------------
+// Keys are serialized as a fixed-size tuple of 32 bytes rather than as a
+// byte string, so that binary codecs such as bincode and postcard emit
+// exactly 32 bytes with no length prefix, and deserialization never needs
+// to allocate.
+
+#[cfg(feature = "serde")]
+use serde::de::{Deserialize, Deserializer, SeqAccess, Visitor};
+#[cfg(feature = "serde")]
+use serde::ser::{Serialize, SerializeTuple, Serializer};
+
+#[cfg(feature = "serde")]
+fn serialize_bytes<S>(bytes: &[u8; 32], serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    let mut tup = serializer.serialize_tuple(32)?;
+    for byte in bytes.iter() {
+        tup.serialize_element(byte)?;
+    }
+    tup.end()
+}
+
+#[cfg(feature = "serde")]
+fn deserialize_bytes<'de, D>(deserializer: D) -> Result<[u8; 32], D::Error>
+where
+    D: Deserializer<'de>,
+{
+    struct BytesVisitor;
+
+    impl<'de> Visitor<'de> for BytesVisitor {
+        type Value = [u8; 32];
+
+        fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
+            formatter.write_str("32 bytes of data")
+        }
+
+        fn visit_seq<A>(self, mut seq: A) -> Result<[u8; 32], A::Error>
+        where
+            A: SeqAccess<'de>,
+        {
+            let mut bytes = [0u8; 32];
+            #[allow(clippy::needless_range_loop)]
+            for i in 0..32 {
+                bytes[i] = seq
+                    .next_element()?
+                    .ok_or_else(|| serde::de::Error::invalid_length(i, &"expected 32 bytes"))?;
+            }
+            Ok(bytes)
+        }
+    }
+
+    deserializer.deserialize_tuple(32, BytesVisitor)
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for PublicKey {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serialize_bytes(self.as_bytes(), serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for PublicKey {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserialize_bytes(deserializer).map(PublicKey::from)
+    }
+}
+
+#[cfg(all(feature = "serde", feature = "static_secrets"))]
+impl Serialize for StaticSecret {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serialize_bytes(self.as_bytes(), serializer)
+    }
+}
+
+#[cfg(all(feature = "serde", feature = "static_secrets"))]
+impl<'de> Deserialize<'de> for StaticSecret {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserialize_bytes(deserializer).map(StaticSecret::from)
+    }
+}
+
+/// Multiply the basepoint by the clamped scalar `bytes`.
+///
+/// This uses the Edwards basepoint table of `curve25519-dalek` when it has
+/// one, and with the `small-base` feature the Montgomery ladder over the
+/// basepoint, which is several times slower but links in no code beyond the
+/// ladder already used for Diffie-Hellman.
+#[cfg(not(feature = "small-base"))]
+pub(crate) fn mul_base_clamped(bytes: [u8; 32]) -> MontgomeryPoint {
+    EdwardsPoint::mul_base_clamped(bytes).to_montgomery()
+}
+
+/// Multiply the basepoint by the clamped scalar `bytes`.
+#[cfg(feature = "small-base")]
+pub(crate) fn mul_base_clamped(bytes: [u8; 32]) -> MontgomeryPoint {
+    MontgomeryPoint(X25519_BASEPOINT_BYTES).mul_clamped(bytes)
+}
+
 /// The bare, byte-oriented x25519 function, exactly as specified in RFC7748.
 ///
 /// This can be used with [`X25519_BASEPOINT_BYTES`] for people who
@@ -0,0 +1,128 @@
+// -*- mode: rust; -*-
+//
+// This file is part of x25519-dalek.
+// Copyright (c) 2017-2021 isis lovecruft
+// Copyright (c) 2019-2021 DebugSteven
+// See LICENSE for licensing information.
+//
+// Authors:
+// - isis agora lovecruft <isis@patternsinthevoid.net>
+// - DebugSteven <debugsteven@gmail.com>
+
+//! The wire format of the X25519MLKEM768 hybrid key exchange for TLS 1.3, as
+//! specified in
+//! [draft-ietf-tls-ecdhe-mlkem](https://datatracker.ietf.org/doc/draft-ietf-tls-ecdhe-mlkem/).
+//!
+//! This crate does not implement ML-KEM.  This module only concatenates and
+//! splits the key shares and shared secrets, so that an X25519 exchange from
+//! this crate can be combined with an ML-KEM-768 implementation from
+//! elsewhere.  In every case the ML-KEM component comes first:
+//!
+//! - the client's key share is the ML-KEM-768 encapsulation key followed by
+//!   the client's X25519 public key;
+//! - the server's key share is the ML-KEM-768 ciphertext followed by the
+//!   server's X25519 public key;
+//! - the shared secret is the ML-KEM-768 shared secret followed by the X25519
+//!   shared secret.
+
+use zeroize::Zeroizing;
+
+use crate::error::Error;
+use crate::x25519::{PublicKey, SharedSecret};
+
+/// The TLS `NamedGroup` code point of X25519MLKEM768.
+pub const NAMED_GROUP: u16 = 0x11ec;
+
+/// The length of an ML-KEM-768 encapsulation key.
+pub const MLKEM768_ENCAPSULATION_KEY_LEN: usize = 1184;
+
+/// The length of an ML-KEM-768 ciphertext.
+pub const MLKEM768_CIPHERTEXT_LEN: usize = 1088;
+
+/// The length of an ML-KEM-768 shared secret.
+pub const MLKEM768_SHARED_SECRET_LEN: usize = 32;
+
+/// The length of the client's key share.
+pub const CLIENT_SHARE_LEN: usize = MLKEM768_ENCAPSULATION_KEY_LEN + 32;
+
+/// The length of the server's key share.
+pub const SERVER_SHARE_LEN: usize = MLKEM768_CIPHERTEXT_LEN + 32;
+
+/// The length of the combined shared secret.
+pub const SHARED_SECRET_LEN: usize = MLKEM768_SHARED_SECRET_LEN + 32;
+
+/// Encode the client's key share from its ML-KEM-768 encapsulation key and
+/// X25519 public key.
+pub fn client_key_share(
+    encapsulation_key: &[u8; MLKEM768_ENCAPSULATION_KEY_LEN],
+    public: &PublicKey,
+) -> [u8; CLIENT_SHARE_LEN] {
+    let mut share = [0u8; CLIENT_SHARE_LEN];
+    share[..MLKEM768_ENCAPSULATION_KEY_LEN].copy_from_slice(encapsulation_key);
+    share[MLKEM768_ENCAPSULATION_KEY_LEN..].copy_from_slice(public.as_bytes());
+    share
+}
+
+/// Decode the client's key share into its ML-KEM-768 encapsulation key and
+/// X25519 public key.
+pub fn parse_client_key_share(
+    share: &[u8],
+) -> Result<(&[u8; MLKEM768_ENCAPSULATION_KEY_LEN], PublicKey), Error> {
+    let (encapsulation_key, public) = split(share, CLIENT_SHARE_LEN)?;
+    Ok((encapsulation_key.try_into().unwrap(), public))
+}
+
+/// Encode the server's key share from its ML-KEM-768 ciphertext and X25519
+/// public key.
+pub fn server_key_share(
+    ciphertext: &[u8; MLKEM768_CIPHERTEXT_LEN],
+    public: &PublicKey,
+) -> [u8; SERVER_SHARE_LEN] {
+    let mut share = [0u8; SERVER_SHARE_LEN];
+    share[..MLKEM768_CIPHERTEXT_LEN].copy_from_slice(ciphertext);
+    share[MLKEM768_CIPHERTEXT_LEN..].copy_from_slice(public.as_bytes());
+    share
+}
+
+/// Decode the server's key share into its ML-KEM-768 ciphertext and X25519
+/// public key.
+pub fn parse_server_key_share(
+    share: &[u8],
+) -> Result<(&[u8; MLKEM768_CIPHERTEXT_LEN], PublicKey), Error> {
+    let (ciphertext, public) = split(share, SERVER_SHARE_LEN)?;
+    Ok((ciphertext.try_into().unwrap(), public))
+}
+
+/// Concatenate the ML-KEM-768 and X25519 shared secrets into the shared
+/// secret of the hybrid group.
+///
+/// As TLS requires for X25519, an all-zero X25519 shared secret is rejected
+/// with [`Error::SmallOrderPoint`].
+pub fn combine_shared_secrets(
+    mlkem_shared_secret: &[u8; MLKEM768_SHARED_SECRET_LEN],
+    x25519_shared_secret: &SharedSecret,
+) -> Result<Zeroizing<[u8; SHARED_SECRET_LEN]>, Error> {
+    if !x25519_shared_secret.was_contributory() {
+        return Err(Error::SmallOrderPoint);
+    }
+    let mut shared = Zeroizing::new([0u8; SHARED_SECRET_LEN]);
+    shared[..MLKEM768_SHARED_SECRET_LEN].copy_from_slice(mlkem_shared_secret);
+    shared[MLKEM768_SHARED_SECRET_LEN..].copy_from_slice(x25519_shared_secret.as_bytes());
+    Ok(shared)
+}
+
+/// Split a key share of length `len` into its ML-KEM component and the
+/// trailing X25519 public key.
+fn split(share: &[u8], len: usize) -> Result<(&[u8], PublicKey), Error> {
+    if share.len() != len {
+        return Err(Error::InvalidLength {
+            expected: len,
+            actual: share.len(),
+        });
+    }
+    let (mlkem, x25519) = share.split_at(len - 32);
+    Ok((
+        mlkem,
+        PublicKey::from(<[u8; 32]>::try_from(x25519).unwrap()),
+    ))
+}
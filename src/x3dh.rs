@@ -0,0 +1,208 @@
+// -*- mode: rust; -*-
+//
+// This file is part of x25519-dalek.
+// Copyright (c) 2017-2021 isis lovecruft
+// Copyright (c) 2019-2021 DebugSteven
+// See LICENSE for licensing information.
+//
+// Authors:
+// - isis agora lovecruft <isis@patternsinthevoid.net>
+// - DebugSteven <debugsteven@gmail.com>
+
+//! The Extended Triple Diffie-Hellman (X3DH) key agreement of the Signal
+//! protocol, as specified in
+//! [The X3DH Key Agreement Protocol](https://signal.org/docs/specifications/x3dh/),
+//! with X25519 and SHA-256.
+//!
+//! The responder, Bob, publishes a [`PreKeyBundle`] of their identity key, a
+//! signed prekey, and optionally a one-time prekey.  The initiator, Alice,
+//! computes a shared key from the bundle and sends Bob an
+//! [`InitialMessage`], from which Bob computes the same shared key:
+//!
+#![cfg_attr(feature = "getrandom", doc = "```")]
+#![cfg_attr(not(feature = "getrandom"), doc = "```ignore")]
+//! use rand_core::OsRng;
+//! use x25519_dalek::{x3dh, PublicKey, StaticSecret};
+//!
+//! let alice_identity = StaticSecret::random();
+//! let bob_identity = StaticSecret::random();
+//! let bob_signed_prekey = StaticSecret::random();
+//! let bob_one_time_prekey = StaticSecret::random();
+//!
+//! let bundle = x3dh::PreKeyBundle {
+//!     identity_key: PublicKey::from(&bob_identity),
+//!     signed_prekey: PublicKey::from(&bob_signed_prekey),
+//!     one_time_prekey: Some(PublicKey::from(&bob_one_time_prekey)),
+//! };
+//! let (alice_key, message) = x3dh::initiate(&alice_identity, &bundle, b"my protocol", OsRng)?;
+//!
+//! let bob_key = x3dh::respond(
+//!     &bob_identity,
+//!     &bob_signed_prekey,
+//!     Some(&bob_one_time_prekey),
+//!     &message,
+//!     b"my protocol",
+//! )?;
+//! assert_eq!(alice_key, bob_key);
+//! # Ok::<(), x25519_dalek::Error>(())
+//! ```
+//!
+//! The signature on the signed prekey is made with XEdDSA over Bob's
+//! identity key, which this crate does not implement: Alice must verify it
+//! before calling [`initiate`].  Likewise, Bob must look up the one-time
+//! prekey named in the [`InitialMessage`] and delete it after use.
+
+use core::fmt;
+
+use hkdf::Hkdf;
+use rand_core::{CryptoRng, RngCore};
+use sha2::Sha256;
+use zeroize::Zeroizing;
+
+use crate::x25519::{PublicKey, SharedSecret, StaticSecret};
+
+/// The length of the shared key.
+pub const SHARED_KEY_LEN: usize = 32;
+
+/// The length of the associated data.
+pub const ASSOCIATED_DATA_LEN: usize = 64;
+
+/// Errors which may occur in the key agreement.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// The initial message names a one-time prekey, but none was given, or
+    /// the other way around.
+    OneTimePrekeyMismatch,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::OneTimePrekeyMismatch => {
+                f.write_str("one-time prekey does not match the initial message")
+            }
+        }
+    }
+}
+
+/// The responder's published keys.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct PreKeyBundle {
+    /// The responder's identity key, `IK_B`.
+    pub identity_key: PublicKey,
+    /// The responder's signed prekey, `SPK_B`, whose signature the
+    /// initiator must have verified.
+    pub signed_prekey: PublicKey,
+    /// One of the responder's one-time prekeys, `OPK_B`, if any remain.
+    pub one_time_prekey: Option<PublicKey>,
+}
+
+/// The public keys the initiator sends to the responder with its first
+/// message.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct InitialMessage {
+    /// The initiator's identity key, `IK_A`.
+    pub identity_key: PublicKey,
+    /// The initiator's ephemeral key, `EK_A`.
+    pub ephemeral_key: PublicKey,
+    /// The responder's signed prekey used by the initiator.
+    pub signed_prekey: PublicKey,
+    /// The responder's one-time prekey used by the initiator, if any.
+    pub one_time_prekey: Option<PublicKey>,
+}
+
+/// Compute the shared key with the responder's `bundle`, using the
+/// initiator's `identity` key and an ephemeral key from `csprng`.
+///
+/// Returns the shared key and the message to send to the responder.  The
+/// `info` identifies the application, and must be the same for both
+/// parties.
+pub fn initiate<T: RngCore + CryptoRng>(
+    identity: &StaticSecret,
+    bundle: &PreKeyBundle,
+    info: &[u8],
+    csprng: T,
+) -> Result<(Zeroizing<[u8; SHARED_KEY_LEN]>, InitialMessage), crate::Error> {
+    let ephemeral = StaticSecret::random_from_rng(csprng);
+    let key = shared_key(
+        identity.diffie_hellman(&bundle.signed_prekey),
+        ephemeral.diffie_hellman(&bundle.identity_key),
+        ephemeral.diffie_hellman(&bundle.signed_prekey),
+        bundle
+            .one_time_prekey
+            .as_ref()
+            .map(|one_time_prekey| ephemeral.diffie_hellman(one_time_prekey)),
+        info,
+    )?;
+    let message = InitialMessage {
+        identity_key: PublicKey::from(identity),
+        ephemeral_key: PublicKey::from(&ephemeral),
+        signed_prekey: bundle.signed_prekey,
+        one_time_prekey: bundle.one_time_prekey,
+    };
+    Ok((key, message))
+}
+
+/// Compute the shared key from the initiator's `message`, using the
+/// responder's `identity` key, `signed_prekey`, and the `one_time_prekey`
+/// named in the message, if any.
+pub fn respond(
+    identity: &StaticSecret,
+    signed_prekey: &StaticSecret,
+    one_time_prekey: Option<&StaticSecret>,
+    message: &InitialMessage,
+    info: &[u8],
+) -> Result<Zeroizing<[u8; SHARED_KEY_LEN]>, crate::Error> {
+    if one_time_prekey.is_some() != message.one_time_prekey.is_some() {
+        return Err(Error::OneTimePrekeyMismatch.into());
+    }
+    shared_key(
+        signed_prekey.diffie_hellman(&message.identity_key),
+        identity.diffie_hellman(&message.ephemeral_key),
+        signed_prekey.diffie_hellman(&message.ephemeral_key),
+        one_time_prekey
+            .map(|one_time_prekey| one_time_prekey.diffie_hellman(&message.ephemeral_key)),
+        info,
+    )
+}
+
+/// The associated data `AD`: the initiator's identity key followed by the
+/// responder's.
+///
+/// The X3DH specification encodes each identity key with a prefix byte
+/// naming the curve; this is the raw encoding, without it.
+pub fn associated_data(
+    initiator_identity: &PublicKey,
+    responder_identity: &PublicKey,
+) -> [u8; ASSOCIATED_DATA_LEN] {
+    let mut ad = [0u8; ASSOCIATED_DATA_LEN];
+    ad[..32].copy_from_slice(initiator_identity.as_bytes());
+    ad[32..].copy_from_slice(responder_identity.as_bytes());
+    ad
+}
+
+/// The X3DH `KDF`: HKDF-SHA256 of 32 `0xff` bytes followed by the
+/// Diffie-Hellman results, with an all-zero salt.
+fn shared_key(
+    dh1: SharedSecret,
+    dh2: SharedSecret,
+    dh3: SharedSecret,
+    dh4: Option<SharedSecret>,
+    info: &[u8],
+) -> Result<Zeroizing<[u8; SHARED_KEY_LEN]>, crate::Error> {
+    let mut ikm = Zeroizing::new([0xffu8; 5 * 32]);
+    let mut len = 32;
+    for dh in [Some(dh1), Some(dh2), Some(dh3), dh4].iter().flatten() {
+        if !dh.was_contributory() {
+            return Err(crate::Error::SmallOrderPoint);
+        }
+        ikm[len..len + 32].copy_from_slice(dh.as_bytes());
+        len += 32;
+    }
+
+    let mut key = Zeroizing::new([0u8; SHARED_KEY_LEN]);
+    Hkdf::<Sha256>::new(Some(&[0u8; 32]), &ikm[..len])
+        .expand(info, &mut key[..])
+        .expect("32 bytes is a valid HKDF-SHA256 output length");
+    Ok(key)
+}
@@ -0,0 +1,81 @@
+//! age recipient and identity encoding tests.
+
+#![cfg(feature = "age")]
+
+use x25519_dalek::{AgeKeyError, Error, PublicKey};
+
+#[cfg(feature = "static_secrets")]
+use x25519_dalek::StaticSecret;
+
+/// Alice's public key from RFC7748 section 6.1, as an age recipient.
+const ALICE_RECIPIENT: &str = "age1s5s0qzvfxzn4gayt0hwtg0hhtgxm7wsdycup4a8t5j5ca25mfe4qt4hs7q";
+
+/// Alice's private key from RFC7748 section 6.1, as an age identity.
+#[cfg(feature = "static_secrets")]
+const ALICE_IDENTITY: &str =
+    "AGE-SECRET-KEY-1WURK6ZNNRZJH60QKC9E9RVNXGH05CTU8A0QFJ243WLA628DE9S4QRFH26J";
+
+/// Alice's public key, from RFC7748 section 6.1.
+const ALICE_PUBLIC: [u8; 32] = [
+    0x85, 0x20, 0xf0, 0x09, 0x89, 0x30, 0xa7, 0x54, 0x74, 0x8b, 0x7d, 0xdc, 0xb4, 0x3e, 0xf7, 0x5a,
+    0x0d, 0xbf, 0x3a, 0x0d, 0x26, 0x38, 0x1a, 0xf4, 0xeb, 0xa4, 0xa9, 0x8e, 0xaa, 0x9b, 0x4e, 0x6a,
+];
+
+#[test]
+fn public_key_age_recipient_roundtrip() {
+    let public = PublicKey::from_age_recipient(ALICE_RECIPIENT).unwrap();
+    assert_eq!(public.to_bytes(), ALICE_PUBLIC);
+    assert_eq!(public.to_age_recipient(), ALICE_RECIPIENT);
+}
+
+#[test]
+fn public_key_age_recipient_rejects_wrong_case() {
+    assert_eq!(
+        PublicKey::from_age_recipient(&ALICE_RECIPIENT.to_uppercase()),
+        Err(Error::Age(AgeKeyError::InvalidPrefix))
+    );
+}
+
+#[test]
+fn public_key_age_recipient_rejects_bad_checksum() {
+    let corrupted = ALICE_RECIPIENT.replace("s7q", "s7p");
+    assert_eq!(
+        PublicKey::from_age_recipient(&corrupted),
+        Err(Error::Age(AgeKeyError::InvalidEncoding))
+    );
+}
+
+#[test]
+fn public_key_age_recipient_rejects_short_keys() {
+    // 31 zero bytes.
+    let short = "age1qqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqar9jk6";
+    assert_eq!(
+        PublicKey::from_age_recipient(short),
+        Err(Error::Age(AgeKeyError::InvalidLength))
+    );
+}
+
+#[test]
+#[cfg(feature = "static_secrets")]
+fn static_secret_age_identity_roundtrip() {
+    let secret = StaticSecret::from_age_identity(ALICE_IDENTITY).unwrap();
+    assert_eq!(*secret.to_age_identity(), ALICE_IDENTITY);
+    assert_eq!(PublicKey::from(&secret).to_age_recipient(), ALICE_RECIPIENT);
+}
+
+#[test]
+#[cfg(feature = "static_secrets")]
+fn static_secret_age_identity_rejects_recipients() {
+    assert_eq!(
+        StaticSecret::from_age_identity(&ALICE_IDENTITY.to_lowercase()).err(),
+        Some(Error::Age(AgeKeyError::InvalidPrefix))
+    );
+    assert_eq!(
+        StaticSecret::from_age_identity(ALICE_RECIPIENT).err(),
+        Some(Error::Age(AgeKeyError::InvalidPrefix))
+    );
+    assert_eq!(
+        PublicKey::from_age_recipient(ALICE_IDENTITY),
+        Err(Error::Age(AgeKeyError::InvalidPrefix))
+    );
+}
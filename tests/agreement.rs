@@ -0,0 +1,59 @@
+//! `ring::agreement`-style API tests.
+
+#![cfg(feature = "agreement")]
+
+mod common;
+
+use common::{hex32, ConstRng};
+use x25519_dalek::agreement::{
+    agree_ephemeral, EphemeralPrivateKey, UnparsedPublicKey, Unspecified, X25519,
+};
+
+fn copy(shared: &[u8]) -> Result<[u8; 32], Unspecified> {
+    shared.try_into().map_err(|_| Unspecified)
+}
+
+#[test]
+fn rfc7748_diffie_hellman() {
+    let alice = hex32("77076d0a7318a57d3c16c17251b26645df4c2f87ebc0992ab177fba51db92c2a");
+    let alice = EphemeralPrivateKey::generate(&X25519, ConstRng(alice)).unwrap();
+    assert_eq!(
+        alice.compute_public_key().unwrap().as_ref(),
+        hex32("8520f0098930a754748b7ddcb43ef75a0dbf3a0d26381af4eba4a98eaa9b4e6a")
+    );
+
+    let bob_public = hex32("de9edb7d7b7dc1b4d35b61c2ece435373f8343c85b78674dadfc7e146f882b4f");
+    let shared = agree_ephemeral(
+        alice,
+        &UnparsedPublicKey::new(&X25519, bob_public),
+        Unspecified,
+        copy,
+    );
+    assert_eq!(
+        shared,
+        Ok(hex32(
+            "4a5d9d5ba4ce2de1728e3bf480350f25e07e21c947d19e3376f09b3c1e161742"
+        ))
+    );
+}
+
+#[test]
+fn rejects_wrong_length() {
+    let key = EphemeralPrivateKey::generate(&X25519, ConstRng([0x11; 32])).unwrap();
+    let peer = UnparsedPublicKey::new(&X25519, [9u8; 31]);
+    assert_eq!(
+        agree_ephemeral(key, &peer, "length", copy_str),
+        Err("length")
+    );
+}
+
+#[test]
+fn rejects_small_order_points() {
+    let key = EphemeralPrivateKey::generate(&X25519, ConstRng([0x11; 32])).unwrap();
+    let peer = UnparsedPublicKey::new(&X25519, [0u8; 32]);
+    assert_eq!(agree_ephemeral(key, &peer, "zero", copy_str), Err("zero"));
+}
+
+fn copy_str(shared: &[u8]) -> Result<[u8; 32], &'static str> {
+    shared.try_into().map_err(|_| "kdf")
+}
@@ -0,0 +1,52 @@
+//! Base64 key encoding tests.
+
+#![cfg(feature = "base64")]
+
+use x25519_dalek::{Base64Error, Error, PublicKey};
+
+#[cfg(feature = "static_secrets")]
+use x25519_dalek::StaticSecret;
+
+/// Alice's private key from RFC7748 section 6.1, in base64.
+#[cfg(feature = "static_secrets")]
+const ALICE_PRIVATE_BASE64: &str = "dwdtCnMYpX08FsFyUbJmRd9ML4frwJkqsXf7pR25LCo=";
+
+/// Alice's public key from RFC7748 section 6.1, in base64.
+const ALICE_PUBLIC_BASE64: &str = "hSDwCYkwp1R0i33ctD73Wg2/Og0mOBr066SpjqqbTmo=";
+
+/// Alice's public key, from RFC7748 section 6.1.
+const ALICE_PUBLIC: [u8; 32] = [
+    0x85, 0x20, 0xf0, 0x09, 0x89, 0x30, 0xa7, 0x54, 0x74, 0x8b, 0x7d, 0xdc, 0xb4, 0x3e, 0xf7, 0x5a,
+    0x0d, 0xbf, 0x3a, 0x0d, 0x26, 0x38, 0x1a, 0xf4, 0xeb, 0xa4, 0xa9, 0x8e, 0xaa, 0x9b, 0x4e, 0x6a,
+];
+
+#[test]
+fn public_key_base64_roundtrip() {
+    let public = PublicKey::from_base64(ALICE_PUBLIC_BASE64).unwrap();
+    assert_eq!(public.to_bytes(), ALICE_PUBLIC);
+    assert_eq!(public.to_base64(), ALICE_PUBLIC_BASE64);
+}
+
+#[test]
+fn public_key_base64_rejects_invalid_input() {
+    assert_eq!(
+        PublicKey::from_base64(&ALICE_PUBLIC_BASE64[..43]),
+        Err(Error::Base64(Base64Error::InvalidLength))
+    );
+    assert_eq!(
+        PublicKey::from_base64(&ALICE_PUBLIC_BASE64.replace('/', "_")),
+        Err(Error::Base64(Base64Error::InvalidEncoding))
+    );
+    assert_eq!(
+        PublicKey::from_base64(&ALICE_PUBLIC_BASE64.replace("mo=", "mp=")),
+        Err(Error::Base64(Base64Error::InvalidEncoding))
+    );
+}
+
+#[test]
+#[cfg(feature = "static_secrets")]
+fn static_secret_base64_roundtrip() {
+    let secret = StaticSecret::from_base64(ALICE_PRIVATE_BASE64).unwrap();
+    assert_eq!(*secret.to_base64(), ALICE_PRIVATE_BASE64);
+    assert_eq!(PublicKey::from(&secret).to_base64(), ALICE_PUBLIC_BASE64);
+}
@@ -0,0 +1,75 @@
+//! Tests for batch Diffie-Hellman with a single field inversion.
+
+#![cfg(feature = "batch")]
+
+use x25519_dalek::{PublicKey, StaticSecret};
+
+fn secret(i: u16) -> StaticSecret {
+    let mut bytes = [0x5a; 32];
+    bytes[..2].copy_from_slice(&i.to_le_bytes());
+    StaticSecret::from(bytes)
+}
+
+#[test]
+fn diffie_hellman_batch_matches_single() {
+    let ours = secret(0xffff);
+    let mut publics: Vec<PublicKey> = (0..64).map(|i| PublicKey::from(&secret(i))).collect();
+    // Points of small order, whose products are the point at infinity or
+    // zero, between ordinary ones.
+    publics[3] = PublicKey::from([0; 32]);
+    publics[10] = PublicKey::from({
+        let mut one = [0; 32];
+        one[0] = 1;
+        one
+    });
+    publics[11] = PublicKey::from([
+        0xe0, 0xeb, 0x7a, 0x7c, 0x3b, 0x41, 0xb8, 0xae, 0x16, 0x56, 0xe3, 0xfa, 0xf1, 0x9f, 0xc4,
+        0x6a, 0xda, 0x09, 0x8d, 0xeb, 0x9c, 0x32, 0xb1, 0xfd, 0x86, 0x62, 0x05, 0x16, 0x5f, 0x49,
+        0xb8, 0x00,
+    ]);
+    // Non-canonical encodings, with the top bit set and at or above p.
+    publics[20] = PublicKey::from([0xff; 32]);
+    publics[21] = PublicKey::from({
+        let mut p = [0xff; 32];
+        p[0] = 0xed;
+        p[31] = 0x7f;
+        p
+    });
+    publics[63] = PublicKey::from([0; 32]);
+
+    let shared = ours.diffie_hellman_batch(&publics);
+    assert_eq!(shared.len(), publics.len());
+    for (shared, public) in shared.iter().zip(&publics) {
+        assert_eq!(shared.as_bytes(), ours.diffie_hellman(public).as_bytes());
+    }
+    assert_eq!(shared[3].as_bytes(), &[0; 32]);
+    assert_eq!(shared[11].as_bytes(), &[0; 32]);
+}
+
+#[test]
+fn diffie_hellman_batch_rfc7748_vector() {
+    let alice = StaticSecret::from([
+        0x77, 0x07, 0x6d, 0x0a, 0x73, 0x18, 0xa5, 0x7d, 0x3c, 0x16, 0xc1, 0x72, 0x51, 0xb2, 0x66,
+        0x45, 0xdf, 0x4c, 0x2f, 0x87, 0xeb, 0xc0, 0x99, 0x2a, 0xb1, 0x77, 0xfb, 0xa5, 0x1d, 0xb9,
+        0x2c, 0x2a,
+    ]);
+    let bob_public = PublicKey::from([
+        0xde, 0x9e, 0xdb, 0x7d, 0x7b, 0x7d, 0xc1, 0xb4, 0xd3, 0x5b, 0x61, 0xc2, 0xec, 0xe4, 0x35,
+        0x37, 0x3f, 0x83, 0x43, 0xc8, 0x5b, 0x78, 0x67, 0x4d, 0xad, 0xfc, 0x7e, 0x14, 0x6f, 0x88,
+        0x2b, 0x4f,
+    ]);
+    let expected = [
+        0x4a, 0x5d, 0x9d, 0x5b, 0xa4, 0xce, 0x2d, 0xe1, 0x72, 0x8e, 0x3b, 0xf4, 0x80, 0x35, 0x0f,
+        0x25, 0xe0, 0x7e, 0x21, 0xc9, 0x47, 0xd1, 0x9e, 0x33, 0x76, 0xf0, 0x9b, 0x3c, 0x1e, 0x16,
+        0x17, 0x42,
+    ];
+
+    let shared = alice.diffie_hellman_batch(&[bob_public]);
+    assert_eq!(shared.len(), 1);
+    assert_eq!(shared[0].as_bytes(), &expected);
+}
+
+#[test]
+fn diffie_hellman_batch_empty() {
+    assert!(secret(0).diffie_hellman_batch(&[]).is_empty());
+}
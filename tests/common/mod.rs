@@ -0,0 +1,73 @@
+//! Fixtures shared by the integration tests.
+
+// Each test crate uses only some of these.
+#![allow(dead_code)]
+
+use ::rand_core::{impls, CryptoRng, Error, RngCore};
+
+/// An RNG which always outputs the same `N` bytes, for deriving keys and
+/// nonces from known test vectors.
+pub struct ConstRng<const N: usize>(pub [u8; N]);
+
+impl<const N: usize> RngCore for ConstRng<N> {
+    fn next_u32(&mut self) -> u32 {
+        impls::next_u32_via_fill(self)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        impls::next_u64_via_fill(self)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        dest.copy_from_slice(&self.0[..dest.len()]);
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+impl<const N: usize> CryptoRng for ConstRng<N> {}
+
+/// An RNG which always fails.
+pub struct FailingRng;
+
+impl RngCore for FailingRng {
+    fn next_u32(&mut self) -> u32 {
+        impls::next_u32_via_fill(self)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        impls::next_u64_via_fill(self)
+    }
+
+    /// Panics with the error of `try_fill_bytes`, as infallible callers of
+    /// a failing RNG do.
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        if let Err(err) = self.try_fill_bytes(dest) {
+            panic!("FailingRng: {}", err);
+        }
+    }
+
+    fn try_fill_bytes(&mut self, _dest: &mut [u8]) -> Result<(), Error> {
+        Err(core::num::NonZeroU32::new(Error::CUSTOM_START)
+            .unwrap()
+            .into())
+    }
+}
+
+impl CryptoRng for FailingRng {}
+
+/// Decode a hex string.
+pub fn hex(s: &str) -> Vec<u8> {
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+/// Decode a hex string of 32 bytes.
+pub fn hex32(s: &str) -> [u8; 32] {
+    hex(s).try_into().unwrap()
+}
@@ -0,0 +1,183 @@
+//! COSE_Key encoding tests.
+
+#![cfg(feature = "cose")]
+
+use x25519_dalek::{CoseError, Error, PublicKey};
+
+#[cfg(feature = "static_secrets")]
+use x25519_dalek::StaticSecret;
+
+/// Alice's private key, from RFC7748 section 6.1.
+#[cfg(feature = "static_secrets")]
+const ALICE_PRIVATE: [u8; 32] = [
+    0x77, 0x07, 0x6d, 0x0a, 0x73, 0x18, 0xa5, 0x7d, 0x3c, 0x16, 0xc1, 0x72, 0x51, 0xb2, 0x66, 0x45,
+    0xdf, 0x4c, 0x2f, 0x87, 0xeb, 0xc0, 0x99, 0x2a, 0xb1, 0x77, 0xfb, 0xa5, 0x1d, 0xb9, 0x2c, 0x2a,
+];
+
+/// Alice's public key, from RFC7748 section 6.1.
+const ALICE_PUBLIC: [u8; 32] = [
+    0x85, 0x20, 0xf0, 0x09, 0x89, 0x30, 0xa7, 0x54, 0x74, 0x8b, 0x7d, 0xdc, 0xb4, 0x3e, 0xf7, 0x5a,
+    0x0d, 0xbf, 0x3a, 0x0d, 0x26, 0x38, 0x1a, 0xf4, 0xeb, 0xa4, 0xa9, 0x8e, 0xaa, 0x9b, 0x4e, 0x6a,
+];
+
+/// The COSE_Key header `{1: 1, -1: 4, -2: h'..'}` preceding the public key.
+const PUBLIC_HEADER: [u8; 8] = [0xa3, 0x01, 0x01, 0x20, 0x04, 0x21, 0x58, 0x20];
+
+/// Build a COSE_Key map from already-encoded entries.
+fn cose_key(entries: &[&[u8]]) -> Vec<u8> {
+    let mut out = vec![0xa0 | entries.len() as u8];
+    for entry in entries {
+        out.extend_from_slice(entry);
+    }
+    out
+}
+
+/// Encode the `x` parameter.
+fn x_param(x: &[u8; 32]) -> Vec<u8> {
+    [&[0x21, 0x58, 0x20][..], x].concat()
+}
+
+#[test]
+fn public_key_cose_roundtrip() {
+    let encoded = PublicKey::from(ALICE_PUBLIC).to_cose_key();
+    assert_eq!(encoded[..8], PUBLIC_HEADER);
+    assert_eq!(encoded[8..], ALICE_PUBLIC);
+
+    let decoded = PublicKey::from_cose_key(&encoded).unwrap();
+    assert_eq!(decoded.to_bytes(), ALICE_PUBLIC);
+}
+
+#[test]
+fn public_key_cose_accepts_reordered_and_unknown_parameters() {
+    let encoded = cose_key(&[
+        &x_param(&ALICE_PUBLIC),
+        // kid: h'0102'
+        &[0x02, 0x42, 0x01, 0x02],
+        // key_ops: [7]
+        &[0x04, 0x81, 0x07],
+        &[0x20, 0x04],
+        // "comment": "alice"
+        b"\x67comment\x65alice",
+        &[0x01, 0x01],
+    ]);
+
+    let decoded = PublicKey::from_cose_key(&encoded).unwrap();
+    assert_eq!(decoded.to_bytes(), ALICE_PUBLIC);
+}
+
+#[test]
+fn public_key_cose_rejects_wrong_key_type_and_curve() {
+    // kty: EC2
+    let encoded = cose_key(&[&[0x01, 0x02], &[0x20, 0x04], &x_param(&ALICE_PUBLIC)]);
+    assert_eq!(
+        PublicKey::from_cose_key(&encoded),
+        Err(Error::Cose(CoseError::UnsupportedKeyType))
+    );
+
+    // crv: Ed25519
+    let encoded = cose_key(&[&[0x01, 0x01], &[0x20, 0x06], &x_param(&ALICE_PUBLIC)]);
+    assert_eq!(
+        PublicKey::from_cose_key(&encoded),
+        Err(Error::Cose(CoseError::UnsupportedCurve))
+    );
+}
+
+#[test]
+fn public_key_cose_rejects_malformed_input() {
+    let encoded = PublicKey::from(ALICE_PUBLIC).to_cose_key();
+
+    // Truncated.
+    assert_eq!(
+        PublicKey::from_cose_key(&encoded[..39]),
+        Err(Error::Cose(CoseError::Malformed))
+    );
+
+    // Trailing data.
+    let mut trailing = encoded.to_vec();
+    trailing.push(0x00);
+    assert_eq!(
+        PublicKey::from_cose_key(&trailing),
+        Err(Error::Cose(CoseError::Malformed))
+    );
+
+    // Duplicate `crv`.
+    let duplicate = cose_key(&[
+        &[0x01, 0x01],
+        &[0x20, 0x04],
+        &[0x20, 0x04],
+        &x_param(&ALICE_PUBLIC),
+    ]);
+    assert_eq!(
+        PublicKey::from_cose_key(&duplicate),
+        Err(Error::Cose(CoseError::Malformed))
+    );
+
+    // Missing `x`.
+    let missing = cose_key(&[&[0x01, 0x01], &[0x20, 0x04]]);
+    assert_eq!(
+        PublicKey::from_cose_key(&missing),
+        Err(Error::Cose(CoseError::Malformed))
+    );
+
+    // Indefinite-length map.
+    let mut indefinite = encoded.to_vec();
+    indefinite[0] = 0xbf;
+    indefinite.push(0xff);
+    assert_eq!(
+        PublicKey::from_cose_key(&indefinite),
+        Err(Error::Cose(CoseError::Malformed))
+    );
+}
+
+#[test]
+#[cfg(feature = "static_secrets")]
+fn static_secret_cose_roundtrip() {
+    let secret = StaticSecret::from(ALICE_PRIVATE);
+    let encoded = secret.to_cose_key();
+
+    assert_eq!(encoded[0], 0xa4);
+    assert_eq!(encoded[1..8], PUBLIC_HEADER[1..]);
+    assert_eq!(encoded[8..40], ALICE_PUBLIC);
+    assert_eq!(encoded[40..43], [0x23, 0x58, 0x20]);
+    assert_eq!(encoded[43..], ALICE_PRIVATE);
+
+    let decoded = StaticSecret::from_cose_key(&*encoded).unwrap();
+    assert_eq!(decoded.to_bytes(), ALICE_PRIVATE);
+
+    // The public part of a private key can be decoded on its own.
+    let public = PublicKey::from_cose_key(&*encoded).unwrap();
+    assert_eq!(public.to_bytes(), ALICE_PUBLIC);
+}
+
+#[test]
+#[cfg(feature = "static_secrets")]
+fn static_secret_cose_without_public_key() {
+    let d = [&[0x23, 0x58, 0x20][..], &ALICE_PRIVATE].concat();
+    let encoded = cose_key(&[&[0x01, 0x01], &[0x20, 0x04], &d]);
+
+    let decoded = StaticSecret::from_cose_key(&encoded).unwrap();
+    assert_eq!(decoded.to_bytes(), ALICE_PRIVATE);
+}
+
+#[test]
+#[cfg(feature = "static_secrets")]
+fn static_secret_cose_requires_private_key() {
+    let encoded = PublicKey::from(ALICE_PUBLIC).to_cose_key();
+
+    assert_eq!(
+        StaticSecret::from_cose_key(&encoded).err(),
+        Some(Error::Cose(CoseError::MissingPrivateKey))
+    );
+}
+
+#[test]
+#[cfg(feature = "static_secrets")]
+fn static_secret_cose_rejects_mismatched_public_key() {
+    let mut encoded = StaticSecret::from(ALICE_PRIVATE).to_cose_key();
+    encoded[8] ^= 1;
+
+    assert_eq!(
+        StaticSecret::from_cose_key(&*encoded).err(),
+        Some(Error::Cose(CoseError::PublicKeyMismatch))
+    );
+}
@@ -0,0 +1,104 @@
+//! CPace PAKE tests.
+
+#![cfg(feature = "cpace")]
+
+mod common;
+
+use common::{hex, hex32, ConstRng};
+use x25519_dalek::pake::cpace::{self, CPace, Role};
+use x25519_dalek::{Error, PublicKey};
+
+// The inputs of the X25519 test vectors of draft-irtf-cfrg-cpace, with
+// outputs computed by an independent implementation of the draft.
+const PRS: &[u8] = b"Password";
+const CI: &[u8] = b"\nAinitiator\nBresponder";
+const SID: &str = "7e4b4791d6a8ef019b936c79fb7f2c57";
+const YA: &str = "21b4f4bd9e64ed355c3eb676a28ebedaf6d8f17bdc365995b319097153044080";
+const YB: &str = "848b0779ff415f0af4ea14df9dd1d3c29ac41d836c7808896c4eba19c51ac40a";
+const ADA: &[u8] = b"ADa";
+const ADB: &[u8] = b"ADb";
+
+fn parties() -> (CPace, CPace) {
+    let sid = hex(SID);
+    let alice = CPace::new(PRS, CI, &sid, ConstRng(hex32(YA)));
+    let bob = CPace::new(PRS, CI, &sid, ConstRng(hex32(YB)));
+    (alice, bob)
+}
+
+#[test]
+fn generator_matches_reference() {
+    assert_eq!(
+        cpace::generator(PRS, CI, &hex(SID)).to_bytes(),
+        hex32("4e6098733061c0e8486611a904fe5edb049804d26130a44131a6229e55c5c321")
+    );
+}
+
+/// A password too long for zero padding, whose length takes two bytes.
+#[test]
+fn generator_of_long_password_matches_reference() {
+    let prs: Vec<u8> = (0..200).collect();
+    assert_eq!(
+        cpace::generator(&prs, CI, &hex(SID)).to_bytes(),
+        hex32("d1f571b2ea7eb9b091663b21f2fa0173d6d03aedf99486432fd6924e5a84ad69")
+    );
+}
+
+#[test]
+fn initiator_responder_matches_reference() {
+    let (alice, bob) = parties();
+    assert_eq!(
+        alice.share().to_bytes(),
+        hex32("f970e36f37cfcd9a39e37dd2d1fbc9156d6d2f9ae422f4722cbd9d32e9b1e704")
+    );
+    assert_eq!(
+        bob.share().to_bytes(),
+        hex32("0178bbbab0804a4455b8f02e5d6e7d80997c6470bfb3618d7e74c39647af5a29")
+    );
+
+    let alice_share = *alice.share();
+    let bob_share = *bob.share();
+    let alice_isk = alice.finish(Role::Initiator, ADA, &bob_share, ADB).unwrap();
+    let bob_isk = bob.finish(Role::Responder, ADB, &alice_share, ADA).unwrap();
+    let expected = hex(concat!(
+        "f5ef3c13fdb9dfe839bdbf8a9256e8cee7db8a8f1dfa74958a925450cf8089cd",
+        "560d9a4e7956b7334b6f625c8559b75ea0764ac2be894b8f3d434b30e87797d5",
+    ));
+    assert_eq!(alice_isk.to_vec(), expected);
+    assert_eq!(bob_isk.to_vec(), expected);
+}
+
+#[test]
+fn symmetric_matches_reference() {
+    let (alice, bob) = parties();
+    let alice_share = *alice.share();
+    let bob_share = *bob.share();
+    let alice_isk = alice.finish(Role::Symmetric, ADA, &bob_share, ADB).unwrap();
+    let bob_isk = bob.finish(Role::Symmetric, ADB, &alice_share, ADA).unwrap();
+    let expected = hex(concat!(
+        "f4051edc63b2620e10d5ecf76d9f0c5ccd1447858a98d4bf847fafac737478c1",
+        "350e14619bc0fcd4f028d10e4102dfca39f91fe9b829a503ab3e0549bd835edf",
+    ));
+    assert_eq!(alice_isk.to_vec(), expected);
+    assert_eq!(bob_isk.to_vec(), expected);
+}
+
+#[test]
+fn different_passwords_give_different_keys() {
+    let sid = hex(SID);
+    let alice = CPace::new(PRS, CI, &sid, ConstRng(hex32(YA)));
+    let bob = CPace::new(b"Passwore", CI, &sid, ConstRng(hex32(YB)));
+    let alice_share = *alice.share();
+    let bob_share = *bob.share();
+    let alice_isk = alice.finish(Role::Initiator, ADA, &bob_share, ADB).unwrap();
+    let bob_isk = bob.finish(Role::Responder, ADB, &alice_share, ADA).unwrap();
+    assert_ne!(alice_isk, bob_isk);
+}
+
+#[test]
+fn small_order_share_is_rejected() {
+    let (alice, _) = parties();
+    assert!(matches!(
+        alice.finish(Role::Initiator, ADA, &PublicKey::from([0u8; 32]), ADB),
+        Err(Error::SmallOrderPoint)
+    ));
+}
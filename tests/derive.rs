@@ -0,0 +1,50 @@
+//! Seed derivation tests.
+
+#![cfg(feature = "derive_from_seed")]
+
+use x25519_dalek::{PublicKey, StaticSecret};
+
+/// The seed `00 01 02 .. 1f`.
+const SEED: [u8; 32] = [
+    0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e, 0x0f,
+    0x10, 0x11, 0x12, 0x13, 0x14, 0x15, 0x16, 0x17, 0x18, 0x19, 0x1a, 0x1b, 0x1c, 0x1d, 0x1e, 0x1f,
+];
+
+#[test]
+fn derive_from_seed_matches_hkdf_sha256() {
+    let secret = StaticSecret::derive_from_seed(&SEED, b"encryption");
+    assert_eq!(
+        secret.to_bytes(),
+        [
+            0x36, 0xd4, 0x00, 0xd0, 0x93, 0x76, 0x19, 0xfa, 0x66, 0x7d, 0x31, 0x6b, 0x76, 0x8d,
+            0x6a, 0x7f, 0xb3, 0xd3, 0x92, 0x45, 0x30, 0x53, 0x3e, 0xcb, 0x5e, 0x3a, 0x9f, 0xb2,
+            0xf4, 0x8f, 0xfa, 0x4d,
+        ]
+    );
+
+    let secret = StaticSecret::derive_from_seed(&SEED, b"signing");
+    assert_eq!(
+        secret.to_bytes(),
+        [
+            0xce, 0x22, 0xb9, 0x32, 0x9a, 0x18, 0xef, 0xec, 0xc8, 0xda, 0x06, 0x4b, 0x34, 0x4e,
+            0xfe, 0x11, 0xe7, 0xbc, 0x3a, 0xba, 0x45, 0x9f, 0xed, 0xc3, 0x9a, 0xdb, 0x94, 0xc9,
+            0x26, 0xff, 0x4d, 0xf4,
+        ]
+    );
+}
+
+#[test]
+fn derive_from_seed_is_deterministic_and_separated() {
+    let a = StaticSecret::derive_from_seed(&SEED, b"a");
+    let b = StaticSecret::derive_from_seed(&SEED, b"b");
+
+    assert_eq!(
+        a.to_bytes(),
+        StaticSecret::derive_from_seed(&SEED, b"a").to_bytes()
+    );
+    assert_ne!(a.to_bytes(), b.to_bytes());
+    assert_ne!(PublicKey::from(&a), PublicKey::from(&b));
+
+    let other_seed = StaticSecret::derive_from_seed(&SEED[1..], b"a");
+    assert_ne!(a.to_bytes(), other_seed.to_bytes());
+}
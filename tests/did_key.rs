@@ -0,0 +1,91 @@
+//! `did:key` encoding tests.
+
+#![cfg(feature = "did_key")]
+
+use x25519_dalek::{DidKeyError, Error, PublicKey};
+
+/// Alice's public key, from RFC7748 section 6.1.
+const ALICE_PUBLIC: [u8; 32] = [
+    0x85, 0x20, 0xf0, 0x09, 0x89, 0x30, 0xa7, 0x54, 0x74, 0x8b, 0x7d, 0xdc, 0xb4, 0x3e, 0xf7, 0x5a,
+    0x0d, 0xbf, 0x3a, 0x0d, 0x26, 0x38, 0x1a, 0xf4, 0xeb, 0xa4, 0xa9, 0x8e, 0xaa, 0x9b, 0x4e, 0x6a,
+];
+
+/// [`ALICE_PUBLIC`] as a `did:key`.
+const ALICE_DID_KEY: &str = "did:key:z6LSkdrX4EvewpktHBjvNxRDogPdC5iVF8LT3LPKefGAgi89";
+
+/// The X25519 key agreement key from the did:key method specification.
+const SPEC_DID_KEY: &str = "did:key:z6LSbysY2xFMRpGMhb7tFTLMpeuPRaqaWM1yECx2AtzE3KCc";
+
+#[test]
+fn public_key_did_key_roundtrip() {
+    assert_eq!(PublicKey::from(ALICE_PUBLIC).to_did_key(), ALICE_DID_KEY);
+
+    let public = PublicKey::from_did_key(ALICE_DID_KEY).unwrap();
+    assert_eq!(public.to_bytes(), ALICE_PUBLIC);
+
+    let public = PublicKey::from_did_key(SPEC_DID_KEY).unwrap();
+    assert_eq!(public.to_did_key(), SPEC_DID_KEY);
+}
+
+#[test]
+fn public_key_did_key_accepts_own_fragment() {
+    let did_url = format!("{}#{}", ALICE_DID_KEY, &ALICE_DID_KEY[8..]);
+    let public = PublicKey::from_did_key(&did_url).unwrap();
+    assert_eq!(public.to_bytes(), ALICE_PUBLIC);
+
+    let did_url = format!("{}#key-1", ALICE_DID_KEY);
+    assert_eq!(
+        PublicKey::from_did_key(&did_url),
+        Err(Error::DidKey(DidKeyError::InvalidMethod))
+    );
+}
+
+#[test]
+fn public_key_did_key_rejects_other_methods() {
+    let did = ALICE_DID_KEY.replace("did:key:", "did:web:");
+    assert_eq!(
+        PublicKey::from_did_key(&did),
+        Err(Error::DidKey(DidKeyError::InvalidMethod))
+    );
+}
+
+#[test]
+fn public_key_did_key_rejects_other_multibases() {
+    // base64url multibase.
+    let did = "did:key:u7AGFIPAJiTCnVHSLfdy0PvdaDb86DSY4GvTrpKmOqptOag";
+    assert_eq!(
+        PublicKey::from_did_key(did),
+        Err(Error::DidKey(DidKeyError::UnsupportedMultibase))
+    );
+
+    // '0' is not in the base58 alphabet.
+    let did = ALICE_DID_KEY.replace("X4", "X0");
+    assert_eq!(
+        PublicKey::from_did_key(&did),
+        Err(Error::DidKey(DidKeyError::InvalidEncoding))
+    );
+}
+
+#[test]
+fn public_key_did_key_rejects_other_multicodecs() {
+    // The same bytes under the ed25519-pub multicodec.
+    let did = "did:key:z6MkoQwQ8BNEBuXcJJCrXss7LBj9AWTDxQQerNaZzUaetZ8m";
+    assert_eq!(
+        PublicKey::from_did_key(did),
+        Err(Error::DidKey(DidKeyError::UnsupportedMulticodec))
+    );
+
+    // Too short.
+    let did = &ALICE_DID_KEY[..ALICE_DID_KEY.len() - 2];
+    assert_eq!(
+        PublicKey::from_did_key(did),
+        Err(Error::DidKey(DidKeyError::UnsupportedMulticodec))
+    );
+
+    // Too long.
+    let did = format!("{}2", ALICE_DID_KEY);
+    assert_eq!(
+        PublicKey::from_did_key(&did),
+        Err(Error::DidKey(DidKeyError::UnsupportedMulticodec))
+    );
+}
@@ -0,0 +1,119 @@
+//! Verifiable Diffie-Hellman tests.
+
+#![cfg(feature = "dleq")]
+
+use rand_core::OsRng;
+use x25519_dalek::dleq::{self, DleqProof};
+use x25519_dalek::{Error, PublicKey, StaticSecret};
+
+/// Alice's private key, from RFC7748 section 6.1.
+const ALICE_PRIVATE: [u8; 32] = [
+    0x77, 0x07, 0x6d, 0x0a, 0x73, 0x18, 0xa5, 0x7d, 0x3c, 0x16, 0xc1, 0x72, 0x51, 0xb2, 0x66, 0x45,
+    0xdf, 0x4c, 0x2f, 0x87, 0xeb, 0xc0, 0x99, 0x2a, 0xb1, 0x77, 0xfb, 0xa5, 0x1d, 0xb9, 0x2c, 0x2a,
+];
+
+/// Bob's public key, from RFC7748 section 6.1.
+const BOB_PUBLIC: [u8; 32] = [
+    0xde, 0x9e, 0xdb, 0x7d, 0x7b, 0x7d, 0xc1, 0xb4, 0xd3, 0x5b, 0x61, 0xc2, 0xec, 0xe4, 0x35, 0x37,
+    0x3f, 0x83, 0x43, 0xc8, 0x5b, 0x78, 0x67, 0x4d, 0xad, 0xfc, 0x7e, 0x14, 0x6f, 0x88, 0x2b, 0x4f,
+];
+
+/// The shared secret between Alice and Bob, from RFC7748 section 6.1.
+const SHARED: [u8; 32] = [
+    0x4a, 0x5d, 0x9d, 0x5b, 0xa4, 0xce, 0x2d, 0xe1, 0x72, 0x8e, 0x3b, 0xf4, 0x80, 0x35, 0x0f, 0x25,
+    0xe0, 0x7e, 0x21, 0xc9, 0x47, 0xd1, 0x9e, 0x33, 0x76, 0xf0, 0x9b, 0x3c, 0x1e, 0x16, 0x17, 0x42,
+];
+
+#[test]
+fn dleq_matches_rfc7748() {
+    let alice = StaticSecret::from(ALICE_PRIVATE);
+    let bob = PublicKey::from(BOB_PUBLIC);
+
+    let (shared, proof) = alice.diffie_hellman_with_proof(&bob, OsRng).unwrap();
+    assert_eq!(shared.to_bytes(), SHARED);
+    assert_eq!(
+        proof.verify(&PublicKey::from(&alice), &bob, &SHARED),
+        Ok(())
+    );
+}
+
+#[test]
+fn dleq_matches_diffie_hellman() {
+    for _ in 0..16 {
+        let secret = StaticSecret::random_from_rng(OsRng);
+        let peer = PublicKey::from(&StaticSecret::random_from_rng(OsRng));
+
+        let (shared, proof) = secret.diffie_hellman_with_proof(&peer, OsRng).unwrap();
+        assert_eq!(shared.as_bytes(), secret.diffie_hellman(&peer).as_bytes());
+        assert!(proof
+            .verify(&PublicKey::from(&secret), &peer, shared.as_bytes())
+            .is_ok());
+    }
+}
+
+#[test]
+fn dleq_rejects_wrong_statements() {
+    let invalid = Err(Error::Dleq(dleq::Error::InvalidProof));
+    let alice = StaticSecret::from(ALICE_PRIVATE);
+    let alice_public = PublicKey::from(&alice);
+    let bob = PublicKey::from(BOB_PUBLIC);
+    let carol = PublicKey::from(&StaticSecret::from([0x33; 32]));
+    let (_, proof) = alice.diffie_hellman_with_proof(&bob, OsRng).unwrap();
+
+    let mut wrong_shared = SHARED;
+    wrong_shared[0] ^= 1;
+    assert_eq!(proof.verify(&alice_public, &bob, &wrong_shared), invalid);
+    assert_eq!(proof.verify(&carol, &bob, &SHARED), invalid);
+    assert_eq!(proof.verify(&alice_public, &carol, &SHARED), invalid);
+
+    // The correct shared secret for a different pair of keys.
+    let (carol_shared, _) = alice.diffie_hellman_with_proof(&carol, OsRng).unwrap();
+    assert_eq!(
+        proof.verify(&alice_public, &carol, carol_shared.as_bytes()),
+        invalid
+    );
+
+    for offset in [32, 64] {
+        let mut encoded = proof.to_bytes();
+        encoded[offset] ^= 1;
+        let tampered = DleqProof::from_bytes(&encoded).unwrap();
+        assert_eq!(tampered.verify(&alice_public, &bob, &SHARED), invalid);
+    }
+}
+
+#[test]
+fn dleq_bytes_roundtrip() {
+    let alice = StaticSecret::from(ALICE_PRIVATE);
+    let bob = PublicKey::from(BOB_PUBLIC);
+    let (_, proof) = alice.diffie_hellman_with_proof(&bob, OsRng).unwrap();
+
+    let encoded = proof.to_bytes();
+    assert_eq!(encoded.len(), dleq::PROOF_LEN);
+    let decoded = DleqProof::from_bytes(&encoded).unwrap();
+    assert_eq!(decoded, proof);
+    assert!(decoded
+        .verify(&PublicKey::from(&alice), &bob, &SHARED)
+        .is_ok());
+
+    // A challenge which is not reduced modulo the group order.
+    let mut encoded = proof.to_bytes();
+    encoded[63] = 0xff;
+    assert_eq!(
+        DleqProof::from_bytes(&encoded),
+        Err(Error::Dleq(dleq::Error::InvalidProof))
+    );
+}
+
+#[test]
+fn dleq_rejects_twist_points() {
+    let mut twist = [0u8; 32];
+    twist[0] = 2;
+    let alice = StaticSecret::from(ALICE_PRIVATE);
+
+    assert_eq!(
+        alice
+            .diffie_hellman_with_proof(&PublicKey::from(twist), OsRng)
+            .err(),
+        Some(Error::TwistPoint)
+    );
+}
@@ -0,0 +1,91 @@
+//! One-shot public-key encryption tests.
+
+#![cfg(feature = "ecies")]
+
+use chacha20poly1305::{ChaCha20Poly1305, XChaCha20Poly1305};
+use rand_core::OsRng;
+use x25519_dalek::ecies::{self, EPHEMERAL_KEY_LEN};
+use x25519_dalek::{Error, PublicKey, StaticSecret};
+
+#[test]
+fn seal_open_round_trip() {
+    let recipient = StaticSecret::random_from_rng(OsRng);
+    let public = PublicKey::from(&recipient);
+
+    let sealed =
+        ecies::seal::<ChaCha20Poly1305, _>(&public, b"attack at dawn", b"header", OsRng).unwrap();
+    assert_eq!(sealed.len(), EPHEMERAL_KEY_LEN + 14 + 16);
+    let opened = ecies::open::<ChaCha20Poly1305>(&recipient, &sealed, b"header").unwrap();
+    assert_eq!(opened, b"attack at dawn");
+}
+
+#[test]
+fn seal_is_generic_over_aead() {
+    let recipient = StaticSecret::random_from_rng(OsRng);
+    let public = PublicKey::from(&recipient);
+
+    let sealed = ecies::seal::<XChaCha20Poly1305, _>(&public, b"", b"", OsRng).unwrap();
+    assert_eq!(sealed.len(), EPHEMERAL_KEY_LEN + 16);
+    assert_eq!(
+        ecies::open::<XChaCha20Poly1305>(&recipient, &sealed, b"").unwrap(),
+        b""
+    );
+    assert!(ecies::open::<ChaCha20Poly1305>(&recipient, &sealed, b"").is_err());
+}
+
+#[test]
+fn seal_uses_fresh_ephemeral_keys() {
+    let public = PublicKey::from(&StaticSecret::random_from_rng(OsRng));
+    let first = ecies::seal::<ChaCha20Poly1305, _>(&public, b"message", b"", OsRng).unwrap();
+    let second = ecies::seal::<ChaCha20Poly1305, _>(&public, b"message", b"", OsRng).unwrap();
+    assert_ne!(first, second);
+}
+
+#[test]
+fn open_rejects_tampering() {
+    let recipient = StaticSecret::random_from_rng(OsRng);
+    let public = PublicKey::from(&recipient);
+    let sealed = ecies::seal::<ChaCha20Poly1305, _>(&public, b"message", b"aad", OsRng).unwrap();
+    let invalid = Some(Error::Ecies(ecies::Error::InvalidCiphertext));
+
+    assert_eq!(
+        ecies::open::<ChaCha20Poly1305>(&recipient, &sealed, b"other aad").err(),
+        invalid
+    );
+
+    for i in [0, EPHEMERAL_KEY_LEN, sealed.len() - 1] {
+        let mut tampered = sealed.clone();
+        tampered[i] ^= 1;
+        assert!(ecies::open::<ChaCha20Poly1305>(&recipient, &tampered, b"aad").is_err());
+    }
+
+    let other = StaticSecret::random_from_rng(OsRng);
+    assert_eq!(
+        ecies::open::<ChaCha20Poly1305>(&other, &sealed, b"aad").err(),
+        invalid
+    );
+    assert_eq!(
+        ecies::open::<ChaCha20Poly1305>(&recipient, &sealed[..EPHEMERAL_KEY_LEN - 1], b"aad").err(),
+        invalid
+    );
+    assert_eq!(
+        ecies::open::<ChaCha20Poly1305>(&recipient, &sealed[..EPHEMERAL_KEY_LEN + 15], b"aad")
+            .err(),
+        invalid
+    );
+}
+
+#[test]
+fn small_order_keys_are_rejected() {
+    let invalid = Some(Error::SmallOrderPoint);
+    assert_eq!(
+        ecies::seal::<ChaCha20Poly1305, _>(&PublicKey::from([0u8; 32]), b"", b"", OsRng).err(),
+        invalid
+    );
+
+    let recipient = StaticSecret::random_from_rng(OsRng);
+    assert_eq!(
+        ecies::open::<ChaCha20Poly1305>(&recipient, &[0u8; EPHEMERAL_KEY_LEN + 16], b"").err(),
+        invalid
+    );
+}
@@ -0,0 +1,60 @@
+//! Ed25519 to X25519 key conversion tests.
+
+#![cfg(feature = "ed25519")]
+
+use ed25519_dalek::VerifyingKey;
+use x25519_dalek::PublicKey;
+
+/// The public key of RFC8032 section 7.1, test 1.
+const ED25519_PUBLIC: [u8; 32] = [
+    0xd7, 0x5a, 0x98, 0x01, 0x82, 0xb1, 0x0a, 0xb7, 0xd5, 0x4b, 0xfe, 0xd3, 0xc9, 0x64, 0x07, 0x3a,
+    0x0e, 0xe1, 0x72, 0xf3, 0xda, 0xa6, 0x23, 0x25, 0xaf, 0x02, 0x1a, 0x68, 0xf7, 0x07, 0x51, 0x1a,
+];
+
+/// `crypto_sign_ed25519_pk_to_curve25519` of [`ED25519_PUBLIC`], computed
+/// with libsodium.
+const X25519_PUBLIC: [u8; 32] = [
+    0xd8, 0x5e, 0x07, 0xec, 0x22, 0xb0, 0xad, 0x88, 0x15, 0x37, 0xc2, 0xf4, 0x4d, 0x66, 0x2d, 0x1a,
+    0x14, 0x3c, 0xf8, 0x30, 0xc5, 0x7a, 0xca, 0x43, 0x05, 0xd8, 0x5c, 0x7a, 0x90, 0xf6, 0xb6, 0x2e,
+];
+
+#[test]
+fn verifying_key_matches_libsodium() {
+    let verifying_key = VerifyingKey::from_bytes(&ED25519_PUBLIC).unwrap();
+    assert_eq!(PublicKey::from(&verifying_key).to_bytes(), X25519_PUBLIC);
+    assert_eq!(PublicKey::from(verifying_key).to_bytes(), X25519_PUBLIC);
+}
+
+/// The secret key seed of RFC8032 section 7.1, test 1.
+#[cfg(feature = "static_secrets")]
+const ED25519_SEED: [u8; 32] = [
+    0x9d, 0x61, 0xb1, 0x9d, 0xef, 0xfd, 0x5a, 0x60, 0xba, 0x84, 0x4a, 0xf4, 0x92, 0xec, 0x2c, 0xc4,
+    0x44, 0x49, 0xc5, 0x69, 0x7b, 0x32, 0x69, 0x19, 0x70, 0x3b, 0xac, 0x03, 0x1c, 0xae, 0x7f, 0x60,
+];
+
+/// `crypto_sign_ed25519_sk_to_curve25519` of [`ED25519_SEED`], computed
+/// with libsodium.
+#[cfg(feature = "static_secrets")]
+const X25519_SECRET: [u8; 32] = [
+    0x30, 0x7c, 0x83, 0x86, 0x4f, 0x28, 0x33, 0xcb, 0x42, 0x7a, 0x2e, 0xf1, 0xc0, 0x0a, 0x01, 0x3c,
+    0xfd, 0xff, 0x27, 0x68, 0xd9, 0x80, 0xc0, 0xa3, 0xa5, 0x20, 0xf0, 0x06, 0x90, 0x4d, 0xe9, 0x4f,
+];
+
+#[test]
+#[cfg(feature = "static_secrets")]
+fn signing_key_matches_libsodium() {
+    use ed25519_dalek::SigningKey;
+    use x25519_dalek::StaticSecret;
+
+    let signing_key = SigningKey::from_bytes(&ED25519_SEED);
+    let secret = StaticSecret::from(&signing_key);
+    assert_eq!(secret.to_bytes(), X25519_SECRET);
+    assert_eq!(
+        PublicKey::from(&secret),
+        PublicKey::from(signing_key.verifying_key())
+    );
+    assert_ne!(
+        PublicKey::from(&StaticSecret::from(ED25519_SEED)),
+        PublicKey::from(signing_key.verifying_key())
+    );
+}
@@ -0,0 +1,180 @@
+//! Elligator2 representative tests.
+
+#![cfg(feature = "elligator")]
+
+mod common;
+
+use common::hex32;
+use x25519_dalek::PublicKey;
+
+/// (u, Q.x) pairs from RFC 9380 appendix J.7.1, the
+/// `curve25519_XMD:SHA-512_ELL2_NU_` suite: the field element `u` which the
+/// message hashes to, and the u-coordinate of its Elligator2 map `Q`, before
+/// the cofactor is cleared.  Both are big-endian, as printed in the RFC.
+const RFC9380_NU: &[(&str, &str)] = &[
+    (
+        "608d892b641f0328523802a6603427c26e55e6f27e71a91a478148d45b5093cd",
+        "51125222da5e763d97f3c10fcc92ea6860b9ccbbd2eb1285728f566721c1e65b",
+    ),
+    (
+        "46f5b22494bfeaa7f232cc8d054be68561af50230234d7d1d63d1d9abeca8da5",
+        "7d56d1e08cb0ccb92baf069c18c49bb5a0dcd927eff8dcf75ca921ef7f3e6eeb",
+    ),
+    (
+        "235fe40c443766ce7e18111c33862d66c3b33267efa50d50f9e8e5d252a40aaa",
+        "3fbe66b9c9883d79e8407150e7c2a1c8680bee496c62fabe4619a72b3cabe90f",
+    ),
+    (
+        "001e92a544463bda9bd04ddbe3d6eed248f82de32f522669efc5ddce95f46f5b",
+        "227e0bb89de700385d19ec40e857db6e6a3e634b1c32962f370d26f84ff19683",
+    ),
+    (
+        "1a68a1af9f663592291af987203393f707305c7bac9c8d63d6a729bdc553dc19",
+        "3bcd651ee54d5f7b6013898aab251ee8ecc0688166fce6e9548d38472f6bd196",
+    ),
+];
+
+/// (u, Q.x) pairs from RFC 9380 appendix J.7.1, the
+/// `curve25519_XMD:SHA-512_ELL2_RO_` suite, which maps two field elements
+/// per message.
+const RFC9380_RO: &[(&str, &str)] = &[
+    (
+        "005fe8a7b8fef0a16c105e6cadf5a6740b3365e18692a9c05bfbb4d97f645a6a",
+        "36b4df0c864c64707cbf6cf36e9ee2c09a6cb93b28313c169be29561bb904f98",
+    ),
+    (
+        "1347edbec6a2b5d8c02e058819819bee177077c9d10a4ce165aab0fd0252261a",
+        "3fa114783a505c0b2b2fbeef0102853c0b494e7757f2a089d0daae7ed9a0db2b",
+    ),
+    (
+        "49bed021c7a3748f09fa8cdfcac044089f7829d3531066ac9e74e0994e05bc7d",
+        "16b3d86e056b7970fa00165f6f48d90b619ad618791661b7b5e1ec78be10eac1",
+    ),
+    (
+        "5c36525b663e63389d886105cee7ed712325d5a97e60e140aba7e2ce5ae851b6",
+        "7ec29ddbf34539c40adfa98fcb39ec36368f47f30e8f888cc7e86f4d46e0c264",
+    ),
+    (
+        "6412b7485ba26d3d1b6c290a8e1435b2959f03721874939b21782df17323d160",
+        "71de3dadfe268872326c35ac512164850860567aea0e7325e6b91a98f86533ad",
+    ),
+    (
+        "24c7b46c1c6d9a21d32f5707be1380ab82db1054fde82865d5c9e3d968f287b2",
+        "5704069021f61e41779e2ba6b932268316d6d2a6f064f997a22fef16d1eaeaca",
+    ),
+    (
+        "5e123990f11bbb5586613ffabdb58d47f64bb5f2fa115f8ea8df0188e0c9e1b5",
+        "7a94d45a198fb5daa381f45f2619ab279744efdd8bd8ed587fc5b65d6cea1df0",
+    ),
+    (
+        "5e8553eb00438a0bb1e7faa59dec6d8087f9c8011e5fb8ed9df31cb6c0d4ac19",
+        "30506fb7a32136694abd61b6113770270debe593027a968a01f271e146e60c18",
+    ),
+    (
+        "20f481e85da7a3bf60ac0fb11ed1d0558fc6f941b3ac5469aa8b56ec883d6d7d",
+        "02d606e2699b918ee36f2818f2bc5013e437e673c9f9b9cdc15fd0c5ee913970",
+    ),
+    (
+        "017d57fd257e9a78913999a23b52ca988157a81b09c5442501d07fed20869465",
+        "38920e9b988d1ab7449c0fa9a6058192c0c797bb3d42ac345724341a1aa98745",
+    ),
+];
+
+/// p = 2^255 - 19, in little-endian.
+const P: [u8; 32] = [
+    0xed, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x7f,
+];
+
+/// Decode a big-endian field element from the RFC into little-endian.
+fn from_rfc(s: &str) -> [u8; 32] {
+    let mut bytes = hex32(s);
+    bytes.reverse();
+    bytes
+}
+
+/// The representative of the field element `u`, given in little-endian.
+///
+/// The map depends only on the square of its input, so `u` and `p - u`
+/// map to the same point, and of these the one below 2^254 is the
+/// representative.
+fn representative(u: &[u8; 32]) -> [u8; 32] {
+    if u[31] < 0x40 {
+        return *u;
+    }
+    let mut negated = [0u8; 32];
+    let mut borrow = 0i16;
+    for ((negated, p), u) in negated.iter_mut().zip(&P).zip(u) {
+        let diff = i16::from(*p) - i16::from(*u) - borrow;
+        *negated = diff as u8;
+        borrow = i16::from(diff < 0);
+    }
+    negated
+}
+
+#[test]
+fn representatives_decode_to_rfc9380_points() {
+    for (u, q) in RFC9380_NU.iter().chain(RFC9380_RO) {
+        let decoded = PublicKey::from_elligator_representative(&representative(&from_rfc(u)));
+        assert_eq!(decoded.to_bytes(), from_rfc(q));
+    }
+}
+
+#[test]
+fn zero_representative_decodes_to_zero() {
+    assert_eq!(
+        PublicKey::from_elligator_representative(&[0u8; 32]).to_bytes(),
+        [0u8; 32]
+    );
+}
+
+#[test]
+fn top_two_bits_are_ignored() {
+    for (u, _) in RFC9380_NU {
+        let representative = representative(&from_rfc(u));
+        let expected = PublicKey::from_elligator_representative(&representative);
+        for padding in 0..4u8 {
+            let mut padded = representative;
+            padded[31] = (padded[31] & 0x3f) | (padding << 6);
+            assert_eq!(PublicKey::from_elligator_representative(&padded), expected);
+        }
+    }
+}
+
+#[test]
+fn generated_representatives_decode_to_equivalent_public_keys() {
+    use rand_core::OsRng;
+    use x25519_dalek::EphemeralSecret;
+
+    let mut with_low_order_component = 0;
+    for _ in 0..32 {
+        let (alice_secret, representative) = EphemeralSecret::generate_elligator(OsRng);
+        let alice_public = PublicKey::from(&alice_secret);
+        let decoded = PublicKey::from(&representative);
+        if decoded != alice_public {
+            with_low_order_component += 1;
+        }
+
+        let bob_secret = EphemeralSecret::random_from_rng(OsRng);
+        let bob_public = PublicKey::from(&bob_secret);
+        assert_eq!(
+            alice_secret.diffie_hellman(&bob_public).as_bytes(),
+            bob_secret.diffie_hellman(&decoded).as_bytes(),
+        );
+    }
+    // A low-order component is added to seven in eight keys.
+    assert!(with_low_order_component > 0);
+}
+
+#[test]
+fn generated_representatives_have_random_padding() {
+    use rand_core::OsRng;
+    use x25519_dalek::EphemeralSecret;
+
+    let mut seen = [false; 4];
+    for _ in 0..64 {
+        let (_, representative) = EphemeralSecret::generate_elligator(OsRng);
+        seen[usize::from(representative.as_bytes()[31] >> 6)] = true;
+    }
+    assert_eq!(seen, [true; 4]);
+}
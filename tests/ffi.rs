@@ -0,0 +1,97 @@
+//! C interface tests.
+
+#![cfg(feature = "ffi")]
+
+mod common;
+
+use common::hex32;
+use x25519_dalek::ffi::*;
+
+#[test]
+fn rfc7748_diffie_hellman() {
+    let alice = hex32("77076d0a7318a57d3c16c17251b26645df4c2f87ebc0992ab177fba51db92c2a");
+    let bob_public = hex32("de9edb7d7b7dc1b4d35b61c2ece435373f8343c85b78674dadfc7e146f882b4f");
+
+    let mut public = [0xffu8; X25519_KEY_LEN];
+    let mut shared = [0xffu8; X25519_KEY_LEN];
+    unsafe {
+        assert_eq!(
+            x25519_public_from_secret(public.as_mut_ptr(), alice.as_ptr()),
+            0
+        );
+        assert_eq!(
+            x25519_dh(shared.as_mut_ptr(), alice.as_ptr(), bob_public.as_ptr()),
+            0
+        );
+    }
+    assert_eq!(
+        public,
+        hex32("8520f0098930a754748b7ddcb43ef75a0dbf3a0d26381af4eba4a98eaa9b4e6a")
+    );
+    assert_eq!(
+        shared,
+        hex32("4a5d9d5ba4ce2de1728e3bf480350f25e07e21c947d19e3376f09b3c1e161742")
+    );
+}
+
+#[test]
+fn keygen_matches_public_from_secret() {
+    let mut secret = [0u8; X25519_KEY_LEN];
+    let mut public = [0u8; X25519_KEY_LEN];
+    let mut expected = [0u8; X25519_KEY_LEN];
+    unsafe {
+        assert_eq!(x25519_keygen(secret.as_mut_ptr(), public.as_mut_ptr()), 0);
+        assert_eq!(
+            x25519_public_from_secret(expected.as_mut_ptr(), secret.as_ptr()),
+            0
+        );
+    }
+    assert_eq!(public, expected);
+}
+
+#[test]
+fn dh_rejects_small_order_points() {
+    let secret = [0x11u8; X25519_KEY_LEN];
+    let mut shared = [0xffu8; X25519_KEY_LEN];
+    let result = unsafe { x25519_dh(shared.as_mut_ptr(), secret.as_ptr(), [0u8; 32].as_ptr()) };
+    assert_eq!(result, -1);
+    assert_eq!(shared, [0u8; 32]);
+}
+
+#[test]
+fn null_pointers_fail() {
+    let key = [0x11u8; X25519_KEY_LEN];
+    let mut out = [0xffu8; X25519_KEY_LEN];
+    unsafe {
+        assert_eq!(x25519_keygen(out.as_mut_ptr(), core::ptr::null_mut()), -1);
+        assert_eq!(out, [0u8; 32]);
+
+        out = [0xff; 32];
+        assert_eq!(
+            x25519_public_from_secret(out.as_mut_ptr(), core::ptr::null()),
+            -1
+        );
+        assert_eq!(out, [0u8; 32]);
+
+        out = [0xff; 32];
+        assert_eq!(
+            x25519_dh(out.as_mut_ptr(), key.as_ptr(), core::ptr::null()),
+            -1
+        );
+        assert_eq!(out, [0u8; 32]);
+        assert_eq!(
+            x25519_dh(core::ptr::null_mut(), key.as_ptr(), key.as_ptr()),
+            -1
+        );
+    }
+}
+
+#[test]
+fn zeroize_clears_buffer() {
+    let mut buf = [0xffu8; 48];
+    unsafe {
+        x25519_zeroize(buf.as_mut_ptr(), buf.len());
+        x25519_zeroize(core::ptr::null_mut(), 16);
+    }
+    assert_eq!(buf, [0u8; 48]);
+}
@@ -0,0 +1,58 @@
+//! Public key fingerprint tests.
+
+#![cfg(feature = "fingerprint")]
+
+use sha2::Sha512;
+use x25519_dalek::PublicKey;
+
+/// Alice's public key, from RFC7748 section 6.1.
+const ALICE_PUBLIC: [u8; 32] = [
+    0x85, 0x20, 0xf0, 0x09, 0x89, 0x30, 0xa7, 0x54, 0x74, 0x8b, 0x7d, 0xdc, 0xb4, 0x3e, 0xf7, 0x5a,
+    0x0d, 0xbf, 0x3a, 0x0d, 0x26, 0x38, 0x1a, 0xf4, 0xeb, 0xa4, 0xa9, 0x8e, 0xaa, 0x9b, 0x4e, 0x6a,
+];
+
+#[test]
+fn public_key_fingerprint_sha256() {
+    assert_eq!(
+        PublicKey::from(ALICE_PUBLIC).fingerprint_sha256(),
+        [
+            0x30, 0x0c, 0x9c, 0x96, 0x03, 0xb9, 0x2a, 0x4b, 0x39, 0xed, 0x39, 0x58, 0xbf, 0x92,
+            0x40, 0x11, 0x48, 0x04, 0xdb, 0x4f, 0xd3, 0x73, 0x01, 0x2c, 0x0c, 0xa4, 0x74, 0x32,
+            0xd6, 0x34, 0x25, 0xae,
+        ]
+    );
+}
+
+#[test]
+fn public_key_fingerprint_with_other_digest() {
+    let fingerprint = PublicKey::from(ALICE_PUBLIC).fingerprint::<Sha512>();
+    assert_eq!(
+        fingerprint[..16],
+        [
+            0x6d, 0x1b, 0x58, 0x20, 0x02, 0x26, 0xe5, 0x83, 0x74, 0x38, 0x8a, 0xa8, 0xed, 0x39,
+            0x1e, 0x85,
+        ]
+    );
+    assert_eq!(fingerprint.len(), 64);
+}
+
+#[test]
+fn public_key_fingerprint_ignores_non_canonical_encoding() {
+    let canonical = PublicKey::from(ALICE_PUBLIC).fingerprint_sha256();
+
+    let mut high_bit = ALICE_PUBLIC;
+    high_bit[31] |= 0x80;
+    assert_eq!(PublicKey::from(high_bit).fingerprint_sha256(), canonical);
+
+    // p + 1 is the same field element as 1.
+    let mut one = [0u8; 32];
+    one[0] = 1;
+    let mut p_plus_one = [0xff; 32];
+    p_plus_one[0] = 0xee;
+    p_plus_one[31] = 0x7f;
+    assert_eq!(
+        PublicKey::from(p_plus_one).fingerprint_sha256(),
+        PublicKey::from(one).fingerprint_sha256()
+    );
+    assert_ne!(PublicKey::from(one).fingerprint_sha256(), canonical);
+}
@@ -0,0 +1,248 @@
+//! HPKE DHKEM(X25519, HKDF-SHA256) tests.
+
+#![cfg(feature = "hpke")]
+
+mod common;
+
+use common::ConstRng;
+use rand_core::OsRng;
+use x25519_dalek::{hpke, Error, PublicKey, StaticSecret};
+
+/// `ikmE` from RFC9180 appendix A.1.1.
+const IKM_E: [u8; 32] = [
+    0x72, 0x68, 0x60, 0x0d, 0x40, 0x3f, 0xce, 0x43, 0x15, 0x61, 0xae, 0xf5, 0x83, 0xee, 0x16, 0x13,
+    0x52, 0x7c, 0xff, 0x65, 0x5c, 0x13, 0x43, 0xf2, 0x98, 0x12, 0xe6, 0x67, 0x06, 0xdf, 0x32, 0x34,
+];
+
+/// `skEm` from RFC9180 appendix A.1.1.
+const SK_E: [u8; 32] = [
+    0x52, 0xc4, 0xa7, 0x58, 0xa8, 0x02, 0xcd, 0x8b, 0x93, 0x6e, 0xce, 0xea, 0x31, 0x44, 0x32, 0x79,
+    0x8d, 0x5b, 0xaf, 0x2d, 0x7e, 0x92, 0x35, 0xdc, 0x08, 0x4a, 0xb1, 0xb9, 0xcf, 0xa2, 0xf7, 0x36,
+];
+
+/// `pkEm` and `enc` from RFC9180 appendix A.1.1.
+const ENC: [u8; 32] = [
+    0x37, 0xfd, 0xa3, 0x56, 0x7b, 0xdb, 0xd6, 0x28, 0xe8, 0x86, 0x68, 0xc3, 0xc8, 0xd7, 0xe9, 0x7d,
+    0x1d, 0x12, 0x53, 0xb6, 0xd4, 0xea, 0x6d, 0x44, 0xc1, 0x50, 0xf7, 0x41, 0xf1, 0xbf, 0x44, 0x31,
+];
+
+/// `ikmR` from RFC9180 appendix A.1.1.
+const IKM_R: [u8; 32] = [
+    0x6d, 0xb9, 0xdf, 0x30, 0xaa, 0x07, 0xdd, 0x42, 0xee, 0x5e, 0x81, 0x81, 0xaf, 0xdb, 0x97, 0x7e,
+    0x53, 0x8f, 0x5e, 0x1f, 0xec, 0x8a, 0x06, 0x22, 0x3f, 0x33, 0xf7, 0x01, 0x3e, 0x52, 0x50, 0x37,
+];
+
+/// `skRm` from RFC9180 appendix A.1.1.
+const SK_R: [u8; 32] = [
+    0x46, 0x12, 0xc5, 0x50, 0x26, 0x3f, 0xc8, 0xad, 0x58, 0x37, 0x5d, 0xf3, 0xf5, 0x57, 0xaa, 0xc5,
+    0x31, 0xd2, 0x68, 0x50, 0x90, 0x3e, 0x55, 0xa9, 0xf2, 0x3f, 0x21, 0xd8, 0x53, 0x4e, 0x8a, 0xc8,
+];
+
+/// `pkRm` from RFC9180 appendix A.1.1.
+const PK_R: [u8; 32] = [
+    0x39, 0x48, 0xcf, 0xe0, 0xad, 0x1d, 0xdb, 0x69, 0x5d, 0x78, 0x0e, 0x59, 0x07, 0x71, 0x95, 0xda,
+    0x6c, 0x56, 0x50, 0x6b, 0x02, 0x73, 0x29, 0x79, 0x4a, 0xb0, 0x2b, 0xca, 0x80, 0x81, 0x5c, 0x4d,
+];
+
+/// `shared_secret` from RFC9180 appendix A.1.1.
+const SHARED_SECRET: [u8; 32] = [
+    0xfe, 0x0e, 0x18, 0xc9, 0xf0, 0x24, 0xce, 0x43, 0x79, 0x9a, 0xe3, 0x93, 0xc7, 0xe8, 0xfe, 0x8f,
+    0xce, 0x9d, 0x21, 0x88, 0x75, 0xe8, 0x22, 0x7b, 0x01, 0x87, 0xc0, 0x4e, 0x7d, 0x2e, 0xa1, 0xfc,
+];
+
+/// `ikmE` from RFC9180 appendix A.1.3.
+const AUTH_IKM_E: [u8; 32] = [
+    0x6e, 0x6d, 0x8f, 0x20, 0x0e, 0xa2, 0xfb, 0x20, 0xc3, 0x0b, 0x00, 0x3a, 0x8b, 0x4f, 0x43, 0x3d,
+    0x2f, 0x4e, 0xd4, 0xc2, 0x65, 0x8d, 0x5b, 0xc8, 0xce, 0x2f, 0xef, 0x71, 0x80, 0x59, 0xc9, 0xf7,
+];
+
+/// `skEm` from RFC9180 appendix A.1.3.
+const AUTH_SK_E: [u8; 32] = [
+    0xff, 0x44, 0x42, 0xef, 0x24, 0xfb, 0xc3, 0xc1, 0xff, 0x86, 0x37, 0x5b, 0x0b, 0xe1, 0xe7, 0x7e,
+    0x88, 0xa0, 0xde, 0x1e, 0x79, 0xb3, 0x08, 0x96, 0xd7, 0x34, 0x11, 0xc5, 0xff, 0x4c, 0x35, 0x18,
+];
+
+/// `pkEm` and `enc` from RFC9180 appendix A.1.3.
+const AUTH_ENC: [u8; 32] = [
+    0x23, 0xfb, 0x95, 0x25, 0x71, 0xa1, 0x4a, 0x25, 0xe3, 0xd6, 0x78, 0x14, 0x0c, 0xd0, 0xe5, 0xeb,
+    0x47, 0xa0, 0x96, 0x1b, 0xb1, 0x8a, 0xfc, 0xf8, 0x58, 0x96, 0xe5, 0x45, 0x3c, 0x31, 0x2e, 0x76,
+];
+
+/// `skRm` from RFC9180 appendix A.1.3.
+const AUTH_SK_R: [u8; 32] = [
+    0xfd, 0xea, 0x67, 0xcf, 0x83, 0x1f, 0x1c, 0xa9, 0x8d, 0x8e, 0x27, 0xb1, 0xf6, 0xab, 0xeb, 0x5b,
+    0x77, 0x45, 0xe9, 0xd3, 0x53, 0x48, 0xb8, 0x0f, 0xa4, 0x07, 0xff, 0x69, 0x58, 0xf9, 0x13, 0x7e,
+];
+
+/// `pkRm` from RFC9180 appendix A.1.3.
+const AUTH_PK_R: [u8; 32] = [
+    0x16, 0x32, 0xd5, 0xc2, 0xf7, 0x1c, 0x2b, 0x38, 0xd0, 0xa8, 0xfc, 0xc3, 0x59, 0x35, 0x52, 0x00,
+    0xca, 0xa8, 0xb1, 0xff, 0xdf, 0x28, 0x61, 0x80, 0x80, 0x46, 0x6c, 0x90, 0x9c, 0xb6, 0x9b, 0x2e,
+];
+
+/// `skSm` from RFC9180 appendix A.1.3.
+const AUTH_SK_S: [u8; 32] = [
+    0xdc, 0x4a, 0x14, 0x63, 0x13, 0xcc, 0xe6, 0x0a, 0x27, 0x8a, 0x53, 0x23, 0xd3, 0x21, 0xf0, 0x51,
+    0xc5, 0x70, 0x7e, 0x9c, 0x45, 0xba, 0x21, 0xa3, 0x47, 0x9f, 0xec, 0xdf, 0x76, 0xfc, 0x69, 0xdd,
+];
+
+/// `pkSm` from RFC9180 appendix A.1.3.
+const AUTH_PK_S: [u8; 32] = [
+    0x8b, 0x0c, 0x70, 0x87, 0x3d, 0xc5, 0xae, 0xcb, 0x7f, 0x9e, 0xe4, 0xe6, 0x24, 0x06, 0xa3, 0x97,
+    0xb3, 0x50, 0xe5, 0x70, 0x12, 0xbe, 0x45, 0xcf, 0x53, 0xb7, 0x10, 0x5a, 0xe7, 0x31, 0x79, 0x0b,
+];
+
+/// `shared_secret` from RFC9180 appendix A.1.3.
+const AUTH_SHARED_SECRET: [u8; 32] = [
+    0x2d, 0x6d, 0xb4, 0xcf, 0x71, 0x9d, 0xc7, 0x29, 0x3f, 0xcb, 0xf3, 0xfa, 0x64, 0x69, 0x07, 0x08,
+    0xe4, 0x4e, 0x2b, 0xeb, 0xc8, 0x1f, 0x84, 0x60, 0x86, 0x77, 0x95, 0x8c, 0x0d, 0x44, 0x48, 0xa7,
+];
+
+#[test]
+fn derive_key_pair_matches_rfc9180() {
+    let (secret, public) = hpke::derive_key_pair(&IKM_E);
+    assert_eq!(secret.to_bytes(), SK_E);
+    assert_eq!(public.to_bytes(), ENC);
+
+    let (secret, public) = hpke::derive_key_pair(&IKM_R);
+    assert_eq!(secret.to_bytes(), SK_R);
+    assert_eq!(public.to_bytes(), PK_R);
+}
+
+#[test]
+fn encap_matches_rfc9180() {
+    let (shared, enc) = hpke::encap(&PublicKey::from(PK_R), ConstRng(SK_E)).unwrap();
+    assert_eq!(enc, ENC);
+    assert_eq!(*shared, SHARED_SECRET);
+}
+
+#[test]
+fn decap_matches_rfc9180() {
+    let shared = hpke::decap(&ENC, &StaticSecret::from(SK_R)).unwrap();
+    assert_eq!(*shared, SHARED_SECRET);
+}
+
+#[test]
+fn encap_decap_round_trip() {
+    let recipient = StaticSecret::random_from_rng(OsRng);
+    let (shared, enc) = hpke::encap(&PublicKey::from(&recipient), OsRng).unwrap();
+    assert_eq!(hpke::decap(&enc, &recipient).unwrap(), shared);
+
+    let other = StaticSecret::random_from_rng(OsRng);
+    assert_ne!(hpke::decap(&enc, &other).unwrap(), shared);
+}
+
+#[test]
+fn small_order_keys_are_rejected() {
+    let invalid = Some(Error::SmallOrderPoint);
+    assert_eq!(
+        hpke::encap(&PublicKey::from([0u8; 32]), OsRng).err(),
+        invalid
+    );
+    assert_eq!(
+        hpke::decap(&[0u8; 32], &StaticSecret::from(SK_R)).err(),
+        invalid
+    );
+}
+
+#[test]
+fn auth_derive_key_pair_matches_rfc9180() {
+    let (secret, public) = hpke::derive_key_pair(&AUTH_IKM_E);
+    assert_eq!(secret.to_bytes(), AUTH_SK_E);
+    assert_eq!(public.to_bytes(), AUTH_ENC);
+    assert_eq!(
+        PublicKey::from(&StaticSecret::from(AUTH_SK_R)).to_bytes(),
+        AUTH_PK_R
+    );
+    assert_eq!(
+        PublicKey::from(&StaticSecret::from(AUTH_SK_S)).to_bytes(),
+        AUTH_PK_S
+    );
+}
+
+#[test]
+fn auth_encap_matches_rfc9180() {
+    let (shared, enc) = hpke::auth_encap(
+        &PublicKey::from(AUTH_PK_R),
+        &StaticSecret::from(AUTH_SK_S),
+        ConstRng(AUTH_SK_E),
+    )
+    .unwrap();
+    assert_eq!(enc, AUTH_ENC);
+    assert_eq!(*shared, AUTH_SHARED_SECRET);
+}
+
+#[test]
+fn auth_decap_matches_rfc9180() {
+    let shared = hpke::auth_decap(
+        &AUTH_ENC,
+        &StaticSecret::from(AUTH_SK_R),
+        &PublicKey::from(AUTH_PK_S),
+    )
+    .unwrap();
+    assert_eq!(*shared, AUTH_SHARED_SECRET);
+}
+
+#[test]
+fn auth_decap_authenticates_sender() {
+    let recipient = StaticSecret::random_from_rng(OsRng);
+    let sender = StaticSecret::random_from_rng(OsRng);
+    let (shared, enc) = hpke::auth_encap(&PublicKey::from(&recipient), &sender, OsRng).unwrap();
+    assert_eq!(
+        hpke::auth_decap(&enc, &recipient, &PublicKey::from(&sender)).unwrap(),
+        shared
+    );
+
+    let impostor = PublicKey::from(&StaticSecret::random_from_rng(OsRng));
+    assert_ne!(
+        hpke::auth_decap(&enc, &recipient, &impostor).unwrap(),
+        shared
+    );
+    assert_ne!(hpke::decap(&enc, &recipient).unwrap(), shared);
+}
+
+#[test]
+fn auth_small_order_keys_are_rejected() {
+    let invalid = Some(Error::SmallOrderPoint);
+    let secret = StaticSecret::from(AUTH_SK_R);
+    assert_eq!(
+        hpke::auth_encap(&PublicKey::from([0u8; 32]), &secret, OsRng).err(),
+        invalid
+    );
+    assert_eq!(
+        hpke::auth_decap(&AUTH_ENC, &secret, &PublicKey::from([0u8; 32])).err(),
+        invalid
+    );
+}
+
+#[cfg(feature = "kem")]
+#[test]
+fn kem_traits_match_rfc9180() {
+    use x25519_dalek::kem::{Decapsulate, Encapsulate};
+
+    let (enc, shared) = PublicKey::from(PK_R)
+        .encapsulate(&mut ConstRng(SK_E))
+        .unwrap();
+    assert_eq!(enc, ENC);
+    assert_eq!(*shared, SHARED_SECRET);
+
+    let shared = StaticSecret::from(SK_R).decapsulate(&ENC).unwrap();
+    assert_eq!(*shared, SHARED_SECRET);
+}
+
+#[cfg(feature = "kem")]
+#[test]
+fn kem_traits_round_trip() {
+    use x25519_dalek::kem::{Decapsulate, Encapsulate};
+
+    fn round_trip<EK, SS: PartialEq + core::fmt::Debug>(
+        encapsulator: &impl Encapsulate<EK, SS>,
+        decapsulator: &impl Decapsulate<EK, SS>,
+    ) {
+        let (enc, shared) = encapsulator.encapsulate(&mut OsRng).unwrap();
+        assert_eq!(decapsulator.decapsulate(&enc).unwrap(), shared);
+    }
+
+    let recipient = StaticSecret::random_from_rng(OsRng);
+    round_trip(&PublicKey::from(&recipient), &recipient);
+}
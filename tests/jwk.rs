@@ -0,0 +1,126 @@
+//! JSON Web Key encoding tests.
+
+#![cfg(feature = "jwk")]
+
+use x25519_dalek::{Error, Jwk, JwkError, PublicKey};
+
+#[cfg(feature = "static_secrets")]
+use x25519_dalek::StaticSecret;
+
+/// The ephemeral private key from RFC8037 appendix A.6.
+#[cfg(feature = "static_secrets")]
+const RFC8037_PRIVATE_JWK: &str = r#"{"kty":"OKP","crv":"X25519","x":"hSDwCYkwp1R0i33ctD73Wg2_Og0mOBr066SpjqqbTmo","d":"dwdtCnMYpX08FsFyUbJmRd9ML4frwJkqsXf7pR25LCo"}"#;
+
+/// Bob's public key from RFC8037 appendix A.6.
+const RFC8037_PUBLIC_JWK: &str =
+    r#"{"kty":"OKP","crv":"X25519","x":"3p7bfXt9wbTTW2HC7OQ1Nz-DQ8hbeGdNrfx-FG-IK08"}"#;
+
+/// Bob's public key, from RFC7748 section 6.1.
+const BOB_PUBLIC: [u8; 32] = [
+    0xde, 0x9e, 0xdb, 0x7d, 0x7b, 0x7d, 0xc1, 0xb4, 0xd3, 0x5b, 0x61, 0xc2, 0xec, 0xe4, 0x35, 0x37,
+    0x3f, 0x83, 0x43, 0xc8, 0x5b, 0x78, 0x67, 0x4d, 0xad, 0xfc, 0x7e, 0x14, 0x6f, 0x88, 0x2b, 0x4f,
+];
+
+#[test]
+fn public_key_jwk_roundtrip() {
+    let jwk: Jwk = serde_json::from_str(RFC8037_PUBLIC_JWK).unwrap();
+    let public = PublicKey::from_jwk(&jwk).unwrap();
+    assert_eq!(public.to_bytes(), BOB_PUBLIC);
+
+    let json = serde_json::to_string(&public.to_jwk()).unwrap();
+    assert_eq!(json, RFC8037_PUBLIC_JWK);
+}
+
+#[test]
+fn public_key_jwk_rejects_wrong_curve() {
+    let mut jwk = PublicKey::from(BOB_PUBLIC).to_jwk();
+    jwk.crv = "Ed25519".into();
+
+    assert_eq!(
+        PublicKey::from_jwk(&jwk),
+        Err(Error::Jwk(JwkError::UnsupportedCurve))
+    );
+}
+
+#[test]
+fn public_key_jwk_rejects_wrong_key_type() {
+    let mut jwk = PublicKey::from(BOB_PUBLIC).to_jwk();
+    jwk.kty = "EC".into();
+
+    assert_eq!(
+        PublicKey::from_jwk(&jwk),
+        Err(Error::Jwk(JwkError::UnsupportedKeyType))
+    );
+}
+
+#[test]
+fn public_key_jwk_rejects_bad_encoding() {
+    let mut jwk = PublicKey::from(BOB_PUBLIC).to_jwk();
+
+    // Padded base64url is not allowed by RFC7515.
+    jwk.x.push('=');
+    assert_eq!(
+        PublicKey::from_jwk(&jwk),
+        Err(Error::Jwk(JwkError::InvalidEncoding))
+    );
+
+    // Too short.
+    jwk.x.truncate(40);
+    assert_eq!(
+        PublicKey::from_jwk(&jwk),
+        Err(Error::Jwk(JwkError::InvalidEncoding))
+    );
+}
+
+#[test]
+#[cfg(feature = "static_secrets")]
+fn static_secret_jwk_roundtrip() {
+    let jwk: Jwk = serde_json::from_str(RFC8037_PRIVATE_JWK).unwrap();
+    let secret = StaticSecret::from_jwk(&jwk).unwrap();
+
+    let json = serde_json::to_string(&secret.to_jwk()).unwrap();
+    assert_eq!(json, RFC8037_PRIVATE_JWK);
+
+    // The shared secret from RFC8037 appendix A.6.
+    let shared = secret.diffie_hellman(&PublicKey::from(BOB_PUBLIC));
+    assert_eq!(
+        shared.to_bytes(),
+        [
+            0x4a, 0x5d, 0x9d, 0x5b, 0xa4, 0xce, 0x2d, 0xe1, 0x72, 0x8e, 0x3b, 0xf4, 0x80, 0x35,
+            0x0f, 0x25, 0xe0, 0x7e, 0x21, 0xc9, 0x47, 0xd1, 0x9e, 0x33, 0x76, 0xf0, 0x9b, 0x3c,
+            0x1e, 0x16, 0x17, 0x42,
+        ]
+    );
+}
+
+#[test]
+#[cfg(feature = "static_secrets")]
+fn static_secret_jwk_requires_private_key() {
+    let jwk: Jwk = serde_json::from_str(RFC8037_PUBLIC_JWK).unwrap();
+
+    assert_eq!(
+        StaticSecret::from_jwk(&jwk).err(),
+        Some(Error::Jwk(JwkError::MissingPrivateKey))
+    );
+}
+
+#[test]
+#[cfg(feature = "static_secrets")]
+fn static_secret_jwk_rejects_mismatched_public_key() {
+    let mut jwk: Jwk = serde_json::from_str(RFC8037_PRIVATE_JWK).unwrap();
+    jwk.x = PublicKey::from(BOB_PUBLIC).to_jwk().x.clone();
+
+    assert_eq!(
+        StaticSecret::from_jwk(&jwk).err(),
+        Some(Error::Jwk(JwkError::PublicKeyMismatch))
+    );
+}
+
+#[test]
+#[cfg(feature = "static_secrets")]
+fn jwk_debug_redacts_private_key() {
+    let jwk: Jwk = serde_json::from_str(RFC8037_PRIVATE_JWK).unwrap();
+    let debug = format!("{:?}", jwk);
+
+    assert!(!debug.contains(jwk.d.as_ref().unwrap()));
+}
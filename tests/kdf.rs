@@ -0,0 +1,356 @@
+//! Shared secret key derivation tests.
+
+#![cfg(all(feature = "kdf", feature = "static_secrets"))]
+
+mod common;
+
+use common::ConstRng;
+use sha2::{Sha256, Sha512};
+use x25519_dalek::kdf::{self, Role, SessionKeys, MAX_OUTPUT_LEN};
+use x25519_dalek::{EphemeralSecret, Error, PublicKey, StaticSecret};
+
+/// Alice's private key, from RFC7748 section 6.1.
+const ALICE_PRIVATE: [u8; 32] = [
+    0x77, 0x07, 0x6d, 0x0a, 0x73, 0x18, 0xa5, 0x7d, 0x3c, 0x16, 0xc1, 0x72, 0x51, 0xb2, 0x66, 0x45,
+    0xdf, 0x4c, 0x2f, 0x87, 0xeb, 0xc0, 0x99, 0x2a, 0xb1, 0x77, 0xfb, 0xa5, 0x1d, 0xb9, 0x2c, 0x2a,
+];
+
+/// Bob's public key, from RFC7748 section 6.1.
+const BOB_PUBLIC: [u8; 32] = [
+    0xde, 0x9e, 0xdb, 0x7d, 0x7b, 0x7d, 0xc1, 0xb4, 0xd3, 0x5b, 0x61, 0xc2, 0xec, 0xe4, 0x35, 0x37,
+    0x3f, 0x83, 0x43, 0xc8, 0x5b, 0x78, 0x67, 0x4d, 0xad, 0xfc, 0x7e, 0x14, 0x6f, 0x88, 0x2b, 0x4f,
+];
+
+/// Alice's public key, from RFC7748 section 6.1.
+const ALICE_PUBLIC: [u8; 32] = [
+    0x85, 0x20, 0xf0, 0x09, 0x89, 0x30, 0xa7, 0x54, 0x74, 0x8b, 0x7d, 0xdc, 0xb4, 0x3e, 0xf7, 0x5a,
+    0x0d, 0xbf, 0x3a, 0x0d, 0x26, 0x38, 0x1a, 0xf4, 0xeb, 0xa4, 0xa9, 0x8e, 0xaa, 0x9b, 0x4e, 0x6a,
+];
+
+/// Bob's private key, from RFC7748 section 6.1.
+const BOB_PRIVATE: [u8; 32] = [
+    0x5d, 0xab, 0x08, 0x7e, 0x62, 0x4a, 0x8a, 0x4b, 0x79, 0xe1, 0x7f, 0x8b, 0x83, 0x80, 0x0e, 0xe6,
+    0x6f, 0x3b, 0xb1, 0x29, 0x26, 0x18, 0xb6, 0xfd, 0x1c, 0x2f, 0x8b, 0x27, 0xff, 0x88, 0xe0, 0xeb,
+];
+
+/// HKDF-SHA256 of the RFC7748 section 6.1 shared secret, with the salt
+/// `"x25519-dalek test"`.
+const PRK: [u8; 32] = [
+    0x4a, 0x37, 0xf2, 0x06, 0x94, 0x45, 0x6a, 0x5f, 0x8a, 0xd3, 0x86, 0x39, 0xe6, 0xa6, 0xe7, 0x88,
+    0x9d, 0xca, 0xaf, 0x92, 0xcf, 0x33, 0x0d, 0x47, 0xa2, 0x9d, 0x55, 0xd6, 0x62, 0x42, 0xcd, 0xce,
+];
+
+/// The first 42 bytes expanded from [`PRK`] with the info `"encryption key"`.
+const OKM: [u8; 42] = [
+    0xff, 0x85, 0x84, 0xa1, 0x7d, 0xb9, 0xf9, 0x14, 0x5f, 0xfe, 0x59, 0x8b, 0x9e, 0xfc, 0x40, 0x44,
+    0x26, 0xc7, 0x52, 0x0c, 0x39, 0x62, 0xd5, 0xe2, 0xed, 0xbd, 0xf9, 0xa8, 0x14, 0xae, 0x73, 0xee,
+    0xec, 0x3f, 0x45, 0x8d, 0x60, 0xc3, 0xd1, 0xd3, 0x0e, 0xda,
+];
+
+fn shared_secret() -> x25519_dalek::SharedSecret {
+    StaticSecret::from(ALICE_PRIVATE).diffie_hellman(&PublicKey::from(BOB_PUBLIC))
+}
+
+#[test]
+fn extract_matches_hkdf_sha256() {
+    let prk = shared_secret().extract(b"x25519-dalek test");
+    assert_eq!(prk.as_bytes(), &PRK);
+}
+
+#[test]
+fn expand_matches_hkdf_sha256() {
+    let prk = shared_secret().extract(b"x25519-dalek test");
+
+    let mut okm = [0u8; 42];
+    prk.expand(b"encryption key", &mut okm).unwrap();
+    assert_eq!(okm, OKM);
+
+    let mut key = [0u8; 32];
+    prk.expand(b"encryption key", &mut key).unwrap();
+    assert_eq!(key, OKM[..32]);
+}
+
+#[test]
+fn expand_separates_info() {
+    let prk = shared_secret().extract(b"x25519-dalek test");
+
+    let mut encryption = [0u8; 32];
+    let mut authentication = [0u8; 32];
+    prk.expand(b"encryption key", &mut encryption).unwrap();
+    prk.expand(b"authentication key", &mut authentication)
+        .unwrap();
+    assert_ne!(encryption, authentication);
+}
+
+#[test]
+fn extract_separates_salt() {
+    let shared = shared_secret();
+    assert_ne!(
+        shared.extract(b"protocol a").as_bytes(),
+        shared.extract(b"protocol b").as_bytes()
+    );
+    assert_eq!(
+        shared.extract(b"").as_bytes(),
+        shared.extract(&[0u8; 32]).as_bytes()
+    );
+}
+
+#[test]
+fn expand_rejects_long_output() {
+    let prk = shared_secret().extract(b"x25519-dalek test");
+
+    let mut okm = [0u8; MAX_OUTPUT_LEN + 1];
+    assert_eq!(
+        prk.expand(b"", &mut okm),
+        Err(Error::Kdf(kdf::Error::InvalidOutputLength))
+    );
+    assert!(prk.expand(b"", &mut okm[..MAX_OUTPUT_LEN]).is_ok());
+}
+
+#[test]
+fn derive_key_sha256_matches_hkdf() {
+    let key = shared_secret().derive_key::<Sha256, 32>(b"x25519-dalek test encryption");
+    assert_eq!(
+        *key,
+        [
+            0xa1, 0xdb, 0xfb, 0x9f, 0xaa, 0xba, 0x7e, 0x1a, 0xfe, 0x3a, 0xb7, 0x01, 0x7b, 0x88,
+            0xc6, 0x43, 0xb6, 0x28, 0x0b, 0x45, 0x79, 0x48, 0xac, 0xce, 0x83, 0xda, 0x67, 0x2b,
+            0xe3, 0x5a, 0x66, 0x94,
+        ]
+    );
+}
+
+#[test]
+fn derive_key_sha512_matches_hkdf() {
+    let key = shared_secret().derive_key::<Sha512, 64>(b"x25519-dalek test encryption");
+    assert_eq!(
+        *key,
+        [
+            0x9a, 0xd1, 0xcb, 0x66, 0x04, 0xcf, 0x26, 0xde, 0xde, 0x8b, 0x68, 0xba, 0xa5, 0xad,
+            0x22, 0xf2, 0x69, 0x2c, 0x32, 0xd4, 0x45, 0x3d, 0x9c, 0x7e, 0x2b, 0x08, 0xc1, 0x57,
+            0xd0, 0x6c, 0x24, 0x45, 0x36, 0x4c, 0xa6, 0x9c, 0x5f, 0x01, 0x67, 0x94, 0xa7, 0xb3,
+            0x01, 0x29, 0xbb, 0x2e, 0x23, 0x00, 0xc6, 0x21, 0x25, 0x68, 0xa1, 0x1d, 0x1d, 0xff,
+            0xec, 0xfe, 0x55, 0x40, 0x06, 0x08, 0x9b, 0xe3,
+        ]
+    );
+}
+
+#[test]
+fn derive_key_separates_context() {
+    let shared = shared_secret();
+    assert_ne!(
+        *shared.derive_key::<Sha256, 32>(b"encryption"),
+        *shared.derive_key::<Sha256, 32>(b"authentication")
+    );
+}
+
+#[test]
+fn session_keys_match_hkdf_sha256() {
+    let keys = SessionKeys::derive(
+        &shared_secret(),
+        Role::Initiator,
+        b"x25519-dalek test transcript",
+    );
+    assert_eq!(
+        keys.send_key(),
+        &[
+            0xe6, 0xbf, 0x20, 0x33, 0x00, 0x5e, 0xbc, 0x2f, 0x82, 0x12, 0xf7, 0x0f, 0xe4, 0x25,
+            0x22, 0xaf, 0x3b, 0x23, 0xc2, 0x74, 0xe6, 0x69, 0xd2, 0x66, 0xb1, 0xe0, 0xa3, 0x77,
+            0xab, 0x54, 0xbf, 0xc6,
+        ]
+    );
+    assert_eq!(
+        keys.send_iv(),
+        &[0x27, 0x48, 0x7a, 0xbb, 0xf1, 0xbc, 0xe6, 0xda, 0xe5, 0x89, 0xd8, 0xb8]
+    );
+    assert_eq!(
+        keys.receive_key(),
+        &[
+            0x20, 0xb8, 0x75, 0x4a, 0x55, 0xda, 0xe7, 0xa4, 0xf5, 0x23, 0x26, 0x66, 0xb1, 0x9d,
+            0xf0, 0x73, 0x55, 0x23, 0x69, 0xf1, 0x33, 0xd9, 0xe5, 0xde, 0xd7, 0x05, 0x0d, 0x05,
+            0xd3, 0x18, 0x8f, 0xa1,
+        ]
+    );
+    assert_eq!(
+        keys.receive_iv(),
+        &[0xef, 0x9f, 0xc0, 0x40, 0x04, 0x6d, 0x5d, 0x8e, 0x47, 0x36, 0x83, 0x5e]
+    );
+}
+
+#[test]
+fn session_keys_are_mirrored() {
+    let alice_shared = shared_secret();
+    let bob_shared = StaticSecret::from(BOB_PRIVATE).diffie_hellman(&PublicKey::from(ALICE_PUBLIC));
+
+    let alice = SessionKeys::derive(&alice_shared, Role::Initiator, b"transcript");
+    let bob = SessionKeys::derive(&bob_shared, Role::Responder, b"transcript");
+    assert_eq!(alice.send_key(), bob.receive_key());
+    assert_eq!(alice.send_iv(), bob.receive_iv());
+    assert_eq!(alice.receive_key(), bob.send_key());
+    assert_eq!(alice.receive_iv(), bob.send_iv());
+    assert_ne!(alice.send_key(), alice.receive_key());
+}
+
+#[test]
+fn session_keys_bind_transcript() {
+    let shared = shared_secret();
+    let a = SessionKeys::derive(&shared, Role::Initiator, b"transcript a");
+    let b = SessionKeys::derive(&shared, Role::Initiator, b"transcript b");
+    assert_ne!(a.send_key(), b.send_key());
+    assert_ne!(a.receive_key(), b.receive_key());
+}
+
+#[test]
+fn derive_bound_key_matches_hkdf_sha256() {
+    let alice_public = PublicKey::from(ALICE_PUBLIC);
+    let bob_public = PublicKey::from(BOB_PUBLIC);
+    let key = shared_secret().derive_bound_key(&alice_public, &bob_public, b"x25519-dalek test");
+    assert_eq!(
+        *key,
+        [
+            0xd8, 0x42, 0x7d, 0x8e, 0xab, 0x03, 0x40, 0x32, 0x1e, 0x31, 0x8a, 0xcf, 0xdb, 0xd5,
+            0x1a, 0x3f, 0x9d, 0x07, 0x43, 0x72, 0xef, 0xeb, 0xe8, 0xa9, 0x8c, 0x82, 0x3d, 0xcf,
+            0xc5, 0xee, 0x68, 0x1b,
+        ]
+    );
+
+    let mut transcript = [0u8; 64];
+    transcript[..32].copy_from_slice(&ALICE_PUBLIC);
+    transcript[32..].copy_from_slice(&BOB_PUBLIC);
+    assert_eq!(
+        key,
+        shared_secret().derive_key_with_transcript(&transcript, b"x25519-dalek test")
+    );
+}
+
+#[test]
+fn derive_bound_key_is_symmetric() {
+    let alice_public = PublicKey::from(ALICE_PUBLIC);
+    let bob_public = PublicKey::from(BOB_PUBLIC);
+    let bob_shared = StaticSecret::from(BOB_PRIVATE).diffie_hellman(&alice_public);
+
+    assert_eq!(
+        shared_secret().derive_bound_key(&alice_public, &bob_public, b"context"),
+        bob_shared.derive_bound_key(&bob_public, &alice_public, b"context")
+    );
+}
+
+#[test]
+fn derive_bound_key_binds_public_keys() {
+    let alice_public = PublicKey::from(ALICE_PUBLIC);
+    let bob_public = PublicKey::from(BOB_PUBLIC);
+    let mallory_public = PublicKey::from([9u8; 32]);
+    let shared = shared_secret();
+
+    assert_ne!(
+        shared.derive_bound_key(&alice_public, &bob_public, b"context"),
+        shared.derive_bound_key(&alice_public, &mallory_public, b"context")
+    );
+    assert_ne!(
+        shared.derive_bound_key(&alice_public, &bob_public, b"context a"),
+        shared.derive_bound_key(&alice_public, &bob_public, b"context b")
+    );
+}
+
+/// The pre-shared key `00 01 02 .. 1f`.
+const PSK: [u8; 32] = [
+    0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e, 0x0f,
+    0x10, 0x11, 0x12, 0x13, 0x14, 0x15, 0x16, 0x17, 0x18, 0x19, 0x1a, 0x1b, 0x1c, 0x1d, 0x1e, 0x1f,
+];
+
+#[test]
+fn extract_with_psk_matches_wireguard_kdf1() {
+    // HMAC(HMAC(PRK, PSK), 0x01), as in WireGuard's KDF1, over SHA-256.
+    let prk = shared_secret().extract_with_psk(b"x25519-dalek test", &PSK);
+    assert_eq!(
+        prk.as_bytes(),
+        &[
+            0x5c, 0xff, 0xa6, 0x6f, 0xae, 0x2a, 0xa3, 0xb1, 0x36, 0xd2, 0xed, 0x6b, 0x40, 0x10,
+            0x85, 0x93, 0x1c, 0x45, 0xde, 0x4c, 0x72, 0xba, 0xbb, 0x12, 0x91, 0xbd, 0xce, 0xd8,
+            0x04, 0x3a, 0xbc, 0x09,
+        ]
+    );
+    assert_eq!(
+        prk.as_bytes(),
+        shared_secret()
+            .extract(b"x25519-dalek test")
+            .mix_key(&PSK)
+            .as_bytes()
+    );
+}
+
+#[test]
+fn mix_key_separates_psk() {
+    let prk = shared_secret().extract(b"x25519-dalek test");
+    assert_ne!(prk.mix_key(&PSK).as_bytes(), prk.as_bytes());
+    assert_ne!(
+        prk.mix_key(&PSK).as_bytes(),
+        prk.mix_key(&[0u8; 32]).as_bytes()
+    );
+}
+
+/// The triple Diffie-Hellman key of Alice and Bob, with ephemeral secret
+/// keys of all `0x11` and all `0x22` bytes, computed with Python.
+const TRIPLE_DH_KEY: [u8; 32] = [
+    0xfe, 0x9e, 0x93, 0xab, 0xef, 0xd4, 0xe4, 0x5a, 0xde, 0x9d, 0xf5, 0x28, 0x33, 0x14, 0x10, 0x49,
+    0x01, 0xaa, 0x54, 0x8c, 0x5c, 0xe3, 0x5e, 0x21, 0xcf, 0x47, 0x0c, 0x57, 0x4c, 0x34, 0xc8, 0x68,
+];
+
+/// An ephemeral secret key of all `byte` bytes.
+fn ephemeral(byte: u8) -> EphemeralSecret {
+    EphemeralSecret::random_from_rng(ConstRng([byte; 32]))
+}
+
+#[test]
+fn triple_dh_matches_hkdf_sha256() {
+    let alice = StaticSecret::from(ALICE_PRIVATE);
+    let bob = StaticSecret::from(BOB_PRIVATE);
+    let alice_ephemeral = ephemeral(0x11);
+    let bob_ephemeral = ephemeral(0x22);
+    let alice_ephemeral_public = PublicKey::from(&alice_ephemeral);
+    let bob_ephemeral_public = PublicKey::from(&bob_ephemeral);
+
+    let alice_key = kdf::triple_dh(
+        &alice,
+        alice_ephemeral,
+        &PublicKey::from(BOB_PUBLIC),
+        &bob_ephemeral_public,
+    )
+    .unwrap();
+    let bob_key = kdf::triple_dh(
+        &bob,
+        bob_ephemeral,
+        &PublicKey::from(ALICE_PUBLIC),
+        &alice_ephemeral_public,
+    )
+    .unwrap();
+    assert_eq!(*alice_key, TRIPLE_DH_KEY);
+    assert_eq!(*bob_key, TRIPLE_DH_KEY);
+}
+
+#[test]
+fn triple_dh_binds_identities() {
+    let alice = StaticSecret::from(ALICE_PRIVATE);
+    let bob_ephemeral_public = PublicKey::from(&ephemeral(0x22));
+
+    // Swapping Bob's identity and ephemeral keys changes the key.
+    let swapped = kdf::triple_dh(
+        &alice,
+        ephemeral(0x11),
+        &bob_ephemeral_public,
+        &PublicKey::from(BOB_PUBLIC),
+    )
+    .unwrap();
+    assert_ne!(*swapped, TRIPLE_DH_KEY);
+
+    assert_eq!(
+        kdf::triple_dh(
+            &alice,
+            ephemeral(0x11),
+            &PublicKey::from(BOB_PUBLIC),
+            &PublicKey::from([0u8; 32]),
+        )
+        .err(),
+        Some(Error::SmallOrderPoint)
+    );
+}
@@ -0,0 +1,66 @@
+//! Key blinding tests.
+
+#![cfg(feature = "key_blinding")]
+
+use rand_core::{OsRng, RngCore};
+use x25519_dalek::{PublicKey, StaticSecret};
+
+/// Clamp a scalar as X25519 does.
+fn clamp(mut bytes: [u8; 32]) -> [u8; 32] {
+    bytes[0] &= 248;
+    bytes[31] &= 127;
+    bytes[31] |= 64;
+    bytes
+}
+
+#[test]
+fn blinded_secret_matches_blinded_public_key() {
+    // Both branches of the re-encoding are exercised with overwhelming
+    // probability.
+    for _ in 0..32 {
+        let secret = StaticSecret::random_from_rng(OsRng);
+        let mut factor = [0u8; 32];
+        OsRng.fill_bytes(&mut factor);
+
+        let blinded = secret.blind(&factor);
+        assert_eq!(clamp(blinded.to_bytes()), blinded.to_bytes());
+        assert_eq!(
+            PublicKey::from(&blinded),
+            PublicKey::from(&secret).blind(&factor)
+        );
+    }
+}
+
+#[test]
+fn blinded_keys_agree() {
+    let secret = StaticSecret::from([0x11; 32]);
+    let peer = StaticSecret::from([0x22; 32]);
+
+    let blinded = secret.blind(b"period 1");
+    let blinded_public = PublicKey::from(&secret).blind(b"period 1");
+
+    assert_eq!(
+        blinded.diffie_hellman(&PublicKey::from(&peer)).as_bytes(),
+        peer.diffie_hellman(&blinded_public).as_bytes()
+    );
+
+    // Blinding both sides by different factors still agrees.
+    let peer_blinded = peer.blind(b"peer");
+    assert_eq!(
+        blinded
+            .diffie_hellman(&PublicKey::from(&peer).blind(b"peer"))
+            .as_bytes(),
+        peer_blinded.diffie_hellman(&blinded_public).as_bytes()
+    );
+}
+
+#[test]
+fn blinding_is_deterministic_and_unlinkable() {
+    let secret = StaticSecret::from([0x11; 32]);
+    let public = PublicKey::from(&secret);
+
+    assert_eq!(public.blind(b"a"), public.blind(b"a"));
+    assert_eq!(secret.blind(b"a").to_bytes(), secret.blind(b"a").to_bytes());
+    assert_ne!(public.blind(b"a"), public.blind(b"b"));
+    assert_ne!(public.blind(b"a"), public);
+}
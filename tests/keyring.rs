@@ -0,0 +1,140 @@
+//! Platform keyring storage tests, against an in-memory credential store.
+
+#![cfg(feature = "keyring")]
+
+use std::any::Any;
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex, Once};
+
+use keyring::credential::{Credential, CredentialApi, CredentialBuilderApi};
+use rand_core::OsRng;
+use x25519_dalek::keyring::{delete, load, store, Error as KeyringError};
+use x25519_dalek::{Error, PublicKey, StaticSecret};
+
+/// The entries of the test store, by service and name.
+type Entries = Arc<Mutex<BTreeMap<(String, String), Vec<u8>>>>;
+
+/// A credential of the test store.  Unlike the `keyring` crate's mock, its
+/// secret outlives the `Entry` which set it.
+struct TestCredential {
+    entries: Entries,
+    key: (String, String),
+}
+
+impl CredentialApi for TestCredential {
+    fn set_secret(&self, secret: &[u8]) -> keyring::Result<()> {
+        let mut entries = self.entries.lock().unwrap();
+        entries.insert(self.key.clone(), secret.to_vec());
+        Ok(())
+    }
+
+    fn get_secret(&self) -> keyring::Result<Vec<u8>> {
+        let entries = self.entries.lock().unwrap();
+        entries
+            .get(&self.key)
+            .cloned()
+            .ok_or(keyring::Error::NoEntry)
+    }
+
+    fn delete_credential(&self) -> keyring::Result<()> {
+        let mut entries = self.entries.lock().unwrap();
+        entries
+            .remove(&self.key)
+            .map(|_| ())
+            .ok_or(keyring::Error::NoEntry)
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+#[derive(Default)]
+struct TestCredentialBuilder {
+    entries: Entries,
+}
+
+impl CredentialBuilderApi for TestCredentialBuilder {
+    fn build(
+        &self,
+        _target: Option<&str>,
+        service: &str,
+        user: &str,
+    ) -> keyring::Result<Box<Credential>> {
+        if service.is_empty() || user.is_empty() {
+            return Err(keyring::Error::Invalid(
+                "service or user".into(),
+                "empty".into(),
+            ));
+        }
+        Ok(Box::new(TestCredential {
+            entries: self.entries.clone(),
+            key: (service.into(), user.into()),
+        }))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+fn use_test_store() {
+    static INIT: Once = Once::new();
+    INIT.call_once(|| {
+        keyring::set_default_credential_builder(Box::new(TestCredentialBuilder::default()))
+    });
+}
+
+#[test]
+fn keyring_round_trip() {
+    use_test_store();
+    let secret = StaticSecret::random_from_rng(OsRng);
+
+    store("x25519-dalek", "round trip", &secret).unwrap();
+    let loaded = load("x25519-dalek", "round trip").unwrap();
+    assert_eq!(loaded.to_bytes(), secret.to_bytes());
+    assert_eq!(PublicKey::from(&loaded), PublicKey::from(&secret));
+
+    // Storing again replaces the secret key.
+    let replacement = StaticSecret::random_from_rng(OsRng);
+    store("x25519-dalek", "round trip", &replacement).unwrap();
+    let loaded = load("x25519-dalek", "round trip").unwrap();
+    assert_eq!(loaded.to_bytes(), replacement.to_bytes());
+
+    delete("x25519-dalek", "round trip").unwrap();
+    assert_eq!(
+        load("x25519-dalek", "round trip").err(),
+        Some(Error::Keyring(KeyringError::NoEntry))
+    );
+}
+
+#[test]
+fn keyring_errors() {
+    use_test_store();
+
+    assert_eq!(
+        load("x25519-dalek", "missing").err(),
+        Some(Error::Keyring(KeyringError::NoEntry))
+    );
+    assert_eq!(
+        delete("x25519-dalek", "missing").err(),
+        Some(Error::Keyring(KeyringError::NoEntry))
+    );
+    assert_eq!(
+        load("", "identity").err(),
+        Some(Error::Keyring(KeyringError::InvalidName))
+    );
+
+    // An entry of another length, such as a password, is not a secret key.
+    keyring::Entry::new("x25519-dalek", "password")
+        .unwrap()
+        .set_password("hunter2")
+        .unwrap();
+    assert_eq!(
+        load("x25519-dalek", "password").err(),
+        Some(Error::InvalidLength {
+            expected: 32,
+            actual: 7
+        })
+    );
+}
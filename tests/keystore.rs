@@ -0,0 +1,215 @@
+//! JSON keystore tests.
+
+#![cfg(feature = "json_keystore")]
+
+mod common;
+
+use common::FailingRng;
+use rand_core::OsRng;
+use x25519_dalek::keystore::{self, Keystore, Params, VERSION};
+use x25519_dalek::{Error, StaticSecret};
+
+/// Parameters cheap enough for tests.
+const PARAMS: Params = Params {
+    log_n: 10,
+    r: 8,
+    p: 1,
+};
+
+/// A keystore with `geth --lightkdf`'s scrypt parameters, made with Python's
+/// `hashlib.scrypt` and `cryptography`, holding the secret key of the Web3
+/// Secret Storage Definition's test vectors under the password
+/// "testpassword".
+const LIGHT_KEYSTORE: &str = r#"{
+    "address": "008aeeda4d805471df9b2a5b0f38a0c3bcba786b",
+    "crypto": {
+        "cipher": "aes-128-ctr",
+        "cipherparams": {
+            "iv": "a0a1a2a3a4a5a6a7a8a9aaabacadaeaf"
+        },
+        "ciphertext": "1b466e0cb82a90a11c644751846a7ba64c7cb82ae6700876dba9814e2d262b50",
+        "kdf": "scrypt",
+        "kdfparams": {
+            "dklen": 32,
+            "n": 4096,
+            "p": 6,
+            "r": 8,
+            "salt": "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f"
+        },
+        "mac": "a5043b14cea0d3dc68b73e9784d44271ccb39aae552a020d3853fcb844ccb78d"
+    },
+    "id": "e13b209c-3b2f-4327-bab0-3bef2e51630d",
+    "version": 3
+}"#;
+
+/// The secret key of `LIGHT_KEYSTORE`.
+const LIGHT_SECRET: &str = "7a28b5ba57c53603b0b07b56bba752f7784bf506fa95edc395f5cf6c7514fe9d";
+
+fn new_keystore(secret: &StaticSecret) -> Keystore {
+    secret
+        .to_keystore(b"correct horse", &PARAMS, OsRng)
+        .unwrap()
+}
+
+#[test]
+fn decode_light_keystore() {
+    let keystore: Keystore = serde_json::from_str(LIGHT_KEYSTORE).unwrap();
+    let secret = StaticSecret::from_keystore(&keystore, b"testpassword").unwrap();
+    assert_eq!(secret.to_bytes(), common::hex32(LIGHT_SECRET));
+
+    assert_eq!(
+        StaticSecret::from_keystore(&keystore, b"wrongpassword").err(),
+        Some(Error::Keystore(keystore::Error::InvalidMac))
+    );
+
+    // Some older tools capitalise the `crypto` member.
+    let capitalised = LIGHT_KEYSTORE.replace(r#""crypto""#, r#""Crypto""#);
+    let capitalised: Keystore = serde_json::from_str(&capitalised).unwrap();
+    assert_eq!(capitalised, keystore);
+}
+
+#[test]
+fn keystore_round_trip() {
+    let secret = StaticSecret::random_from_rng(OsRng);
+    let keystore = new_keystore(&secret);
+    assert_eq!(keystore.version, VERSION);
+    assert_eq!(keystore.crypto.kdfparams.n, 1024);
+    assert_eq!(keystore.id.len(), 36);
+    assert_eq!(&keystore.id[14..15], "4");
+
+    let json = serde_json::to_string(&keystore).unwrap();
+    let decoded: Keystore = serde_json::from_str(&json).unwrap();
+    assert_eq!(decoded, keystore);
+    let decrypted = StaticSecret::from_keystore(&decoded, b"correct horse").unwrap();
+    assert_eq!(decrypted.to_bytes(), secret.to_bytes());
+
+    // Each keystore has a new salt, IV and UUID.
+    let again = new_keystore(&secret);
+    assert_ne!(again.crypto.kdfparams.salt, keystore.crypto.kdfparams.salt);
+    assert_ne!(
+        again.crypto.cipherparams.iv,
+        keystore.crypto.cipherparams.iv
+    );
+    assert_ne!(again.id, keystore.id);
+}
+
+#[test]
+fn keystore_wrong_or_modified() {
+    let secret = StaticSecret::random_from_rng(OsRng);
+    let keystore = new_keystore(&secret);
+
+    assert_eq!(
+        StaticSecret::from_keystore(&keystore, b"battery staple").err(),
+        Some(Error::Keystore(keystore::Error::InvalidMac))
+    );
+
+    let mut ciphertext = keystore.clone();
+    ciphertext.crypto.ciphertext.replace_range(0..1, "x");
+    assert_eq!(
+        StaticSecret::from_keystore(&ciphertext, b"correct horse").err(),
+        Some(Error::Keystore(keystore::Error::InvalidEncoding))
+    );
+    let flipped = if keystore.crypto.ciphertext.starts_with('0') {
+        "1"
+    } else {
+        "0"
+    };
+    ciphertext.crypto.ciphertext.replace_range(0..1, flipped);
+    assert_eq!(
+        StaticSecret::from_keystore(&ciphertext, b"correct horse").err(),
+        Some(Error::Keystore(keystore::Error::InvalidMac))
+    );
+
+    let mut salt = keystore.clone();
+    salt.crypto.kdfparams.salt.push_str("00");
+    assert_eq!(
+        StaticSecret::from_keystore(&salt, b"correct horse").err(),
+        Some(Error::Keystore(keystore::Error::InvalidMac))
+    );
+
+    let mut short = keystore;
+    short.crypto.ciphertext.truncate(62);
+    short.crypto.mac.truncate(62);
+    assert_eq!(
+        StaticSecret::from_keystore(&short, b"correct horse").err(),
+        Some(Error::InvalidLength {
+            expected: 32,
+            actual: 31
+        })
+    );
+}
+
+#[test]
+fn keystore_unsupported() {
+    let keystore = new_keystore(&StaticSecret::random_from_rng(OsRng));
+
+    let mut version = keystore.clone();
+    version.version = 4;
+    assert_eq!(
+        StaticSecret::from_keystore(&version, b"correct horse").err(),
+        Some(Error::Keystore(keystore::Error::UnsupportedVersion))
+    );
+
+    let mut cipher = keystore.clone();
+    cipher.crypto.cipher = "aes-128-cbc".into();
+    assert_eq!(
+        StaticSecret::from_keystore(&cipher, b"correct horse").err(),
+        Some(Error::Keystore(keystore::Error::UnsupportedCipher))
+    );
+
+    // A PBKDF2 keystore parses, but is not decrypted.
+    let json = serde_json::to_string(&keystore)
+        .unwrap()
+        .replace(r#""kdf":"scrypt""#, r#""kdf":"pbkdf2""#)
+        .replace(
+            r#""n":1024,"r":8,"p":1,"#,
+            r#""c":262144,"prf":"hmac-sha256","#,
+        );
+    let pbkdf2: Keystore = serde_json::from_str(&json).unwrap();
+    assert_eq!(
+        StaticSecret::from_keystore(&pbkdf2, b"correct horse").err(),
+        Some(Error::Keystore(keystore::Error::UnsupportedKdf))
+    );
+
+    // The definition's own scrypt test vector has `n = 2^18` with `r = 1`,
+    // beyond the `n < 2^(16 * r)` of RFC7914.
+    for (n, r, p) in [
+        (1000, 8, 1),
+        (1 << 21, 8, 1),
+        (1024, 8, 17),
+        (1024, 0, 1),
+        (1 << 18, 1, 8),
+    ] {
+        let mut params = keystore.clone();
+        params.crypto.kdfparams.n = n;
+        params.crypto.kdfparams.r = r;
+        params.crypto.kdfparams.p = p;
+        assert_eq!(
+            StaticSecret::from_keystore(&params, b"correct horse").err(),
+            Some(Error::Keystore(keystore::Error::InvalidParams)),
+            "{} {} {}",
+            n,
+            r,
+            p
+        );
+    }
+}
+
+#[test]
+fn keystore_write_errors() {
+    let secret = StaticSecret::random_from_rng(OsRng);
+    let params = Params {
+        log_n: 21,
+        ..PARAMS
+    };
+    assert_eq!(
+        secret.to_keystore(b"correct horse", &params, OsRng).err(),
+        Some(Error::Keystore(keystore::Error::InvalidParams))
+    );
+    assert_eq!(
+        secret
+            .to_keystore(b"correct horse", &PARAMS, FailingRng)
+            .err(),
+        Some(Error::Rng)
+    );
+}
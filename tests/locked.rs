@@ -0,0 +1,67 @@
+//! Page-locked static secret tests.
+
+#![cfg(feature = "mlock")]
+
+mod common;
+
+use common::{ConstRng, FailingRng};
+use rand_core::OsRng;
+use x25519_dalek::{Error, LockedSecret, PublicKey, StaticSecret, X25519Provider};
+
+#[test]
+fn locked_secret_matches_static_secret() {
+    let locked = LockedSecret::random_from_rng(ConstRng([0x42; 32])).unwrap();
+    let secret = StaticSecret::from([0x42; 32]);
+    assert_eq!(locked.secret().to_bytes(), secret.to_bytes());
+    assert_eq!(PublicKey::from(&locked), PublicKey::from(&secret));
+
+    let peer = PublicKey::from(&StaticSecret::random_from_rng(OsRng));
+    assert_eq!(
+        locked.diffie_hellman(&peer).as_bytes(),
+        secret.diffie_hellman(&peer).as_bytes()
+    );
+    assert_eq!(
+        X25519Provider::diffie_hellman(&locked, &peer)
+            .unwrap()
+            .as_bytes(),
+        secret.diffie_hellman(&peer).as_bytes()
+    );
+}
+
+#[test]
+fn locked_secret_from_bytes() {
+    let secret = StaticSecret::random_from_rng(OsRng);
+    let locked = LockedSecret::try_from(&secret.to_bytes()[..]).unwrap();
+    assert_eq!(locked.secret().to_bytes(), secret.to_bytes());
+
+    assert_eq!(
+        LockedSecret::try_from(&[0u8; 31][..]).err(),
+        Some(Error::InvalidLength {
+            expected: 32,
+            actual: 31
+        })
+    );
+}
+
+#[test]
+fn locked_secret_rng_failure() {
+    assert_eq!(
+        LockedSecret::random_from_rng(FailingRng).err(),
+        Some(Error::Rng)
+    );
+}
+
+#[test]
+fn many_locked_secrets() {
+    let secrets: Vec<_> = (0..16)
+        .map(|_| LockedSecret::random_from_rng(OsRng).unwrap())
+        .collect();
+    let publics: Vec<_> = secrets.iter().map(PublicKey::from).collect();
+
+    // Each secret has pages of its own, so dropping half of them leaves the
+    // others intact.
+    let kept: Vec<_> = secrets.into_iter().skip(1).step_by(2).collect();
+    for (secret, public) in kept.iter().zip(publics.iter().skip(1).step_by(2)) {
+        assert_eq!(PublicKey::from(secret), *public);
+    }
+}
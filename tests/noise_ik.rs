@@ -0,0 +1,199 @@
+//! Noise IK handshake tests.
+
+#![cfg(feature = "noise_ik")]
+
+use chacha20poly1305::aead::generic_array::GenericArray;
+use chacha20poly1305::{AeadInPlace, ChaCha20Poly1305, KeyInit};
+use x25519_dalek::noise_ik::{self, INITIATION_OVERHEAD, RESPONSE_OVERHEAD};
+use x25519_dalek::{Error, PublicKey, StaticSecret};
+
+/// The prologue of the cacophony test vectors.
+const PROLOGUE: &[u8] = b"John Galt";
+
+/// `init_static` of the cacophony `Noise_IK_25519_ChaChaPoly_BLAKE2s` vector.
+const INIT_STATIC: [u8; 32] = [
+    0xe6, 0x1e, 0xf9, 0x91, 0x9c, 0xde, 0x45, 0xdd, 0x5f, 0x82, 0x16, 0x64, 0x04, 0xbd, 0x08, 0xe3,
+    0x8b, 0xce, 0xb5, 0xdf, 0xdf, 0xde, 0xd0, 0xa3, 0x4c, 0x8d, 0xf7, 0xed, 0x54, 0x22, 0x14, 0xd1,
+];
+
+/// `init_ephemeral` of the cacophony vector.
+const INIT_EPHEMERAL: [u8; 32] = [
+    0x89, 0x3e, 0x28, 0xb9, 0xdc, 0x6c, 0xa8, 0xd6, 0x11, 0xab, 0x66, 0x47, 0x54, 0xb8, 0xce, 0xb7,
+    0xba, 0xc5, 0x11, 0x73, 0x49, 0xa4, 0x43, 0x9a, 0x6b, 0x05, 0x69, 0xda, 0x97, 0x7c, 0x46, 0x4a,
+];
+
+/// `resp_static` of the cacophony vector.
+const RESP_STATIC: [u8; 32] = [
+    0x4a, 0x3a, 0xcb, 0xfd, 0xb1, 0x63, 0xde, 0xc6, 0x51, 0xdf, 0xa3, 0x19, 0x4d, 0xec, 0xe6, 0x76,
+    0xd4, 0x37, 0x02, 0x9c, 0x62, 0xa4, 0x08, 0xb4, 0xc5, 0xea, 0x91, 0x14, 0x24, 0x6e, 0x48, 0x93,
+];
+
+/// `resp_ephemeral` of the cacophony vector.
+const RESP_EPHEMERAL: [u8; 32] = [
+    0xbb, 0xdb, 0x4c, 0xdb, 0xd3, 0x09, 0xf1, 0xa1, 0xf2, 0xe1, 0x45, 0x69, 0x67, 0xfe, 0x28, 0x8c,
+    0xad, 0xd6, 0xf7, 0x12, 0xd6, 0x5d, 0xc7, 0xb7, 0x79, 0x3d, 0x5e, 0x63, 0xda, 0x6b, 0x37, 0x5b,
+];
+
+/// `handshake_hash` of the cacophony vector.
+const HANDSHAKE_HASH: [u8; 32] = [
+    0x48, 0xf3, 0xcb, 0x8b, 0xc9, 0x31, 0x9d, 0xa4, 0xba, 0x1e, 0x99, 0x33, 0x99, 0x1b, 0x1c, 0x4e,
+    0xd4, 0x03, 0x4f, 0x1f, 0x12, 0x6a, 0x76, 0xd3, 0xa1, 0xfb, 0xcf, 0xd7, 0xf9, 0x42, 0x48, 0xd4,
+];
+
+/// The first handshake message of the cacophony vector, with the payload
+/// `"Ludwig von Mises"`.
+const MESSAGE_1: [u8; 112] = [
+    0xca, 0x35, 0xde, 0xf5, 0xae, 0x56, 0xce, 0xc3, 0x3d, 0xc2, 0x03, 0x67, 0x31, 0xab, 0x14, 0x89,
+    0x6b, 0xc4, 0xc7, 0x5d, 0xbb, 0x07, 0xa6, 0x1f, 0x87, 0x9f, 0x8e, 0x3a, 0xfa, 0x4c, 0x79, 0x44,
+    0x0b, 0x03, 0xdd, 0xc7, 0xaa, 0xc5, 0x12, 0x3d, 0x06, 0xa1, 0xb2, 0x3b, 0x71, 0x67, 0x0e, 0x32,
+    0xe7, 0x6c, 0x28, 0x23, 0x9a, 0x7c, 0xa4, 0xac, 0x8f, 0x78, 0x4d, 0xe7, 0xe4, 0x4c, 0x1a, 0xdb,
+    0xfc, 0x6e, 0x83, 0xfe, 0xf7, 0x35, 0x2a, 0x58, 0xd9, 0xd5, 0x61, 0x57, 0x40, 0x0c, 0x0a, 0x73,
+    0x7b, 0x1d, 0x17, 0x1c, 0xe3, 0x68, 0x22, 0x9c, 0x7b, 0x75, 0x2a, 0xc2, 0x5b, 0x8f, 0xaf, 0x4e,
+    0xca, 0x69, 0x0f, 0x6d, 0x89, 0x6f, 0x54, 0x3b, 0xe0, 0x2c, 0x99, 0x6a, 0xb2, 0xb8, 0x6b, 0x76,
+];
+
+/// The second handshake message of the cacophony vector, with the payload
+/// `"Murray Rothbard"`.
+const MESSAGE_2: [u8; 63] = [
+    0x95, 0xeb, 0xc6, 0x0d, 0x2b, 0x1f, 0xa6, 0x72, 0xc1, 0xf4, 0x6a, 0x8a, 0xa2, 0x65, 0xef, 0x51,
+    0xbf, 0xe3, 0x8e, 0x7c, 0xcb, 0x39, 0xec, 0x5b, 0xe3, 0x40, 0x69, 0xf1, 0x44, 0x80, 0x88, 0x43,
+    0xd9, 0xb5, 0xa8, 0x92, 0x7f, 0x0a, 0xc9, 0x65, 0x5e, 0xf7, 0x68, 0x33, 0xbc, 0x7e, 0x55, 0x61,
+    0xf4, 0x2e, 0x69, 0x1a, 0xc8, 0x40, 0x4e, 0xfd, 0x6f, 0xbd, 0x63, 0x08, 0xb6, 0xa2, 0x7c,
+];
+
+/// The initiator's first transport message of the cacophony vector, of
+/// `"F. A. Hayek"`.
+const TRANSPORT_1: [u8; 27] = [
+    0x2c, 0x25, 0x6e, 0xd0, 0x8f, 0xcd, 0x08, 0xc2, 0x98, 0x0f, 0x95, 0x4e, 0xe4, 0xbe, 0xac, 0xcb,
+    0x61, 0xc9, 0x58, 0x13, 0x40, 0xf5, 0xdd, 0x2f, 0xd1, 0xcf, 0x3b,
+];
+
+/// The responder's first transport message of the cacophony vector, of
+/// `"Carl Menger"`.
+const TRANSPORT_2: [u8; 27] = [
+    0xd6, 0x03, 0x3f, 0x70, 0xee, 0xe2, 0x09, 0x45, 0xc7, 0xc9, 0xdb, 0xa3, 0x04, 0xe3, 0x97, 0xee,
+    0x3b, 0x28, 0x4f, 0xf5, 0xe0, 0x0f, 0xd9, 0xef, 0xb0, 0x95, 0xd3,
+];
+
+/// Encrypt `plaintext` as the first transport message under `key`.
+fn transport(key: &[u8; 32], plaintext: &[u8]) -> Vec<u8> {
+    let mut buffer = plaintext.to_vec();
+    let tag = ChaCha20Poly1305::new(GenericArray::from_slice(key))
+        .encrypt_in_place_detached(&GenericArray::default(), b"", &mut buffer)
+        .unwrap();
+    buffer.extend_from_slice(&tag);
+    buffer
+}
+
+#[test]
+fn handshake_matches_cacophony() {
+    let init_static = StaticSecret::from(INIT_STATIC);
+    let resp_static = StaticSecret::from(RESP_STATIC);
+
+    let (initiator, message) = noise_ik::initiate(
+        PROLOGUE,
+        &init_static,
+        &StaticSecret::from(INIT_EPHEMERAL),
+        &PublicKey::from(&resp_static),
+        b"Ludwig von Mises",
+    )
+    .unwrap();
+    assert_eq!(message, MESSAGE_1);
+
+    let responder = noise_ik::accept(PROLOGUE, &resp_static, &message).unwrap();
+    assert_eq!(responder.remote_static(), &PublicKey::from(&init_static));
+    assert_eq!(responder.payload(), b"Ludwig von Mises");
+    let (resp_keys, message) = responder
+        .finish(&StaticSecret::from(RESP_EPHEMERAL), b"Murray Rothbard")
+        .unwrap();
+    assert_eq!(message, MESSAGE_2);
+
+    let (init_keys, payload) = initiator.finish(&message).unwrap();
+    assert_eq!(payload, b"Murray Rothbard");
+
+    assert_eq!(init_keys.handshake_hash(), &HANDSHAKE_HASH);
+    assert_eq!(resp_keys.handshake_hash(), &HANDSHAKE_HASH);
+    assert_eq!(init_keys.send_key(), resp_keys.receive_key());
+    assert_eq!(init_keys.receive_key(), resp_keys.send_key());
+    assert_eq!(transport(init_keys.send_key(), b"F. A. Hayek"), TRANSPORT_1);
+    assert_eq!(transport(resp_keys.send_key(), b"Carl Menger"), TRANSPORT_2);
+}
+
+#[test]
+fn message_lengths() {
+    assert_eq!(
+        MESSAGE_1.len(),
+        INITIATION_OVERHEAD + b"Ludwig von Mises".len()
+    );
+    assert_eq!(
+        MESSAGE_2.len(),
+        RESPONSE_OVERHEAD + b"Murray Rothbard".len()
+    );
+}
+
+#[test]
+fn tampered_messages_are_rejected() {
+    let invalid = Some(Error::NoiseIk(noise_ik::Error::InvalidMessage));
+    let init_static = StaticSecret::from(INIT_STATIC);
+    let resp_static = StaticSecret::from(RESP_STATIC);
+
+    for i in [0, 40, MESSAGE_1.len() - 1] {
+        let mut message = MESSAGE_1;
+        message[i] ^= 1;
+        assert_eq!(
+            noise_ik::accept(PROLOGUE, &resp_static, &message).err(),
+            invalid
+        );
+    }
+    assert_eq!(
+        noise_ik::accept(
+            PROLOGUE,
+            &resp_static,
+            &MESSAGE_1[..INITIATION_OVERHEAD - 1]
+        )
+        .err(),
+        invalid
+    );
+    assert_eq!(
+        noise_ik::accept(b"Ayn Rand", &resp_static, &MESSAGE_1).err(),
+        invalid
+    );
+
+    let (initiator, _) = noise_ik::initiate(
+        PROLOGUE,
+        &init_static,
+        &StaticSecret::from(INIT_EPHEMERAL),
+        &PublicKey::from(&resp_static),
+        b"Ludwig von Mises",
+    )
+    .unwrap();
+    let mut message = MESSAGE_2;
+    message[40] ^= 1;
+    assert_eq!(initiator.finish(&message).err(), invalid);
+}
+
+#[test]
+fn small_order_keys_are_rejected() {
+    let invalid = Some(Error::SmallOrderPoint);
+    let init_static = StaticSecret::from(INIT_STATIC);
+    let resp_static = StaticSecret::from(RESP_STATIC);
+
+    assert_eq!(
+        noise_ik::initiate(
+            PROLOGUE,
+            &init_static,
+            &StaticSecret::from(INIT_EPHEMERAL),
+            &PublicKey::from([0u8; 32]),
+            b"",
+        )
+        .err(),
+        invalid
+    );
+
+    let mut message = MESSAGE_1;
+    message[..32].copy_from_slice(&[0u8; 32]);
+    assert_eq!(
+        noise_ik::accept(PROLOGUE, &resp_static, &message).err(),
+        invalid
+    );
+}
@@ -0,0 +1,153 @@
+//! Tor ntor handshake tests.
+
+#![cfg(feature = "ntor")]
+
+mod common;
+
+use common::{hex, hex32, ConstRng};
+use x25519_dalek::{ntor, Error, PublicKey, StaticSecret};
+
+const NODE_ID: &str = "69546f6c64596f7541626f75745374616972732e";
+const SERVER_SECRET: &str = "4820544f4c4420594f5520444f474954204b454550532048415050454e494e47";
+const CLIENT_EPHEMERAL: &str = "706f6461792069207075742e2e2e2e2e2e2e2e4a4641514f4f4f4f4f4f4f4f4f";
+const SERVER_EPHEMERAL: &str = "70686520737175697272656c2e2e2e2e2e2e2e2e2e2e2e2e2e2e2e2e2e2e2e2e";
+
+fn node_id() -> [u8; ntor::NODE_ID_LEN] {
+    hex(NODE_ID).try_into().unwrap()
+}
+
+/// Run a handshake with the test keys, returning the client's state and
+/// the server's key material and reply.
+fn handshake() -> (ntor::ClientState, ntor::KeyGenerator, [u8; 64]) {
+    let server_secret = StaticSecret::from(hex32(SERVER_SECRET));
+    let (client, create) = ntor::client_handshake(
+        &node_id(),
+        &PublicKey::from(&server_secret),
+        ConstRng(hex32(CLIENT_EPHEMERAL)),
+    );
+    let (server_keys, created) = ntor::server_handshake(
+        &node_id(),
+        &server_secret,
+        &create,
+        ConstRng(hex32(SERVER_EPHEMERAL)),
+    )
+    .unwrap();
+    (client, server_keys, created)
+}
+
+/// Check the messages and key material against values computed with an
+/// independent implementation of section 5.1.4 of the Tor specification, in
+/// Python with the `cryptography` package's X25519 and the standard
+/// library's HMAC-SHA256, for the secret keys above: `KEY_SEED` and
+/// `verify` are HMACs of `secret_input` keyed with `t_key` and `t_verify`,
+/// `auth` is the HMAC of `auth_input` keyed with `t_mac`, and the key
+/// material is HKDF-SHA256-Expand of `KEY_SEED` with `m_expand` as info.
+#[test]
+fn handshake_matches_reference() {
+    let server_secret = StaticSecret::from(hex32(SERVER_SECRET));
+    let (_, create) = ntor::client_handshake(
+        &node_id(),
+        &PublicKey::from(&server_secret),
+        ConstRng(hex32(CLIENT_EPHEMERAL)),
+    );
+    assert_eq!(
+        create.to_vec(),
+        hex(concat!(
+            "69546f6c64596f7541626f75745374616972732e",
+            "ccbc8541904d18af08753eae967874749e6149f873de937f57f8fd903a21c471",
+            "12f0a0cb6ff4be773ec3ae9373fdfbf19f4239a1e6f6a3631a858b1b658aa209",
+        ))
+    );
+
+    let (client, server_keys, created) = handshake();
+    assert_eq!(
+        created.to_vec(),
+        hex(concat!(
+            "f845dcc3fe169db4298e6f1ed23b04c1ef2ea19ff6c2f95280e121cb5e60f549",
+            "60fb223c1654c861769d20c5403d9d9b78073b624b8f0596209236d49071dcbd",
+        ))
+    );
+
+    let expected = hex(concat!(
+        "2e922f700885d9e219fe74ce05bd82794c2722719b836d17cb0b2577467319fb",
+        "4ed7487504b65022a5c4a3f95443168ad97088f40f2b610ce07e61098e83a3f3",
+        "7cfcbe8456b231be",
+    ));
+    let mut server_material = [0u8; 72];
+    server_keys.expand(&mut server_material);
+    assert_eq!(server_material.to_vec(), expected);
+
+    let client_keys = client.finish(&created).unwrap();
+    let mut client_material = [0u8; 72];
+    client_keys.expand(&mut client_material);
+    assert_eq!(client_material.to_vec(), expected);
+}
+
+#[test]
+fn wrong_authenticator_is_rejected() {
+    let (client, _, mut created) = handshake();
+    created[63] ^= 1;
+    assert!(matches!(
+        client.finish(&created),
+        Err(Error::Ntor(ntor::Error::AuthenticationFailed))
+    ));
+}
+
+#[test]
+fn small_order_server_key_is_rejected() {
+    let (client, _, mut created) = handshake();
+    created[..32].copy_from_slice(&[0u8; 32]);
+    assert!(matches!(
+        client.finish(&created),
+        Err(Error::SmallOrderPoint)
+    ));
+}
+
+#[test]
+fn server_rejects_other_keys() {
+    let server_secret = StaticSecret::from(hex32(SERVER_SECRET));
+    let other_secret = StaticSecret::from(hex32(SERVER_EPHEMERAL));
+    let (_, create) = ntor::client_handshake(
+        &node_id(),
+        &PublicKey::from(&other_secret),
+        ConstRng(hex32(CLIENT_EPHEMERAL)),
+    );
+    let result = ntor::server_handshake(
+        &node_id(),
+        &server_secret,
+        &create,
+        ConstRng(hex32(SERVER_EPHEMERAL)),
+    );
+    assert!(matches!(result, Err(Error::Ntor(ntor::Error::UnknownKey))));
+
+    let (_, create) = ntor::client_handshake(
+        &[0u8; ntor::NODE_ID_LEN],
+        &PublicKey::from(&server_secret),
+        ConstRng(hex32(CLIENT_EPHEMERAL)),
+    );
+    let result = ntor::server_handshake(
+        &node_id(),
+        &server_secret,
+        &create,
+        ConstRng(hex32(SERVER_EPHEMERAL)),
+    );
+    assert!(matches!(result, Err(Error::Ntor(ntor::Error::UnknownKey))));
+}
+
+#[test]
+fn small_order_client_key_is_rejected() {
+    let server_secret = StaticSecret::from(hex32(SERVER_SECRET));
+    let (_, mut create) = ntor::client_handshake(
+        &node_id(),
+        &PublicKey::from(&server_secret),
+        ConstRng(hex32(CLIENT_EPHEMERAL)),
+    );
+    create[ntor::NODE_ID_LEN + 32..].copy_from_slice(&[0u8; 32]);
+    let result = ntor::server_handshake(
+        &node_id(),
+        &server_secret,
+        &create,
+        ConstRng(hex32(SERVER_EPHEMERAL)),
+    );
+    assert!(matches!(result, Err(Error::SmallOrderPoint)));
+}
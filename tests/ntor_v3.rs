@@ -0,0 +1,152 @@
+//! Tor ntor-v3 handshake tests.
+
+#![cfg(feature = "ntor_v3")]
+
+mod common;
+
+use common::{hex, hex32, ConstRng};
+use x25519_dalek::{ntor_v3, Error, PublicKey, StaticSecret};
+
+// The test vector of Tor's `test_ntor_v3.c`.
+const NODE_ID: &str = "9fad2af287ef942632833d21f946c6260c33fae6172b60006e86e4a6911753a2";
+const SERVER_SECRET: &str = "4051daa5921cfa2a1c27b08451324919538e79e788a81b38cbed097a5dff454a";
+const CLIENT_EPHEMERAL: &str = "b825a3719147bcbe5fb1d0b0fcb9c09e51948048e2e3283d2ab7b45b5ef38b49";
+const SERVER_EPHEMERAL: &str = "4865a5b7689dafd978f529291c7171bc159be076b92186405d13220b80e2a053";
+const VERIFICATION: &[u8] = b"xyzzy";
+const CLIENT_MESSAGE: &[u8] = b"hello world";
+const SERVER_MESSAGE: &[u8] = b"Hola Mundo";
+
+const CLIENT_HANDSHAKE: &str = concat!(
+    "9fad2af287ef942632833d21f946c6260c33fae6172b60006e86e4a6911753a2",
+    "f8307a2bc1870b00b828bb74dbb8fd88e632a6375ab3bcd1ae706aaa8b6cdd1d",
+    "252fe9ae91264c91d4ecb8501f79d0387e34ad8ca0f7c995184f7d11d5da4f46",
+    "3bebd9151fd3b47c180abc",
+    "9e044d53565f04d82bbb3bebed3d06cea65db8be9c72b68cd461942088502f67",
+);
+const SERVER_HANDSHAKE: &str = concat!(
+    "4bf4814326fdab45ad5184f5518bd7fae25dc59374062698201a50a22954246d",
+    "2fc5f8773ca824542bc6cf6f57c7c29bbf4e5476461ab130c5b18ab0a9127665",
+    "1202c3e1e87c0d32054c",
+);
+const KEYS: &str = concat!(
+    "9c19b631fd94ed86a817e01f6c80b0743a43f5faebd39cfaa8b00fa8bcc65c3b",
+    "feaa403d91acbd68a821bf6ee8504602b094a254392a07737d5662768c7a9fb1",
+    "b2814bb34780eaee6e867c773e28c212ead563e98a1cd5d5b4576f5ee61c59bd",
+    "e025ff2851bb19b721421694f263818e3531e43a9e4e3e2c661e2ad547d8984c",
+    "aa28ebecd3e4525452299be26b9185a20a90ce1eac20a91f2832d731b54502b0",
+    "9749b5a2a2949292f8cfcbeffb790c7790ed935a9d251e7e336148ea83b063a5",
+    "618fcff674a44581585fd22077ca0e52c59a24347a38d1a1ceebddbf238541f2",
+    "26b8f88d0fb9c07a1bcd2ea764bbbb5dacdaf5312a14c0b9e4f06309b0333b4a",
+);
+
+fn node_id() -> [u8; ntor_v3::NODE_ID_LEN] {
+    hex32(NODE_ID)
+}
+
+fn client_handshake() -> (ntor_v3::ClientState, Vec<u8>) {
+    let server_secret = StaticSecret::from(hex32(SERVER_SECRET));
+    ntor_v3::client_handshake(
+        &node_id(),
+        &PublicKey::from(&server_secret),
+        VERIFICATION,
+        CLIENT_MESSAGE,
+        ConstRng(hex32(CLIENT_EPHEMERAL)),
+    )
+    .unwrap()
+}
+
+fn server_handshake(message: &[u8]) -> Result<ntor_v3::ServerState, Error> {
+    let server_secret = StaticSecret::from(hex32(SERVER_SECRET));
+    ntor_v3::server_handshake(&node_id(), &server_secret, VERIFICATION, message)
+}
+
+#[test]
+fn handshake_matches_tor() {
+    let (client, create) = client_handshake();
+    assert_eq!(create, hex(CLIENT_HANDSHAKE));
+
+    let server = server_handshake(&create).unwrap();
+    assert_eq!(server.client_message(), CLIENT_MESSAGE);
+    let (server_keys, created) = server.finish(SERVER_MESSAGE, ConstRng(hex32(SERVER_EPHEMERAL)));
+    assert_eq!(created, hex(SERVER_HANDSHAKE));
+
+    let mut server_material = [0u8; 256];
+    server_keys.expand(&mut server_material);
+    assert_eq!(server_material.to_vec(), hex(KEYS));
+
+    let (client_keys, server_message) = client.finish(&created).unwrap();
+    assert_eq!(server_message, SERVER_MESSAGE);
+    let mut client_material = [0u8; 256];
+    client_keys.expand(&mut client_material);
+    assert_eq!(client_material.to_vec(), hex(KEYS));
+}
+
+#[test]
+fn tampered_client_handshake_is_rejected() {
+    let (_, create) = client_handshake();
+    for i in [ntor_v3::NODE_ID_LEN + 64, create.len() - 1] {
+        let mut tampered = create.clone();
+        tampered[i] ^= 1;
+        assert!(matches!(
+            server_handshake(&tampered),
+            Err(Error::NtorV3(ntor_v3::Error::InvalidMessage))
+        ));
+    }
+    assert!(matches!(
+        server_handshake(&create[..ntor_v3::CLIENT_OVERHEAD - 1]),
+        Err(Error::NtorV3(ntor_v3::Error::InvalidMessage))
+    ));
+}
+
+#[test]
+fn wrong_verification_is_rejected() {
+    let (_, create) = client_handshake();
+    let server_secret = StaticSecret::from(hex32(SERVER_SECRET));
+    let result = ntor_v3::server_handshake(&node_id(), &server_secret, b"plugh", &create);
+    assert!(matches!(
+        result,
+        Err(Error::NtorV3(ntor_v3::Error::InvalidMessage))
+    ));
+}
+
+#[test]
+fn server_rejects_other_keys() {
+    let (_, mut create) = client_handshake();
+    create[0] ^= 1;
+    assert!(matches!(
+        server_handshake(&create),
+        Err(Error::NtorV3(ntor_v3::Error::UnknownKey))
+    ));
+}
+
+#[test]
+fn tampered_server_handshake_is_rejected() {
+    let (client, create) = client_handshake();
+    let server = server_handshake(&create).unwrap();
+    let (_, mut created) = server.finish(SERVER_MESSAGE, ConstRng(hex32(SERVER_EPHEMERAL)));
+    let last = created.len() - 1;
+    created[last] ^= 1;
+    assert!(matches!(
+        client.finish(&created),
+        Err(Error::NtorV3(ntor_v3::Error::AuthenticationFailed))
+    ));
+}
+
+#[test]
+fn small_order_keys_are_rejected() {
+    let (client, _) = client_handshake();
+    let created = [0u8; ntor_v3::SERVER_OVERHEAD];
+    assert!(matches!(
+        client.finish(&created),
+        Err(Error::SmallOrderPoint)
+    ));
+
+    let result = ntor_v3::client_handshake(
+        &node_id(),
+        &PublicKey::from([0u8; 32]),
+        VERIFICATION,
+        CLIENT_MESSAGE,
+        ConstRng(hex32(CLIENT_EPHEMERAL)),
+    );
+    assert!(matches!(result, Err(Error::SmallOrderPoint)));
+}
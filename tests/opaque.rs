@@ -0,0 +1,211 @@
+//! OPAQUE 3DH key exchange tests.
+
+#![cfg(feature = "opaque")]
+
+mod common;
+
+use common::hex;
+use x25519_dalek::{opaque, Error, PublicKey, StaticSecret};
+
+/// The test keys, KE1 and credential response.
+struct Fixture {
+    client_secret: StaticSecret,
+    client_ephemeral: StaticSecret,
+    server_secret: StaticSecret,
+    server_ephemeral: StaticSecret,
+    ke1: Vec<u8>,
+    credential_response: [u8; 208],
+}
+
+impl Fixture {
+    fn new() -> Fixture {
+        let client_ephemeral = StaticSecret::from([2; 32]);
+        let mut ke1 = vec![7; 32];
+        ke1.extend_from_slice(&[5; opaque::NONCE_LEN]);
+        ke1.extend_from_slice(PublicKey::from(&client_ephemeral).as_bytes());
+        Fixture {
+            client_secret: StaticSecret::from([1; 32]),
+            client_ephemeral,
+            server_secret: StaticSecret::from([3; 32]),
+            server_ephemeral: StaticSecret::from([4; 32]),
+            ke1,
+            credential_response: [8; 208],
+        }
+    }
+
+    fn preamble<'a>(
+        &'a self,
+        client_identity: &'a [u8; 32],
+        server_identity: &'a [u8; 32],
+        server_keyshare: &'a PublicKey,
+    ) -> opaque::Preamble<'a> {
+        opaque::Preamble {
+            context: b"OPAQUE-POC",
+            client_identity,
+            ke1: &self.ke1,
+            server_identity,
+            credential_response: &self.credential_response,
+            server_nonce: &[6; opaque::NONCE_LEN],
+            server_keyshare,
+        }
+    }
+}
+
+/// Check the MACs and session key against values computed with an
+/// independent implementation of the specification.
+#[test]
+fn exchange_matches_reference() {
+    let f = Fixture::new();
+    let client_public = PublicKey::from(&f.client_secret);
+    let server_public = PublicKey::from(&f.server_secret);
+    let server_keyshare = PublicKey::from(&f.server_ephemeral);
+    let preamble = f.preamble(
+        client_public.as_bytes(),
+        server_public.as_bytes(),
+        &server_keyshare,
+    );
+
+    let (server, server_mac) = opaque::server_respond(
+        &f.server_secret,
+        &f.server_ephemeral,
+        &client_public,
+        &PublicKey::from(&f.client_ephemeral),
+        &preamble,
+    )
+    .unwrap();
+    assert_eq!(
+        server_mac.to_vec(),
+        hex(concat!(
+            "5b09c745630be604279aed801a79d40fbf218d233e8af05500edf5c0fdeda31f",
+            "8b661b5336af75b39d800078f17813bdbea1252fb4157c83b76fdbabd54de55f",
+        ))
+    );
+
+    let (client_mac, client_key) = opaque::client_finalize(
+        &f.client_secret,
+        &f.client_ephemeral,
+        &server_public,
+        &preamble,
+        &server_mac,
+    )
+    .unwrap();
+    assert_eq!(
+        client_mac.to_vec(),
+        hex(concat!(
+            "05311fad5a2acd359c0d6d9a603bc90af1314f7b11ed3a0d07bbecdf0c1a46a3",
+            "1a5f25875a6665ea8d7804de2c16d505b4eb86948fbb5d90b669bd3e5862cdef",
+        ))
+    );
+
+    let expected = hex(concat!(
+        "c0f2f964c3cf64f3c8981599c0ee4b6255e14ae0dd1a8999e9d7fa00bfb6f351",
+        "93b2230fbc35358f49c85862954b1522d5cae41a5cc54607df2c0132959de08c",
+    ));
+    assert_eq!(client_key.to_vec(), expected);
+    assert_eq!(server.finish(&client_mac).unwrap().to_vec(), expected);
+}
+
+#[test]
+fn wrong_macs_are_rejected() {
+    let f = Fixture::new();
+    let client_public = PublicKey::from(&f.client_secret);
+    let server_public = PublicKey::from(&f.server_secret);
+    let server_keyshare = PublicKey::from(&f.server_ephemeral);
+    let preamble = f.preamble(
+        client_public.as_bytes(),
+        server_public.as_bytes(),
+        &server_keyshare,
+    );
+
+    let (server, mut server_mac) = opaque::server_respond(
+        &f.server_secret,
+        &f.server_ephemeral,
+        &client_public,
+        &PublicKey::from(&f.client_ephemeral),
+        &preamble,
+    )
+    .unwrap();
+    let (mut client_mac, _) = opaque::client_finalize(
+        &f.client_secret,
+        &f.client_ephemeral,
+        &server_public,
+        &preamble,
+        &server_mac,
+    )
+    .unwrap();
+
+    client_mac[0] ^= 1;
+    assert!(matches!(
+        server.finish(&client_mac),
+        Err(Error::Opaque(opaque::Error::AuthenticationFailed))
+    ));
+
+    server_mac[0] ^= 1;
+    let result = opaque::client_finalize(
+        &f.client_secret,
+        &f.client_ephemeral,
+        &server_public,
+        &preamble,
+        &server_mac,
+    );
+    assert!(matches!(
+        result,
+        Err(Error::Opaque(opaque::Error::AuthenticationFailed))
+    ));
+}
+
+#[test]
+fn mismatched_preambles_fail() {
+    let f = Fixture::new();
+    let client_public = PublicKey::from(&f.client_secret);
+    let server_public = PublicKey::from(&f.server_secret);
+    let server_keyshare = PublicKey::from(&f.server_ephemeral);
+    let preamble = f.preamble(
+        client_public.as_bytes(),
+        server_public.as_bytes(),
+        &server_keyshare,
+    );
+
+    let (_, server_mac) = opaque::server_respond(
+        &f.server_secret,
+        &f.server_ephemeral,
+        &client_public,
+        &PublicKey::from(&f.client_ephemeral),
+        &preamble,
+    )
+    .unwrap();
+    let other_identity = [0x55; 32];
+    let result = opaque::client_finalize(
+        &f.client_secret,
+        &f.client_ephemeral,
+        &server_public,
+        &f.preamble(&other_identity, server_public.as_bytes(), &server_keyshare),
+        &server_mac,
+    );
+    assert!(matches!(
+        result,
+        Err(Error::Opaque(opaque::Error::AuthenticationFailed))
+    ));
+}
+
+#[test]
+fn small_order_keys_are_rejected() {
+    let f = Fixture::new();
+    let client_public = PublicKey::from(&f.client_secret);
+    let server_public = PublicKey::from(&f.server_secret);
+    let server_keyshare = PublicKey::from(&f.server_ephemeral);
+    let preamble = f.preamble(
+        client_public.as_bytes(),
+        server_public.as_bytes(),
+        &server_keyshare,
+    );
+
+    let result = opaque::server_respond(
+        &f.server_secret,
+        &f.server_ephemeral,
+        &client_public,
+        &PublicKey::from([0; 32]),
+        &preamble,
+    );
+    assert!(matches!(result, Err(Error::SmallOrderPoint)));
+}
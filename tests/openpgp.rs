@@ -0,0 +1,177 @@
+//! OpenPGP ECDH tests.
+
+#![cfg(feature = "openpgp")]
+
+use x25519_dalek::openpgp::{Error, KdfHash, KeyEncryptionKey, KeyWrapAlgorithm};
+use x25519_dalek::{PublicKey, StaticSecret};
+
+/// Alice's private key, from RFC7748 section 6.1.
+const ALICE_PRIVATE: [u8; 32] = [
+    0x77, 0x07, 0x6d, 0x0a, 0x73, 0x18, 0xa5, 0x7d, 0x3c, 0x16, 0xc1, 0x72, 0x51, 0xb2, 0x66, 0x45,
+    0xdf, 0x4c, 0x2f, 0x87, 0xeb, 0xc0, 0x99, 0x2a, 0xb1, 0x77, 0xfb, 0xa5, 0x1d, 0xb9, 0x2c, 0x2a,
+];
+
+/// Bob's public key, from RFC7748 section 6.1.
+const BOB_PUBLIC: [u8; 32] = [
+    0xde, 0x9e, 0xdb, 0x7d, 0x7b, 0x7d, 0xc1, 0xb4, 0xd3, 0x5b, 0x61, 0xc2, 0xec, 0xe4, 0x35, 0x37,
+    0x3f, 0x83, 0x43, 0xc8, 0x5b, 0x78, 0x67, 0x4d, 0xad, 0xfc, 0x7e, 0x14, 0x6f, 0x88, 0x2b, 0x4f,
+];
+
+/// A recipient fingerprint for the KDF parameters.
+const FINGERPRINT: [u8; 20] = [
+    0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e, 0x0f,
+    0x10, 0x11, 0x12, 0x13,
+];
+
+/// A formatted AES-256 session key: algorithm identifier, key and checksum.
+fn session_key() -> Vec<u8> {
+    let mut session_key = vec![0x09];
+    session_key.extend(100u8..132);
+    session_key.extend_from_slice(&[0x12, 0x34]);
+    session_key
+}
+
+fn kek(hash: KdfHash, algorithm: KeyWrapAlgorithm) -> KeyEncryptionKey {
+    let shared = StaticSecret::from(ALICE_PRIVATE).diffie_hellman(&PublicKey::from(BOB_PUBLIC));
+    KeyEncryptionKey::derive(&shared, hash, algorithm, &FINGERPRINT)
+}
+
+// The expected values below were computed with the Python `cryptography`
+// package.
+
+#[test]
+fn kdf_sha256_aes128() {
+    let kek = kek(KdfHash::Sha256, KeyWrapAlgorithm::Aes128);
+    assert_eq!(
+        kek.as_bytes(),
+        [
+            0xc9, 0x87, 0x3a, 0xd7, 0x00, 0xcc, 0xdc, 0xab, 0x74, 0x9b, 0xa1, 0x5e, 0x4b, 0xab,
+            0x10, 0xbc,
+        ]
+    );
+
+    let wrapped = kek.wrap(&session_key()).unwrap();
+    assert_eq!(
+        wrapped,
+        [
+            0xfe, 0x11, 0x04, 0xf9, 0x8d, 0xa8, 0xd0, 0x13, 0x7c, 0x8e, 0xb4, 0x54, 0x0f, 0x6d,
+            0x9d, 0x7d, 0xf8, 0xab, 0xc8, 0x72, 0xe0, 0x91, 0x7a, 0xf2, 0x63, 0x1e, 0x12, 0x1c,
+            0x8f, 0xec, 0xe9, 0x3b, 0x0f, 0xdc, 0x05, 0xd8, 0x7a, 0x48, 0x30, 0xee, 0x0f, 0xc7,
+            0x21, 0x3d, 0x1e, 0x54, 0xe4, 0x9f,
+        ]
+    );
+    assert_eq!(&kek.unwrap(&wrapped).unwrap()[..], &session_key()[..]);
+}
+
+#[test]
+fn kdf_sha512_aes256() {
+    let kek = kek(KdfHash::Sha512, KeyWrapAlgorithm::Aes256);
+    assert_eq!(
+        kek.as_bytes(),
+        [
+            0x04, 0xad, 0x80, 0x81, 0x90, 0xa2, 0xb5, 0xca, 0x37, 0x80, 0x10, 0x6f, 0x63, 0x0a,
+            0x8a, 0xe0, 0x97, 0xa5, 0x9a, 0x93, 0x42, 0x70, 0x08, 0x9e, 0xf8, 0xc0, 0x0d, 0x8e,
+            0xd0, 0x74, 0x8e, 0x7a,
+        ]
+    );
+
+    let wrapped = kek.wrap(&session_key()).unwrap();
+    assert_eq!(
+        wrapped,
+        [
+            0x98, 0x36, 0x94, 0x47, 0x11, 0x78, 0x37, 0xf0, 0x16, 0x0d, 0xb8, 0x05, 0x95, 0xd2,
+            0xa7, 0x4c, 0x7b, 0x46, 0xbf, 0xa9, 0xb9, 0x0c, 0x46, 0x1c, 0x5b, 0xae, 0x63, 0xd4,
+            0x67, 0x1b, 0x68, 0x03, 0x72, 0xa9, 0xce, 0xcc, 0x1e, 0x1e, 0x38, 0x0f, 0x60, 0x83,
+            0x91, 0x1f, 0x53, 0x5a, 0x70, 0x96,
+        ]
+    );
+    assert_eq!(&kek.unwrap(&wrapped).unwrap()[..], &session_key()[..]);
+}
+
+#[test]
+fn unwrap_rejects_tampering_and_wrong_key() {
+    let kek = kek(KdfHash::Sha256, KeyWrapAlgorithm::Aes128);
+    let mut wrapped = kek.wrap(&session_key()).unwrap();
+
+    let other = kek_with_fingerprint(&[0xff; 20]);
+    assert_eq!(other.unwrap(&wrapped).err(), Some(Error::UnwrapFailed));
+
+    wrapped[0] ^= 1;
+    assert_eq!(kek.unwrap(&wrapped).err(), Some(Error::UnwrapFailed));
+    assert_eq!(kek.unwrap(&[]).err(), Some(Error::UnwrapFailed));
+}
+
+fn kek_with_fingerprint(fingerprint: &[u8]) -> KeyEncryptionKey {
+    let shared = StaticSecret::from(ALICE_PRIVATE).diffie_hellman(&PublicKey::from(BOB_PUBLIC));
+    KeyEncryptionKey::derive(
+        &shared,
+        KdfHash::Sha256,
+        KeyWrapAlgorithm::Aes128,
+        fingerprint,
+    )
+}
+
+#[test]
+fn wrap_rejects_short_session_keys() {
+    let kek = kek(KdfHash::Sha256, KeyWrapAlgorithm::Aes128);
+    assert_eq!(kek.wrap(&[0x07; 4]), Err(Error::InvalidSessionKey));
+}
+
+#[test]
+fn algorithm_ids() {
+    for hash in [KdfHash::Sha256, KdfHash::Sha384, KdfHash::Sha512] {
+        assert_eq!(KdfHash::from_id(hash.id()), Some(hash));
+    }
+    for algorithm in [
+        KeyWrapAlgorithm::Aes128,
+        KeyWrapAlgorithm::Aes192,
+        KeyWrapAlgorithm::Aes256,
+    ] {
+        assert_eq!(KeyWrapAlgorithm::from_id(algorithm.id()), Some(algorithm));
+    }
+    assert_eq!(KdfHash::from_id(2), None);
+    assert_eq!(KeyWrapAlgorithm::from_id(2), None);
+}
+
+#[test]
+fn public_key_openpgp_point() {
+    let point = PublicKey::from(BOB_PUBLIC).to_openpgp_point();
+    assert_eq!(point[0], 0x40);
+    assert_eq!(point[1..], BOB_PUBLIC);
+
+    let public = PublicKey::from_openpgp_point(&point).unwrap();
+    assert_eq!(public.to_bytes(), BOB_PUBLIC);
+
+    assert_eq!(
+        PublicKey::from_openpgp_point(&BOB_PUBLIC),
+        Err(x25519_dalek::Error::OpenPgp(Error::InvalidPoint))
+    );
+    assert_eq!(
+        PublicKey::from_openpgp_point(&point[..32]),
+        Err(x25519_dalek::Error::OpenPgp(Error::InvalidPoint))
+    );
+}
+
+#[test]
+fn static_secret_openpgp_mpi() {
+    let mpi = StaticSecret::from(ALICE_PRIVATE).to_openpgp_mpi();
+    let mut reversed = ALICE_PRIVATE;
+    reversed.reverse();
+    assert_eq!(*mpi, reversed);
+
+    let secret = StaticSecret::from_openpgp_mpi(&mpi[..]).unwrap();
+    assert_eq!(secret.to_bytes(), ALICE_PRIVATE);
+
+    // Leading zero bytes may be stripped from an MPI.
+    let mut short = [0u8; 32];
+    short[..31].copy_from_slice(&ALICE_PRIVATE[..31]);
+    let mpi = StaticSecret::from(short).to_openpgp_mpi();
+    assert_eq!(mpi[0], 0);
+    let secret = StaticSecret::from_openpgp_mpi(&mpi[1..]).unwrap();
+    assert_eq!(secret.to_bytes(), short);
+
+    assert_eq!(
+        StaticSecret::from_openpgp_mpi(&[0x01; 33]).err(),
+        Some(x25519_dalek::Error::OpenPgp(Error::InvalidSecretKey))
+    );
+}
@@ -0,0 +1,40 @@
+//! Tests for multi-threaded batch Diffie-Hellman.
+
+#![cfg(feature = "parallel")]
+
+use x25519_dalek::{PublicKey, StaticSecret};
+
+fn secret(i: u16) -> StaticSecret {
+    let mut bytes = [0x5a; 32];
+    bytes[..2].copy_from_slice(&i.to_le_bytes());
+    StaticSecret::from(bytes)
+}
+
+#[test]
+fn par_diffie_hellman_batch_matches_single() {
+    let ours = secret(0xffff);
+    // Several chunks, the last of them partial, with a point of small order.
+    let mut publics: Vec<PublicKey> = (0..300).map(|i| PublicKey::from(&secret(i))).collect();
+    publics[100] = PublicKey::from([0; 32]);
+
+    let shared = ours.par_diffie_hellman_batch(&publics);
+    assert_eq!(shared.len(), publics.len());
+    for (shared, public) in shared.iter().zip(&publics) {
+        assert_eq!(shared.as_bytes(), ours.diffie_hellman(public).as_bytes());
+    }
+
+    assert!(ours.par_diffie_hellman_batch(&[]).is_empty());
+}
+
+#[test]
+fn par_diffie_hellman_pairs_matches_single() {
+    let pairs: Vec<(StaticSecret, PublicKey)> = (0..100)
+        .map(|i| (secret(i), PublicKey::from(&secret(1000 + i))))
+        .collect();
+
+    let shared = StaticSecret::par_diffie_hellman_pairs(&pairs);
+    assert_eq!(shared.len(), pairs.len());
+    for (shared, (secret, public)) in shared.iter().zip(&pairs) {
+        assert_eq!(shared.as_bytes(), secret.diffie_hellman(public).as_bytes());
+    }
+}
@@ -0,0 +1,122 @@
+//! Passphrase sealing tests.
+
+#![cfg(feature = "passphrase")]
+
+mod common;
+
+use common::FailingRng;
+use rand_core::OsRng;
+use x25519_dalek::passphrase::{self, Params, SEALED_LEN, VERSION};
+use x25519_dalek::{Error, StaticSecret};
+
+/// Parameters cheap enough for tests.
+const PARAMS: Params = Params {
+    memory_kib: 64,
+    iterations: 1,
+    parallelism: 1,
+};
+
+#[test]
+fn passphrase_round_trip() {
+    let secret = StaticSecret::random_from_rng(OsRng);
+    let sealed = secret
+        .seal_with_passphrase(b"correct horse", &PARAMS, OsRng)
+        .unwrap();
+    assert_eq!(sealed[0], VERSION);
+    assert_eq!(u32::from_be_bytes(sealed[1..5].try_into().unwrap()), 64);
+
+    let unsealed = StaticSecret::unseal_with_passphrase(&sealed, b"correct horse").unwrap();
+    assert_eq!(unsealed.to_bytes(), secret.to_bytes());
+
+    // Each sealing has a new salt.
+    let again = secret
+        .seal_with_passphrase(b"correct horse", &PARAMS, OsRng)
+        .unwrap();
+    assert_ne!(again, sealed);
+}
+
+#[test]
+fn passphrase_wrong_or_modified() {
+    let secret = StaticSecret::random_from_rng(OsRng);
+    let sealed = secret
+        .seal_with_passphrase(b"correct horse", &PARAMS, OsRng)
+        .unwrap();
+
+    assert_eq!(
+        StaticSecret::unseal_with_passphrase(&sealed, b"battery staple").err(),
+        Some(Error::Passphrase(passphrase::Error::InvalidPassphrase))
+    );
+
+    // Every byte after the version is authenticated, the parameters too.
+    // Some flips of the parameters exceed their limits, and are rejected
+    // before a key is derived.
+    for i in 1..SEALED_LEN {
+        let mut modified = sealed;
+        modified[i] ^= 1;
+        assert!(
+            StaticSecret::unseal_with_passphrase(&modified, b"correct horse").is_err(),
+            "{}",
+            i
+        );
+    }
+}
+
+#[test]
+fn passphrase_malformed() {
+    let secret = StaticSecret::random_from_rng(OsRng);
+    let sealed = secret
+        .seal_with_passphrase(b"correct horse", &PARAMS, OsRng)
+        .unwrap();
+
+    assert_eq!(
+        StaticSecret::unseal_with_passphrase(&sealed[1..], b"correct horse").err(),
+        Some(Error::InvalidLength {
+            expected: SEALED_LEN,
+            actual: SEALED_LEN - 1
+        })
+    );
+
+    let mut version = sealed;
+    version[0] = VERSION + 1;
+    assert_eq!(
+        StaticSecret::unseal_with_passphrase(&version, b"correct horse").err(),
+        Some(Error::Passphrase(passphrase::Error::UnsupportedVersion))
+    );
+
+    let mut memory = sealed;
+    memory[1..5].copy_from_slice(&(passphrase::MAX_MEMORY_KIB + 1).to_be_bytes());
+    assert_eq!(
+        StaticSecret::unseal_with_passphrase(&memory, b"correct horse").err(),
+        Some(Error::Passphrase(passphrase::Error::InvalidParams))
+    );
+
+    let mut parallelism = sealed;
+    parallelism[9..13].copy_from_slice(&0u32.to_be_bytes());
+    assert_eq!(
+        StaticSecret::unseal_with_passphrase(&parallelism, b"correct horse").err(),
+        Some(Error::Passphrase(passphrase::Error::InvalidParams))
+    );
+}
+
+#[test]
+fn passphrase_seal_errors() {
+    let secret = StaticSecret::random_from_rng(OsRng);
+    for iterations in [0, passphrase::MAX_ITERATIONS + 1] {
+        let params = Params {
+            iterations,
+            ..PARAMS
+        };
+        assert_eq!(
+            secret
+                .seal_with_passphrase(b"correct horse", &params, OsRng)
+                .err(),
+            Some(Error::Passphrase(passphrase::Error::InvalidParams))
+        );
+    }
+    assert_eq!(
+        secret
+            .seal_with_passphrase(b"correct horse", &PARAMS, FailingRng)
+            .err(),
+        Some(Error::Rng)
+    );
+}
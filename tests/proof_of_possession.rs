@@ -0,0 +1,91 @@
+//! Proof of possession tests.
+
+#![cfg(feature = "proof_of_possession")]
+
+use rand_core::OsRng;
+use x25519_dalek::proof_of_possession::{self, ProofOfPossession};
+use x25519_dalek::{Error, PublicKey, StaticSecret};
+
+const CONTEXT: &[u8] = b"registration 42";
+
+#[test]
+fn proof_of_possession_verifies() {
+    // Both signs of the Edwards public key are exercised with overwhelming
+    // probability.
+    for _ in 0..16 {
+        let secret = StaticSecret::random_from_rng(OsRng);
+        let proof = secret.prove_possession(CONTEXT, OsRng);
+        assert_eq!(
+            PublicKey::from(&secret).verify_possession(&proof, CONTEXT),
+            Ok(())
+        );
+    }
+}
+
+#[test]
+fn proof_of_possession_bytes_roundtrip() {
+    let secret = StaticSecret::from([0x11; 32]);
+    let proof = secret.prove_possession(CONTEXT, OsRng);
+
+    let decoded = ProofOfPossession::from_bytes(&proof.to_bytes()).unwrap();
+    assert_eq!(decoded, proof);
+    assert!(PublicKey::from(&secret)
+        .verify_possession(&decoded, CONTEXT)
+        .is_ok());
+
+    // A response which is not reduced modulo the group order.
+    let mut encoded = proof.to_bytes();
+    encoded[63] = 0xff;
+    assert_eq!(
+        ProofOfPossession::from_bytes(&encoded),
+        Err(Error::ProofOfPossession(
+            proof_of_possession::Error::InvalidProof
+        ))
+    );
+}
+
+#[test]
+fn proof_of_possession_rejects_wrong_context_key_or_proof() {
+    let invalid = Err(Error::ProofOfPossession(
+        proof_of_possession::Error::InvalidProof,
+    ));
+    let secret = StaticSecret::from([0x11; 32]);
+    let public = PublicKey::from(&secret);
+    let proof = secret.prove_possession(CONTEXT, OsRng);
+
+    assert_eq!(
+        public.verify_possession(&proof, b"registration 43"),
+        invalid
+    );
+
+    let other = PublicKey::from(&StaticSecret::from([0x22; 32]));
+    assert_eq!(other.verify_possession(&proof, CONTEXT), invalid);
+
+    let mut encoded = proof.to_bytes();
+    encoded[0] ^= 1;
+    let tampered = ProofOfPossession::from_bytes(&encoded).unwrap();
+    assert_eq!(public.verify_possession(&tampered, CONTEXT), invalid);
+
+    let mut encoded = proof.to_bytes();
+    encoded[32] ^= 1;
+    let tampered = ProofOfPossession::from_bytes(&encoded).unwrap();
+    assert_eq!(public.verify_possession(&tampered, CONTEXT), invalid);
+}
+
+#[test]
+fn proof_of_possession_rejects_invalid_public_keys() {
+    let invalid = Err(Error::ProofOfPossession(
+        proof_of_possession::Error::InvalidPublicKey,
+    ));
+    let proof = StaticSecret::from([0x11; 32]).prove_possession(CONTEXT, OsRng);
+
+    // u = 0 is of small order, and u = 2 lies on the twist.
+    let mut twist = [0u8; 32];
+    twist[0] = 2;
+    for bytes in [[0u8; 32], twist] {
+        assert_eq!(
+            PublicKey::from(bytes).verify_possession(&proof, CONTEXT),
+            invalid
+        );
+    }
+}
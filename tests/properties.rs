@@ -0,0 +1,97 @@
+//! Properties of X25519 checked with proptest over random inputs, to
+//! complement the fixed test vectors.
+//!
+//! A failing case is shrunk and its seed saved in
+//! `tests/properties.proptest-regressions`, which should be committed so
+//! that the case is replayed before any new ones on later runs.
+
+use proptest::prelude::*;
+
+use x25519_dalek::{x25519, PublicKey, X25519_BASEPOINT_BYTES};
+
+const CASES: u32 = 256;
+
+/// The encodings of the points of small order, including the non-canonical
+/// ones with u >= p, with the top bit clear.
+const SMALL_ORDER: [[u8; 32]; 7] = [
+    [0; 32],
+    [
+        0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00,
+    ],
+    [
+        0xe0, 0xeb, 0x7a, 0x7c, 0x3b, 0x41, 0xb8, 0xae, 0x16, 0x56, 0xe3, 0xfa, 0xf1, 0x9f, 0xc4,
+        0x6a, 0xda, 0x09, 0x8d, 0xeb, 0x9c, 0x32, 0xb1, 0xfd, 0x86, 0x62, 0x05, 0x16, 0x5f, 0x49,
+        0xb8, 0x00,
+    ],
+    [
+        0x5f, 0x9c, 0x95, 0xbc, 0xa3, 0x50, 0x8c, 0x24, 0xb1, 0xd0, 0xb1, 0x55, 0x9c, 0x83, 0xef,
+        0x5b, 0x04, 0x44, 0x5c, 0xc4, 0x58, 0x1c, 0x8e, 0x86, 0xd8, 0x22, 0x4e, 0xdd, 0xd0, 0x9f,
+        0x11, 0x57,
+    ],
+    [
+        0xec, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+        0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+        0xff, 0x7f,
+    ],
+    [
+        0xed, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+        0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+        0xff, 0x7f,
+    ],
+    [
+        0xee, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+        0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+        0xff, 0x7f,
+    ],
+];
+
+fn clamp(mut k: [u8; 32]) -> [u8; 32] {
+    k[0] &= 248;
+    k[31] &= 127;
+    k[31] |= 64;
+    k
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(CASES))]
+
+    #[test]
+    fn diffie_hellman_commutes(a in any::<[u8; 32]>(), b in any::<[u8; 32]>()) {
+        let a_pub = x25519(a, X25519_BASEPOINT_BYTES);
+        let b_pub = x25519(b, X25519_BASEPOINT_BYTES);
+        prop_assert_eq!(x25519(a, b_pub), x25519(b, a_pub));
+    }
+
+    #[test]
+    fn clamping_is_idempotent(k in any::<[u8; 32]>(), u in any::<[u8; 32]>()) {
+        prop_assert_eq!(clamp(clamp(k)), clamp(k));
+        prop_assert_eq!(x25519(clamp(k), u), x25519(k, u));
+    }
+
+    #[test]
+    fn public_key_bytes_round_trip(bytes in any::<[u8; 32]>()) {
+        prop_assert_eq!(PublicKey::from(bytes).to_bytes(), bytes);
+
+        // Honestly generated public keys are canonical and of large order.
+        let public = x25519(bytes, X25519_BASEPOINT_BYTES);
+        prop_assert_eq!(
+            PublicKey::from_canonical_bytes(public).map(|key| key.to_bytes()),
+            Ok(public)
+        );
+    }
+
+    #[test]
+    fn small_order_points_give_zero(k in any::<[u8; 32]>()) {
+        for point in SMALL_ORDER.iter() {
+            // The top bit of a u-coordinate is ignored.
+            let mut high = *point;
+            high[31] |= 0x80;
+            for u in [*point, high] {
+                prop_assert_eq!(x25519(k, u), [0u8; 32]);
+                prop_assert!(PublicKey::from_canonical_bytes(u).is_err());
+            }
+        }
+    }
+}
@@ -0,0 +1,107 @@
+//! Tests for running the protocols of this crate against an `X25519Provider`
+//! other than the in-memory secret types.
+
+#![cfg(feature = "static_secrets")]
+
+use rand_core::OsRng;
+use x25519_dalek::{
+    x25519, Error, Keypair, PublicKey, SharedSecret, StaticSecret, X25519Provider,
+    X25519_BASEPOINT_BYTES,
+};
+
+/// A stand-in for a secret key held in a hardware token, which computes
+/// key agreements with the `x25519` function and can be unplugged.
+struct Token {
+    secret: [u8; 32],
+    unplugged: bool,
+}
+
+impl Token {
+    fn new(secret: [u8; 32]) -> Token {
+        Token {
+            secret,
+            unplugged: false,
+        }
+    }
+}
+
+impl X25519Provider for Token {
+    fn public(&self) -> PublicKey {
+        PublicKey::from(x25519(self.secret, X25519_BASEPOINT_BYTES))
+    }
+
+    fn diffie_hellman(&self, their_public: &PublicKey) -> Result<SharedSecret, Error> {
+        if self.unplugged {
+            return Err(Error::Provider);
+        }
+        Ok(SharedSecret::from(x25519(
+            self.secret,
+            their_public.to_bytes(),
+        )))
+    }
+}
+
+#[test]
+fn secret_types_are_providers() {
+    let secret = StaticSecret::random_from_rng(OsRng);
+    let keypair = Keypair::from(secret.clone());
+    let token = Token::new(secret.to_bytes());
+    let peer = PublicKey::from(&StaticSecret::random_from_rng(OsRng));
+
+    let providers: [&dyn X25519Provider; 3] = [&secret, &keypair, &token];
+    for provider in providers {
+        assert_eq!(provider.public(), PublicKey::from(&secret));
+        assert_eq!(
+            provider.diffie_hellman(&peer).unwrap().as_bytes(),
+            secret.diffie_hellman(&peer).as_bytes()
+        );
+    }
+}
+
+#[test]
+#[cfg(feature = "hpke")]
+fn hpke_with_provider() {
+    use x25519_dalek::hpke;
+
+    let recipient = Token::new(StaticSecret::random_from_rng(OsRng).to_bytes());
+    let sender = Token::new(StaticSecret::random_from_rng(OsRng).to_bytes());
+
+    let (shared, enc) = hpke::encap(&recipient.public(), OsRng).unwrap();
+    assert_eq!(hpke::decap(&enc, &recipient).unwrap(), shared);
+
+    let (shared, enc) = hpke::auth_encap(&recipient.public(), &sender, OsRng).unwrap();
+    assert_eq!(
+        hpke::auth_decap(&enc, &recipient, &sender.public()).unwrap(),
+        shared
+    );
+
+    let unplugged = Token {
+        unplugged: true,
+        ..recipient
+    };
+    assert_eq!(hpke::decap(&enc, &unplugged).err(), Some(Error::Provider));
+}
+
+#[test]
+#[cfg(feature = "ecies")]
+fn ecies_with_provider() {
+    use chacha20poly1305::ChaCha20Poly1305;
+    use x25519_dalek::ecies;
+
+    let recipient = Token::new(StaticSecret::random_from_rng(OsRng).to_bytes());
+    let sealed =
+        ecies::seal::<ChaCha20Poly1305, _>(&recipient.public(), b"message", b"", OsRng).unwrap();
+    assert_eq!(
+        ecies::open::<ChaCha20Poly1305>(&recipient, &sealed, b"").unwrap(),
+        b"message"
+    );
+
+    let unplugged = Token {
+        unplugged: true,
+        ..recipient
+    };
+    assert_eq!(
+        ecies::open::<ChaCha20Poly1305>(&unplugged, &sealed, b"").err(),
+        Some(Error::Provider)
+    );
+}
@@ -0,0 +1,85 @@
+//! Diffie-Hellman ratchet tests.
+
+#![cfg(feature = "ratchet")]
+
+mod common;
+
+use common::ConstRng;
+use rand_core::OsRng;
+use x25519_dalek::ratchet::{DhRatchet, Key, KEY_LEN};
+use x25519_dalek::{Error, PublicKey, SharedSecret, StaticSecret};
+
+const SHARED_KEY: [u8; KEY_LEN] = [0x42; KEY_LEN];
+
+/// A toy root KDF: the new root key is the old one XORed with the
+/// Diffie-Hellman output, and the chain key is the new root key reversed.
+fn kdf_rk(root_key: &[u8; KEY_LEN], dh_output: &SharedSecret) -> (Key, Key) {
+    let mut root = Key::new(*root_key);
+    for (r, d) in root.iter_mut().zip(dh_output.as_bytes()) {
+        *r ^= d;
+    }
+    let mut chain = root.clone();
+    chain.reverse();
+    (root, chain)
+}
+
+#[test]
+fn initiator_uses_root_kdf() {
+    let bob = StaticSecret::from([0x22; 32]);
+    let alice_ratchet = StaticSecret::from([0x11; 32]);
+    let (alice, sending) = DhRatchet::initiator(
+        &SHARED_KEY,
+        &PublicKey::from(&bob),
+        kdf_rk,
+        ConstRng(alice_ratchet.to_bytes()),
+    )
+    .unwrap();
+
+    let (_, expected) = kdf_rk(
+        &SHARED_KEY,
+        &alice_ratchet.diffie_hellman(&PublicKey::from(&bob)),
+    );
+    assert_eq!(sending, expected);
+    assert_eq!(alice.public_key(), &PublicKey::from(&alice_ratchet));
+    assert_eq!(alice.remote_public_key(), Some(&PublicKey::from(&bob)));
+}
+
+#[test]
+fn ratchets_agree_over_several_steps() {
+    let bob_ratchet = StaticSecret::random_from_rng(OsRng);
+    let (mut alice, mut alice_sending) =
+        DhRatchet::initiator(&SHARED_KEY, &PublicKey::from(&bob_ratchet), kdf_rk, OsRng).unwrap();
+    let mut bob = DhRatchet::responder(&SHARED_KEY, bob_ratchet, kdf_rk);
+    assert_eq!(bob.remote_public_key(), None);
+
+    for _ in 0..3 {
+        let (bob_receiving, bob_sending) = bob.step(alice.public_key(), OsRng).unwrap();
+        assert_eq!(bob_receiving, alice_sending);
+        assert_eq!(bob.remote_public_key(), Some(alice.public_key()));
+
+        let (alice_receiving, sending) = alice.step(bob.public_key(), OsRng).unwrap();
+        assert_eq!(alice_receiving, bob_sending);
+        assert_ne!(alice_receiving, sending);
+        alice_sending = sending;
+    }
+}
+
+#[test]
+fn step_rejects_small_order_keys() {
+    let bob_ratchet = StaticSecret::random_from_rng(OsRng);
+    let mut bob = DhRatchet::responder(&SHARED_KEY, bob_ratchet, kdf_rk);
+    let public = *bob.public_key();
+    assert_eq!(
+        bob.step(&PublicKey::from([0u8; 32]), OsRng).err(),
+        Some(Error::SmallOrderPoint)
+    );
+    assert_eq!(bob.public_key(), &public);
+    assert_eq!(bob.remote_public_key(), None);
+
+    assert_eq!(
+        DhRatchet::initiator(&SHARED_KEY, &PublicKey::from([0u8; 32]), kdf_rk, OsRng)
+            .err()
+            .map(|_| ()),
+        Some(())
+    );
+}
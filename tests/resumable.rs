@@ -0,0 +1,92 @@
+//! Tests for Diffie-Hellman in bounded steps.
+
+#![cfg(feature = "resumable")]
+
+mod common;
+
+use core::task::Poll;
+
+use common::{hex32, ConstRng};
+use x25519_dalek::{DhOperation, EphemeralSecret, PublicKey, SharedSecret};
+
+/// Run `operation` to completion, checking that it takes exactly
+/// `DhOperation::STEPS` calls.
+fn run(mut operation: DhOperation) -> SharedSecret {
+    for _ in 1..DhOperation::STEPS {
+        assert!(operation.poll_step().is_pending());
+    }
+    let shared = match operation.poll_step() {
+        Poll::Ready(shared) => shared,
+        Poll::Pending => panic!("not done after DhOperation::STEPS steps"),
+    };
+    // Further calls return the same shared secret.
+    match operation.poll_step() {
+        Poll::Ready(again) => assert_eq!(again.as_bytes(), shared.as_bytes()),
+        Poll::Pending => panic!("pending after completion"),
+    }
+    shared
+}
+
+fn secret(byte: u8) -> EphemeralSecret {
+    EphemeralSecret::random_from_rng(ConstRng([byte; 32]))
+}
+
+#[test]
+fn rfc7748_vector() {
+    let alice = EphemeralSecret::random_from_rng(ConstRng(hex32(
+        "77076d0a7318a57d3c16c17251b26645df4c2f87ebc0992ab177fba51db92c2a",
+    )));
+    let bob_public = PublicKey::from(hex32(
+        "de9edb7d7b7dc1b4d35b61c2ece435373f8343c85b78674dadfc7e146f882b4f",
+    ));
+
+    let shared = run(DhOperation::new(alice, &bob_public));
+    assert_eq!(
+        shared.as_bytes(),
+        &hex32("4a5d9d5ba4ce2de1728e3bf480350f25e07e21c947d19e3376f09b3c1e161742")
+    );
+}
+
+#[test]
+fn matches_diffie_hellman() {
+    let mut peers: Vec<PublicKey> = (0..8).map(|i| PublicKey::from(&secret(i))).collect();
+    // A point of small order, whose product is zero, and a point on the
+    // twist.
+    peers.push(PublicKey::from([0; 32]));
+    peers.push(PublicKey::from({
+        let mut two = [0; 32];
+        two[0] = 2;
+        two
+    }));
+
+    for (i, peer) in peers.iter().enumerate() {
+        let byte = 0x80 | i as u8;
+        let shared = run(DhOperation::new(secret(byte), peer));
+        assert_eq!(
+            shared.as_bytes(),
+            secret(byte).diffie_hellman(peer).as_bytes()
+        );
+    }
+    assert_eq!(
+        run(DhOperation::new(secret(1), &peers[8])).as_bytes(),
+        &[0; 32]
+    );
+}
+
+#[test]
+#[cfg(feature = "reusable_secrets")]
+fn reusable_matches_diffie_hellman() {
+    let ours = x25519_dalek::ReusableSecret::random_from_rng(ConstRng([0x42; 32]));
+    let peer = PublicKey::from(&secret(7));
+    let shared = run(DhOperation::new_reusable(&ours, &peer));
+    assert_eq!(shared.as_bytes(), ours.diffie_hellman(&peer).as_bytes());
+}
+
+#[test]
+#[cfg(feature = "static_secrets")]
+fn static_matches_diffie_hellman() {
+    let ours = x25519_dalek::StaticSecret::from([0x42; 32]);
+    let peer = PublicKey::from(&secret(7));
+    let shared = run(DhOperation::new_static(&ours, &peer));
+    assert_eq!(shared.as_bytes(), ours.diffie_hellman(&peer).as_bytes());
+}
@@ -0,0 +1,18 @@
+//! Known-answer self-test tests.
+
+#![cfg(feature = "self_test")]
+
+use x25519_dalek::{self_test, Error, SelfTestError};
+
+#[test]
+fn self_test_passes() {
+    assert_eq!(self_test(), Ok(()));
+}
+
+#[test]
+fn self_test_error_converts_to_error() {
+    assert_eq!(
+        Error::from(SelfTestError::DiffieHellman),
+        Error::SelfTest(SelfTestError::DiffieHellman)
+    );
+}
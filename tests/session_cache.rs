@@ -0,0 +1,84 @@
+//! Shared-secret session cache tests.
+
+#![cfg(feature = "session_cache")]
+
+use core::num::NonZeroUsize;
+
+use x25519_dalek::{PublicKey, SessionCache, StaticSecret};
+
+fn peer(i: u8) -> PublicKey {
+    PublicKey::from(&StaticSecret::from([i; 32]))
+}
+
+fn cache(capacity: usize) -> SessionCache {
+    SessionCache::new(
+        StaticSecret::from([0x42; 32]),
+        NonZeroUsize::new(capacity).unwrap(),
+    )
+}
+
+#[test]
+fn cached_secrets_match_diffie_hellman() {
+    let secret = StaticSecret::from([0x42; 32]);
+    let mut cache = cache(4);
+    for i in 1..=3 {
+        let expected = secret.diffie_hellman(&peer(i));
+        assert_eq!(
+            cache.diffie_hellman(&peer(i)).as_bytes(),
+            expected.as_bytes()
+        );
+        assert_eq!(
+            cache.diffie_hellman(&peer(i)).as_bytes(),
+            expected.as_bytes()
+        );
+        assert_eq!(cache.get(&peer(i)).unwrap().as_bytes(), expected.as_bytes());
+    }
+    assert_eq!(cache.len(), 3);
+    assert_eq!(cache.capacity().get(), 4);
+}
+
+#[test]
+fn least_recently_used_is_evicted() {
+    let mut cache = cache(3);
+    cache.diffie_hellman(&peer(1));
+    cache.diffie_hellman(&peer(2));
+    cache.diffie_hellman(&peer(3));
+    // Using 1 again leaves 2 as the least recently used.
+    cache.diffie_hellman(&peer(1));
+    // Looking up 2 without using it does not change that.
+    assert!(cache.get(&peer(2)).is_some());
+
+    cache.diffie_hellman(&peer(4));
+    assert_eq!(cache.len(), 3);
+    assert!(cache.get(&peer(2)).is_none());
+    for i in [1, 3, 4] {
+        assert!(cache.get(&peer(i)).is_some());
+    }
+
+    cache.diffie_hellman(&peer(5));
+    assert!(cache.get(&peer(3)).is_none());
+    for i in [1, 4, 5] {
+        assert!(cache.get(&peer(i)).is_some());
+    }
+}
+
+#[test]
+fn remove_and_clear() {
+    let mut cache = cache(2);
+    cache.diffie_hellman(&peer(1));
+    cache.diffie_hellman(&peer(2));
+
+    assert!(cache.remove(&peer(1)));
+    assert!(!cache.remove(&peer(1)));
+    assert_eq!(cache.len(), 1);
+
+    // The freed slot is used without evicting 2.
+    cache.diffie_hellman(&peer(3));
+    assert!(cache.get(&peer(2)).is_some());
+    assert!(cache.get(&peer(3)).is_some());
+
+    cache.clear();
+    assert!(cache.is_empty());
+    cache.diffie_hellman(&peer(1));
+    assert_eq!(cache.len(), 1);
+}
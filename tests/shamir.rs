@@ -0,0 +1,108 @@
+//! Shamir secret sharing tests.
+
+#![cfg(feature = "shamir")]
+
+use rand_core::OsRng;
+use x25519_dalek::shamir::{self, Share};
+use x25519_dalek::{Error, StaticSecret};
+
+/// Alice's private key, from RFC7748 section 6.1.
+const ALICE_PRIVATE: [u8; 32] = [
+    0x77, 0x07, 0x6d, 0x0a, 0x73, 0x18, 0xa5, 0x7d, 0x3c, 0x16, 0xc1, 0x72, 0x51, 0xb2, 0x66, 0x45,
+    0xdf, 0x4c, 0x2f, 0x87, 0xeb, 0xc0, 0x99, 0x2a, 0xb1, 0x77, 0xfb, 0xa5, 0x1d, 0xb9, 0x2c, 0x2a,
+];
+
+#[test]
+fn shamir_any_threshold_subset_recovers() {
+    let secret = StaticSecret::from(ALICE_PRIVATE);
+    let shares = secret.split(5, 3, OsRng).unwrap();
+    assert_eq!(shares.len(), 5);
+    assert_eq!(
+        shares.iter().map(Share::index).collect::<Vec<_>>(),
+        [1, 2, 3, 4, 5]
+    );
+
+    for a in 0..5 {
+        for b in a + 1..5 {
+            for c in b + 1..5 {
+                let subset = [shares[c].clone(), shares[a].clone(), shares[b].clone()];
+                let recovered = StaticSecret::recover(&subset).unwrap();
+                assert_eq!(recovered.to_bytes(), ALICE_PRIVATE);
+            }
+        }
+    }
+
+    let recovered = StaticSecret::recover(&shares).unwrap();
+    assert_eq!(recovered.to_bytes(), ALICE_PRIVATE);
+}
+
+#[test]
+fn shamir_below_threshold_does_not_recover() {
+    let secret = StaticSecret::from(ALICE_PRIVATE);
+    let shares = secret.split(5, 3, OsRng).unwrap();
+
+    let recovered = StaticSecret::recover(&shares[..2]).unwrap();
+    assert_ne!(recovered.to_bytes(), ALICE_PRIVATE);
+}
+
+#[test]
+fn shamir_threshold_one_and_max_shares() {
+    let secret = StaticSecret::from(ALICE_PRIVATE);
+
+    for share in secret.split(3, 1, OsRng).unwrap() {
+        let recovered = StaticSecret::recover(&[share]).unwrap();
+        assert_eq!(recovered.to_bytes(), ALICE_PRIVATE);
+    }
+
+    let shares = secret.split(255, 255, OsRng).unwrap();
+    assert_eq!(shares.last().unwrap().index(), 255);
+    let recovered = StaticSecret::recover(&shares).unwrap();
+    assert_eq!(recovered.to_bytes(), ALICE_PRIVATE);
+}
+
+#[test]
+fn shamir_share_bytes_roundtrip() {
+    let secret = StaticSecret::from(ALICE_PRIVATE);
+    let shares = secret.split(3, 2, OsRng).unwrap();
+
+    let encoded = [shares[0].to_bytes(), shares[2].to_bytes()];
+    assert_eq!(encoded[0][0], 1);
+    assert_eq!(encoded[1][0], 3);
+
+    let decoded = [
+        Share::from_bytes(&encoded[0]).unwrap(),
+        Share::from_bytes(&encoded[1]).unwrap(),
+    ];
+    let recovered = StaticSecret::recover(&decoded).unwrap();
+    assert_eq!(recovered.to_bytes(), ALICE_PRIVATE);
+
+    let mut zero_index = encoded[0];
+    zero_index[0] = 0;
+    assert_eq!(
+        Share::from_bytes(&zero_index).err(),
+        Some(Error::Shamir(shamir::Error::InvalidShareIndex))
+    );
+}
+
+#[test]
+fn shamir_rejects_invalid_parameters() {
+    let secret = StaticSecret::from(ALICE_PRIVATE);
+
+    for (n, threshold) in [(3, 0), (3, 4), (0, 0)] {
+        assert_eq!(
+            secret.split(n, threshold, OsRng).err(),
+            Some(Error::Shamir(shamir::Error::InvalidThreshold))
+        );
+    }
+
+    assert_eq!(
+        StaticSecret::recover(&[]).err(),
+        Some(Error::Shamir(shamir::Error::NoShares))
+    );
+
+    let shares = secret.split(3, 2, OsRng).unwrap();
+    assert_eq!(
+        StaticSecret::recover(&[shares[1].clone(), shares[1].clone()]).err(),
+        Some(Error::Shamir(shamir::Error::DuplicateShare))
+    );
+}
@@ -0,0 +1,160 @@
+//! SLIP-0010 hierarchical derivation tests.
+
+#![cfg(feature = "slip10")]
+
+mod common;
+
+use common::hex;
+use x25519_dalek::slip10::{self, ExtendedSecret, HARDENED_OFFSET};
+use x25519_dalek::Error;
+
+/// Check every step of the `m/0'/1'/2'/2'/1000000000'` chain, given as
+/// (path, chain code, private key) triples.
+fn check_chain(seed: &str, vectors: &[(&str, &str, &str)]) {
+    let master = ExtendedSecret::from_seed(&hex(seed)).unwrap();
+    let mut stepwise = master.clone();
+
+    for (i, (path, chain_code, secret)) in vectors.iter().enumerate() {
+        let key = ExtendedSecret::from_seed_and_path(&hex(seed), path).unwrap();
+        assert_eq!(key.chain_code()[..], hex(chain_code)[..], "{}", path);
+        assert_eq!(key.secret().as_bytes()[..], hex(secret)[..], "{}", path);
+
+        if i > 0 {
+            let index = path.rsplit('/').next().unwrap().trim_end_matches('\'');
+            let index = index.parse::<u32>().unwrap() | HARDENED_OFFSET;
+            stepwise = stepwise.derive_child(index).unwrap();
+        }
+        assert_eq!(stepwise.secret().to_bytes(), key.secret().to_bytes());
+        assert_eq!(stepwise.chain_code(), key.chain_code());
+    }
+}
+
+#[test]
+fn slip10_curve25519_test_vector_1() {
+    check_chain(
+        "000102030405060708090a0b0c0d0e0f",
+        &[
+            (
+                "m",
+                "77997ca3588a1a34f3589279ea2962247abfe5277d52770a44c706378c710768",
+                "d70a59c2e68b836cc4bbe8bcae425169b9e2384f3905091e3d60b890e90cd92c",
+            ),
+            (
+                "m/0'",
+                "349a3973aad771c628bf1f1b4d5e071f18eff2e492e4aa7972a7e43895d6597f",
+                "cd7630d7513cbe80515f7317cdb9a47ad4a56b63c3f1dc29583ab8d4cc25a9b2",
+            ),
+            (
+                "m/0'/1'",
+                "2ee5ba14faf2fe9d7ab532451c2be3a0a5375c5e8c44fb31d9ad7edc25cda000",
+                "a95f97cfc1a61dd833b882c89d36a78a030ea6b2fbe3ae2a70e4f1fc9008d6b1",
+            ),
+            (
+                "m/0'/1'/2'",
+                "e1897d5a96459ce2a3d294cb2a6a59050ee61255818c50e03ac4263ef17af084",
+                "3d6cce04a9175929da907a90b02176077b9ae050dcef9b959fed978bb2200cdc",
+            ),
+            (
+                "m/0'/1'/2'/2'",
+                "1cccc84e2737cfe81b51fbe4c97bbdb000f6a76eddffb9ed03108fbff3ff7e4f",
+                "7ae7437efe0a3018999e6f00d72e810ebc50578dbf6728bfa1c7fe73501081a7",
+            ),
+            (
+                "m/0'/1'/2'/2'/1000000000'",
+                "8ccf15d55b1dda246b0c1bf3e979a471a82524c1bd0c1eaecccf00dde72168bb",
+                "7a59954d387abde3bc703f531f67d659ec2b8a12597ae82824547d7e27991e26",
+            ),
+        ],
+    );
+}
+
+#[test]
+fn slip10_curve25519_test_vector_2() {
+    check_chain(
+        "fffcf9f6f3f0edeae7e4e1dedbd8d5d2cfccc9c6c3c0bdbab7b4b1aeaba8a5a2\
+         9f9c999693908d8a8784817e7b7875726f6c696663605d5a5754514e4b484542",
+        &[
+            (
+                "m",
+                "b62c0c81a80a0ee16b977abb3677eb47549d0eef090f7a6c2b2010e739875e34",
+                "088491f5b4dfafbe956de471f3db10e02d784bc76050ee3b7c3f11b9706d3730",
+            ),
+            (
+                "m/0'",
+                "341f386e571229e8adc52b82e824532817a31a35ba49ae334424e7228d020eed",
+                "8e73218a1ba5c7b95e94b6e7cf7b37fb6240fb3b2ecd801402a4439da7067ee2",
+            ),
+            (
+                "m/0'/1'",
+                "973043121a6b6a250cac0f7173b6c24ab560ff159f76104025b695a2bebee24c",
+                "260783de51181128ae0422787cc7bc3ce216b1211f5e8b499dd55e045dbbd40e",
+            ),
+            (
+                "m/0'/1'/2'",
+                "238904cedbc3c706209e6f483c6d78b275005fbcd5c6ee6813b237d1f319d44f",
+                "ad3e3e245ee470dd1ba8aa8652dff17f71f356ca985a868b78af2ff757176558",
+            ),
+            (
+                "m/0'/1'/2'/2'",
+                "8b33e40d5710d9aa1824aa993c2ec30b6f911236023872e8a5ef4cfb9c0d0dfc",
+                "7b4678e7cf69ed565a51965906389c40d56a7b47b2592cad7d3ded2c1094dfdc",
+            ),
+            (
+                "m/0'/1'/2'/2'/1000000000'",
+                "5bd1ca7ef851807d1ca52aac7d3bf6c7fdd6764edad683f142ff409c4ecf9b7c",
+                "ce9ee55eff951fafbd89efc3cac49f660fa05d589933a22fc343c7fbff1f914b",
+            ),
+        ],
+    );
+}
+
+#[test]
+fn slip10_accepts_alternative_hardened_markers() {
+    let seed = [0x42; 32];
+    let expected = ExtendedSecret::from_seed_and_path(&seed, "m/44'/0'").unwrap();
+
+    for path in ["m/44h/0h", "m/44H/0'"] {
+        let key = ExtendedSecret::from_seed_and_path(&seed, path).unwrap();
+        assert_eq!(key.secret().to_bytes(), expected.secret().to_bytes());
+    }
+}
+
+#[test]
+fn slip10_rejects_invalid_seeds_and_paths() {
+    assert_eq!(
+        ExtendedSecret::from_seed(&[0; 15]).err(),
+        Some(Error::Slip10(slip10::Error::InvalidSeedLength))
+    );
+    assert_eq!(
+        ExtendedSecret::from_seed(&[0; 65]).err(),
+        Some(Error::Slip10(slip10::Error::InvalidSeedLength))
+    );
+
+    let master = ExtendedSecret::from_seed(&[0; 16]).unwrap();
+    assert_eq!(
+        master.derive_child(0).err(),
+        Some(Error::Slip10(slip10::Error::NonHardenedIndex))
+    );
+    assert_eq!(
+        master.derive_path("m/44'/0").err(),
+        Some(Error::Slip10(slip10::Error::NonHardenedIndex))
+    );
+
+    for path in [
+        "",
+        "44'",
+        "m/",
+        "m//0'",
+        "m/-1'",
+        "m/+1'",
+        "m/2147483648'",
+        "n/0'",
+    ] {
+        assert_eq!(
+            master.derive_path(path).err(),
+            Some(Error::Slip10(slip10::Error::InvalidPath)),
+            "{}",
+            path
+        );
+    }
+}
@@ -0,0 +1,210 @@
+//! libsodium `crypto_box` compatibility tests.
+
+#![cfg(feature = "sodium")]
+
+mod common;
+
+use common::ConstRng;
+use rand_core::OsRng;
+use x25519_dalek::sodium::{self, MAC_LEN, SEAL_LEN};
+use x25519_dalek::{Error, PublicKey, StaticSecret};
+
+/// Alice's private key, from RFC7748 section 6.1 and NaCl's `box.c` test.
+const ALICE_PRIVATE: [u8; 32] = [
+    0x77, 0x07, 0x6d, 0x0a, 0x73, 0x18, 0xa5, 0x7d, 0x3c, 0x16, 0xc1, 0x72, 0x51, 0xb2, 0x66, 0x45,
+    0xdf, 0x4c, 0x2f, 0x87, 0xeb, 0xc0, 0x99, 0x2a, 0xb1, 0x77, 0xfb, 0xa5, 0x1d, 0xb9, 0x2c, 0x2a,
+];
+
+/// Bob's private key, from RFC7748 section 6.1 and NaCl's `box.c` test.
+const BOB_PRIVATE: [u8; 32] = [
+    0x5d, 0xab, 0x08, 0x7e, 0x62, 0x4a, 0x8a, 0x4b, 0x79, 0xe1, 0x7f, 0x8b, 0x83, 0x80, 0x0e, 0xe6,
+    0x6f, 0x3b, 0xb1, 0x29, 0x26, 0x18, 0xb6, 0xfd, 0x1c, 0x2f, 0x8b, 0x27, 0xff, 0x88, 0xe0, 0xeb,
+];
+
+/// The nonce from NaCl's `box.c` test.
+const NONCE: [u8; 24] = [
+    0x69, 0x69, 0x6e, 0xe9, 0x55, 0xb6, 0x2b, 0x73, 0xcd, 0x62, 0xbd, 0xa8, 0x75, 0xfc, 0x73, 0xd6,
+    0x82, 0x19, 0xe0, 0x03, 0x6b, 0x7a, 0x0b, 0x37,
+];
+
+/// `crypto_box_easy` of `"Cryptography in NaCl"` from Alice to Bob with
+/// [`NONCE`], computed with libsodium.
+const BOXED: [u8; 36] = [
+    0x03, 0xa0, 0xd6, 0xf0, 0x0b, 0x86, 0x7c, 0x8b, 0x81, 0x7a, 0x58, 0x1b, 0x23, 0xb6, 0xd9, 0x78,
+    0x73, 0xec, 0x1d, 0x2a, 0x00, 0x86, 0x87, 0xd4, 0x6c, 0xf2, 0x2b, 0xd5, 0xf9, 0x7e, 0x14, 0x95,
+    0x54, 0x7a, 0xa8, 0xe1,
+];
+
+/// `crypto_box_seal` of `"Cryptography in NaCl"` to Bob, with Alice's key as
+/// the ephemeral key, computed with libsodium's primitives.
+const SEALED: [u8; 68] = [
+    0x85, 0x20, 0xf0, 0x09, 0x89, 0x30, 0xa7, 0x54, 0x74, 0x8b, 0x7d, 0xdc, 0xb4, 0x3e, 0xf7, 0x5a,
+    0x0d, 0xbf, 0x3a, 0x0d, 0x26, 0x38, 0x1a, 0xf4, 0xeb, 0xa4, 0xa9, 0x8e, 0xaa, 0x9b, 0x4e, 0x6a,
+    0x2d, 0x38, 0xc8, 0x82, 0xbc, 0xdd, 0xbb, 0x30, 0xb4, 0x7a, 0x46, 0xe1, 0x19, 0xf6, 0x22, 0x71,
+    0xe4, 0x75, 0xd5, 0xb6, 0x9c, 0x97, 0x64, 0x23, 0xb1, 0x16, 0x23, 0x03, 0xcd, 0x12, 0x47, 0x6b,
+    0x21, 0x39, 0x1d, 0x1a,
+];
+
+/// `crypto_box_seal` of `"Cryptography in NaCl"` to Bob, with a random
+/// ephemeral key, computed with libsodium.
+const LIBSODIUM_SEALED: [u8; 68] = [
+    0xe7, 0x92, 0xe2, 0xf6, 0x65, 0x4c, 0xe4, 0x9d, 0x63, 0x23, 0xe4, 0x43, 0x9d, 0x0f, 0xdf, 0x4d,
+    0xae, 0x25, 0xd1, 0x97, 0x80, 0x04, 0x63, 0x66, 0xe6, 0x12, 0x13, 0xa2, 0x0a, 0x58, 0x8a, 0x4c,
+    0xa2, 0x86, 0x42, 0x35, 0xc2, 0x0f, 0x7d, 0xc4, 0xcb, 0xba, 0x01, 0xe5, 0x02, 0x30, 0xa1, 0x78,
+    0x53, 0xaa, 0x12, 0xc8, 0x59, 0xa1, 0xe5, 0x65, 0xc3, 0xb0, 0xf2, 0xce, 0x17, 0x4e, 0xeb, 0x6a,
+    0xfd, 0x1d, 0x33, 0xa2,
+];
+
+#[test]
+fn beforenm_matches_nacl() {
+    let alice = StaticSecret::from(ALICE_PRIVATE);
+    let bob = StaticSecret::from(BOB_PRIVATE);
+    let expected = [
+        0x1b, 0x27, 0x55, 0x64, 0x73, 0xe9, 0x85, 0xd4, 0x62, 0xcd, 0x51, 0x19, 0x7a, 0x9a, 0x46,
+        0xc7, 0x60, 0x09, 0x54, 0x9e, 0xac, 0x64, 0x74, 0xf2, 0x06, 0xc4, 0xee, 0x08, 0x44, 0xf6,
+        0x83, 0x89,
+    ];
+    assert_eq!(
+        *sodium::beforenm(&PublicKey::from(&bob), &alice).unwrap(),
+        expected
+    );
+    assert_eq!(
+        *sodium::beforenm(&PublicKey::from(&alice), &bob).unwrap(),
+        expected
+    );
+}
+
+#[test]
+fn seal_matches_libsodium() {
+    let alice = StaticSecret::from(ALICE_PRIVATE);
+    let bob = PublicKey::from(&StaticSecret::from(BOB_PRIVATE));
+    let boxed = sodium::seal(b"Cryptography in NaCl", &NONCE, &bob, &alice).unwrap();
+    assert_eq!(boxed, BOXED);
+}
+
+#[test]
+fn open_matches_libsodium() {
+    let alice = PublicKey::from(&StaticSecret::from(ALICE_PRIVATE));
+    let bob = StaticSecret::from(BOB_PRIVATE);
+    let opened = sodium::open(&BOXED, &NONCE, &alice, &bob).unwrap();
+    assert_eq!(opened, b"Cryptography in NaCl");
+}
+
+#[test]
+fn open_rejects_tampering() {
+    let alice = PublicKey::from(&StaticSecret::from(ALICE_PRIVATE));
+    let bob = StaticSecret::from(BOB_PRIVATE);
+    let invalid = Some(Error::Sodium(sodium::Error::InvalidCiphertext));
+
+    for i in [0, MAC_LEN, BOXED.len() - 1] {
+        let mut tampered = BOXED;
+        tampered[i] ^= 1;
+        assert_eq!(sodium::open(&tampered, &NONCE, &alice, &bob).err(), invalid);
+    }
+
+    let mut nonce = NONCE;
+    nonce[0] ^= 1;
+    assert_eq!(sodium::open(&BOXED, &nonce, &alice, &bob).err(), invalid);
+
+    let eve = StaticSecret::random_from_rng(OsRng);
+    assert_eq!(sodium::open(&BOXED, &NONCE, &alice, &eve).err(), invalid);
+    assert_eq!(
+        sodium::open(&BOXED[..MAC_LEN - 1], &NONCE, &alice, &bob).err(),
+        invalid
+    );
+}
+
+#[test]
+fn small_order_keys_are_rejected() {
+    let alice = StaticSecret::from(ALICE_PRIVATE);
+    let zero = PublicKey::from([0u8; 32]);
+    let invalid = Some(Error::SmallOrderPoint);
+    assert_eq!(sodium::seal(b"", &NONCE, &zero, &alice).err(), invalid);
+    assert_eq!(sodium::open(&BOXED, &NONCE, &zero, &alice).err(), invalid);
+}
+
+#[test]
+fn box_seal_matches_libsodium() {
+    let bob = PublicKey::from(&StaticSecret::from(BOB_PRIVATE));
+    let sealed = sodium::box_seal(b"Cryptography in NaCl", &bob, ConstRng(ALICE_PRIVATE)).unwrap();
+    assert_eq!(sealed, SEALED);
+}
+
+#[test]
+fn box_seal_open_matches_libsodium() {
+    let bob = StaticSecret::from(BOB_PRIVATE);
+    for sealed in [&SEALED, &LIBSODIUM_SEALED] {
+        assert_eq!(
+            sodium::box_seal_open(sealed, &bob).unwrap(),
+            b"Cryptography in NaCl"
+        );
+    }
+}
+
+#[test]
+fn box_seal_round_trip() {
+    let recipient = StaticSecret::random_from_rng(OsRng);
+    let sealed = sodium::box_seal(b"hello", &PublicKey::from(&recipient), OsRng).unwrap();
+    assert_eq!(sealed.len(), SEAL_LEN + 5);
+    assert_eq!(
+        sodium::box_seal_open(&sealed, &recipient).unwrap(),
+        b"hello"
+    );
+}
+
+#[test]
+fn box_seal_open_rejects_tampering() {
+    let bob = StaticSecret::from(BOB_PRIVATE);
+    let invalid = Some(Error::Sodium(sodium::Error::InvalidCiphertext));
+
+    for i in [0, 32, SEALED.len() - 1] {
+        let mut tampered = SEALED;
+        tampered[i] ^= 1;
+        assert!(sodium::box_seal_open(&tampered, &bob).is_err());
+    }
+    assert_eq!(
+        sodium::box_seal_open(&SEALED, &StaticSecret::from(ALICE_PRIVATE)).err(),
+        invalid
+    );
+    assert_eq!(
+        sodium::box_seal_open(&SEALED[..SEAL_LEN - 1], &bob).err(),
+        invalid
+    );
+}
+
+/// Alice's receiving key, and Bob's sending key, from libsodium's
+/// `crypto_kx` with Alice as the client and Bob as the server.
+const KX_CLIENT_RX: [u8; 32] = [
+    0x32, 0x2b, 0x7b, 0xe3, 0xb9, 0xbc, 0xe4, 0xa8, 0x4f, 0xe6, 0xe2, 0xde, 0xa6, 0x1e, 0x8e, 0x6d,
+    0x0a, 0x98, 0xf3, 0xe4, 0xc6, 0x0b, 0x58, 0xba, 0xd7, 0x22, 0xb1, 0xc8, 0x55, 0xc9, 0xdb, 0x22,
+];
+
+/// Alice's sending key, and Bob's receiving key, from libsodium's
+/// `crypto_kx` with Alice as the client and Bob as the server.
+const KX_CLIENT_TX: [u8; 32] = [
+    0x28, 0x49, 0x01, 0xa6, 0x11, 0x70, 0x83, 0x79, 0xd0, 0xb5, 0xb0, 0xe4, 0x0d, 0x77, 0xea, 0x20,
+    0x76, 0x24, 0xea, 0xab, 0x8d, 0xd0, 0xc9, 0x5e, 0x69, 0x3f, 0xc3, 0xee, 0x76, 0xc7, 0x3c, 0xcb,
+];
+
+#[test]
+fn kx_matches_libsodium() {
+    let alice = StaticSecret::from(ALICE_PRIVATE);
+    let bob = StaticSecret::from(BOB_PRIVATE);
+
+    let client = sodium::kx_client_session_keys(&alice, &PublicKey::from(&bob)).unwrap();
+    assert_eq!(client.rx(), &KX_CLIENT_RX);
+    assert_eq!(client.tx(), &KX_CLIENT_TX);
+
+    let server = sodium::kx_server_session_keys(&bob, &PublicKey::from(&alice)).unwrap();
+    assert_eq!(server.rx(), &KX_CLIENT_TX);
+    assert_eq!(server.tx(), &KX_CLIENT_RX);
+}
+
+#[test]
+fn kx_rejects_small_order_keys() {
+    let alice = StaticSecret::from(ALICE_PRIVATE);
+    let zero = PublicKey::from([0u8; 32]);
+    let invalid = Some(Error::SmallOrderPoint);
+    assert_eq!(sodium::kx_client_session_keys(&alice, &zero).err(), invalid);
+    assert_eq!(sodium::kx_server_session_keys(&alice, &zero).err(), invalid);
+}
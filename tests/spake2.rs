@@ -0,0 +1,106 @@
+//! SPAKE2 tests.
+
+#![cfg(feature = "spake2")]
+
+mod common;
+
+use common::{hex, ConstRng};
+use x25519_dalek::pake::spake2::{self, Role, Spake2};
+use x25519_dalek::Error;
+
+fn w() -> [u8; 64] {
+    let mut w = [0u8; 64];
+    for (i, byte) in w.iter_mut().enumerate() {
+        *byte = i as u8;
+    }
+    w
+}
+
+/// Start the exchange with the test scalars.
+fn start() -> (Spake2, Spake2) {
+    (
+        Spake2::new(Role::A, &w(), ConstRng([0x11; 64])),
+        Spake2::new(Role::B, &w(), ConstRng([0x22; 64])),
+    )
+}
+
+/// Check the shares, the shared key and the MACs against values computed
+/// with an independent implementation of the specification.
+#[test]
+fn exchange_matches_reference() {
+    for (id_a, id_b, aad, ke, c_a, c_b) in [
+        (
+            &b"server"[..],
+            &b"client"[..],
+            &b""[..],
+            "cf1452c3a99acef410d5533685892160",
+            "9c1b91e66f2943095f257c4e19558ab7d58239d1cdb3ccc248a87bb9b7418f64",
+            "eebe3e991da8fca85e07355e7ce8758318d302ad57931381b931d22cf409f25c",
+        ),
+        (
+            &b""[..],
+            &b""[..],
+            &b"aad"[..],
+            "09984f147490b8fe8f99e8af66999490",
+            "6ac7ef9cc4d2dfd9b788d3d93ab2cb930b44daa395554953c62ac28bfa007275",
+            "95ec4fd92f6e3c8446cf0cd7012bebd30e34853d5e9207ba6b755908bbfcee0f",
+        ),
+    ] {
+        let (a, b) = start();
+        assert_eq!(
+            a.share().to_vec(),
+            hex("8e54526bdfb9c697c725fb5c13a1c0d578dc442374996fb107945500ff7b9442")
+        );
+        assert_eq!(
+            b.share().to_vec(),
+            hex("213ca8ec4209ed2ebcd022fa2ae0e8b4b26ddb77e9d50efbc48027915b0c56cd")
+        );
+        let (share_a, share_b) = (*a.share(), *b.share());
+
+        let a = a.finish(id_a, id_b, aad, &share_b).unwrap();
+        let b = b.finish(id_a, id_b, aad, &share_a).unwrap();
+        assert_eq!(a.mac().to_vec(), hex(c_a));
+        assert_eq!(b.mac().to_vec(), hex(c_b));
+
+        let mac_a = *a.mac();
+        let ke_a = a.verify(b.mac()).unwrap();
+        let ke_b = b.verify(&mac_a).unwrap();
+        assert_eq!(ke_a.to_vec(), hex(ke));
+        assert_eq!(ke_b.to_vec(), hex(ke));
+    }
+}
+
+#[test]
+fn password_mismatch_fails_confirmation() {
+    let a = Spake2::new(Role::A, &w(), ConstRng([0x11; 64]));
+    let b = Spake2::new(Role::B, &[0x99; 64], ConstRng([0x22; 64]));
+    let (share_a, share_b) = (*a.share(), *b.share());
+
+    let a = a.finish(b"", b"", b"", &share_b).unwrap();
+    let b = b.finish(b"", b"", b"", &share_a).unwrap();
+    assert!(matches!(
+        a.verify(b.mac()),
+        Err(Error::Spake2(spake2::Error::ConfirmationFailed))
+    ));
+}
+
+#[test]
+fn invalid_shares_are_rejected() {
+    // With a zero secret scalar, B's share is w * N, and unblinding it
+    // leaves the identity.
+    let (a, _) = start();
+    let share = *Spake2::new(Role::B, &w(), ConstRng([0; 64])).share();
+    assert!(matches!(
+        a.finish(b"", b"", b"", &share),
+        Err(Error::Spake2(spake2::Error::InvalidShare))
+    ));
+
+    // A y-coordinate with no matching x-coordinate.
+    let (a, _) = start();
+    let mut not_a_point = [0u8; 32];
+    not_a_point[0] = 2;
+    assert!(matches!(
+        a.finish(b"", b"", b"", &not_a_point),
+        Err(Error::Spake2(spake2::Error::InvalidShare))
+    ));
+}
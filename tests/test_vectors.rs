@@ -0,0 +1,58 @@
+//! Check the exported test vectors against this implementation.
+
+#![cfg(feature = "test-vectors")]
+
+use x25519_dalek::test_vectors::*;
+use x25519_dalek::{x25519, Error, PublicKey, X25519_BASEPOINT_BYTES};
+
+#[test]
+fn rfc7748_x25519() {
+    for vector in RFC7748_X25519.iter() {
+        assert_eq!(x25519(vector.scalar, vector.u), vector.result);
+    }
+}
+
+#[test]
+fn rfc7748_iterated() {
+    let (mut k, mut u) = (X25519_BASEPOINT_BYTES, X25519_BASEPOINT_BYTES);
+    let mut done = 0;
+    // The last checkpoint, of 1,000,000 iterations, is too slow to test here.
+    for (iterations, expected) in RFC7748_ITERATED.iter().take(2) {
+        for _ in done..*iterations {
+            let result = x25519(k, u);
+            u = k;
+            k = result;
+        }
+        done = *iterations;
+        assert_eq!(k, *expected);
+    }
+}
+
+#[test]
+fn rfc7748_diffie_hellman() {
+    let v = RFC7748_DIFFIE_HELLMAN;
+    assert_eq!(
+        x25519(v.alice_private, X25519_BASEPOINT_BYTES),
+        v.alice_public
+    );
+    assert_eq!(x25519(v.bob_private, X25519_BASEPOINT_BYTES), v.bob_public);
+    assert_eq!(x25519(v.alice_private, v.bob_public), v.shared);
+    assert_eq!(x25519(v.bob_private, v.alice_public), v.shared);
+}
+
+#[test]
+fn edge_cases() {
+    for case in EDGE_CASES.iter() {
+        assert_eq!(x25519(EDGE_CASE_PRIVATE, case.public), case.shared);
+        assert_eq!(case.low_order, case.shared == [0u8; 32]);
+        let canonical = PublicKey::from_canonical_bytes(case.public);
+        if case.non_canonical {
+            assert_eq!(canonical.err(), Some(Error::NonCanonicalEncoding));
+        } else {
+            assert_eq!(canonical.is_err(), case.low_order);
+        }
+        if case.twist {
+            assert!(PublicKey::from(case.public).to_edwards(0).is_none());
+        }
+    }
+}
@@ -0,0 +1,150 @@
+//! Threshold Diffie-Hellman tests.
+
+#![cfg(feature = "threshold")]
+
+use rand_core::OsRng;
+use x25519_dalek::threshold::{self, PartialSharedSecret, ShareholderSecret};
+use x25519_dalek::{Error, PublicKey, StaticSecret};
+
+/// Alice's private key, from RFC7748 section 6.1.
+const ALICE_PRIVATE: [u8; 32] = [
+    0x77, 0x07, 0x6d, 0x0a, 0x73, 0x18, 0xa5, 0x7d, 0x3c, 0x16, 0xc1, 0x72, 0x51, 0xb2, 0x66, 0x45,
+    0xdf, 0x4c, 0x2f, 0x87, 0xeb, 0xc0, 0x99, 0x2a, 0xb1, 0x77, 0xfb, 0xa5, 0x1d, 0xb9, 0x2c, 0x2a,
+];
+
+/// Bob's public key, from RFC7748 section 6.1.
+const BOB_PUBLIC: [u8; 32] = [
+    0xde, 0x9e, 0xdb, 0x7d, 0x7b, 0x7d, 0xc1, 0xb4, 0xd3, 0x5b, 0x61, 0xc2, 0xec, 0xe4, 0x35, 0x37,
+    0x3f, 0x83, 0x43, 0xc8, 0x5b, 0x78, 0x67, 0x4d, 0xad, 0xfc, 0x7e, 0x14, 0x6f, 0x88, 0x2b, 0x4f,
+];
+
+/// The shared secret between Alice and Bob, from RFC7748 section 6.1.
+const SHARED: [u8; 32] = [
+    0x4a, 0x5d, 0x9d, 0x5b, 0xa4, 0xce, 0x2d, 0xe1, 0x72, 0x8e, 0x3b, 0xf4, 0x80, 0x35, 0x0f, 0x25,
+    0xe0, 0x7e, 0x21, 0xc9, 0x47, 0xd1, 0x9e, 0x33, 0x76, 0xf0, 0x9b, 0x3c, 0x1e, 0x16, 0x17, 0x42,
+];
+
+fn partials(shareholders: &[ShareholderSecret], peer: &PublicKey) -> Vec<PartialSharedSecret> {
+    shareholders
+        .iter()
+        .map(|shareholder| shareholder.partial_diffie_hellman(peer).unwrap())
+        .collect()
+}
+
+#[test]
+fn threshold_dh_matches_rfc7748() {
+    let secret = StaticSecret::from(ALICE_PRIVATE);
+    let shareholders = secret.split_threshold(5, 3, OsRng).unwrap();
+    let partials = partials(&shareholders, &PublicKey::from(BOB_PUBLIC));
+
+    for a in 0..5 {
+        for b in a + 1..5 {
+            for c in b + 1..5 {
+                let subset = [
+                    partials[c].clone(),
+                    partials[a].clone(),
+                    partials[b].clone(),
+                ];
+                let shared = PartialSharedSecret::combine(&subset).unwrap();
+                assert_eq!(shared.to_bytes(), SHARED);
+            }
+        }
+    }
+
+    let shared = PartialSharedSecret::combine(&partials).unwrap();
+    assert_eq!(shared.to_bytes(), SHARED);
+
+    let shared = PartialSharedSecret::combine(&partials[..2]).unwrap();
+    assert_ne!(shared.to_bytes(), SHARED);
+}
+
+#[test]
+fn threshold_dh_matches_diffie_hellman() {
+    for _ in 0..16 {
+        let secret = StaticSecret::random_from_rng(OsRng);
+        let peer = PublicKey::from(&StaticSecret::random_from_rng(OsRng));
+        let shareholders = secret.split_threshold(3, 2, OsRng).unwrap();
+
+        let shared = PartialSharedSecret::combine(&partials(&shareholders[1..], &peer)).unwrap();
+        assert_eq!(shared.as_bytes(), secret.diffie_hellman(&peer).as_bytes());
+    }
+}
+
+#[test]
+fn threshold_dh_encoding_roundtrip() {
+    let secret = StaticSecret::from(ALICE_PRIVATE);
+    let shareholders = secret.split_threshold(3, 2, OsRng).unwrap();
+
+    let decoded: Vec<ShareholderSecret> = shareholders
+        .iter()
+        .map(|shareholder| ShareholderSecret::from_bytes(&shareholder.to_bytes()).unwrap())
+        .collect();
+    assert_eq!(decoded[2].index(), 3);
+
+    let partials: Vec<PartialSharedSecret> = partials(&decoded[..2], &PublicKey::from(BOB_PUBLIC))
+        .iter()
+        .map(|partial| PartialSharedSecret::from_bytes(&partial.to_bytes()).unwrap())
+        .collect();
+    let shared = PartialSharedSecret::combine(&partials).unwrap();
+    assert_eq!(shared.to_bytes(), SHARED);
+
+    let mut encoded = shareholders[0].to_bytes();
+    encoded[0] = 0;
+    assert_eq!(
+        ShareholderSecret::from_bytes(&encoded).err(),
+        Some(Error::Threshold(threshold::Error::InvalidShareIndex))
+    );
+
+    // Not reduced modulo the group order.
+    let mut encoded = shareholders[0].to_bytes();
+    encoded[32] = 0xff;
+    assert_eq!(
+        ShareholderSecret::from_bytes(&encoded).err(),
+        Some(Error::Threshold(threshold::Error::InvalidScalar))
+    );
+
+    // y = 2 is not on the curve.
+    let mut encoded = [0u8; threshold::SHARE_LEN];
+    encoded[0] = 1;
+    encoded[1] = 2;
+    assert_eq!(
+        PartialSharedSecret::from_bytes(&encoded).err(),
+        Some(Error::Threshold(threshold::Error::InvalidPoint))
+    );
+}
+
+#[test]
+fn threshold_dh_rejects_invalid_parameters() {
+    let secret = StaticSecret::from(ALICE_PRIVATE);
+
+    for (n, threshold) in [(3, 0), (3, 4)] {
+        assert_eq!(
+            secret.split_threshold(n, threshold, OsRng).err(),
+            Some(Error::Threshold(threshold::Error::InvalidThreshold))
+        );
+    }
+
+    assert_eq!(
+        PartialSharedSecret::combine(&[]).err(),
+        Some(Error::Threshold(threshold::Error::NoShares))
+    );
+
+    let shareholders = secret.split_threshold(3, 2, OsRng).unwrap();
+    let partial = shareholders[0]
+        .partial_diffie_hellman(&PublicKey::from(BOB_PUBLIC))
+        .unwrap();
+    assert_eq!(
+        PartialSharedSecret::combine(&[partial.clone(), partial]).err(),
+        Some(Error::Threshold(threshold::Error::DuplicateShare))
+    );
+
+    // u = 2 lies on the twist.
+    let mut twist = [0u8; 32];
+    twist[0] = 2;
+    assert_eq!(
+        shareholders[0]
+            .partial_diffie_hellman(&PublicKey::from(twist))
+            .err(),
+        Some(Error::TwistPoint)
+    );
+}
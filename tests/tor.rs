@@ -0,0 +1,108 @@
+//! Tor client authorization key encoding tests.
+
+#![cfg(feature = "tor")]
+
+use x25519_dalek::{Error, PublicKey, TorKeyError};
+
+#[cfg(feature = "static_secrets")]
+use x25519_dalek::StaticSecret;
+
+/// Alice's public key from RFC7748 section 6.1, as an `*.auth` line.
+const ALICE_AUTH_KEY: &str =
+    "descriptor:x25519:QUQPACMJGCTVI5ELPXOLIPXXLIG36OQNEY4BV5HLUSUY5KU3JZVA";
+
+/// An onion service address, without the `.onion` suffix.
+#[cfg(feature = "static_secrets")]
+const ONION_ADDRESS: &str = "2gzyxa5ihm7nsggfxnu52rck2vv4rvmdlkiu3zzui5du4xyclen53wid";
+
+/// Alice's private key from RFC7748 section 6.1, as an `*.auth_private` line.
+#[cfg(feature = "static_secrets")]
+const ALICE_AUTH_PRIVATE: &str = "2gzyxa5ihm7nsggfxnu52rck2vv4rvmdlkiu3zzui5du4xyclen53wid:descriptor:x25519:O4DW2CTTDCSX2PAWYFZFDMTGIXPUYL4H5PAJSKVRO752KHNZFQVA";
+
+/// Alice's public key, from RFC7748 section 6.1.
+const ALICE_PUBLIC: [u8; 32] = [
+    0x85, 0x20, 0xf0, 0x09, 0x89, 0x30, 0xa7, 0x54, 0x74, 0x8b, 0x7d, 0xdc, 0xb4, 0x3e, 0xf7, 0x5a,
+    0x0d, 0xbf, 0x3a, 0x0d, 0x26, 0x38, 0x1a, 0xf4, 0xeb, 0xa4, 0xa9, 0x8e, 0xaa, 0x9b, 0x4e, 0x6a,
+];
+
+#[test]
+fn public_key_tor_auth_key_roundtrip() {
+    let public = PublicKey::from_tor_auth_key(ALICE_AUTH_KEY).unwrap();
+    assert_eq!(public.to_bytes(), ALICE_PUBLIC);
+    assert_eq!(public.to_tor_auth_key(), ALICE_AUTH_KEY);
+}
+
+#[test]
+fn public_key_tor_auth_key_is_case_insensitive() {
+    let line = format!("{}\n", ALICE_AUTH_KEY.replace("QUQPACMJ", "quqpacmj"));
+    let public = PublicKey::from_tor_auth_key(&line).unwrap();
+    assert_eq!(public.to_bytes(), ALICE_PUBLIC);
+}
+
+#[test]
+fn public_key_tor_auth_key_rejects_bad_format() {
+    let line = ALICE_AUTH_KEY.replace("x25519", "ed25519");
+    assert_eq!(
+        PublicKey::from_tor_auth_key(&line),
+        Err(Error::Tor(TorKeyError::InvalidFormat))
+    );
+    assert_eq!(
+        PublicKey::from_tor_auth_key(&ALICE_AUTH_KEY[18..]),
+        Err(Error::Tor(TorKeyError::InvalidFormat))
+    );
+}
+
+#[test]
+fn public_key_tor_auth_key_rejects_bad_encoding() {
+    // Padding is not allowed.
+    let padded = format!("{}====", ALICE_AUTH_KEY);
+    assert_eq!(
+        PublicKey::from_tor_auth_key(&padded),
+        Err(Error::Tor(TorKeyError::InvalidEncoding))
+    );
+
+    // '1' and '8' are not in the alphabet.
+    let invalid = ALICE_AUTH_KEY.replace("QUQ", "Q1Q");
+    assert_eq!(
+        PublicKey::from_tor_auth_key(&invalid),
+        Err(Error::Tor(TorKeyError::InvalidEncoding))
+    );
+    let invalid = ALICE_AUTH_KEY.replace("JZVA", "JZV8");
+    assert_eq!(
+        PublicKey::from_tor_auth_key(&invalid),
+        Err(Error::Tor(TorKeyError::InvalidEncoding))
+    );
+
+    // Non-zero trailing bits.
+    let non_canonical = ALICE_AUTH_KEY.replace("JZVA", "JZVB");
+    assert_eq!(
+        PublicKey::from_tor_auth_key(&non_canonical),
+        Err(Error::Tor(TorKeyError::InvalidEncoding))
+    );
+}
+
+#[test]
+#[cfg(feature = "static_secrets")]
+fn static_secret_tor_auth_private_roundtrip() {
+    let (onion_address, secret) = StaticSecret::from_tor_auth_private(ALICE_AUTH_PRIVATE).unwrap();
+    assert_eq!(onion_address, ONION_ADDRESS);
+    assert_eq!(
+        *secret.to_tor_auth_private(ONION_ADDRESS),
+        ALICE_AUTH_PRIVATE
+    );
+    assert_eq!(PublicKey::from(&secret).to_tor_auth_key(), ALICE_AUTH_KEY);
+}
+
+#[test]
+#[cfg(feature = "static_secrets")]
+fn static_secret_tor_auth_private_rejects_bad_onion_address() {
+    let line = ALICE_AUTH_PRIVATE.replacen(':', ".onion:", 1);
+    assert_eq!(
+        StaticSecret::from_tor_auth_private(&line).err(),
+        Some(Error::Tor(TorKeyError::InvalidFormat))
+    );
+    assert_eq!(
+        StaticSecret::from_tor_auth_private(ALICE_AUTH_KEY).err(),
+        Some(Error::Tor(TorKeyError::InvalidFormat))
+    );
+}
@@ -0,0 +1,155 @@
+//! Edge cases in the categories flagged by Google's Wycheproof X25519 tests.
+//!
+//! The cases below were computed independently with Python, with the ladder
+//! of RFC7748 section 5, and checked against OpenSSL through the
+//! `cryptography` package wherever OpenSSL does not reject an all-zero
+//! output.  `wycheproof_file` checks every test in Wycheproof's
+//! `x25519_test.json` (Apache-2.0), once that file is vendored as
+//! `tests/data/x25519_test.json`; until then it is ignored.
+
+mod common;
+
+use common::{hex32, ConstRng};
+use x25519_dalek::{x25519, EphemeralSecret, Error, PublicKey};
+
+/// The private key of every case, from RFC7748 section 5.2.
+const PRIVATE: &str = "c8a9d5a91091ad851c668b0736c1c9a02936c0d3ad62670858088047ba057475";
+
+const ZERO: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// (flags, public key, shared secret) triples, with flags named after
+/// Wycheproof's.
+const EDGE_CASES: &[(&[&str], &str, &str)] = &[
+    (
+        &["LowOrderPublic"],
+        "0000000000000000000000000000000000000000000000000000000000000000",
+        ZERO,
+    ),
+    (
+        &["LowOrderPublic"],
+        "0100000000000000000000000000000000000000000000000000000000000000",
+        ZERO,
+    ),
+    (
+        &["LowOrderPublic"],
+        "e0eb7a7c3b41b8ae1656e3faf19fc46ada098deb9c32b1fd866205165f49b800",
+        ZERO,
+    ),
+    (
+        &["LowOrderPublic"],
+        "5f9c95bca3508c24b1d0b1559c83ef5b04445cc4581c8e86d8224eddd09f1157",
+        ZERO,
+    ),
+    (
+        &["LowOrderPublic", "Twist"],
+        "ecffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff7f",
+        ZERO,
+    ),
+    (
+        &["LowOrderPublic", "NonCanonicalPublic"],
+        "edffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff7f",
+        ZERO,
+    ),
+    (
+        &["LowOrderPublic", "NonCanonicalPublic"],
+        "eeffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff7f",
+        ZERO,
+    ),
+    (
+        &["Twist"],
+        "0200000000000000000000000000000000000000000000000000000000000000",
+        "a5f00a220f82ac43d0d41202117a6766372333090c42c94cf0a1b991c2d6c773",
+    ),
+    (
+        &["Twist", "NonCanonicalPublic"],
+        "efffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff7f",
+        "a5f00a220f82ac43d0d41202117a6766372333090c42c94cf0a1b991c2d6c773",
+    ),
+    (
+        &["NonCanonicalPublic"],
+        "0900000000000000000000000000000000000000000000000000000000000080",
+        "5f64b41cce8a6b3d6a38763088f615a4977d422288ae42b49ab3a57e2fcd6f6d",
+    ),
+];
+
+/// Check one test against the behaviour its flags call for.
+fn check(id: &str, flags: &[&str], private: [u8; 32], public: [u8; 32], shared: [u8; 32]) {
+    // Every public key is accepted by X25519 itself, as RFC7748 requires.
+    assert_eq!(x25519(private, public), shared, "test {}", id);
+    let ours = EphemeralSecret::random_from_rng(ConstRng(private))
+        .diffie_hellman(&PublicKey::from(public));
+    assert_eq!(ours.as_bytes(), &shared, "test {}", id);
+    assert_eq!(ours.was_contributory(), shared != [0u8; 32], "test {}", id);
+
+    let canonical = PublicKey::from_canonical_bytes(public);
+    for flag in flags {
+        match *flag {
+            "NonCanonicalPublic" => {
+                assert_eq!(
+                    canonical.err(),
+                    Some(Error::NonCanonicalEncoding),
+                    "test {}",
+                    id
+                )
+            }
+            "LowOrderPublic" | "ZeroSharedSecret" => assert!(canonical.is_err(), "test {}", id),
+            "Twist" => assert!(
+                PublicKey::from(public).to_edwards(0).is_none(),
+                "test {}",
+                id
+            ),
+            // These call for nothing beyond the shared secret checked above.
+            "Normal"
+            | "Ktv"
+            | "SmallPublicKey"
+            | "SpecialPublicKey"
+            | "EdgeCaseShared"
+            | "EdgeCaseMultiplication"
+            | "EdgeCasePrivateKey" => {}
+            _ => panic!("test {}: unknown flag {}", id, flag),
+        }
+    }
+}
+
+#[test]
+fn edge_cases() {
+    for (i, (flags, public, shared)) in EDGE_CASES.iter().enumerate() {
+        check(
+            &i.to_string(),
+            flags,
+            hex32(PRIVATE),
+            hex32(public),
+            hex32(shared),
+        );
+    }
+}
+
+#[test]
+#[ignore = "Wycheproof's x25519_test.json is not yet vendored under tests/data"]
+fn wycheproof_file() {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data/x25519_test.json");
+    let file = std::fs::read_to_string(path).unwrap();
+    let json: serde_json::Value = serde_json::from_str(&file).unwrap();
+
+    let mut count = 0;
+    for group in json["testGroups"].as_array().unwrap() {
+        for test in group["tests"].as_array().unwrap() {
+            let field = |name: &str| hex32(test[name].as_str().unwrap());
+            let flags: Vec<&str> = test["flags"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|flag| flag.as_str().unwrap())
+                .collect();
+            check(
+                &test["tcId"].to_string(),
+                &flags,
+                field("private"),
+                field("public"),
+                field("shared"),
+            );
+            count += 1;
+        }
+    }
+    assert!(count > 0);
+}
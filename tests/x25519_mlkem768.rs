@@ -0,0 +1,92 @@
+//! X25519MLKEM768 wire format tests.
+
+#![cfg(feature = "x25519_mlkem768")]
+
+use rand_core::OsRng;
+use x25519_dalek::x25519_mlkem768::{self, CLIENT_SHARE_LEN, SERVER_SHARE_LEN};
+use x25519_dalek::{EphemeralSecret, Error, PublicKey};
+
+/// Alice's public key, from RFC7748 section 6.1.
+const ALICE_PUBLIC: [u8; 32] = [
+    0x85, 0x20, 0xf0, 0x09, 0x89, 0x30, 0xa7, 0x54, 0x74, 0x8b, 0x7d, 0xdc, 0xb4, 0x3e, 0xf7, 0x5a,
+    0x0d, 0xbf, 0x3a, 0x0d, 0x26, 0x38, 0x1a, 0xf4, 0xeb, 0xa4, 0xa9, 0x8e, 0xaa, 0x9b, 0x4e, 0x6a,
+];
+
+/// A stand-in for an ML-KEM-768 encapsulation key or ciphertext.
+fn mlkem_bytes<const N: usize>() -> [u8; N] {
+    let mut bytes = [0u8; N];
+    for (i, byte) in bytes.iter_mut().enumerate() {
+        *byte = i as u8;
+    }
+    bytes
+}
+
+#[test]
+fn client_key_share_round_trip() {
+    let encapsulation_key = mlkem_bytes();
+    let public = PublicKey::from(ALICE_PUBLIC);
+
+    let share = x25519_mlkem768::client_key_share(&encapsulation_key, &public);
+    assert_eq!(share.len(), 1216);
+    assert_eq!(share[..1184], encapsulation_key[..]);
+    assert_eq!(share[1184..], ALICE_PUBLIC);
+
+    let (parsed_key, parsed_public) = x25519_mlkem768::parse_client_key_share(&share).unwrap();
+    assert_eq!(parsed_key, &encapsulation_key);
+    assert_eq!(parsed_public, public);
+}
+
+#[test]
+fn server_key_share_round_trip() {
+    let ciphertext = mlkem_bytes();
+    let public = PublicKey::from(ALICE_PUBLIC);
+
+    let share = x25519_mlkem768::server_key_share(&ciphertext, &public);
+    assert_eq!(share.len(), 1120);
+    assert_eq!(share[..1088], ciphertext[..]);
+    assert_eq!(share[1088..], ALICE_PUBLIC);
+
+    let (parsed_ciphertext, parsed_public) =
+        x25519_mlkem768::parse_server_key_share(&share).unwrap();
+    assert_eq!(parsed_ciphertext, &ciphertext);
+    assert_eq!(parsed_public, public);
+}
+
+#[test]
+fn key_shares_reject_wrong_length() {
+    assert_eq!(
+        x25519_mlkem768::parse_client_key_share(&[0u8; SERVER_SHARE_LEN]).err(),
+        Some(Error::InvalidLength {
+            expected: CLIENT_SHARE_LEN,
+            actual: SERVER_SHARE_LEN,
+        })
+    );
+    assert_eq!(
+        x25519_mlkem768::parse_server_key_share(&[0u8; CLIENT_SHARE_LEN]).err(),
+        Some(Error::InvalidLength {
+            expected: SERVER_SHARE_LEN,
+            actual: CLIENT_SHARE_LEN,
+        })
+    );
+}
+
+#[test]
+fn shared_secrets_are_concatenated() {
+    let mlkem_shared = [0x42u8; 32];
+    let x25519_shared =
+        EphemeralSecret::random_from_rng(OsRng).diffie_hellman(&PublicKey::from(ALICE_PUBLIC));
+
+    let shared = x25519_mlkem768::combine_shared_secrets(&mlkem_shared, &x25519_shared).unwrap();
+    assert_eq!(shared[..32], mlkem_shared);
+    assert_eq!(shared[32..], x25519_shared.to_bytes());
+}
+
+#[test]
+fn zero_x25519_shared_secret_is_rejected() {
+    let x25519_shared =
+        EphemeralSecret::random_from_rng(OsRng).diffie_hellman(&PublicKey::from([0u8; 32]));
+    assert_eq!(
+        x25519_mlkem768::combine_shared_secrets(&[0x42u8; 32], &x25519_shared).err(),
+        Some(Error::SmallOrderPoint)
+    );
+}
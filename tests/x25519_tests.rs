@@ -1,3 +1,5 @@
+mod common;
+
 use curve25519_dalek::edwards::EdwardsPoint;
 
 use x25519_dalek::*;
@@ -18,6 +20,22 @@ fn byte_basepoint_matches_edwards_scalar_mul() {
     }
 }
 
+#[test]
+#[cfg(feature = "static_secrets")]
+fn public_key_matches_byte_basepoint() {
+    let mut scalar_bytes = [0x5a; 32];
+
+    for i in 0..32 {
+        scalar_bytes[i] ^= 0xa5;
+
+        let public = PublicKey::from(&StaticSecret::from(scalar_bytes));
+        assert_eq!(
+            public.to_bytes(),
+            x25519(scalar_bytes, X25519_BASEPOINT_BYTES)
+        );
+    }
+}
+
 #[test]
 #[cfg(feature = "serde")]
 fn serde_bincode_public_key_roundtrip() {
@@ -44,7 +62,7 @@ fn serde_bincode_public_key_matches_from_bytes() {
 }
 
 #[test]
-#[cfg(feature = "serde")]
+#[cfg(all(feature = "serde", feature = "static_secrets"))]
 fn serde_bincode_static_secret_roundtrip() {
     use bincode;
 
@@ -57,7 +75,7 @@ fn serde_bincode_static_secret_roundtrip() {
 }
 
 #[test]
-#[cfg(feature = "serde")]
+#[cfg(all(feature = "serde", feature = "static_secrets"))]
 fn serde_bincode_static_secret_matches_from_bytes() {
     use bincode;
 
@@ -67,6 +85,269 @@ fn serde_bincode_static_secret_matches_from_bytes() {
     assert_eq!(decoded.to_bytes(), expected.to_bytes());
 }
 
+#[test]
+#[cfg(feature = "serde")]
+fn serde_postcard_public_key_is_fixed_size() {
+    let public_key = PublicKey::from(X25519_BASEPOINT_BYTES);
+
+    let mut buf = [0u8; 64];
+    let encoded = postcard::to_slice(&public_key, &mut buf).unwrap();
+    assert_eq!(encoded, &X25519_BASEPOINT_BYTES[..]);
+
+    let decoded: PublicKey = postcard::from_bytes(encoded).unwrap();
+    assert_eq!(decoded, public_key);
+}
+
+#[test]
+#[cfg(all(feature = "serde", feature = "static_secrets"))]
+fn serde_postcard_static_secret_roundtrip() {
+    let static_secret = StaticSecret::from([0x24; 32]);
+
+    let mut buf = [0u8; 64];
+    let encoded = postcard::to_slice(&static_secret, &mut buf).unwrap();
+    assert_eq!(encoded, &[0x24; 32][..]);
+
+    let decoded: StaticSecret = postcard::from_bytes(encoded).unwrap();
+    assert_eq!(decoded.to_bytes(), static_secret.to_bytes());
+}
+
+#[test]
+#[cfg(feature = "serde")]
+fn serde_postcard_public_key_rejects_short_input() {
+    assert!(postcard::from_bytes::<PublicKey>(&[0u8; 31]).is_err());
+}
+
+#[test]
+fn public_key_display_is_lowercase_hex() {
+    let public_key = PublicKey::from(X25519_BASEPOINT_BYTES);
+    let hex = "0900000000000000000000000000000000000000000000000000000000000000";
+
+    assert_eq!(format!("{}", public_key), hex);
+    assert_eq!(format!("{:x}", public_key), hex);
+    assert_eq!(format!("{:#x}", public_key), format!("0x{}", hex));
+}
+
+#[test]
+fn public_key_from_str_roundtrip() {
+    let hex = "DE9EDB7D7B7DC1B4D35B61C2ECE435373F8343C85B78674DADFC7E146F882B4F";
+    let public_key: PublicKey = hex.parse().unwrap();
+
+    assert_eq!(public_key.to_string(), hex.to_lowercase());
+    assert_eq!(
+        public_key.to_string().parse::<PublicKey>().unwrap(),
+        public_key
+    );
+}
+
+#[test]
+fn public_key_from_str_rejects_invalid_input() {
+    let hex = "de9edb7d7b7dc1b4d35b61c2ece435373f8343c85b78674dadfc7e146f882b4f";
+
+    assert_eq!(
+        hex[..62].parse::<PublicKey>(),
+        Err(Error::Hex(ParsePublicKeyError::InvalidLength))
+    );
+    assert_eq!(
+        format!("{}00", hex).parse::<PublicKey>(),
+        Err(Error::Hex(ParsePublicKeyError::InvalidLength))
+    );
+    assert_eq!(
+        hex.replace('e', "g").parse::<PublicKey>(),
+        Err(Error::Hex(ParsePublicKeyError::InvalidCharacter))
+    );
+    assert_eq!(
+        format!("0x{}", &hex[2..]).parse::<PublicKey>(),
+        Err(Error::Hex(ParsePublicKeyError::InvalidCharacter))
+    );
+}
+
+#[test]
+fn public_key_ct_eq() {
+    use subtle::ConstantTimeEq;
+
+    let public_key = PublicKey::from(X25519_BASEPOINT_BYTES);
+    let mut other_bytes = X25519_BASEPOINT_BYTES;
+    other_bytes[31] ^= 0x80;
+
+    assert!(bool::from(
+        public_key.ct_eq(&PublicKey::from(X25519_BASEPOINT_BYTES))
+    ));
+    // Encodings which differ only in the unused high bit are distinct keys.
+    assert!(!bool::from(public_key.ct_eq(&PublicKey::from(other_bytes))));
+}
+
+#[test]
+#[cfg(feature = "static_secrets")]
+fn shared_secret_ct_eq() {
+    use subtle::ConstantTimeEq;
+
+    let alice = StaticSecret::from([0x11; 32]);
+    let bob = StaticSecret::from([0x22; 32]);
+    let carol = StaticSecret::from([0x33; 32]);
+
+    let alice_bob = alice.diffie_hellman(&PublicKey::from(&bob));
+    let bob_alice = bob.diffie_hellman(&PublicKey::from(&alice));
+    let alice_carol = alice.diffie_hellman(&PublicKey::from(&carol));
+
+    assert!(bool::from(alice_bob.ct_eq(&bob_alice)));
+    assert!(!bool::from(alice_bob.ct_eq(&alice_carol)));
+    assert!(alice_bob == bob_alice);
+    assert!(alice_bob != alice_carol);
+}
+
+#[test]
+#[cfg(feature = "static_secrets")]
+fn keys_are_usable_as_byte_slices() {
+    fn bytes(key: impl AsRef<[u8]>) -> Vec<u8> {
+        key.as_ref().to_vec()
+    }
+
+    let secret = StaticSecret::from([0x11; 32]);
+    let public_key = PublicKey::from(&secret);
+    let shared_secret = secret.diffie_hellman(&public_key);
+
+    assert_eq!(bytes(public_key), public_key.as_bytes());
+    assert_eq!(bytes(&shared_secret), shared_secret.as_bytes());
+}
+
+#[test]
+fn public_key_from_canonical_bytes() {
+    assert_eq!(
+        PublicKey::from_canonical_bytes(X25519_BASEPOINT_BYTES),
+        Ok(PublicKey::from(X25519_BASEPOINT_BYTES))
+    );
+
+    // The unused high bit is set.
+    let mut high_bit = X25519_BASEPOINT_BYTES;
+    high_bit[31] |= 0x80;
+    assert_eq!(
+        PublicKey::from_canonical_bytes(high_bit),
+        Err(Error::NonCanonicalEncoding)
+    );
+
+    // p = 2^255 - 19 is not reduced.
+    let mut p = [0xff; 32];
+    p[0] = 0xed;
+    p[31] = 0x7f;
+    assert_eq!(
+        PublicKey::from_canonical_bytes(p),
+        Err(Error::NonCanonicalEncoding)
+    );
+
+    // 0, 1 and p - 1 are points of small order.
+    let mut p_minus_one = p;
+    p_minus_one[0] = 0xec;
+    let mut one = [0; 32];
+    one[0] = 1;
+    for bytes in [[0; 32], one, p_minus_one] {
+        assert_eq!(
+            PublicKey::from_canonical_bytes(bytes),
+            Err(Error::SmallOrderPoint)
+        );
+    }
+}
+
+#[test]
+fn public_key_to_edwards() {
+    use curve25519_dalek::constants::ED25519_BASEPOINT_POINT;
+
+    let basepoint = PublicKey::from(X25519_BASEPOINT_BYTES);
+    assert_eq!(basepoint.to_edwards(0), Some(ED25519_BASEPOINT_POINT));
+    assert_eq!(basepoint.to_edwards(1), Some(-ED25519_BASEPOINT_POINT));
+    // Only the lowest bit of the sign is used.
+    assert_eq!(basepoint.to_edwards(2), Some(ED25519_BASEPOINT_POINT));
+
+    let edwards = EdwardsPoint::mul_base_clamped([0x42; 32]);
+    let public = PublicKey::from(edwards.to_montgomery().to_bytes());
+    let sign = edwards.compress().as_bytes()[31] >> 7;
+    assert_eq!(public.to_edwards(sign), Some(edwards));
+    assert_eq!(public.to_edwards(sign ^ 1), Some(-edwards));
+
+    // The map is undefined at u = -1.
+    let mut p_minus_one = [0xff; 32];
+    p_minus_one[0] = 0xec;
+    p_minus_one[31] = 0x7f;
+    assert_eq!(PublicKey::from(p_minus_one).to_edwards(0), None);
+
+    // u = 2 is on the twist.
+    let mut two = [0; 32];
+    two[0] = 2;
+    assert_eq!(PublicKey::from(two).to_edwards(0), None);
+}
+
+#[test]
+fn public_key_try_from_slice() {
+    assert_eq!(
+        PublicKey::try_from(&X25519_BASEPOINT_BYTES[..]),
+        Ok(PublicKey::from(X25519_BASEPOINT_BYTES))
+    );
+    assert_eq!(
+        PublicKey::try_from(&X25519_BASEPOINT_BYTES[..31]),
+        Err(Error::InvalidLength {
+            expected: 32,
+            actual: 31
+        })
+    );
+}
+
+#[test]
+#[cfg(feature = "static_secrets")]
+fn static_secret_try_from_slice() {
+    let secret = StaticSecret::try_from(&[0x11; 32][..]).unwrap();
+    assert_eq!(secret.to_bytes(), [0x11; 32]);
+
+    assert_eq!(
+        StaticSecret::try_from(&[0x11; 33][..]).err(),
+        Some(Error::InvalidLength {
+            expected: 32,
+            actual: 33
+        })
+    );
+}
+
+#[test]
+#[cfg(feature = "static_secrets")]
+fn keypair_caches_public_key() {
+    let secret = StaticSecret::from([0x11; 32]);
+    let public = PublicKey::from(&secret);
+    let peer = PublicKey::from(&StaticSecret::from([0x22; 32]));
+
+    let keypair = Keypair::from(secret.clone());
+    assert_eq!(keypair.public_key(), &public);
+    assert_eq!(PublicKey::from(&keypair), public);
+    assert_eq!(keypair.secret().to_bytes(), secret.to_bytes());
+    assert_eq!(
+        keypair.diffie_hellman(&peer).as_bytes(),
+        secret.diffie_hellman(&peer).as_bytes()
+    );
+}
+
+#[test]
+#[cfg(all(feature = "reusable_secrets", feature = "static_secrets"))]
+fn diffie_hellman_into_matches_diffie_hellman() {
+    let secret = StaticSecret::from([0x11; 32]);
+    let peer = PublicKey::from(&StaticSecret::from([0x22; 32]));
+    let expected = secret.diffie_hellman(&peer);
+
+    let mut out = [0u8; 32];
+    secret.diffie_hellman_into(&peer, &mut out);
+    assert_eq!(&out, expected.as_bytes());
+    secret.public_key_into(&mut out);
+    assert_eq!(out, PublicKey::from(&secret).to_bytes());
+
+    let reusable = ReusableSecret::random_from_rng(common::ConstRng([0x11; 32]));
+    reusable.diffie_hellman_into(&peer, &mut out);
+    assert_eq!(&out, expected.as_bytes());
+    reusable.public_key_into(&mut out);
+    assert_eq!(out, PublicKey::from(&reusable).to_bytes());
+
+    let ephemeral = EphemeralSecret::random_from_rng(common::ConstRng([0x11; 32]));
+    ephemeral.public_key_into(&mut out);
+    assert_eq!(out, PublicKey::from(&secret).to_bytes());
+    ephemeral.diffie_hellman_into(&peer, &mut out);
+    assert_eq!(&out, expected.as_bytes());
+}
+
 fn do_rfc7748_ladder_test1(input_scalar: [u8; 32], input_point: [u8; 32], expected: [u8; 32]) {
     let result = x25519(input_scalar, input_point);
 
@@ -115,61 +396,72 @@ fn rfc7748_ladder_test1_vectorset2() {
     do_rfc7748_ladder_test1(input_scalar, input_point, expected);
 }
 
+/// The state of the iterated test of RFC7748 section 5.2: the scalar `k`
+/// and the u-coordinate `u`, starting from the basepoint.
+struct Rfc7748Iteration {
+    k: [u8; 32],
+    u: [u8; 32],
+}
+
+impl Rfc7748Iteration {
+    fn new() -> Self {
+        Rfc7748Iteration {
+            k: X25519_BASEPOINT_BYTES,
+            u: X25519_BASEPOINT_BYTES,
+        }
+    }
+
+    fn iterate(&mut self, n: usize) {
+        for _ in 0..n {
+            let result = x25519(self.k, self.u);
+            // OBVIOUS THING THAT I'M GOING TO NOTE ANYWAY BECAUSE I'VE
+            // SEEN PEOPLE DO THIS WITH GOLANG'S STDLIB AND YOU SURE AS
+            // HELL SHOULDN'T DO HORRIBLY STUPID THINGS LIKE THIS WITH
+            // MY LIBRARY:
+            //
+            // NEVER EVER TREAT SCALARS AS POINTS AND/OR VICE VERSA.
+            //
+            //                ↓↓ DON'T DO THIS ↓↓
+            self.u = self.k;
+            self.k = result;
+        }
+    }
+}
+
+// After one iteration:
+//     422c8e7a6227d7bca1350b3e2bb7279f7897b87bb6854b783c60e80311ae3079
+const RFC7748_AFTER_1: [u8; 32] = [
+    0x42, 0x2c, 0x8e, 0x7a, 0x62, 0x27, 0xd7, 0xbc, 0xa1, 0x35, 0x0b, 0x3e, 0x2b, 0xb7, 0x27, 0x9f,
+    0x78, 0x97, 0xb8, 0x7b, 0xb6, 0x85, 0x4b, 0x78, 0x3c, 0x60, 0xe8, 0x03, 0x11, 0xae, 0x30, 0x79,
+];
+
+// After 1,000 iterations:
+//     684cf59ba83309552800ef566f2f4d3c1c3887c49360e3875f2eb94d99532c51
+const RFC7748_AFTER_1_000: [u8; 32] = [
+    0x68, 0x4c, 0xf5, 0x9b, 0xa8, 0x33, 0x09, 0x55, 0x28, 0x00, 0xef, 0x56, 0x6f, 0x2f, 0x4d, 0x3c,
+    0x1c, 0x38, 0x87, 0xc4, 0x93, 0x60, 0xe3, 0x87, 0x5f, 0x2e, 0xb9, 0x4d, 0x99, 0x53, 0x2c, 0x51,
+];
+
 #[test]
-#[ignore] // Run only if you want to burn a lot of CPU doing 1,000,000 DH operations
 fn rfc7748_ladder_test2() {
-    use curve25519_dalek::constants::X25519_BASEPOINT;
-
-    let mut k: [u8; 32] = X25519_BASEPOINT.0;
-    let mut u: [u8; 32] = X25519_BASEPOINT.0;
-    let mut result: [u8; 32];
-
-    macro_rules! do_iterations {
-        ($n:expr) => {
-            for _ in 0..$n {
-                result = x25519(k, u);
-                // OBVIOUS THING THAT I'M GOING TO NOTE ANYWAY BECAUSE I'VE
-                // SEEN PEOPLE DO THIS WITH GOLANG'S STDLIB AND YOU SURE AS
-                // HELL SHOULDN'T DO HORRIBLY STUPID THINGS LIKE THIS WITH
-                // MY LIBRARY:
-                //
-                // NEVER EVER TREAT SCALARS AS POINTS AND/OR VICE VERSA.
-                //
-                //                ↓↓ DON'T DO THIS ↓↓
-                u = k.clone();
-                k = result;
-            }
-        };
-    }
+    let mut state = Rfc7748Iteration::new();
+    state.iterate(1);
+    assert_eq!(state.k, RFC7748_AFTER_1);
+    state.iterate(999);
+    assert_eq!(state.k, RFC7748_AFTER_1_000);
+}
 
-    // After one iteration:
-    //     422c8e7a6227d7bca1350b3e2bb7279f7897b87bb6854b783c60e80311ae3079
-    // After 1,000 iterations:
-    //     684cf59ba83309552800ef566f2f4d3c1c3887c49360e3875f2eb94d99532c51
+#[test]
+#[cfg(feature = "slow-tests")] // 1,000,000 DH operations burn a lot of CPU
+fn rfc7748_ladder_test2_1_000_000() {
     // After 1,000,000 iterations:
     //     7c3911e0ab2586fd864497297e575e6f3bc601c0883c30df5f4dd2d24f665424
-
-    do_iterations!(1);
+    let mut state = Rfc7748Iteration::new();
+    state.iterate(1_000);
+    assert_eq!(state.k, RFC7748_AFTER_1_000);
+    state.iterate(999_000);
     assert_eq!(
-        k,
-        [
-            0x42, 0x2c, 0x8e, 0x7a, 0x62, 0x27, 0xd7, 0xbc, 0xa1, 0x35, 0x0b, 0x3e, 0x2b, 0xb7,
-            0x27, 0x9f, 0x78, 0x97, 0xb8, 0x7b, 0xb6, 0x85, 0x4b, 0x78, 0x3c, 0x60, 0xe8, 0x03,
-            0x11, 0xae, 0x30, 0x79,
-        ]
-    );
-    do_iterations!(999);
-    assert_eq!(
-        k,
-        [
-            0x68, 0x4c, 0xf5, 0x9b, 0xa8, 0x33, 0x09, 0x55, 0x28, 0x00, 0xef, 0x56, 0x6f, 0x2f,
-            0x4d, 0x3c, 0x1c, 0x38, 0x87, 0xc4, 0x93, 0x60, 0xe3, 0x87, 0x5f, 0x2e, 0xb9, 0x4d,
-            0x99, 0x53, 0x2c, 0x51,
-        ]
-    );
-    do_iterations!(999_000);
-    assert_eq!(
-        k,
+        state.k,
         [
             0x7c, 0x39, 0x11, 0xe0, 0xab, 0x25, 0x86, 0xfd, 0x86, 0x44, 0x97, 0x29, 0x7e, 0x57,
             0x5e, 0x6f, 0x3b, 0xc6, 0x01, 0xc0, 0x88, 0x3c, 0x30, 0xdf, 0x5f, 0x4d, 0xd2, 0xd2,
@@ -181,6 +473,7 @@ fn rfc7748_ladder_test2() {
 mod rand_core {
 
     use super::*;
+    use crate::common::FailingRng;
     use ::rand_core::OsRng;
 
     #[test]
@@ -205,6 +498,345 @@ mod rand_core {
         StaticSecret::new(OsRng);
         StaticSecret::random_from_rng(OsRng);
     }
+
+    #[test]
+    fn ephemeral_try_from_rng() {
+        assert!(EphemeralSecret::try_random_from_rng(OsRng).is_ok());
+        assert!(matches!(
+            EphemeralSecret::try_random_from_rng(FailingRng),
+            Err(Error::Rng)
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "reusable_secrets")]
+    fn reusable_try_from_rng() {
+        assert!(ReusableSecret::try_random_from_rng(OsRng).is_ok());
+        assert!(matches!(
+            ReusableSecret::try_random_from_rng(FailingRng),
+            Err(Error::Rng)
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "static_secrets")]
+    fn static_try_from_rng() {
+        assert!(StaticSecret::try_random_from_rng(OsRng).is_ok());
+        assert!(matches!(
+            StaticSecret::try_random_from_rng(FailingRng),
+            Err(Error::Rng)
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "static_secrets")]
+    fn keypair_random_checked_from_rng() {
+        let keypair = Keypair::random_checked_from_rng(OsRng).unwrap();
+        assert_eq!(keypair.public_key(), &PublicKey::from(keypair.secret()));
+        assert!(matches!(
+            Keypair::random_checked_from_rng(FailingRng),
+            Err(Error::Rng)
+        ));
+    }
+}
+
+#[cfg(feature = "rand_core_0_9")]
+mod rand_core_0_9 {
+
+    use super::*;
+    use ::rand_core_0_9::{impls, CryptoRng, RngCore, TryCryptoRng, TryRngCore};
+
+    /// A deterministic counter "RNG", good enough to check that its output
+    /// is used as the secret key.
+    struct CounterRng(u8);
+
+    impl RngCore for CounterRng {
+        fn next_u32(&mut self) -> u32 {
+            impls::next_u32_via_fill(self)
+        }
+
+        fn next_u64(&mut self) -> u64 {
+            impls::next_u64_via_fill(self)
+        }
+
+        fn fill_bytes(&mut self, dst: &mut [u8]) {
+            for byte in dst {
+                *byte = self.0;
+                self.0 = self.0.wrapping_add(1);
+            }
+        }
+    }
+
+    impl CryptoRng for CounterRng {}
+
+    /// An RNG which always fails.
+    struct FailingRng;
+
+    impl TryRngCore for FailingRng {
+        type Error = &'static str;
+
+        fn try_next_u32(&mut self) -> Result<u32, Self::Error> {
+            Err("failed")
+        }
+
+        fn try_next_u64(&mut self) -> Result<u64, Self::Error> {
+            Err("failed")
+        }
+
+        fn try_fill_bytes(&mut self, _dst: &mut [u8]) -> Result<(), Self::Error> {
+            Err("failed")
+        }
+    }
+
+    impl TryCryptoRng for FailingRng {}
+
+    fn counter_bytes() -> [u8; 32] {
+        let mut bytes = [0u8; 32];
+        CounterRng(0).fill_bytes(&mut bytes);
+        bytes
+    }
+
+    #[test]
+    fn ephemeral_from_rng_0_9() {
+        let secret = EphemeralSecret::random_from_rng_0_9(CounterRng(0));
+        let expected = x25519(counter_bytes(), X25519_BASEPOINT_BYTES);
+        assert_eq!(PublicKey::from(&secret).to_bytes(), expected);
+
+        let mut rng = CounterRng(0);
+        assert!(EphemeralSecret::try_random_from_rng_0_9(&mut rng).is_ok());
+        assert!(matches!(
+            EphemeralSecret::try_random_from_rng_0_9(FailingRng),
+            Err(Error::Rng)
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "reusable_secrets")]
+    fn reusable_from_rng_0_9() {
+        let secret = ReusableSecret::random_from_rng_0_9(CounterRng(0));
+        let expected = x25519(counter_bytes(), X25519_BASEPOINT_BYTES);
+        assert_eq!(PublicKey::from(&secret).to_bytes(), expected);
+
+        assert!(matches!(
+            ReusableSecret::try_random_from_rng_0_9(FailingRng),
+            Err(Error::Rng)
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "static_secrets")]
+    fn static_from_rng_0_9() {
+        let secret = StaticSecret::random_from_rng_0_9(CounterRng(0));
+        assert_eq!(secret.to_bytes(), counter_bytes());
+
+        let secret = StaticSecret::try_random_from_rng_0_9(CounterRng(0)).unwrap();
+        assert_eq!(secret.to_bytes(), counter_bytes());
+        assert!(matches!(
+            StaticSecret::try_random_from_rng_0_9(FailingRng),
+            Err(Error::Rng)
+        ));
+    }
+}
+
+#[cfg(feature = "scalar_blinding")]
+mod scalar_blinding {
+
+    use super::*;
+    use crate::common::ConstRng;
+    use ::rand_core::{OsRng, RngCore};
+    use curve25519_dalek::montgomery::MontgomeryPoint;
+
+    /// Public keys to test against: Bob's from RFC7748 section 6.1, a
+    /// small-order point, and a point on the twist.
+    fn peers() -> Vec<PublicKey> {
+        let mut twist = [0u8; 32];
+        twist[0] = 2;
+        let mut peers = vec![
+            PublicKey::from([
+                0xde, 0x9e, 0xdb, 0x7d, 0x7b, 0x7d, 0xc1, 0xb4, 0xd3, 0x5b, 0x61, 0xc2, 0xec, 0xe4,
+                0x35, 0x37, 0x3f, 0x83, 0x43, 0xc8, 0x5b, 0x78, 0x67, 0x4d, 0xad, 0xfc, 0x7e, 0x14,
+                0x6f, 0x88, 0x2b, 0x4f,
+            ]),
+            PublicKey::from([0u8; 32]),
+            PublicKey::from(twist),
+        ];
+        for _ in 0..8 {
+            let mut bytes = [0u8; 32];
+            OsRng.fill_bytes(&mut bytes);
+            peers.push(PublicKey::from(bytes));
+        }
+        peers
+    }
+
+    /// Whether `peer` is on the twist of the curve rather than the curve.
+    fn on_twist(peer: &PublicKey) -> bool {
+        MontgomeryPoint(peer.to_bytes()).to_edwards(0).is_none()
+    }
+
+    #[test]
+    fn ephemeral_diffie_hellman_blinded() {
+        for peer in peers() {
+            let mut bytes = [0u8; 32];
+            OsRng.fill_bytes(&mut bytes);
+            let expected = x25519(bytes, peer.to_bytes());
+
+            let secret = EphemeralSecret::random_from_rng(ConstRng(bytes));
+            match secret.diffie_hellman_blinded(&peer, OsRng) {
+                Ok(shared) => assert_eq!(shared.to_bytes(), expected),
+                Err(err) => {
+                    assert!(on_twist(&peer));
+                    assert_eq!(err, Error::TwistPoint);
+                }
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "reusable_secrets")]
+    fn reusable_diffie_hellman_blinded() {
+        let secret = ReusableSecret::random_from_rng(OsRng);
+        for peer in peers().iter().filter(|peer| !on_twist(peer)) {
+            assert_eq!(
+                secret
+                    .diffie_hellman_blinded(peer, OsRng)
+                    .unwrap()
+                    .to_bytes(),
+                secret.diffie_hellman(peer).to_bytes()
+            );
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "static_secrets")]
+    fn static_diffie_hellman_blinded() {
+        let secret = StaticSecret::random_from_rng(OsRng);
+        for peer in peers().iter().filter(|peer| !on_twist(peer)) {
+            assert_eq!(
+                secret
+                    .diffie_hellman_blinded(peer, OsRng)
+                    .unwrap()
+                    .to_bytes(),
+                secret.diffie_hellman(peer).to_bytes()
+            );
+        }
+    }
+
+    #[test]
+    fn diffie_hellman_blinded_rejects_twist_points() {
+        // u = 2 and u = -1 are on the twist.
+        let mut two = [0u8; 32];
+        two[0] = 2;
+        let mut minus_one = [0xff; 32];
+        minus_one[0] = 0xec;
+        minus_one[31] = 0x7f;
+
+        for twist in [two, minus_one] {
+            let peer = PublicKey::from(twist);
+            assert!(on_twist(&peer));
+            let secret = EphemeralSecret::random_from_rng(OsRng);
+            assert_eq!(
+                secret.diffie_hellman_blinded(&peer, OsRng).err(),
+                Some(Error::TwistPoint)
+            );
+        }
+    }
+}
+
+#[cfg(feature = "projective_randomization")]
+mod projective_randomization {
+
+    use super::*;
+    use crate::common::ConstRng;
+    use ::rand_core::{OsRng, RngCore};
+
+    /// Public keys to test against: Bob's from RFC7748 section 6.1, points
+    /// of small order, points on the twist, and non-canonical encodings.
+    fn peers() -> Vec<PublicKey> {
+        let mut one = [0u8; 32];
+        one[0] = 1;
+        let mut two = [0u8; 32];
+        two[0] = 2;
+        let mut minus_one = [0xff; 32];
+        minus_one[0] = 0xec;
+        minus_one[31] = 0x7f;
+        let mut peers = vec![
+            PublicKey::from([
+                0xde, 0x9e, 0xdb, 0x7d, 0x7b, 0x7d, 0xc1, 0xb4, 0xd3, 0x5b, 0x61, 0xc2, 0xec, 0xe4,
+                0x35, 0x37, 0x3f, 0x83, 0x43, 0xc8, 0x5b, 0x78, 0x67, 0x4d, 0xad, 0xfc, 0x7e, 0x14,
+                0x6f, 0x88, 0x2b, 0x4f,
+            ]),
+            PublicKey::from([0u8; 32]),
+            PublicKey::from(one),
+            PublicKey::from(two),
+            PublicKey::from(minus_one),
+            PublicKey::from([0xff; 32]),
+        ];
+        for _ in 0..8 {
+            let mut bytes = [0u8; 32];
+            OsRng.fill_bytes(&mut bytes);
+            peers.push(PublicKey::from(bytes));
+        }
+        peers
+    }
+
+    #[test]
+    fn ephemeral_diffie_hellman_randomized() {
+        for peer in peers() {
+            let mut bytes = [0u8; 32];
+            OsRng.fill_bytes(&mut bytes);
+            let expected = x25519(bytes, peer.to_bytes());
+
+            let secret = EphemeralSecret::random_from_rng(ConstRng(bytes));
+            let shared = secret.diffie_hellman_randomized(&peer, OsRng);
+            assert_eq!(shared.to_bytes(), expected);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "reusable_secrets")]
+    fn reusable_diffie_hellman_randomized() {
+        let secret = ReusableSecret::random_from_rng(OsRng);
+        for peer in peers() {
+            assert_eq!(
+                secret.diffie_hellman_randomized(&peer, OsRng).to_bytes(),
+                secret.diffie_hellman(&peer).to_bytes()
+            );
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "static_secrets")]
+    fn static_diffie_hellman_randomized() {
+        let secret = StaticSecret::random_from_rng(OsRng);
+        for peer in peers() {
+            assert_eq!(
+                secret.diffie_hellman_randomized(&peer, OsRng).to_bytes(),
+                secret.diffie_hellman(&peer).to_bytes()
+            );
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "static_secrets")]
+    fn diffie_hellman_randomized_rfc7748_vector() {
+        let alice = StaticSecret::from([
+            0x77, 0x07, 0x6d, 0x0a, 0x73, 0x18, 0xa5, 0x7d, 0x3c, 0x16, 0xc1, 0x72, 0x51, 0xb2,
+            0x66, 0x45, 0xdf, 0x4c, 0x2f, 0x87, 0xeb, 0xc0, 0x99, 0x2a, 0xb1, 0x77, 0xfb, 0xa5,
+            0x1d, 0xb9, 0x2c, 0x2a,
+        ]);
+        let expected = [
+            0x4a, 0x5d, 0x9d, 0x5b, 0xa4, 0xce, 0x2d, 0xe1, 0x72, 0x8e, 0x3b, 0xf4, 0x80, 0x35,
+            0x0f, 0x25, 0xe0, 0x7e, 0x21, 0xc9, 0x47, 0xd1, 0x9e, 0x33, 0x76, 0xf0, 0x9b, 0x3c,
+            0x1e, 0x16, 0x17, 0x42,
+        ];
+
+        // The result does not depend on the randomness, even when it
+        // encodes a field element which is not reduced modulo p.
+        for lambda in [[0x01; 32], [0xff; 32], [0x5a; 32]] {
+            let shared = alice.diffie_hellman_randomized(&peers()[0], ConstRng(lambda));
+            assert_eq!(shared.to_bytes(), expected);
+        }
+    }
 }
 
 #[cfg(feature = "getrandom")]
@@ -229,3 +861,51 @@ mod getrandom {
         StaticSecret::random();
     }
 }
+
+#[test]
+#[cfg(all(feature = "precomputed-tables", feature = "static_secrets"))]
+fn diffie_hellman_precomputed_matches_diffie_hellman() {
+    use curve25519_dalek::montgomery::MontgomeryPoint;
+
+    let mut publics: Vec<[u8; 32]> = (1..=8u8)
+        .map(|i| PublicKey::from(&StaticSecret::from([i; 32])).to_bytes())
+        .collect();
+    // Points of small order, including 0 and 1.
+    let mut one = [0; 32];
+    one[0] = 1;
+    publics.push([0; 32]);
+    publics.push(one);
+    publics.push([
+        0xe0, 0xeb, 0x7a, 0x7c, 0x3b, 0x41, 0xb8, 0xae, 0x16, 0x56, 0xe3, 0xfa, 0xf1, 0x9f, 0xc4,
+        0x6a, 0xda, 0x09, 0x8d, 0xeb, 0x9c, 0x32, 0xb1, 0xfd, 0x86, 0x62, 0x05, 0x16, 0x5f, 0x49,
+        0xb8, 0x00,
+    ]);
+    // -1 and 2, which are on the twist.
+    let mut minus_one = [0xff; 32];
+    minus_one[0] = 0xec;
+    minus_one[31] = 0x7f;
+    let mut two = [0; 32];
+    two[0] = 2;
+    assert!(MontgomeryPoint(minus_one).to_edwards(0).is_none());
+    assert!(MontgomeryPoint(two).to_edwards(0).is_none());
+    publics.push(minus_one);
+    publics.push(two);
+    // Non-canonical encodings.
+    publics.push([0xff; 32]);
+    let mut high = publics[0];
+    high[31] |= 0x80;
+    publics.push(high);
+
+    for secret in [[0x42; 32], [0xff; 32], [0; 32]] {
+        let secret = StaticSecret::from(secret);
+        for public in &publics {
+            let public = PublicKey::from(*public);
+            let precomputed = PrecomputedPublicKey::from(&public);
+            assert_eq!(precomputed.public_key(), &public);
+            assert_eq!(
+                secret.diffie_hellman_precomputed(&precomputed).as_bytes(),
+                secret.diffie_hellman(&public).as_bytes(),
+            );
+        }
+    }
+}
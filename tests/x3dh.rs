@@ -0,0 +1,160 @@
+//! X3DH key agreement tests.
+
+#![cfg(feature = "x3dh")]
+
+mod common;
+
+use common::ConstRng;
+use x25519_dalek::x3dh::{self, InitialMessage, PreKeyBundle};
+use x25519_dalek::{Error, PublicKey, StaticSecret};
+
+const INFO: &[u8] = b"x3dh test";
+
+// The expected keys below are an independent computation of the X3DH
+// specification with Python's `cryptography` package, for the secret keys of
+// all `0x11` (Alice's identity), `0x22` (Bob's identity), `0x33` (Bob's
+// signed prekey), `0x44` (Bob's one-time prekey) and `0x55` (Alice's
+// ephemeral) bytes:
+//
+//     ikm = b"\xff" * 32 + DH1 + DH2 + DH3 [+ DH4]
+//     HKDF(SHA256(), 32, salt=b"\0" * 32, info=INFO).derive(ikm)
+
+/// Alice's ephemeral public key, for the secret key of all `0x55` bytes.
+const ALICE_EPHEMERAL: [u8; 32] = [
+    0x38, 0xab, 0x66, 0x4b, 0xd8, 0x6f, 0x77, 0xd7, 0xe6, 0x6b, 0xdd, 0x9a, 0xe0, 0x79, 0x29, 0x13,
+    0xa9, 0x4f, 0xd8, 0xb3, 0x3a, 0x12, 0x60, 0x02, 0x7e, 0x4b, 0x46, 0xc1, 0xf4, 0x88, 0x4c, 0x67,
+];
+
+/// The shared key with a one-time prekey.
+const SHARED_KEY: [u8; 32] = [
+    0xcc, 0x4f, 0x74, 0x0e, 0x06, 0x07, 0x15, 0xd8, 0xd8, 0x82, 0x5d, 0x71, 0xb7, 0xc7, 0x93, 0xa9,
+    0x00, 0x62, 0x72, 0x11, 0x92, 0x8f, 0x81, 0x4c, 0xc2, 0xcb, 0x61, 0x68, 0xa1, 0xff, 0x75, 0x7d,
+];
+
+/// The shared key without a one-time prekey.
+const SHARED_KEY_WITHOUT_OPK: [u8; 32] = [
+    0x6d, 0x5c, 0xc5, 0xd5, 0x3c, 0x0d, 0x06, 0x0d, 0x0a, 0xf7, 0xbb, 0xff, 0x18, 0xfa, 0x25, 0x94,
+    0xb0, 0xb9, 0x69, 0x6a, 0x65, 0x60, 0x58, 0x94, 0xad, 0xcd, 0x61, 0x84, 0x75, 0x3d, 0x32, 0xad,
+];
+
+struct Keys {
+    alice_identity: StaticSecret,
+    bob_identity: StaticSecret,
+    bob_signed_prekey: StaticSecret,
+    bob_one_time_prekey: StaticSecret,
+}
+
+fn keys() -> Keys {
+    Keys {
+        alice_identity: StaticSecret::from([0x11; 32]),
+        bob_identity: StaticSecret::from([0x22; 32]),
+        bob_signed_prekey: StaticSecret::from([0x33; 32]),
+        bob_one_time_prekey: StaticSecret::from([0x44; 32]),
+    }
+}
+
+fn bundle(keys: &Keys, with_one_time_prekey: bool) -> PreKeyBundle {
+    PreKeyBundle {
+        identity_key: PublicKey::from(&keys.bob_identity),
+        signed_prekey: PublicKey::from(&keys.bob_signed_prekey),
+        one_time_prekey: Some(PublicKey::from(&keys.bob_one_time_prekey))
+            .filter(|_| with_one_time_prekey),
+    }
+}
+
+#[test]
+fn x3dh_with_one_time_prekey() {
+    let keys = keys();
+    let bundle = bundle(&keys, true);
+    let (alice_key, message) =
+        x3dh::initiate(&keys.alice_identity, &bundle, INFO, ConstRng([0x55; 32])).unwrap();
+    assert_eq!(*alice_key, SHARED_KEY);
+    assert_eq!(
+        message,
+        InitialMessage {
+            identity_key: PublicKey::from(&keys.alice_identity),
+            ephemeral_key: PublicKey::from(ALICE_EPHEMERAL),
+            signed_prekey: bundle.signed_prekey,
+            one_time_prekey: bundle.one_time_prekey,
+        }
+    );
+
+    let bob_key = x3dh::respond(
+        &keys.bob_identity,
+        &keys.bob_signed_prekey,
+        Some(&keys.bob_one_time_prekey),
+        &message,
+        INFO,
+    )
+    .unwrap();
+    assert_eq!(*bob_key, SHARED_KEY);
+}
+
+#[test]
+fn x3dh_without_one_time_prekey() {
+    let keys = keys();
+    let (alice_key, message) = x3dh::initiate(
+        &keys.alice_identity,
+        &bundle(&keys, false),
+        INFO,
+        ConstRng([0x55; 32]),
+    )
+    .unwrap();
+    assert_eq!(*alice_key, SHARED_KEY_WITHOUT_OPK);
+    assert_eq!(message.one_time_prekey, None);
+
+    let bob_key = x3dh::respond(
+        &keys.bob_identity,
+        &keys.bob_signed_prekey,
+        None,
+        &message,
+        INFO,
+    )
+    .unwrap();
+    assert_eq!(*bob_key, SHARED_KEY_WITHOUT_OPK);
+}
+
+#[test]
+fn one_time_prekey_must_match_message() {
+    let keys = keys();
+    let mismatch = Some(Error::X3dh(x3dh::Error::OneTimePrekeyMismatch));
+    let (_, message) = x3dh::initiate(
+        &keys.alice_identity,
+        &bundle(&keys, true),
+        INFO,
+        ConstRng([0x55; 32]),
+    )
+    .unwrap();
+    assert_eq!(
+        x3dh::respond(
+            &keys.bob_identity,
+            &keys.bob_signed_prekey,
+            None,
+            &message,
+            INFO
+        )
+        .err(),
+        mismatch
+    );
+}
+
+#[test]
+fn small_order_keys_are_rejected() {
+    let keys = keys();
+    let mut bundle = bundle(&keys, true);
+    bundle.one_time_prekey = Some(PublicKey::from([0u8; 32]));
+    assert_eq!(
+        x3dh::initiate(&keys.alice_identity, &bundle, INFO, ConstRng([0x55; 32])).err(),
+        Some(Error::SmallOrderPoint)
+    );
+}
+
+#[test]
+fn associated_data_is_both_identity_keys() {
+    let keys = keys();
+    let alice = PublicKey::from(&keys.alice_identity);
+    let bob = PublicKey::from(&keys.bob_identity);
+    let ad = x3dh::associated_data(&alice, &bob);
+    assert_eq!(&ad[..32], alice.as_bytes());
+    assert_eq!(&ad[32..], bob.as_bytes());
+}